@@ -0,0 +1,243 @@
+//! Commit message suggestions from a language model. The staged diff is
+//! redacted, sent to an OpenAI-compatible endpoint or a local Ollama, and
+//! the reply lands in the editor for review; nothing is committed without
+//! the user pressing Enter.
+
+use crate::{
+    app::{App, AppMode, TaskKind},
+    report::GitError,
+};
+use regex::Regex;
+use serde_json::{Value, json};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+const SYSTEM_PROMPT: &str = "You write git commit messages in the Conventional Commits \
+format: a `type(scope): summary` subject of at most 72 characters in the imperative mood, \
+then, only if the change needs explaining, a blank line and a short body wrapped at 72 \
+columns. Reply with the message only.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provider {
+    /// `POST {endpoint}/chat/completions`, as served by OpenAI and most
+    /// hosted or self-hosted gateways.
+    OpenAiCompatible,
+    /// `POST {endpoint}/api/chat` on a local Ollama.
+    Ollama,
+}
+
+#[derive(Debug, Clone)]
+pub struct AiConfig {
+    pub provider: Provider,
+    pub endpoint: String,
+    pub model: String,
+    /// Environment variable holding the API key, if the endpoint needs one.
+    pub api_key_env: Option<String>,
+    /// Matches are replaced with `[REDACTED]` before the diff leaves the
+    /// machine.
+    pub redact: Vec<Regex>,
+    /// Larger diffs are cut off here.
+    pub max_diff_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl AiConfig {
+    pub fn openai(model: &str) -> AiConfig {
+        AiConfig {
+            provider: Provider::OpenAiCompatible,
+            endpoint: "https://api.openai.com/v1".to_string(),
+            model: model.to_string(),
+            api_key_env: Some("OPENAI_API_KEY".to_string()),
+            redact: default_redactions(),
+            max_diff_bytes: 16_000,
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    pub fn ollama(model: &str) -> AiConfig {
+        AiConfig {
+            provider: Provider::Ollama,
+            endpoint: "http://localhost:11434".to_string(),
+            api_key_env: None,
+            ..AiConfig::openai(model)
+        }
+    }
+}
+
+/// Private keys, AWS access keys, and `key = value` assignments whose name
+/// looks like a credential.
+pub fn default_redactions() -> Vec<Regex> {
+    [
+        r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+        r"\bAKIA[0-9A-Z]{16}\b",
+        r#"(?i)\b[\w-]*(api[_-]?key|secret|token|passw(or)?d)\w*["']?\s*[:=]\s*\S+"#,
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).expect("valid regex"))
+    .collect()
+}
+
+pub fn redact(text: &str, patterns: &[Regex]) -> String {
+    patterns.iter().fold(text.to_string(), |text, pattern| {
+        pattern.replace_all(&text, "[REDACTED]").into_owned()
+    })
+}
+
+/// Something that turns a staged diff into a commit message.
+pub trait MessageProvider: Send + Sync {
+    fn suggest(&self, diff: &str) -> Result<String, String>;
+}
+
+/// Talks to the configured endpoint over HTTP.
+pub struct HttpProvider {
+    config: AiConfig,
+    agent: ureq::Agent,
+}
+
+impl HttpProvider {
+    pub fn new(config: AiConfig) -> HttpProvider {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(config.timeout))
+            .build()
+            .into();
+        HttpProvider { config, agent }
+    }
+
+    fn post(&self, path: &str, body: Value) -> Result<Value, String> {
+        let url = format!("{}{}", self.config.endpoint.trim_end_matches('/'), path);
+        let mut request = self
+            .agent
+            .post(&url)
+            .header("Content-Type", "application/json");
+        if let Some(var) = &self.config.api_key_env {
+            let key = std::env::var(var).map_err(|_| format!("{} is not set", var))?;
+            request = request.header("Authorization", &format!("Bearer {}", key));
+        }
+        let text = request
+            .send(body.to_string())
+            .map_err(|e| e.to_string())?
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str(&text).map_err(|e| format!("unexpected reply: {}", e))
+    }
+}
+
+impl MessageProvider for HttpProvider {
+    fn suggest(&self, diff: &str) -> Result<String, String> {
+        let messages = json!([
+            { "role": "system", "content": SYSTEM_PROMPT },
+            { "role": "user", "content": diff },
+        ]);
+        let (reply, pointer) = match self.config.provider {
+            Provider::OpenAiCompatible => (
+                self.post(
+                    "/chat/completions",
+                    json!({ "model": self.config.model, "messages": messages, "temperature": 0.2 }),
+                )?,
+                "/choices/0/message/content",
+            ),
+            Provider::Ollama => (
+                self.post(
+                    "/api/chat",
+                    json!({ "model": self.config.model, "messages": messages, "stream": false }),
+                )?,
+                "/message/content",
+            ),
+        };
+        reply
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map(clean_suggestion)
+            .filter(|message| !message.is_empty())
+            .ok_or_else(|| "the reply held no message".to_string())
+    }
+}
+
+/// Strips the code fences and quotes models like to wrap messages in.
+pub fn clean_suggestion(reply: &str) -> String {
+    let mut message = reply.trim();
+    if let Some(inner) = message.strip_prefix("```") {
+        // Drop the fence line, which may name a language.
+        let inner = inner.split_once('\n').map_or("", |(_, rest)| rest);
+        message = inner.trim_end().trim_end_matches("```").trim();
+    }
+    message.trim_matches('"').trim().to_string()
+}
+
+/// Cuts `diff` to at most `max_bytes`, on a character boundary.
+fn truncate(diff: &str, max_bytes: usize) -> &str {
+    if diff.len() <= max_bytes {
+        return diff;
+    }
+    let mut end = max_bytes;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    &diff[..end]
+}
+
+impl App {
+    pub(crate) fn suggest_message(&mut self) {
+        let Some(provider) = self.suggester.clone() else {
+            self.show_notification("Message suggestions are not configured".to_string());
+            return;
+        };
+        if self.task.is_some() {
+            self.show_notification("Another git operation is still running".to_string());
+            return;
+        }
+
+        let diff = match self.staged_diff() {
+            Ok(diff) => diff,
+            Err(error) => {
+                self.report_error(GitError::new("Diffing staged changes", error));
+                return;
+            }
+        };
+        if diff.trim().is_empty() {
+            self.show_notification("Stage some changes first".to_string());
+            return;
+        }
+        let (patterns, max_bytes) = match &self.config.ai {
+            Some(config) => (config.redact.clone(), config.max_diff_bytes),
+            None => (default_redactions(), usize::MAX),
+        };
+        // Redact before truncating so a cut can't split a secret past its
+        // pattern.
+        let diff = truncate(&redact(&diff, &patterns), max_bytes).to_string();
+
+        self.start_task(TaskKind::Suggest, move |cancel: &AtomicBool| {
+            let message = provider.suggest(&diff)?;
+            if cancel.load(Ordering::Relaxed) {
+                return Err("cancelled".to_string());
+            }
+            Ok(message)
+        });
+    }
+
+    pub(crate) fn insert_suggestion(&mut self, message: String) {
+        let draft = std::mem::replace(&mut self.commit_message, message);
+        if !draft.trim().is_empty() {
+            // Keep what was typed reachable with Up.
+            let _ = self.history.record(self.git_dir.as_deref(), &draft);
+        }
+        self.cursor_position = self.commit_message.len();
+        self.mode = AppMode::CommitMessage;
+        self.save_draft();
+        self.show_notification(
+            "Suggested message inserted; review it before committing".to_string(),
+        );
+    }
+}
+
+/// The provider for `config`, if suggestions are turned on.
+pub fn provider_for(config: Option<&AiConfig>) -> Option<Arc<dyn MessageProvider>> {
+    let config = config?.clone();
+    Some(Arc::new(HttpProvider::new(config)))
+}
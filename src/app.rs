@@ -0,0 +1,1838 @@
+use crate::{
+    ai::{self, MessageProvider},
+    auto_fetch::AutoFetch,
+    branch::NewBranch,
+    checks::ChecksRun,
+    ci::{CiProvider, CiWatch},
+    clean::CleanView,
+    clipboard,
+    command_bar::CommandBar,
+    commit_type::{self, CommitPrefix, PrefixPicker},
+    compare::{CompareView, Comparison},
+    config::Config,
+    confirm::{Confirm, PendingAction},
+    diff_nav, draft, editor,
+    filter::FileFilter,
+    fixup::FixupPicker,
+    git::{
+        CommitOptions, ConflictSide, DiffOptions, FileStatus, GitBackend, GitFile, GitStatus,
+        Identity, Operation, PullMode, PushOptions, SigningConfig, default_remote,
+        explain_push_error, explain_signing_error, open_backend,
+    },
+    groups::CommitGroups,
+    history::History,
+    ignore::IgnoreMenu,
+    issues::{IssueBrowser, IssueProvider},
+    lfs::{self, LfsPatterns},
+    log::LogView,
+    mouse::ClickAreas,
+    panes::Panes,
+    patch::PatchPrompt,
+    pull_request::{Forges, PullRequestForm, PullRequestHost},
+    rebase::RebaseEditor,
+    reflog::ReflogView,
+    release::ReleaseDraft,
+    remotes::RemotesView,
+    repo::RepoPicker,
+    report::{GitError, MessageLog, Notification},
+    search::DiffSearch,
+    spell::Spelling,
+    submodules::SubmodulesView,
+    tags::{TagInput, TagsView},
+    terminal,
+    trailers::{TrailerPanel, append_trailers},
+    tree::{self, Row, RowKind},
+    undo::EditHistory,
+    validate::{Issue, Severity, validate},
+    watch::Watcher,
+    worktrees::WorktreesView,
+};
+use crossterm::event::{self, Event, KeyEventKind};
+use ratatui::{Terminal, backend::Backend, widgets::ListState};
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+    path::PathBuf,
+    process::Command,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub(crate) const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+#[derive(Debug, PartialEq)]
+pub enum AppMode {
+    FileList,
+    DiffView,
+    CommitMessage,
+    Help,
+    PullMenu,
+    PushDialog,
+    FilterInput,
+    Output,
+    RebasePick,
+    Rebase,
+    FixupPick,
+    CommitOptions,
+    NoRepo,
+    RepoSwitch,
+    Messages,
+    DiffSearch,
+    Log,
+    Tags,
+    Release,
+    Reflog,
+    Remotes,
+    Checks,
+    /// The submodules section of the file list has the keyboard.
+    Submodules,
+    Worktrees,
+    IgnoreMenu,
+    Clean,
+    CopyMenu,
+    CommandBar,
+    NewBranch,
+    Compare,
+    Patch,
+    PullRequest,
+    Ci,
+    Issues,
+    Profiles,
+    Prefixes,
+    Spelling,
+    Groups,
+}
+
+/// A git operation that runs on a worker thread so the UI keeps drawing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskKind {
+    Commit,
+    Push,
+    Fetch,
+    Pull,
+    Continue,
+    Rebase,
+    Fixup,
+    Autosquash,
+    CherryPick,
+    PushTags,
+    Release,
+    /// `no_commit` leaves the revert staged instead of committing it.
+    Revert {
+        no_commit: bool,
+    },
+    Suggest,
+    UpdateSubmodules,
+    /// From the command bar.
+    Command,
+    ApplyPatch,
+    PullRequest,
+    /// One of the groups in the groups view.
+    CommitGroup,
+    Wip,
+}
+
+impl TaskKind {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            TaskKind::Commit => "Committing",
+            TaskKind::Push => "Pushing",
+            TaskKind::Fetch => "Fetching",
+            TaskKind::Pull => "Pulling",
+            TaskKind::Continue => "Continuing",
+            TaskKind::Rebase => "Rebasing",
+            TaskKind::Fixup => "Committing fixup",
+            TaskKind::Autosquash => "Squashing fixups",
+            TaskKind::CherryPick => "Cherry-picking",
+            TaskKind::PushTags => "Pushing tags",
+            TaskKind::Release => "Releasing",
+            TaskKind::Revert { .. } => "Reverting",
+            TaskKind::Suggest => "Writing message",
+            TaskKind::UpdateSubmodules => "Updating submodules",
+            TaskKind::Command => "Running git",
+            TaskKind::ApplyPatch => "Applying patch",
+            TaskKind::PullRequest => "Creating pull request",
+            TaskKind::CommitGroup => "Committing groups",
+            TaskKind::Wip => "Committing WIP",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BackgroundTask {
+    pub kind: TaskKind,
+    pub started: Instant,
+    pub(crate) cancel: Arc<AtomicBool>,
+    receiver: Receiver<Result<String, String>>,
+}
+
+impl BackgroundTask {
+    fn spawn<F>(kind: TaskKind, job: F) -> BackgroundTask
+    where
+        F: FnOnce(&AtomicBool) -> Result<String, String> + Send + 'static,
+    {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let worker_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let _ = sender.send(job(&worker_cancel));
+        });
+
+        BackgroundTask {
+            kind,
+            started: Instant::now(),
+            cancel,
+            receiver,
+        }
+    }
+
+    pub(crate) fn spinner(&self) -> &'static str {
+        let frame = self.started.elapsed().as_millis() / 100;
+        SPINNER_FRAMES[frame as usize % SPINNER_FRAMES.len()]
+    }
+}
+
+/// State of the push dialog while it is open.
+#[derive(Debug, Default)]
+pub struct PushDialog {
+    pub remotes: Vec<String>,
+    pub selected_remote: usize,
+    pub options: PushOptions,
+    /// The remote branch's name while `b` edits it.
+    pub branch_input: Option<String>,
+}
+
+impl PushDialog {
+    pub fn cycle_remote(&mut self, forward: bool) {
+        if self.remotes.is_empty() {
+            return;
+        }
+        let len = self.remotes.len();
+        self.selected_remote = if forward {
+            (self.selected_remote + 1) % len
+        } else {
+            (self.selected_remote + len - 1) % len
+        };
+        self.options.remote = self.remotes[self.selected_remote].clone();
+    }
+}
+
+/// What the diff view is showing, so it can be made again with other diff
+/// options.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffSource {
+    /// The marked files, or else the selected row.
+    Selection,
+    /// Everything staged.
+    Staged,
+    /// A commit's details, and what it changed in one file or all of them.
+    Commit { id: String, path: Option<String> },
+    /// What `head` changed since it forked from `base`.
+    Range { base: String, head: String },
+}
+
+/// Which half of the split layout takes the keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Focus {
+    Files,
+    Diff,
+}
+
+/// A program that needs the whole terminal. Input handlers request one and
+/// [`App::run`] suspends the TUI while it runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum External {
+    CommitEditor,
+    /// A signed commit that may need pinentry to ask for a passphrase.
+    SignedCommit,
+    /// `git mergetool` on one conflicted path.
+    MergeTool(String),
+    /// `git difftool` on one path, against the index or, when `staged`,
+    /// between HEAD and the index.
+    DiffTool {
+        path: String,
+        staged: bool,
+    },
+    /// The message of the annotated tag being created.
+    TagEditor,
+    /// A push that may need to ask for credentials.
+    Push(PushOptions),
+    /// Another gch, in the submodule at this path.
+    Submodule(PathBuf),
+    /// The editor, on a file at a line.
+    EditFile(PathBuf, Option<usize>),
+    /// Stopped with Ctrl+Z until the shell continues it.
+    Suspend,
+    /// An interactive shell at the top of the working tree.
+    Shell,
+}
+
+/// Full output of a failed command, e.g. a rejecting pre-commit hook, shown
+/// in a scrollable pane.
+#[derive(Debug, Default)]
+pub struct OutputPane {
+    pub title: String,
+    pub text: String,
+    pub scroll: u16,
+    /// Where closing it goes; the file list unless set.
+    pub back: Option<AppMode>,
+}
+
+impl OutputPane {
+    pub fn scroll_by(&mut self, lines: i32) {
+        let max = self.text.lines().count().saturating_sub(1) as i32;
+        self.scroll = (self.scroll as i32 + lines).clamp(0, max) as u16;
+    }
+}
+
+pub struct App {
+    pub backend: Arc<dyn GitBackend>,
+    pub config: Config,
+    pub mode: AppMode,
+    /// Every changed file reported by the last refresh. Marks live here.
+    pub all_files: Vec<GitFile>,
+    /// The files shown in the list: `all_files` narrowed by `filter`.
+    pub files: Vec<GitFile>,
+    pub filter: FileFilter,
+    /// What the list shows: one row per file, or a directory tree.
+    pub rows: Vec<Row>,
+    pub tree_view: bool,
+    pub collapsed_dirs: HashSet<String>,
+    /// Index into `rows`.
+    pub selected_row: usize,
+    pub file_list_state: ListState,
+    pub commit_message: String,
+    pub commit_options: CommitOptions,
+    pub signing: SigningConfig,
+    pub identity: Identity,
+    /// The profile selected in [`AppMode::Profiles`].
+    pub profile_selected: usize,
+    pub commit_prefix: String,
+    pub commit_prefixes: Vec<CommitPrefix>,
+    pub selected_prefix: usize,
+    pub prefix_picker: PrefixPicker,
+    /// The first row of the soft-wrapped message shown in the editor.
+    pub commit_scroll: u16,
+    pub spelling: Spelling,
+    pub git_status: GitStatus,
+    pub diff_content: String,
+    pub diff_scroll: u16,
+    pub diff_options: DiffOptions,
+    /// What the diff view shows.
+    pub diff_source: DiffSource,
+    /// Where the diff view goes back to.
+    pub(crate) diff_return: AppMode,
+    pub diff_search: DiffSearch,
+    /// Show the selected file's diff next to the list instead of in its own
+    /// mode.
+    pub split_view: bool,
+    pub focus: Focus,
+    pub panes: Panes,
+    /// Where the last frame drew the parts the mouse can click.
+    pub areas: ClickAreas,
+    /// The time and row of the last click on the file list, to spot double
+    /// clicks.
+    pub(crate) last_click: Option<(Instant, usize)>,
+    pub(crate) watcher: Option<Watcher>,
+    pub(crate) auto_fetch: Option<AutoFetch>,
+    /// When a fetch last succeeded, by hand or in the background.
+    pub last_fetch: Option<Instant>,
+    /// Ahead/behind against `compare_ref`, when one is set.
+    pub comparison: Option<Comparison>,
+    /// Why the last fetch failed, until one succeeds.
+    pub fetch_error: Option<String>,
+    /// Which paths go through Git LFS, reread with the status.
+    pub lfs: LfsPatterns,
+    pub repo_picker: RepoPicker,
+    /// The row whose diff the split view shows, to keep the scroll position
+    /// across refreshes.
+    preview_key: Option<String>,
+    pub notification: Option<Notification>,
+    pub messages: MessageLog,
+    pub should_quit: bool,
+    pub cursor_position: usize,
+    pub task: Option<BackgroundTask>,
+    /// The pre-commit checks, while they run and after one fails.
+    pub checks: Option<ChecksRun>,
+    pub push_dialog: PushDialog,
+    pub confirm: Option<Confirm>,
+    pub output: OutputPane,
+    pub pending_external: Option<External>,
+    /// Text to put on the clipboard; copied by [`App::run`], through the
+    /// terminal when there is no system clipboard.
+    pub pending_copy: Option<String>,
+    /// Where the commit message draft is saved; `None` disables saving.
+    pub git_dir: Option<PathBuf>,
+    pub history: History,
+    /// Undo and redo of the commit message.
+    pub edits: EditHistory,
+    pub rebase: RebaseEditor,
+    pub fixup: FixupPicker,
+    pub log: LogView,
+    pub tags: TagsView,
+    pub release: ReleaseDraft,
+    pub reflog: ReflogView,
+    pub remotes: RemotesView,
+    pub submodules: SubmodulesView,
+    pub worktrees: WorktreesView,
+    pub ignore_menu: IgnoreMenu,
+    pub clean: CleanView,
+    pub groups: CommitGroups,
+    pub command_bar: CommandBar,
+    pub new_branch: NewBranch,
+    pub compare: CompareView,
+    pub patch: Option<PatchPrompt>,
+    pub pull_request: PullRequestForm,
+    pub trailers: TrailerPanel,
+    /// Leave the branch's ticket out of the next commit.
+    pub skip_ticket: bool,
+    /// Writes suggested commit messages; `None` when `Config::ai` is unset.
+    pub suggester: Option<Arc<dyn MessageProvider>>,
+    /// Creates pull and merge requests on the forge of each remote.
+    pub pull_request_host: Arc<dyn PullRequestHost>,
+    /// Polls the pushed commit's checks; `None` when `Config::ci_poll` is.
+    pub ci: Option<CiWatch>,
+    pub ci_provider: Arc<dyn CiProvider>,
+    pub issues: IssueBrowser,
+    pub issue_provider: Arc<dyn IssueProvider>,
+}
+
+impl Default for App {
+    fn default() -> App {
+        App::open(Config::default())
+    }
+}
+
+impl App {
+    /// Opens the repository in the current directory, or the screen for
+    /// finding one if there is none.
+    pub fn open(config: Config) -> App {
+        match open_backend() {
+            Ok(backend) => App::new(backend, config),
+            Err(reason) => App::without_repo(config, reason),
+        }
+    }
+
+    pub fn new(backend: Arc<dyn GitBackend>, config: Config) -> App {
+        let forges = Arc::new(Forges::new(config.forges.clone(), backend.work_dir()));
+        let mut app = App {
+            git_dir: backend.git_dir(),
+            backend,
+            mode: AppMode::FileList,
+            all_files: Vec::new(),
+            files: Vec::new(),
+            filter: FileFilter::default(),
+            rows: Vec::new(),
+            tree_view: false,
+            collapsed_dirs: HashSet::new(),
+            selected_row: 0,
+            file_list_state: ListState::default(),
+            commit_message: String::new(),
+            commit_options: CommitOptions {
+                sign_off: config.sign_off,
+                ..CommitOptions::default()
+            },
+            signing: SigningConfig::default(),
+            identity: Identity::default(),
+            profile_selected: 0,
+            commit_prefix: String::new(),
+            commit_prefixes: config.commit_prefixes.clone(),
+            selected_prefix: 0,
+            prefix_picker: PrefixPicker::default(),
+            commit_scroll: 0,
+            spelling: Spelling::default(),
+            git_status: GitStatus::default(),
+            diff_content: String::new(),
+            diff_scroll: 0,
+            diff_options: config.diff,
+            diff_source: DiffSource::Selection,
+            diff_return: AppMode::FileList,
+            diff_search: DiffSearch::default(),
+            split_view: config.split_view,
+            focus: Focus::Files,
+            panes: config.panes,
+            areas: ClickAreas::default(),
+            last_click: None,
+            watcher: None,
+            auto_fetch: None,
+            last_fetch: None,
+            comparison: None,
+            fetch_error: None,
+            lfs: LfsPatterns::default(),
+            repo_picker: RepoPicker::default(),
+            preview_key: None,
+            notification: None,
+            messages: MessageLog::default(),
+            should_quit: false,
+            cursor_position: 0,
+            task: None,
+            checks: None,
+            push_dialog: PushDialog::default(),
+            confirm: None,
+            output: OutputPane::default(),
+            pending_external: None,
+            pending_copy: None,
+            history: History::default(),
+            edits: EditHistory::default(),
+            rebase: RebaseEditor::default(),
+            fixup: FixupPicker::default(),
+            log: LogView::default(),
+            tags: TagsView::default(),
+            release: ReleaseDraft::default(),
+            reflog: ReflogView::default(),
+            remotes: RemotesView::default(),
+            submodules: SubmodulesView::default(),
+            worktrees: WorktreesView::default(),
+            ignore_menu: IgnoreMenu::default(),
+            clean: CleanView::default(),
+            groups: CommitGroups::default(),
+            command_bar: CommandBar::default(),
+            new_branch: NewBranch::default(),
+            compare: CompareView::default(),
+            patch: None,
+            pull_request: PullRequestForm::default(),
+            trailers: TrailerPanel::default(),
+            skip_ticket: false,
+            suggester: ai::provider_for(config.ai.as_ref()),
+            pull_request_host: forges.clone(),
+            ci: config.ci_poll.map(CiWatch::new),
+            ci_provider: forges.clone(),
+            issues: IssueBrowser::default(),
+            issue_provider: forges,
+            config,
+        };
+        app.file_list_state.select(Some(0));
+        app.signing = app.backend.signing_config();
+        app.identity = app.backend.identity();
+        match Spelling::load(&app.config.dictionaries, app.config.state_dir.as_deref()) {
+            Ok(spelling) => app.spelling = spelling,
+            Err(error) => app.report_error(GitError::new("Loading the dictionary", error)),
+        }
+        app.commit_prefixes = commit_type::repo_prefixes(
+            &app.backend.config_values("gch.prefix"),
+            &app.config.commit_prefixes,
+        );
+        if let Some(git_dir) = &app.git_dir {
+            app.history = History::load(git_dir);
+            app.trailers = TrailerPanel::load(git_dir);
+            app.panes = Panes::load(git_dir, app.config.panes);
+        }
+        if let Some(draft) = app.git_dir.as_deref().and_then(draft::load) {
+            app.cursor_position = draft.len();
+            app.edits = EditHistory::new(&draft);
+            app.commit_message = draft;
+            app.show_notification("Restored unsaved commit message".to_string());
+        }
+        app
+    }
+
+    pub fn run<B: Backend>(mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        self.refresh_git_status();
+        self.remember_repo();
+        self.start_watcher();
+        self.start_auto_fetch();
+
+        loop {
+            terminal.draw(|f| self.draw(f))?;
+
+            if self.should_quit {
+                break;
+            }
+
+            if event::poll(Duration::from_millis(100))? {
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
+                    Event::Mouse(mouse) => self.handle_mouse(mouse),
+                    Event::Paste(text) => self.handle_paste(&text),
+                    _ => {}
+                }
+            }
+
+            if let Some(program) = self.pending_external.take() {
+                terminal::leave()?;
+                self.run_external(program);
+                terminal::enter()?;
+                terminal.clear()?;
+            }
+            if let Some(text) = self.pending_copy.take()
+                && clipboard::copy_to_system(&text).is_err()
+            {
+                let mut stdout = io::stdout();
+                stdout.write_all(clipboard::osc52(&text).as_bytes())?;
+                stdout.flush()?;
+            }
+
+            self.tick();
+        }
+
+        Ok(())
+    }
+
+    /// Housekeeping run once per event-loop iteration.
+    pub fn tick(&mut self) {
+        self.poll_task();
+        self.poll_watcher();
+        self.poll_auto_fetch();
+        self.poll_ci();
+        self.poll_issues();
+        self.poll_checks();
+        self.collect_commands();
+
+        // Clear expired notifications
+        if let Some(notification) = &self.notification
+            && notification.shown.elapsed() > self.config.notification_timeout
+        {
+            self.notification = None;
+        }
+    }
+
+    pub fn refresh_git_status(&mut self) {
+        let marked: HashSet<String> = self
+            .all_files
+            .iter()
+            .filter(|f| f.marked)
+            .map(|f| f.path.clone())
+            .collect();
+
+        self.git_status = self.backend.status();
+        self.reload_comparison();
+        self.lfs = self
+            .backend
+            .work_dir()
+            .map(|dir| LfsPatterns::load(&dir))
+            .unwrap_or_default();
+        self.reload_submodules();
+        self.all_files = self.git_status.files.clone();
+        for file in &mut self.all_files {
+            file.marked = marked.contains(&file.path);
+        }
+        self.apply_filter();
+    }
+
+    /// Rebuilds the visible list from `all_files` and its rows, keeping
+    /// the selection on the same file or directory when it is still shown.
+    pub(crate) fn apply_filter(&mut self) {
+        let selected_key = self
+            .rows
+            .get(self.selected_row)
+            .map(|row| row.key(&self.files).to_string());
+
+        self.files = self
+            .all_files
+            .iter()
+            // Listed in their own section.
+            .filter(|f| self.filter.matches(f) && !self.submodules.contains(&f.path))
+            .cloned()
+            .collect();
+        self.rows = if self.tree_view {
+            tree::tree_rows(&self.files, &self.collapsed_dirs)
+        } else {
+            tree::flat_rows(&self.files)
+        };
+
+        if let Some(index) = selected_key
+            .and_then(|key| self.rows.iter().position(|row| row.key(&self.files) == key))
+        {
+            self.selected_row = index;
+        }
+        self.select_row(self.selected_row.min(self.rows.len().saturating_sub(1)));
+    }
+
+    pub(crate) fn select_row(&mut self, index: usize) {
+        self.selected_row = index;
+        match self.rows.get(index) {
+            Some(_) => self.file_list_state.select(Some(index)),
+            None => {
+                self.selected_row = 0;
+                self.file_list_state.select(None);
+            }
+        }
+        self.update_preview();
+    }
+
+    /// Loads the diff of the row under the cursor for the split view.
+    fn update_preview(&mut self) {
+        if !self.split_view {
+            return;
+        }
+        let key = self
+            .rows
+            .get(self.selected_row)
+            .map(|row| row.key(&self.files).to_string());
+        if key != self.preview_key {
+            self.diff_scroll = 0;
+            self.preview_key = key;
+        }
+        self.diff_content = self
+            .selected_row_files()
+            .into_iter()
+            .filter_map(|index| self.diff_file(&self.files[index]).ok())
+            .collect();
+    }
+
+    pub(crate) fn toggle_split_view(&mut self) {
+        self.split_view = !self.split_view;
+        self.focus = Focus::Files;
+        self.preview_key = None;
+        self.update_preview();
+    }
+
+    pub(crate) fn scroll_diff(&mut self, lines: i32) {
+        let max = self.diff_content.lines().count().saturating_sub(1) as i32;
+        self.diff_scroll = (self.diff_scroll as i32 + lines).clamp(0, max) as u16;
+    }
+
+    /// The file under the cursor, or `None` on a directory row.
+    pub fn selected(&self) -> Option<&GitFile> {
+        match self.rows.get(self.selected_row)?.kind {
+            RowKind::File(index) => self.files.get(index),
+            RowKind::Dir { .. } => None,
+        }
+    }
+
+    pub(crate) fn select_next(&mut self) {
+        if !self.rows.is_empty() {
+            self.select_row((self.selected_row + 1) % self.rows.len());
+        }
+    }
+
+    pub(crate) fn select_previous(&mut self) {
+        if !self.rows.is_empty() {
+            let len = self.rows.len();
+            self.select_row((self.selected_row + len - 1) % len);
+        }
+    }
+
+    pub(crate) fn toggle_tree_view(&mut self) {
+        self.tree_view = !self.tree_view;
+        self.apply_filter();
+    }
+
+    /// Expands or collapses the directory under the cursor. On a file row,
+    /// collapsing jumps to and folds the parent directory.
+    pub(crate) fn set_selected_dir_collapsed(&mut self, collapse: bool) {
+        let Some(row) = self.rows.get(self.selected_row) else {
+            return;
+        };
+        let path = match &row.kind {
+            RowKind::Dir { path, .. } => path.clone(),
+            RowKind::File(index) if collapse && row.depth > 0 => {
+                let file = &self.files[*index].path;
+                match file.trim_end_matches('/').rsplit_once('/') {
+                    Some((parent, _)) => parent.to_string(),
+                    None => return,
+                }
+            }
+            RowKind::File(_) => return,
+        };
+
+        if collapse {
+            self.collapsed_dirs.insert(path.clone());
+        } else {
+            self.collapsed_dirs.remove(&path);
+        }
+        self.apply_filter();
+        if let Some(index) = self
+            .rows
+            .iter()
+            .position(|row| row.key(&self.files) == path)
+        {
+            self.select_row(index);
+        }
+    }
+
+    /// Indices into `files` covered by the row under the cursor.
+    fn selected_row_files(&self) -> Vec<usize> {
+        match self.rows.get(self.selected_row).map(|row| &row.kind) {
+            Some(RowKind::File(index)) => vec![*index],
+            Some(RowKind::Dir { path, .. }) => tree::files_under(&self.files, path),
+            None => Vec::new(),
+        }
+    }
+
+    fn set_selected_mark(&mut self, mark: impl Fn(bool) -> bool) {
+        let paths: HashSet<String> = self
+            .selected_row_files()
+            .into_iter()
+            .map(|index| self.files[index].path.clone())
+            .collect();
+        // A directory row toggles as one: marked only if all children were.
+        let current = self
+            .files
+            .iter()
+            .filter(|f| paths.contains(&f.path))
+            .all(|f| f.marked);
+        for file in self.all_files.iter_mut().chain(self.files.iter_mut()) {
+            if paths.contains(&file.path) {
+                file.marked = mark(current);
+            }
+        }
+    }
+
+    pub(crate) fn toggle_mark(&mut self) {
+        self.set_selected_mark(|marked| !marked);
+    }
+
+    /// Marks the selected row and moves the selection, extending a range.
+    pub(crate) fn extend_marks(&mut self, forward: bool) {
+        self.set_selected_mark(|_| true);
+        if forward {
+            self.select_next();
+        } else {
+            self.select_previous();
+        }
+        self.set_selected_mark(|_| true);
+    }
+
+    pub(crate) fn clear_marks(&mut self) {
+        for file in self.all_files.iter_mut().chain(self.files.iter_mut()) {
+            file.marked = false;
+        }
+    }
+
+    /// The files a file-list action applies to: the marked ones if there
+    /// are any, otherwise the selected file or everything under the
+    /// selected directory.
+    pub(crate) fn target_files(&self) -> Vec<GitFile> {
+        let marked: Vec<GitFile> = self.files.iter().filter(|f| f.marked).cloned().collect();
+        if !marked.is_empty() {
+            return marked;
+        }
+        self.selected_row_files()
+            .into_iter()
+            .map(|index| self.files[index].clone())
+            .collect()
+    }
+
+    /// Toggles staging for the targets. With a mixed selection everything
+    /// gets staged; only a fully staged selection is unstaged.
+    pub(crate) fn toggle_stage_file(&mut self) {
+        let targets = self.target_files();
+        if targets.iter().all(|f| f.staged) {
+            for file in &targets {
+                if let Err(error) = self.unstage_file(file) {
+                    self.report_error(GitError::new(format!("Unstaging {}", file.path), error));
+                }
+            }
+            self.refresh_git_status();
+            return;
+        }
+
+        let paths: Vec<String> = targets.iter().map(|f| f.path.clone()).collect();
+        if !self.confirm_large_files(&targets, PendingAction::Stage(paths.clone())) {
+            self.stage_paths(&paths);
+        }
+    }
+
+    pub(crate) fn stage_paths(&mut self, paths: &[String]) {
+        for path in paths {
+            if let Err(error) = self.backend.stage(path) {
+                self.report_error(GitError::new(format!("Staging {}", path), error));
+            }
+        }
+        self.refresh_git_status();
+    }
+
+    /// Unstages `file`. A staged rename also restores its original path,
+    /// or the deletion half of the rename would stay staged.
+    fn unstage_file(&self, file: &GitFile) -> Result<(), String> {
+        self.backend.unstage(&file.path)?;
+        match &file.orig_path {
+            Some(orig_path) => self.backend.unstage(orig_path),
+            None => Ok(()),
+        }
+    }
+
+    /// `N` in the file list: adds the targeted untracked files with intent
+    /// to add, so their content shows as an unstaged change, or makes
+    /// intent-to-add files untracked again.
+    pub(crate) fn toggle_intent_to_add(&mut self) {
+        let targets = self.target_files();
+        if targets.is_empty() {
+            return;
+        }
+        let undo = targets.iter().all(|f| f.status == FileStatus::IntentToAdd);
+        let paths: Vec<&str> = targets
+            .iter()
+            .filter(|f| f.status == FileStatus::Untracked || undo)
+            .map(|f| f.path.as_str())
+            .collect();
+        if paths.is_empty() {
+            self.show_notification("Intent to add is for untracked files".to_string());
+            return;
+        }
+        let mut failures = Vec::new();
+        for path in &paths {
+            let result = if undo {
+                self.backend.unstage(path)
+            } else {
+                self.backend.intent_to_add(path)
+            };
+            if let Err(error) = result {
+                failures.push(format!("{}: {}", path, error));
+            }
+        }
+        if failures.is_empty() {
+            self.show_notification(match (undo, paths.as_slice()) {
+                (false, [path]) => format!("Added {} with intent to add", path),
+                (false, _) => format!("Added {} files with intent to add", paths.len()),
+                (true, [path]) => format!("{} is untracked again", path),
+                (true, _) => format!("{} files are untracked again", paths.len()),
+            });
+        } else {
+            let action = if undo { "Untracking" } else { "Intent to add" };
+            self.report_error(GitError::new(action, failures.join("\n")));
+        }
+        self.refresh_git_status();
+    }
+
+    pub(crate) fn show_diff(&mut self) {
+        let targets = self.target_files();
+        if targets.is_empty() {
+            return;
+        }
+
+        let diffs = self.diffs_of(&targets);
+        if !diffs.is_empty() {
+            self.open_diff_view(diffs.concat(), DiffSource::Selection);
+        }
+    }
+
+    /// Shows everything staged as one diff, i.e. exactly what the next
+    /// commit will contain. Esc goes back to wherever it was opened from.
+    pub(crate) fn show_staged_diff(&mut self) {
+        let staged = self.staged_files();
+        if staged.is_empty() {
+            self.show_notification("Nothing staged to review".to_string());
+            return;
+        }
+        let diff = self.diffs_of(&staged).concat();
+        self.open_diff_view(diff, DiffSource::Staged);
+    }
+
+    /// Everything staged as one diff, without the display options.
+    pub(crate) fn staged_diff(&self) -> Result<String, String> {
+        let mut diff = String::new();
+        for file in self.all_files.iter().filter(|f| f.staged) {
+            diff.push_str(
+                &self
+                    .backend
+                    .diff(&file.path, true, &DiffOptions::default())?,
+            );
+        }
+        Ok(diff)
+    }
+
+    fn staged_files(&self) -> Vec<GitFile> {
+        self.all_files
+            .iter()
+            .filter(|f| f.staged)
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn open_diff_view(&mut self, diff: String, source: DiffSource) {
+        self.diff_content = diff;
+        self.diff_scroll = 0;
+        self.diff_search = DiffSearch::default();
+        self.diff_source = source;
+        self.diff_return = std::mem::replace(&mut self.mode, AppMode::DiffView);
+    }
+
+    pub(crate) fn close_diff_view(&mut self) {
+        self.mode = std::mem::replace(&mut self.diff_return, AppMode::FileList);
+    }
+
+    pub(crate) fn diffs_of(&mut self, files: &[GitFile]) -> Vec<String> {
+        let mut diffs = Vec::new();
+        for file in files {
+            match self.diff_file(file) {
+                Ok(diff) => diffs.push(diff),
+                Err(error) => {
+                    self.report_error(GitError::new(format!("Diffing {}", file.path), error));
+                }
+            }
+        }
+        diffs
+    }
+
+    /// Opens the commit detail view: metadata, message, and full patch.
+    pub(crate) fn show_commit(&mut self, id: String) {
+        let diff = self.show_commit_diff(&id, None);
+        if !diff.is_empty() {
+            self.open_diff_view(diff, DiffSource::Commit { id, path: None });
+        }
+    }
+
+    /// `H` in the file list: the commit just made, or any HEAD.
+    pub(crate) fn show_head_commit(&mut self) {
+        match self.backend.log(1) {
+            Ok(log) if !log.is_empty() => self.show_commit(log[0].id.clone()),
+            Ok(_) => self.show_notification("No commits yet".to_string()),
+            Err(error) => self.report_error(GitError::new("Reading HEAD", error)),
+        }
+    }
+
+    /// Commit `id` with what it changed, in `path` only if given; empty after
+    /// reporting a failure.
+    pub(crate) fn show_commit_diff(&mut self, id: &str, path: Option<&str>) -> String {
+        match self.backend.show(id, path, &self.diff_options) {
+            Ok(diff) => lfs::describe_pointer_diffs(&diff),
+            Err(error) => {
+                self.report_error(GitError::new(format!("Showing {}", id), error));
+                String::new()
+            }
+        }
+    }
+
+    pub(crate) fn diff_file(&self, file: &GitFile) -> Result<String, String> {
+        if file.status == FileStatus::Untracked {
+            self.backend.diff_untracked(&file.path)
+        } else {
+            self.backend
+                .diff(&file.path, file.staged, &self.diff_options)
+                .map(|diff| lfs::describe_pointer_diffs(&diff))
+        }
+    }
+
+    /// Re-runs the shown diff with new options, keeping the scroll position
+    /// where the diff is still long enough.
+    pub(crate) fn set_diff_options(&mut self, options: DiffOptions) {
+        self.diff_options = options;
+        if self.mode == AppMode::DiffView {
+            self.diff_content = match self.diff_source.clone() {
+                DiffSource::Selection => {
+                    let targets = self.target_files();
+                    self.diffs_of(&targets).concat()
+                }
+                DiffSource::Staged => {
+                    let staged = self.staged_files();
+                    self.diffs_of(&staged).concat()
+                }
+                DiffSource::Commit { id, path } => self.show_commit_diff(&id, path.as_deref()),
+                DiffSource::Range { base, head } => self.range_diff(&base, &head),
+            };
+            self.diff_search.update(&self.diff_content);
+            self.diff_search.select_from(self.diff_scroll as usize);
+        } else {
+            self.update_preview();
+        }
+        self.scroll_diff(0);
+    }
+
+    /// Re-runs the diff search as the query is typed, from the top of the
+    /// visible diff.
+    pub(crate) fn update_diff_search(&mut self) {
+        self.diff_search.update(&self.diff_content);
+        self.diff_search.select_from(self.diff_scroll as usize);
+        self.scroll_to_match();
+    }
+
+    /// `n` / `N` in the diff view.
+    pub(crate) fn step_diff_search(&mut self, forward: bool) {
+        self.diff_search.step(forward);
+        self.scroll_to_match();
+    }
+
+    /// Scrolls the current match into view with a little context above it.
+    fn scroll_to_match(&mut self) {
+        if let Some(line) = self.diff_search.current_line() {
+            self.diff_scroll = line.saturating_sub(2) as u16;
+            self.scroll_diff(0);
+        }
+    }
+
+    pub(crate) fn stage_all(&mut self) {
+        let files = self.all_files.clone();
+        if !self.confirm_large_files(&files, PendingAction::StageAll) {
+            self.stage_all_now();
+        }
+    }
+
+    pub(crate) fn stage_all_now(&mut self) {
+        match self.backend.stage_all() {
+            Ok(()) => self.show_notification("Staged all changes".to_string()),
+            Err(error) => self.report_error(GitError::new("Stage all", error)),
+        }
+        self.refresh_git_status();
+    }
+
+    pub(crate) fn unstage_all(&mut self) {
+        match self.backend.unstage_all() {
+            Ok(()) => self.show_notification("Unstaged all changes".to_string()),
+            Err(error) => self.report_error(GitError::new("Unstage all", error)),
+        }
+        self.refresh_git_status();
+    }
+
+    /// Stages every unstaged file and unstages every staged one.
+    pub(crate) fn invert_staging(&mut self) {
+        let files = self.files.clone();
+        if !self.confirm_large_files(&files, PendingAction::InvertStaging) {
+            self.invert_staging_now();
+        }
+    }
+
+    pub(crate) fn invert_staging_now(&mut self) {
+        let mut failures = Vec::new();
+        for file in &self.files {
+            let result = if file.staged {
+                self.unstage_file(file)
+            } else {
+                self.backend.stage(&file.path)
+            };
+            if let Err(error) = result {
+                failures.push(format!("{}: {}", file.path, error));
+            }
+        }
+        if !failures.is_empty() {
+            let action = format!("Inverting {} file(s)", failures.len());
+            self.report_error(GitError::new(action, failures.join("\n")));
+        }
+        self.refresh_git_status();
+    }
+
+    pub(crate) fn confirm_discard(&mut self) {
+        let targets: Vec<(String, bool)> = self
+            .target_files()
+            .into_iter()
+            .map(|f| {
+                let untracked = matches!(f.status, FileStatus::Untracked | FileStatus::IntentToAdd);
+                (f.path, untracked)
+            })
+            .collect();
+
+        let message = match targets.as_slice() {
+            [] => return,
+            [(path, true)] => format!("Delete untracked {}?", path),
+            [(path, false)] => format!("Discard working-tree changes to {}?", path),
+            many => {
+                let untracked = many.iter().filter(|(_, untracked)| *untracked).count();
+                format!(
+                    "Discard changes to {} marked files ({} untracked will be deleted)?",
+                    many.len(),
+                    untracked
+                )
+            }
+        };
+        self.ask("Discard", message, PendingAction::Discard(targets));
+    }
+
+    /// Resolves the targeted conflicted files by keeping `side`.
+    pub(crate) fn resolve_conflicts(&mut self, side: ConflictSide) {
+        let conflicted: Vec<GitFile> = self
+            .target_files()
+            .into_iter()
+            .filter(|f| f.status == FileStatus::Conflicted)
+            .collect();
+        if conflicted.is_empty() {
+            self.show_notification("No conflicted file selected".to_string());
+            return;
+        }
+
+        let errors: Vec<String> = conflicted
+            .iter()
+            .filter_map(|f| self.backend.resolve_conflict(&f.path, side).err())
+            .collect();
+        match errors.first() {
+            Some(_) => self.report_error(GitError::new("Resolve", errors.join("\n"))),
+            None => self.show_notification(format!(
+                "Resolved {} file(s) using {}",
+                conflicted.len(),
+                side.flag().trim_start_matches('-')
+            )),
+        }
+        self.refresh_git_status();
+    }
+
+    /// `e` in the file list: the selected file in the editor, at its first
+    /// change.
+    pub(crate) fn edit_selected_file(&mut self) {
+        let Some(file) = self.selected().cloned() else {
+            return;
+        };
+        if file.status == FileStatus::Deleted {
+            self.show_notification(format!("{} is deleted", file.path));
+            return;
+        }
+        let position = self
+            .diff_file(&file)
+            .ok()
+            .and_then(|diff| diff_nav::file_line_at(&diff, 0));
+        match position {
+            Some((path, line)) => self.edit_file(&path, Some(line)),
+            None => self.edit_file(file.path.trim_end_matches('/'), None),
+        }
+    }
+
+    /// `e` in the diff view: the file on screen, at the change at the top.
+    pub(crate) fn edit_diff_file(&mut self) {
+        if let DiffSource::Commit { .. } | DiffSource::Range { .. } = self.diff_source {
+            self.show_notification("Open files from the working tree diff".to_string());
+            return;
+        }
+        if let Some((path, line)) =
+            diff_nav::file_line_at(&self.diff_content, self.diff_scroll as usize)
+        {
+            self.edit_file(&path, Some(line));
+        }
+    }
+
+    /// `path` is from the top of the working tree.
+    fn edit_file(&mut self, path: &str, line: Option<usize>) {
+        let Some(root) = self.backend.work_dir() else {
+            return;
+        };
+        self.pending_external = Some(External::EditFile(root.join(path), line));
+    }
+
+    /// `M` in the file list: `git mergetool` on a conflicted file, and
+    /// `git difftool` on any other.
+    pub(crate) fn open_external_tool(&mut self) {
+        let Some(file) = self.selected() else {
+            return;
+        };
+        self.pending_external = match file.status {
+            FileStatus::Conflicted => Some(External::MergeTool(file.path.clone())),
+            FileStatus::Untracked => {
+                self.show_notification("Untracked; git has nothing to compare it with".to_string());
+                return;
+            }
+            _ => Some(External::DiffTool {
+                path: file.path.clone(),
+                staged: file.staged,
+            }),
+        };
+    }
+
+    pub fn conflict_count(&self) -> usize {
+        self.all_files
+            .iter()
+            .filter(|f| f.status == FileStatus::Conflicted)
+            .count()
+    }
+
+    pub(crate) fn continue_operation(&mut self) {
+        let Some(operation) = self.git_status.operation else {
+            return;
+        };
+        let conflicts = self.conflict_count();
+        if conflicts > 0 {
+            self.show_notification(format!("Resolve {} conflicted file(s) first", conflicts));
+            return;
+        }
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Continue, move |cancel| {
+            backend.continue_operation(operation, cancel)
+        });
+    }
+
+    pub(crate) fn confirm_abort(&mut self) {
+        if let Some(operation) = self.git_status.operation {
+            let message = format!(
+                "Abort the {} and throw away its changes?",
+                operation.label().to_lowercase()
+            );
+            self.ask("Abort", message, PendingAction::Abort(operation));
+        }
+    }
+
+    pub(crate) fn abort_operation(&mut self, operation: Operation) {
+        match self.backend.abort_operation(operation) {
+            Ok(()) => self.show_notification(format!("{} aborted", operation.label())),
+            Err(error) => self.report_error(GitError::new("Abort", error)),
+        }
+        self.refresh_git_status();
+    }
+
+    pub(crate) fn confirm_undo_commit(&mut self) {
+        if self.task.is_some() || self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        let Some(head) = self
+            .backend
+            .log(1)
+            .ok()
+            .and_then(|log| log.into_iter().next())
+        else {
+            self.show_notification("No commit to undo".to_string());
+            return;
+        };
+
+        let mut message = format!(
+            "Undo {} \"{}\"? Its changes stay staged.",
+            head.short_id, head.summary
+        );
+        if self.git_status.upstream.is_some() && self.git_status.ahead == 0 {
+            message.push_str(" It has already been pushed.");
+        }
+        self.ask("Undo commit", message, PendingAction::UndoCommit);
+    }
+
+    /// Soft-resets the last commit and reopens its message in the editor.
+    /// A draft already in the editor is kept in the history.
+    pub(crate) fn undo_last_commit(&mut self) {
+        match self.backend.undo_last_commit() {
+            Ok(message) => {
+                if !self.commit_message.trim().is_empty() {
+                    let draft = std::mem::take(&mut self.commit_message);
+                    let _ = self.history.record(self.git_dir.as_deref(), &draft);
+                }
+                self.cursor_position = message.len();
+                self.commit_message = message;
+                self.save_draft();
+                self.mode = AppMode::CommitMessage;
+                self.show_notification(
+                    "Commit undone; its message is back in the editor".to_string(),
+                );
+            }
+            Err(error) => self.report_error(GitError::new("Undo", error)),
+        }
+        self.refresh_git_status();
+    }
+
+    pub(crate) fn discard_files(&mut self, files: &[(String, bool)]) {
+        let errors: Vec<String> = files
+            .iter()
+            .filter_map(|(path, untracked)| {
+                // Checking out an intent-to-add file would empty it; it is
+                // deleted like an untracked one once out of the index.
+                let intent_to_add = self
+                    .all_files
+                    .iter()
+                    .any(|f| f.path == *path && f.status == FileStatus::IntentToAdd);
+                if intent_to_add && let Err(error) = self.backend.unstage(path) {
+                    return Some(error);
+                }
+                self.backend.discard(path, *untracked).err()
+            })
+            .collect();
+
+        match (files, errors.first()) {
+            (_, Some(_)) => self.report_error(GitError::new("Discard", errors.join("\n"))),
+            ([(path, true)], None) => self.show_notification(format!("Deleted {}", path)),
+            ([(path, false)], None) => {
+                self.show_notification(format!("Discarded changes to {}", path))
+            }
+            _ => self.show_notification(format!("Discarded {} files", files.len())),
+        }
+        self.refresh_git_status();
+    }
+
+    pub(crate) fn has_staged_files(&self) -> bool {
+        self.all_files.iter().any(|f| f.staged)
+    }
+
+    /// Problems with the current draft, errors first.
+    pub fn commit_issues(&self) -> Vec<Issue> {
+        validate(&self.commit_message, &self.config.validation)
+    }
+
+    /// The first error that should stop the commit, if blocking is enabled.
+    pub(crate) fn blocking_issue(&self) -> Option<Issue> {
+        if !self.config.validation.block_on_error {
+            return None;
+        }
+        self.commit_issues()
+            .into_iter()
+            .find(|issue| issue.severity == Severity::Error)
+    }
+
+    /// The ticket from the branch name that the next commit will carry.
+    pub fn ticket(&self) -> Option<String> {
+        if self.skip_ticket {
+            return None;
+        }
+        self.config.ticket.find(&self.git_status.current_branch)
+    }
+
+    /// The message as it will be committed: the ticket worked into the
+    /// subject and the trailers appended.
+    pub fn full_commit_message(&self) -> String {
+        let message = match self.ticket() {
+            Some(ticket) => self.config.ticket.apply(&self.commit_message, &ticket),
+            None => self.commit_message.clone(),
+        };
+        append_trailers(&message, &self.trailers.trailers())
+    }
+
+    pub(crate) fn toggle_ticket(&mut self) {
+        if self
+            .config
+            .ticket
+            .find(&self.git_status.current_branch)
+            .is_none()
+        {
+            self.show_notification("No ticket ID in the branch name".to_string());
+            return;
+        }
+        self.skip_ticket = !self.skip_ticket;
+    }
+
+    /// Whether the next commit will be signed, by choice or by
+    /// `commit.gpgsign`.
+    pub fn signs_commit(&self) -> bool {
+        self.commit_options
+            .sign
+            .unwrap_or(self.signing.sign_by_default)
+    }
+
+    pub(crate) fn toggle_signing(&mut self) {
+        if !self.signs_commit() && !self.signing.is_configured() {
+            self.show_notification("Signing is not configured: set user.signingkey".to_string());
+            return;
+        }
+        self.commit_options.sign = Some(!self.signs_commit());
+    }
+
+    /// Commits, once the author's address suits the repository, nothing in
+    /// it looks like a secret, and the configured checks pass. Either
+    /// question leaves the editor open.
+    pub(crate) fn perform_commit(&mut self) {
+        if !self.confirm_identity(PendingAction::CommitAsIdentity) {
+            self.scan_and_commit();
+        }
+    }
+
+    pub(crate) fn scan_and_commit(&mut self) {
+        if !self.confirm_secrets() {
+            self.check_and_commit();
+        }
+    }
+
+    pub(crate) fn check_and_commit(&mut self) {
+        self.mode = AppMode::FileList;
+        if !self.start_checks() {
+            self.commit_now();
+        }
+    }
+
+    pub(crate) fn commit_now(&mut self) {
+        // pinentry needs the terminal, so the TUI steps aside.
+        if self.signs_commit() && self.signing.may_prompt() {
+            self.pending_external = Some(External::SignedCommit);
+            return;
+        }
+        let backend = Arc::clone(&self.backend);
+        let message = self.full_commit_message();
+        let options = self.commit_options.clone();
+        self.start_task(TaskKind::Commit, move |cancel| {
+            backend.commit(&message, &options, cancel)
+        });
+    }
+
+    pub(crate) fn open_push_dialog(&mut self) {
+        if self.git_status.detached() {
+            self.open_new_branch(true);
+            return;
+        }
+        if !self.git_status.on_branch() {
+            self.show_notification("Cannot push: not on a branch".to_string());
+            return;
+        }
+        let branch = self.git_status.current_branch.clone();
+        let remotes = self.backend.remotes();
+        if remotes.is_empty() {
+            self.show_notification("No remotes configured".to_string());
+            return;
+        }
+
+        let selected_remote = default_remote(&remotes, self.git_status.upstream.as_deref());
+
+        self.push_dialog = PushDialog {
+            options: PushOptions {
+                remote: remotes[selected_remote].clone(),
+                branch,
+                set_upstream: self.git_status.upstream.is_none(),
+                ..PushOptions::default()
+            },
+            remotes,
+            selected_remote,
+            branch_input: None,
+        };
+        self.mode = AppMode::PushDialog;
+    }
+
+    /// `B`: pushes a branch without an upstream to the default remote and
+    /// makes that its upstream, i.e. `git push -u origin <branch>`.
+    pub(crate) fn publish_branch(&mut self) {
+        if let Some(upstream) = &self.git_status.upstream {
+            self.show_notification(format!("Already tracking {}; p to push", upstream));
+            return;
+        }
+        if self.git_status.detached() {
+            self.show_notification("HEAD is detached; b to create a branch here".to_string());
+            return;
+        }
+        if !self.git_status.on_branch() {
+            self.show_notification("Cannot publish: not on a branch".to_string());
+            return;
+        }
+        let remotes = self.backend.remotes();
+        if remotes.is_empty() {
+            self.show_notification("No remotes configured; E to add one".to_string());
+            return;
+        }
+        self.confirm_push(PushOptions {
+            remote: remotes[default_remote(&remotes, None)].clone(),
+            branch: self.git_status.current_branch.clone(),
+            set_upstream: true,
+            ..PushOptions::default()
+        });
+    }
+
+    pub(crate) fn push_to_remote(&mut self, options: PushOptions) {
+        if options.in_terminal {
+            self.pending_external = Some(External::Push(options));
+            return;
+        }
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Push, move |cancel| backend.push(&options, cancel));
+    }
+
+    pub(crate) fn fetch(&mut self) {
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Fetch, move |cancel| backend.fetch(cancel));
+    }
+
+    pub(crate) fn pull(&mut self, mode: PullMode) {
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Pull, move |cancel| backend.pull(mode, cancel));
+    }
+
+    pub(crate) fn start_task<F>(&mut self, kind: TaskKind, job: F)
+    where
+        F: FnOnce(&AtomicBool) -> Result<String, String> + Send + 'static,
+    {
+        if self.task.is_some() {
+            self.show_notification("Another git operation is still running".to_string());
+            return;
+        }
+        self.task = Some(BackgroundTask::spawn(kind, job));
+    }
+
+    /// Picks up the result of the background task, if it has finished.
+    pub fn poll_task(&mut self) {
+        let Some(task) = &self.task else {
+            return;
+        };
+
+        let result = match task.receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("worker thread exited".to_string()),
+        };
+        let kind = task.kind;
+        let cancelled = task.cancel.load(Ordering::Relaxed);
+        self.task = None;
+
+        match (kind, result) {
+            (_, Err(_)) if cancelled => {
+                if kind == TaskKind::CommitGroup {
+                    self.groups.requeue();
+                }
+                self.show_notification(format!("{} cancelled", kind.label()));
+            }
+            (TaskKind::Commit, result) => self.finish_commit(result),
+            (TaskKind::CommitGroup, result) => self.finish_commit_group(result),
+            (TaskKind::Wip, result) => self.finish_wip(result),
+            (TaskKind::Push, result) => self.finish_push(result),
+            (TaskKind::Fetch, Ok(_)) => {
+                self.last_fetch = Some(Instant::now());
+                self.fetch_error = None;
+                self.show_notification("Fetch complete".to_string());
+            }
+            (TaskKind::Fetch, Err(error)) => {
+                self.fetch_error = Some(error.clone());
+                self.report_error(GitError::new("Fetch", error));
+            }
+            (TaskKind::Continue, Ok(_)) => {
+                self.show_notification("Continued".to_string());
+            }
+            (TaskKind::Continue, Err(error)) if error.lines().count() > 1 => {
+                self.log_error(GitError::new("Continue", error.clone()));
+                self.show_output("Continue failed", error);
+            }
+            (TaskKind::Continue, Err(error)) => {
+                self.report_error(GitError::new("Continue", error));
+            }
+            (TaskKind::Suggest, Ok(message)) => self.insert_suggestion(message),
+            (TaskKind::Suggest, Err(error)) => {
+                self.report_error(GitError::new("Suggestion", error));
+            }
+            (TaskKind::Fixup, Ok(_)) => self.show_notification(
+                "Fixup committed; press Z to squash it into its target".to_string(),
+            ),
+            (TaskKind::Fixup, Err(error)) if error.lines().count() > 1 => {
+                self.log_error(GitError::new("Fixup commit", error.clone()));
+                self.show_output("Fixup commit failed", error);
+            }
+            (TaskKind::Fixup, Err(error)) => {
+                self.report_error(GitError::new("Fixup commit", error));
+            }
+            (TaskKind::Rebase | TaskKind::Autosquash, Ok(_)) => {
+                self.show_notification("Rebase complete".to_string());
+            }
+            (TaskKind::Rebase | TaskKind::Autosquash, Err(error)) => {
+                self.refresh_git_status();
+                if self.git_status.operation == Some(Operation::Rebase) {
+                    self.show_notification(
+                        "Rebase stopped: resolve conflicts, then C to continue".to_string(),
+                    );
+                } else {
+                    self.log_error(GitError::new("Rebase", error.clone()));
+                    self.show_output("Rebase failed", error);
+                }
+            }
+            (TaskKind::CherryPick, Ok(_)) => {
+                self.show_notification("Cherry-pick complete".to_string());
+            }
+            (TaskKind::Command, result) => self.finish_command(result),
+            (TaskKind::ApplyPatch, result) => self.finish_apply_patch(result),
+            (TaskKind::PullRequest, result) => self.finish_pull_request(result),
+            (TaskKind::UpdateSubmodules, Ok(_)) => {
+                self.show_notification("Submodules updated".to_string());
+            }
+            (TaskKind::UpdateSubmodules, Err(error)) => {
+                self.report_error(GitError::new("Updating submodules", error));
+            }
+            (TaskKind::PushTags, Ok(_)) => self.show_notification("Tags pushed".to_string()),
+            (TaskKind::PushTags, Err(error)) => {
+                self.report_error(GitError::new("Pushing tags", error));
+            }
+            (TaskKind::Release, Ok(tag)) => {
+                self.show_notification(format!("Released {}; T to push the tag", tag));
+            }
+            (TaskKind::Release, Err(error)) if error.lines().count() > 1 => {
+                self.log_error(GitError::new("Release", error.clone()));
+                self.show_output("Release failed", error);
+            }
+            (TaskKind::Release, Err(error)) => self.report_error(GitError::new("Release", error)),
+            (TaskKind::Revert { no_commit: false }, Ok(_)) => {
+                self.show_notification("Revert committed".to_string());
+            }
+            (TaskKind::Revert { no_commit: true }, Ok(_)) => {
+                self.show_notification("Revert staged; review it, then c to commit".to_string());
+            }
+            (TaskKind::CherryPick | TaskKind::Revert { .. }, Err(error)) => {
+                let operation = match kind {
+                    TaskKind::CherryPick => Operation::CherryPick,
+                    _ => Operation::Revert,
+                };
+                self.refresh_git_status();
+                if self.git_status.operation == Some(operation) {
+                    self.show_warning(format!(
+                        "{} stopped: resolve conflicts, then C to continue or X to abort",
+                        operation.label()
+                    ));
+                } else {
+                    self.log_error(GitError::new(operation.label(), error.clone()));
+                    self.show_output(&format!("{} failed", operation.label()), error);
+                }
+            }
+            (TaskKind::Pull, Ok(_)) => self.show_notification("Pull complete".to_string()),
+            (TaskKind::Pull, Err(error)) => {
+                let conflicts = error.lines().filter(|l| l.starts_with("CONFLICT")).count();
+                if conflicts > 0 {
+                    self.show_warning(format!(
+                        "Pull stopped: {} conflicting file(s) need resolving",
+                        conflicts
+                    ));
+                } else {
+                    self.report_error(GitError::new("Pull", error));
+                }
+            }
+        }
+        self.refresh_git_status();
+    }
+
+    fn finish_commit(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(_) => {
+                let message = std::mem::take(&mut self.commit_message);
+                let _ = self.history.record(self.git_dir.as_deref(), &message);
+                self.show_notification("Commit successful (H to view it)".to_string());
+                self.cursor_position = 0;
+                self.commit_options = CommitOptions {
+                    sign_off: self.config.sign_off,
+                    ..CommitOptions::default()
+                };
+                self.skip_ticket = false;
+                if let Err(e) = self.trailers.finish_commit(self.git_dir.as_deref()) {
+                    self.show_warning(format!("Could not save co-authors: {}", e));
+                }
+                self.save_draft();
+            }
+            Err(error) if self.signs_commit() && explain_signing_error(&error).is_some() => {
+                let hint = explain_signing_error(&error).unwrap_or_default();
+                self.log_error(GitError::new("Commit", error.clone()));
+                self.show_output("Signing failed", format!("{}\n\n{}", hint, error));
+            }
+            // Hooks can print a lot; show all of it rather than the first
+            // line in a notification.
+            Err(error) if error.lines().count() > 1 => {
+                self.log_error(GitError::new("Commit", error.clone()));
+                self.show_output("Commit failed", error);
+            }
+            Err(error) => self.report_error(GitError::new("Commit", error)),
+        }
+    }
+
+    fn finish_push(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(_) => {
+                if let Some(ci) = &mut self.ci {
+                    ci.poll_soon();
+                }
+                self.show_notification("Push successful; o to open a pull request".to_string());
+            }
+            Err(error) if explain_push_error(&error).is_some() => {
+                let hint = explain_push_error(&error).unwrap_or_default();
+                self.log_error(GitError::new("Push", error.clone()));
+                self.show_output("Push needs credentials", format!("{}\n\n{}", hint, error));
+            }
+            Err(error) => self.report_error(GitError::new("Push", error)),
+        }
+    }
+
+    /// Runs `program` in the foreground. The terminal must already be out
+    /// of raw mode.
+    pub fn run_external(&mut self, program: External) {
+        match program {
+            External::CommitEditor => {
+                let path =
+                    std::env::temp_dir().join(format!("gch-{}-COMMIT_EDITMSG", std::process::id()));
+                match editor::edit_text(&editor::resolve_editor(), &self.commit_message, &path) {
+                    Ok(message) if message.is_empty() => {
+                        self.show_notification("Empty message; kept the draft".to_string());
+                    }
+                    Ok(message) => {
+                        self.cursor_position = message.len();
+                        self.commit_message = message;
+                        self.save_draft();
+                    }
+                    Err(error) => self.show_warning(format!("Editor failed: {}", error)),
+                }
+            }
+            External::SignedCommit => {
+                let result = self
+                    .backend
+                    .commit_foreground(&self.full_commit_message(), &self.commit_options);
+                self.finish_commit(result);
+                self.refresh_git_status();
+            }
+            External::Push(options) => {
+                let result = self.backend.push_foreground(&options);
+                self.finish_push(result);
+                self.refresh_git_status();
+            }
+            External::TagEditor => {
+                let Some(TagInput {
+                    message: Some(message),
+                    ..
+                }) = &mut self.tags.input
+                else {
+                    return;
+                };
+                let path =
+                    std::env::temp_dir().join(format!("gch-{}-TAG_EDITMSG", std::process::id()));
+                match editor::edit_text(&editor::resolve_editor(), message, &path) {
+                    Ok(edited) if edited.is_empty() => {}
+                    Ok(edited) => *message = edited,
+                    Err(error) => self.show_warning(format!("Editor failed: {}", error)),
+                }
+            }
+            External::Submodule(path) => {
+                match std::env::current_exe().and_then(|gch| Command::new(gch).arg(&path).status())
+                {
+                    Ok(status) if status.success() => {}
+                    Ok(status) => self.show_warning(format!("gch exited with {}", status)),
+                    Err(e) => {
+                        self.show_warning(format!("Could not open {}: {}", path.display(), e))
+                    }
+                }
+                self.refresh_git_status();
+            }
+            External::EditFile(path, line) => {
+                if let Err(error) = editor::open_file(&editor::resolve_editor(), &path, line) {
+                    self.show_warning(format!("Editor failed: {}", error));
+                }
+                self.refresh_git_status();
+                if self.mode == AppMode::DiffView {
+                    self.set_diff_options(self.diff_options);
+                }
+            }
+            External::Suspend => {
+                if let Err(e) = terminal::suspend() {
+                    self.show_warning(format!("Could not suspend: {}", e));
+                }
+                self.refresh_git_status();
+            }
+            External::Shell => {
+                let dir = self
+                    .backend
+                    .work_dir()
+                    .or_else(|| std::env::current_dir().ok())
+                    .unwrap_or_default();
+                if let Err(e) = terminal::run_shell(&dir) {
+                    self.show_warning(format!("Could not start {}: {}", terminal::user_shell(), e));
+                }
+                self.refresh_git_status();
+            }
+            External::DiffTool { path, staged } => {
+                let mut args = vec!["difftool", "--no-prompt"];
+                if staged {
+                    args.push("--cached");
+                }
+                args.extend(["--", &path]);
+                match Command::new("git").args(&args).status() {
+                    Ok(status) if status.success() => {}
+                    Ok(status) => self.show_warning(format!("difftool exited with {}", status)),
+                    Err(e) => self.show_warning(format!("Could not run difftool: {}", e)),
+                }
+                self.refresh_git_status();
+            }
+            External::MergeTool(path) => {
+                match Command::new("git")
+                    .args(["mergetool", "--", &path])
+                    .status()
+                {
+                    Ok(status) if status.success() => {}
+                    Ok(status) => self.show_warning(format!("mergetool exited with {}", status)),
+                    Err(e) => self.show_warning(format!("Could not run mergetool: {}", e)),
+                }
+                self.refresh_git_status();
+            }
+        }
+    }
+
+    /// Writes the commit message to the draft file, or removes the file
+    /// once the message is empty.
+    ///
+    /// Every change to the message comes through here, so it is also where
+    /// undo steps are taken.
+    pub(crate) fn save_draft(&mut self) {
+        self.edits.record(&self.commit_message);
+        if let Some(git_dir) = &self.git_dir
+            && let Err(e) = draft::save(git_dir, &self.commit_message)
+        {
+            self.show_warning(format!("Could not save draft: {}", e));
+        }
+    }
+
+    pub(crate) fn show_output(&mut self, title: &str, text: String) {
+        self.output = OutputPane {
+            title: title.to_string(),
+            text,
+            scroll: 0,
+            back: None,
+        };
+        self.mode = AppMode::Output;
+    }
+}
@@ -0,0 +1,80 @@
+//! Command-line arguments for the `gch` binary.
+
+use clap::{Parser, Subcommand};
+use std::{io, path::PathBuf};
+
+/// Stage files and write commit messages from a terminal UI.
+#[derive(Debug, Parser)]
+#[command(name = "gch", version, about)]
+pub struct Args {
+    /// Repository to open, instead of the current directory.
+    pub path: Option<PathBuf>,
+    /// Path to the repository's `.git` directory, as with `git --git-dir`.
+    #[arg(long, value_name = "DIR")]
+    pub git_dir: Option<PathBuf>,
+    /// Path to the working tree, as with `git --work-tree`.
+    #[arg(long, value_name = "DIR")]
+    pub work_tree: Option<PathBuf>,
+    /// Run without the UI instead.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands for scripts and CI.
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum Command {
+    /// Print the branch and changed files.
+    Status {
+        /// Print JSON instead of `git status --short` style text.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Commit the staged changes.
+    Commit {
+        #[arg(short, long)]
+        message: String,
+        /// Stage every change first, like `git add -A`.
+        #[arg(long)]
+        stage_all: bool,
+        /// Push to the tracked remote afterwards.
+        #[arg(long)]
+        push: bool,
+        /// Skip the pre-commit and commit-msg hooks.
+        #[arg(long)]
+        no_verify: bool,
+        /// Add a `Signed-off-by` trailer.
+        #[arg(short, long)]
+        signoff: bool,
+        /// Commit even with nothing staged, e.g. to trigger CI.
+        #[arg(long)]
+        allow_empty: bool,
+    },
+}
+
+impl Args {
+    /// Moves into `path` like `git -C`, then resolves `--git-dir` and
+    /// `--work-tree` against it. The backends open the repository from the
+    /// current directory and the `GIT_DIR` and `GIT_WORK_TREE` variables, so
+    /// this must run before one is opened.
+    ///
+    /// # Safety
+    ///
+    /// Sets environment variables, so no other threads may be running.
+    pub unsafe fn apply(&self) -> io::Result<()> {
+        if let Some(path) = &self.path {
+            std::env::set_current_dir(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        }
+        for (var, dir) in [
+            ("GIT_DIR", &self.git_dir),
+            ("GIT_WORK_TREE", &self.work_tree),
+        ] {
+            if let Some(dir) = dir {
+                let dir = std::path::absolute(dir)?;
+                // SAFETY: the caller guarantees no other threads are running.
+                unsafe { std::env::set_var(var, dir) };
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+//! Fetching on a timer in the background, so the ahead/behind counts keep
+//! up with the remote without pressing `f`.
+
+use crate::{
+    app::{App, AppMode},
+    git::GitBackend,
+    report::GitError,
+};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub struct AutoFetch {
+    interval: Duration,
+    /// When the last fetch started; `None` until the first one.
+    last_started: Option<Instant>,
+    /// The fetch in progress.
+    running: Option<Receiver<Result<String, String>>>,
+    // Raised on drop, so a fetch still running for a repository that was
+    // switched away from gives up.
+    cancel: Arc<AtomicBool>,
+}
+
+impl AutoFetch {
+    pub fn new(interval: Duration) -> AutoFetch {
+        AutoFetch {
+            interval,
+            last_started: None,
+            running: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether the next fetch should start: straight away at first, then an
+    /// interval after the previous one started.
+    pub fn is_due(&self) -> bool {
+        self.running.is_none()
+            && self
+                .last_started
+                .is_none_or(|time| time.elapsed() >= self.interval)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    pub fn start(&mut self, backend: Arc<dyn GitBackend>) {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::clone(&self.cancel);
+        thread::spawn(move || {
+            let _ = sender.send(backend.fetch(&cancel));
+        });
+        self.running = Some(receiver);
+        self.last_started = Some(Instant::now());
+    }
+
+    /// The result of the fetch in progress, once it has finished.
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        let result = match self.running.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("worker thread exited".to_string()),
+        };
+        self.running = None;
+        Some(result)
+    }
+}
+
+impl Drop for AutoFetch {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// How long ago something happened, briefly: `just now`, `5m ago`, `2h ago`.
+pub fn ago(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        0..60 => "just now".to_string(),
+        secs @ 60..3600 => format!("{}m ago", secs / 60),
+        secs @ 3600..86400 => format!("{}h ago", secs / 3600),
+        secs => format!("{}d ago", secs / 86400),
+    }
+}
+
+impl App {
+    /// Starts fetching on a timer, if the config asks for it.
+    pub fn start_auto_fetch(&mut self) {
+        if let Some(interval) = self.config.auto_fetch
+            && self.mode != AppMode::NoRepo
+        {
+            self.auto_fetch = Some(AutoFetch::new(interval));
+        }
+    }
+
+    pub(crate) fn poll_auto_fetch(&mut self) {
+        let Some(auto_fetch) = &mut self.auto_fetch else {
+            return;
+        };
+        match auto_fetch.poll() {
+            Some(result) => self.finish_fetch(result),
+            // A fetch of its own could get in the way of a push or pull.
+            None if auto_fetch.is_due() && self.task.is_none() => {
+                auto_fetch.start(Arc::clone(&self.backend));
+            }
+            None => {}
+        }
+    }
+
+    /// Records how a background fetch went. Failures are logged once and
+    /// then only flagged in the header, rather than interrupting each time.
+    fn finish_fetch(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(_) => {
+                self.last_fetch = Some(Instant::now());
+                self.fetch_error = None;
+                // A running task refreshes the status when it finishes.
+                if self.task.is_none() {
+                    self.refresh_git_status();
+                }
+            }
+            Err(error) => {
+                if self.fetch_error.as_ref() != Some(&error) {
+                    self.log_error(GitError::new("Background fetch", error.clone()));
+                }
+                self.fetch_error = Some(error);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Creating a branch at HEAD from the file list, which is also the way out
+//! of a detached HEAD before pushing.
+
+use crate::{
+    app::{App, AppMode},
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Default)]
+pub struct NewBranch {
+    pub input: String,
+    /// Whether the push dialog opens once the branch exists, when `p` was
+    /// pressed on a detached HEAD.
+    pub then_push: bool,
+}
+
+impl App {
+    /// `b` in the file list.
+    pub(crate) fn open_new_branch(&mut self, then_push: bool) {
+        self.new_branch = NewBranch {
+            input: String::new(),
+            then_push,
+        };
+        self.mode = AppMode::NewBranch;
+    }
+
+    pub(crate) fn handle_new_branch_input(&mut self, key: KeyCode) {
+        let input = &mut self.new_branch.input;
+        match key {
+            KeyCode::Esc => self.mode = AppMode::FileList,
+            KeyCode::Enter => {
+                let name = input.trim().to_string();
+                self.mode = AppMode::FileList;
+                if !name.is_empty() {
+                    self.create_branch(&name);
+                }
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(input, input.len());
+                input.truncate(end);
+            }
+            // Branch names can't contain spaces.
+            KeyCode::Char(' ') => {}
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    fn create_branch(&mut self, name: &str) {
+        let result = self.backend.create_branch(name);
+        self.refresh_git_status();
+        match result {
+            Ok(()) if self.new_branch.then_push => self.open_push_dialog(),
+            Ok(()) => self.show_notification(format!("Switched to new branch {}", name)),
+            Err(error) => {
+                self.report_error(GitError::new(format!("Creating branch {}", name), error));
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Conventional Commits breaking changes: `F8` in the commit editor marks
+//! the type with `!` (`feat!: …`) and adds a `BREAKING CHANGE:` footer to
+//! describe it, or takes both out again.
+
+use crate::app::App;
+
+/// The footer token, and the hyphenated form the spec also accepts.
+pub const BREAKING_FOOTERS: [&str; 2] = ["BREAKING CHANGE:", "BREAKING-CHANGE:"];
+
+/// Where the `:` ending the subject's `type(scope)` prefix is, if it has one.
+fn type_end(subject: &str) -> Option<usize> {
+    let end = subject.find(':')?;
+    let prefix = &subject[..end];
+    let name = prefix.split('(').next().unwrap_or_default();
+    let is_type = !name.is_empty()
+        && name
+            .trim_end_matches('!')
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric())
+        && !prefix.contains(char::is_whitespace);
+    is_type.then_some(end)
+}
+
+/// Whether a footer line opens a breaking change description.
+pub fn is_breaking_footer(line: &str) -> bool {
+    BREAKING_FOOTERS.iter().any(|token| line.starts_with(token))
+}
+
+/// Whether `message` marks its type with `!` or has a breaking change footer.
+pub fn is_breaking(message: &str) -> bool {
+    let subject = message.lines().next().unwrap_or_default();
+    let marked = type_end(subject).is_some_and(|end| subject[..end].ends_with('!'));
+    marked || message.lines().skip(1).any(is_breaking_footer)
+}
+
+/// `message` marked as a breaking change, with an empty footer to fill in
+/// unless it has one; `None` without a `type:` prefix to mark.
+pub fn mark_breaking(message: &str) -> Option<String> {
+    let end = type_end(message.lines().next().unwrap_or_default())?;
+    let mut marked = message.to_string();
+    if !message[..end].ends_with('!') {
+        marked.insert(end, '!');
+    }
+    if !message.lines().skip(1).any(is_breaking_footer) {
+        marked.truncate(marked.trim_end().len());
+        marked.push_str("\n\n");
+        marked.push_str(BREAKING_FOOTERS[0]);
+        marked.push(' ');
+    }
+    Some(marked)
+}
+
+/// `message` without the `!` and the breaking change footer, up to the next
+/// footer or the end.
+pub fn unmark_breaking(message: &str) -> String {
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default();
+    let subject = match type_end(subject) {
+        Some(end) if subject[..end].ends_with('!') => {
+            format!("{}{}", &subject[..end - 1], &subject[end..])
+        }
+        _ => subject.to_string(),
+    };
+    let mut kept = vec![subject];
+    let mut in_footer = false;
+    for line in lines {
+        if is_breaking_footer(line) {
+            in_footer = true;
+        } else if in_footer && is_footer(line) {
+            in_footer = false;
+        }
+        if !in_footer {
+            kept.push(line.to_string());
+        }
+    }
+    kept.join("\n").trim_end().to_string()
+}
+
+/// A `Token: value` or `Token #value` footer line.
+fn is_footer(line: &str) -> bool {
+    let Some(end) = line.find([':', '#']) else {
+        return false;
+    };
+    let token = line[..end].trim_end();
+    !token.is_empty() && !token.contains(char::is_whitespace)
+}
+
+impl App {
+    /// `F8` in the commit editor.
+    pub(crate) fn toggle_breaking(&mut self) {
+        if is_breaking(&self.commit_message) {
+            self.commit_message = unmark_breaking(&self.commit_message);
+            self.cursor_position = self.cursor_position.min(self.commit_message.len());
+            while !self.commit_message.is_char_boundary(self.cursor_position) {
+                self.cursor_position -= 1;
+            }
+            return;
+        }
+        match mark_breaking(&self.commit_message) {
+            Some(marked) => {
+                self.commit_message = marked;
+                self.cursor_position = self.commit_message.len();
+            }
+            None => self.show_notification(
+                "Breaking changes need a Conventional Commits type, e.g. feat: …".to_string(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Checks run before each commit, such as `cargo fmt --check` or `npm test`.
+//! Their output streams into a pane, and a failure holds the commit back
+//! until it is fixed or overridden.
+
+use crate::app::{App, AppMode};
+use crossterm::event::KeyCode;
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Running,
+    Passed,
+    /// The command at `current` failed.
+    Failed,
+}
+
+enum CheckEvent {
+    Started(usize),
+    Line(String),
+    Finished(Result<(), String>),
+}
+
+/// One run of the configured checks.
+pub struct ChecksRun {
+    pub commands: Vec<String>,
+    /// The command running, or the one that failed.
+    pub current: usize,
+    pub state: CheckState,
+    /// Everything the commands printed, with a `$ command` line before each.
+    pub output: String,
+    pub scroll: u16,
+    /// Keep the newest output in view; scrolling up stops it.
+    pub follow: bool,
+    pub started: Instant,
+    receiver: Receiver<CheckEvent>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ChecksRun {
+    /// Starts running `commands` one after another in `dir` through `sh -c`,
+    /// stopping at the first that fails.
+    pub fn start(commands: Vec<String>, dir: Option<PathBuf>) -> ChecksRun {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_commands = commands.clone();
+        let worker_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            for (index, command) in worker_commands.iter().enumerate() {
+                let _ = sender.send(CheckEvent::Started(index));
+                if let Err(error) = run_check(command, dir.as_deref(), &sender, &worker_cancel) {
+                    let _ = sender.send(CheckEvent::Finished(Err(error)));
+                    return;
+                }
+            }
+            let _ = sender.send(CheckEvent::Finished(Ok(())));
+        });
+        ChecksRun {
+            commands,
+            current: 0,
+            state: CheckState::Running,
+            output: String::new(),
+            scroll: 0,
+            follow: true,
+            started: Instant::now(),
+            receiver,
+            cancel,
+        }
+    }
+
+    /// Takes in whatever the commands printed since the last call; returns
+    /// the result once the run is over.
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        if self.state != CheckState::Running {
+            return None;
+        }
+        loop {
+            let event = match self.receiver.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    CheckEvent::Finished(Err("checks thread exited".to_string()))
+                }
+            };
+            match event {
+                CheckEvent::Started(index) => {
+                    self.current = index;
+                    self.output
+                        .push_str(&format!("$ {}\n", self.commands[index]));
+                }
+                CheckEvent::Line(line) => {
+                    self.output.push_str(&line);
+                    self.output.push('\n');
+                }
+                CheckEvent::Finished(result) => {
+                    self.state = match result {
+                        Ok(()) => CheckState::Passed,
+                        Err(_) => CheckState::Failed,
+                    };
+                    return Some(result);
+                }
+            }
+        }
+    }
+
+    pub fn scroll_by(&mut self, lines: i32) {
+        let max = self.output.lines().count().saturating_sub(1) as i32;
+        self.scroll = (self.scroll as i32 + lines).clamp(0, max) as u16;
+        self.follow = false;
+    }
+}
+
+impl Drop for ChecksRun {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Runs one check, sending its output line by line.
+fn run_check(
+    command: &str,
+    dir: Option<&Path>,
+    sender: &Sender<CheckEvent>,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    let mut process = Command::new("sh");
+    process
+        .args(["-c", command])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = dir {
+        process.current_dir(dir);
+    }
+    let mut child = process.spawn().map_err(|e| format!("{}: {}", command, e))?;
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|out| forward_lines(out, sender.clone())),
+        child
+            .stderr
+            .take()
+            .map(|err| forward_lines(err, sender.clone())),
+    ];
+
+    let status = loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("cancelled".to_string());
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+    // All of its output goes in before the verdict.
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed ({})", command, status))
+    }
+}
+
+fn forward_lines<R: Read + Send + 'static>(
+    pipe: R,
+    sender: Sender<CheckEvent>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            let _ = sender.send(CheckEvent::Line(line));
+        }
+    })
+}
+
+impl App {
+    /// Runs the configured checks, then commits if they pass. Returns false
+    /// when there are none to run, or `--no-verify` skips them.
+    pub(crate) fn start_checks(&mut self) -> bool {
+        if self.config.checks.is_empty() || self.commit_options.no_verify {
+            return false;
+        }
+        self.checks = Some(ChecksRun::start(
+            self.config.checks.clone(),
+            self.backend.work_dir(),
+        ));
+        self.mode = AppMode::Checks;
+        true
+    }
+
+    pub(crate) fn poll_checks(&mut self) {
+        let Some(run) = &mut self.checks else {
+            return;
+        };
+        match run.poll() {
+            Some(Ok(())) => {
+                self.checks = None;
+                if self.mode == AppMode::Checks {
+                    self.mode = AppMode::FileList;
+                }
+                self.commit_now();
+            }
+            Some(Err(error)) => {
+                self.show_warning(format!("Commit held back: {}", error));
+            }
+            None => {}
+        }
+    }
+
+    pub(crate) fn handle_checks_input(&mut self, key: KeyCode) {
+        let Some(run) = &mut self.checks else {
+            self.mode = AppMode::FileList;
+            return;
+        };
+        match key {
+            // Stops a run in progress too; the message stays in the editor.
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.checks = None;
+                self.mode = AppMode::CommitMessage;
+            }
+            KeyCode::Char('c') if run.state == CheckState::Failed => {
+                self.checks = None;
+                self.mode = AppMode::FileList;
+                self.commit_now();
+            }
+            KeyCode::Char('r') if run.state == CheckState::Failed => {
+                self.start_checks();
+            }
+            KeyCode::Down | KeyCode::Char('j') => run.scroll_by(1),
+            KeyCode::Up | KeyCode::Char('k') => run.scroll_by(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => run.scroll_by(10),
+            KeyCode::PageUp => run.scroll_by(-10),
+            KeyCode::Home | KeyCode::Char('g') => run.scroll_by(i32::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => run.follow = true,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! The CI status of the pushed branch: the forge's checks for the commit
+//! the upstream points at, polled in the background while a token for the
+//! forge is configured, shown in the header and listed with `w`.
+
+use crate::{
+    app::{App, AppMode},
+    pull_request::{Forge, Forges, api_repo_url, remote_repo},
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+use serde_json::Value;
+use std::{
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiState {
+    Pending,
+    Success,
+    Failure,
+    /// Skipped, cancelled, or allowed to fail; it doesn't decide the result.
+    Neutral,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CiCheck {
+    pub name: String,
+    pub state: CiState,
+    /// Where the forge shows its log.
+    pub url: Option<String>,
+}
+
+/// The checks of one commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CiStatus {
+    pub commit: String,
+    pub checks: Vec<CiCheck>,
+}
+
+impl CiStatus {
+    /// Failed if any check failed, else pending if any is still running,
+    /// else passed; `None` without checks.
+    pub fn overall(&self) -> Option<CiState> {
+        let states = || self.checks.iter().map(|check| check.state);
+        if self.checks.is_empty() {
+            None
+        } else if states().any(|state| state == CiState::Failure) {
+            Some(CiState::Failure)
+        } else if states().any(|state| state == CiState::Pending) {
+            Some(CiState::Pending)
+        } else {
+            Some(CiState::Success)
+        }
+    }
+
+    pub fn count(&self, state: CiState) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.state == state)
+            .count()
+    }
+}
+
+fn text(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn url(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+}
+
+/// GitHub's check runs, from `/commits/{sha}/check-runs`.
+pub fn parse_check_runs(reply: &Value) -> Vec<CiCheck> {
+    let runs = reply.get("check_runs").and_then(Value::as_array);
+    runs.into_iter()
+        .flatten()
+        .map(|run| CiCheck {
+            name: text(run, "name"),
+            state: match (
+                text(run, "status").as_str(),
+                text(run, "conclusion").as_str(),
+            ) {
+                ("completed", "success") => CiState::Success,
+                ("completed", "neutral" | "skipped" | "cancelled" | "stale") => CiState::Neutral,
+                ("completed", _) => CiState::Failure,
+                _ => CiState::Pending,
+            },
+            url: url(run, "html_url"),
+        })
+        .collect()
+}
+
+/// Commit statuses from GitHub's or Gitea's `/commits/{sha}/status`.
+pub fn parse_commit_statuses(reply: &Value) -> Vec<CiCheck> {
+    let statuses = reply.get("statuses").and_then(Value::as_array);
+    statuses
+        .into_iter()
+        .flatten()
+        .map(|status| {
+            // Gitea names the field `status`, GitHub `state`.
+            let state = status.get("state").or_else(|| status.get("status"));
+            CiCheck {
+                name: text(status, "context"),
+                state: match state.and_then(Value::as_str) {
+                    Some("success") => CiState::Success,
+                    Some("pending") => CiState::Pending,
+                    Some("warning") => CiState::Neutral,
+                    _ => CiState::Failure,
+                },
+                url: url(status, "target_url"),
+            }
+        })
+        .collect()
+}
+
+/// GitLab's `/repository/commits/{sha}/statuses`, one entry per job.
+pub fn parse_gitlab_statuses(reply: &Value) -> Vec<CiCheck> {
+    let jobs = reply.as_array();
+    jobs.into_iter()
+        .flatten()
+        .map(|job| {
+            let allowed = job.get("allow_failure").and_then(Value::as_bool) == Some(true);
+            CiCheck {
+                name: text(job, "name"),
+                state: match text(job, "status").as_str() {
+                    "success" => CiState::Success,
+                    "failed" if allowed => CiState::Neutral,
+                    "failed" => CiState::Failure,
+                    "canceled" | "skipped" | "manual" => CiState::Neutral,
+                    _ => CiState::Pending,
+                },
+                url: url(job, "target_url"),
+            }
+        })
+        .collect()
+}
+
+/// Somewhere to read a commit's checks from.
+pub trait CiProvider: Send + Sync {
+    fn checks(&self, forge: Forge, remote_url: &str, commit: &str) -> Result<Vec<CiCheck>, String>;
+}
+
+impl CiProvider for Forges {
+    fn checks(&self, forge: Forge, remote_url: &str, commit: &str) -> Result<Vec<CiCheck>, String> {
+        let repo = api_repo_url(forge, remote_url)?;
+        match forge {
+            Forge::GitHub => {
+                let runs = format!("{}/commits/{}/check-runs?per_page=100", repo, commit);
+                let statuses = format!("{}/commits/{}/status", repo, commit);
+                let mut checks = parse_check_runs(&self.api(forge, &runs, None)?);
+                checks.extend(parse_commit_statuses(&self.api(forge, &statuses, None)?));
+                Ok(checks)
+            }
+            Forge::GitLab => {
+                let url = format!(
+                    "{}/repository/commits/{}/statuses?per_page=100",
+                    repo, commit
+                );
+                Ok(parse_gitlab_statuses(&self.api(forge, &url, None)?))
+            }
+            Forge::Gitea => {
+                let url = format!("{}/commits/{}/status", repo, commit);
+                Ok(parse_commit_statuses(&self.api(forge, &url, None)?))
+            }
+        }
+    }
+}
+
+/// What to ask the forge about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CiTarget {
+    pub forge: Forge,
+    pub remote_url: String,
+    pub commit: String,
+}
+
+type CiResult = (String, Result<Vec<CiCheck>, String>);
+
+/// Polls the checks on a timer, like [`crate::auto_fetch::AutoFetch`].
+pub struct CiWatch {
+    interval: Duration,
+    last_started: Option<Instant>,
+    running: Option<Receiver<CiResult>>,
+    /// The latest checks read, for the commit they belong to.
+    pub status: Option<CiStatus>,
+    /// Why the last poll failed, until one succeeds.
+    pub error: Option<String>,
+    /// The check selected in [`AppMode::Ci`].
+    pub selected: usize,
+}
+
+impl CiWatch {
+    pub fn new(interval: Duration) -> CiWatch {
+        CiWatch {
+            interval,
+            last_started: None,
+            running: None,
+            status: None,
+            error: None,
+            selected: 0,
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        self.running.is_none()
+            && self
+                .last_started
+                .is_none_or(|time| time.elapsed() >= self.interval)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Polls on the next tick, e.g. right after a push.
+    pub fn poll_soon(&mut self) {
+        self.last_started = None;
+    }
+
+    fn start(&mut self, provider: Arc<dyn CiProvider>, target: CiTarget) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let checks = provider.checks(target.forge, &target.remote_url, &target.commit);
+            let _ = sender.send((target.commit, checks));
+        });
+        self.running = Some(receiver);
+        self.last_started = Some(Instant::now());
+    }
+
+    fn poll(&mut self) -> Option<CiResult> {
+        let result = match self.running.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                (String::new(), Err("worker thread exited".to_string()))
+            }
+        };
+        self.running = None;
+        Some(result)
+    }
+}
+
+impl App {
+    /// The upstream's commit on a forge with a token, if there is one.
+    fn ci_target(&self) -> Option<CiTarget> {
+        let upstream = self.git_status.upstream.as_deref()?;
+        let remote = self
+            .backend
+            .remote_list()
+            .ok()?
+            .into_iter()
+            .find(|remote| upstream.starts_with(&format!("{}/", remote.name)))?;
+        let remote_url = remote.push_url.unwrap_or(remote.url);
+        let (host, _) = remote_repo(&remote_url)?;
+        let forges = &self.config.forges;
+        let forge = forges.forge_for(&remote.name, &host)?;
+        forges.token(forge)?;
+        let commit = self.backend.log_range(upstream, 1).ok()?.pop()?.id;
+        Some(CiTarget {
+            forge,
+            remote_url,
+            commit,
+        })
+    }
+
+    pub(crate) fn poll_ci(&mut self) {
+        let Some(watch) = &mut self.ci else {
+            return;
+        };
+        if let Some((commit, result)) = watch.poll() {
+            match result {
+                Ok(checks) => {
+                    watch.error = None;
+                    watch.selected = watch.selected.min(checks.len().saturating_sub(1));
+                    watch.status = Some(CiStatus { commit, checks });
+                }
+                Err(error) => {
+                    if watch.error.as_ref() != Some(&error) {
+                        self.log_error(GitError::new("Reading CI status", error.clone()));
+                    }
+                    if let Some(watch) = &mut self.ci {
+                        watch.error = Some(error);
+                    }
+                }
+            }
+            return;
+        }
+        if !watch.is_due() {
+            return;
+        }
+        match self.ci_target() {
+            Some(target) => {
+                let provider = Arc::clone(&self.ci_provider);
+                if let Some(watch) = &mut self.ci {
+                    // Checks of an older push would mislead.
+                    if watch
+                        .status
+                        .as_ref()
+                        .is_some_and(|s| s.commit != target.commit)
+                    {
+                        watch.status = None;
+                    }
+                    watch.start(provider, target);
+                }
+            }
+            None => {
+                if let Some(watch) = &mut self.ci {
+                    watch.status = None;
+                    watch.error = None;
+                    watch.last_started = Some(Instant::now());
+                }
+            }
+        }
+    }
+
+    /// `w` in the file list.
+    pub(crate) fn open_ci(&mut self) {
+        match &self.ci {
+            Some(CiWatch {
+                status: Some(_), ..
+            }) => self.mode = AppMode::Ci,
+            Some(watch) if watch.is_running() => {
+                self.show_notification("Reading CI status…".to_string());
+            }
+            Some(CiWatch {
+                error: Some(error), ..
+            }) => {
+                let error = error.clone();
+                self.show_warning(format!("CI status unavailable: {}", error));
+            }
+            Some(_) => self.show_notification(
+                "No CI status: push the branch to a forge with a token in the config".to_string(),
+            ),
+            None => self.show_notification("CI status is off (ci_poll = 0)".to_string()),
+        }
+    }
+
+    pub(crate) fn handle_ci_input(&mut self, key: KeyCode) {
+        let Some(watch) = &mut self.ci else {
+            self.mode = AppMode::FileList;
+            return;
+        };
+        let count = watch
+            .status
+            .as_ref()
+            .map_or(0, |status| status.checks.len());
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if watch.selected + 1 < count => {
+                watch.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => watch.selected = watch.selected.saturating_sub(1),
+            KeyCode::Char('r') => watch.poll_soon(),
+            KeyCode::Char('y') => {
+                let url = watch
+                    .status
+                    .as_ref()
+                    .and_then(|status| status.checks.get(watch.selected))
+                    .and_then(|check| check.url.clone());
+                match url {
+                    Some(url) => self.copy_to_clipboard(url, "check URL"),
+                    None => self.show_notification("That check has no link".to_string()),
+                }
+            }
+            _ => {}
+        }
+    }
+}
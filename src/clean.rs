@@ -0,0 +1,143 @@
+//! The clean panel: what `git clean` would delete, previewed first, with
+//! only the entries picked from the list deleted.
+
+use crate::{
+    app::{App, AppMode},
+    confirm::PendingAction,
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanEntry {
+    /// From the top of the working tree; directories go as a whole.
+    pub path: String,
+    pub is_dir: bool,
+    pub marked: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct CleanView {
+    pub entries: Vec<CleanEntry>,
+    pub selected: usize,
+    /// Whether ignored files are listed too, as with `git clean -x`.
+    pub ignored: bool,
+}
+
+impl CleanView {
+    pub fn marked_paths(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.marked)
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+}
+
+impl App {
+    /// `G` in the file list.
+    pub(crate) fn open_clean(&mut self) {
+        self.clean = CleanView::default();
+        if self.reload_clean() {
+            self.mode = AppMode::Clean;
+        }
+    }
+
+    /// Runs the preview again; marks are kept on entries still listed.
+    fn reload_clean(&mut self) -> bool {
+        let paths = match self.backend.clean_preview(self.clean.ignored) {
+            Ok(paths) => paths,
+            Err(error) => {
+                self.report_error(GitError::new("Previewing git clean", error));
+                return false;
+            }
+        };
+        let root = self.backend.work_dir();
+        let view = &mut self.clean;
+        let marked = view.marked_paths();
+        view.entries = paths
+            .into_iter()
+            .map(|path| {
+                let path = path.trim_end_matches('/').to_string();
+                CleanEntry {
+                    is_dir: root.as_ref().is_some_and(|root| root.join(&path).is_dir()),
+                    marked: marked.contains(&path),
+                    path,
+                }
+            })
+            .collect();
+        view.selected = view.selected.min(view.entries.len().saturating_sub(1));
+        true
+    }
+
+    pub(crate) fn handle_clean_input(&mut self, key: KeyCode) {
+        let view = &mut self.clean;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if view.selected + 1 < view.entries.len() => {
+                view.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Char(' ') => {
+                if let Some(entry) = view.entries.get_mut(view.selected) {
+                    entry.marked = !entry.marked;
+                }
+                if view.selected + 1 < view.entries.len() {
+                    view.selected += 1;
+                }
+            }
+            KeyCode::Char('a') => {
+                let mark = !view.entries.iter().all(|entry| entry.marked);
+                for entry in &mut view.entries {
+                    entry.marked = mark;
+                }
+            }
+            KeyCode::Char('x') => {
+                view.ignored = !view.ignored;
+                self.reload_clean();
+            }
+            KeyCode::Enter | KeyCode::Char('d') => self.confirm_clean(),
+            _ => {}
+        }
+    }
+
+    fn confirm_clean(&mut self) {
+        let paths = self.clean.marked_paths();
+        if paths.is_empty() {
+            self.show_notification("Mark entries to delete with Space, or a for all".to_string());
+            return;
+        }
+        let mut message = String::new();
+        for entry in self
+            .clean
+            .entries
+            .iter()
+            .filter(|entry| entry.marked)
+            .take(8)
+        {
+            let slash = if entry.is_dir { "/" } else { "" };
+            message.push_str(&format!("{}{}\n", entry.path, slash));
+        }
+        if paths.len() > 8 {
+            message.push_str(&format!("…and {} more\n", paths.len() - 8));
+        }
+        message.push_str("\nThese aren't tracked, so git can't bring them back. Delete?");
+        self.ask(
+            "Delete untracked files",
+            message,
+            PendingAction::Clean(paths),
+        );
+    }
+
+    pub(crate) fn clean_now(&mut self, paths: &[String]) {
+        match self.backend.clean(paths, self.clean.ignored) {
+            Ok(()) => {
+                let noun = if paths.len() == 1 { "entry" } else { "entries" };
+                self.show_notification(format!("Deleted {} untracked {}", paths.len(), noun));
+            }
+            Err(error) => self.report_error(GitError::new("Cleaning", error)),
+        }
+        self.refresh_git_status();
+        self.reload_clean();
+    }
+}
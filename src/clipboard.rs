@@ -0,0 +1,181 @@
+//! Copying to the system clipboard, or else through the terminal with an
+//! OSC 52 escape sequence, which also works over SSH without a clipboard
+//! tool; and pasting into the commit editor, from the terminal's bracketed
+//! paste or the system clipboard.
+
+use crate::{
+    app::{App, AppMode},
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+use std::sync::Mutex;
+
+/// Kept for the life of the program: on X11 and Wayland the copied text is
+/// served from it, so it would vanish with it.
+static SYSTEM: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// Whether the terminal is on another machine, whose clipboard only OSC 52
+/// can reach.
+pub fn over_ssh() -> bool {
+    std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// Puts `text` on this machine's clipboard. Fails over SSH, and where there
+/// is no clipboard, e.g. without a display.
+pub fn copy_to_system(text: &str) -> Result<(), String> {
+    if over_ssh() {
+        return Err("the terminal is over SSH".to_string());
+    }
+    let mut system = SYSTEM.lock().unwrap_or_else(|e| e.into_inner());
+    if system.is_none() {
+        *system = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+    }
+    system.as_mut().map_or(Ok(()), |clipboard| {
+        clipboard.set_text(text).map_err(|e| e.to_string())
+    })
+}
+
+/// The text on this machine's clipboard, for `Ctrl+V`.
+pub fn paste_from_system() -> Result<String, String> {
+    if over_ssh() {
+        return Err("the terminal is over SSH".to_string());
+    }
+    let mut system = SYSTEM.lock().unwrap_or_else(|e| e.into_inner());
+    if system.is_none() {
+        *system = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+    }
+    system.as_mut().map_or(Ok(String::new()), |clipboard| {
+        clipboard.get_text().map_err(|e| e.to_string())
+    })
+}
+
+/// Pasted text as the commit message wants it: `\r\n` and `\r` line ends
+/// as `\n`, tabs as spaces, no other control characters, no trailing spaces
+/// on its lines, and no blank lines at its end.
+pub fn normalize_paste(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let line: String = line
+                .chars()
+                .map(|c| if c == '\t' { ' ' } else { c })
+                .filter(|c| !c.is_control())
+                .collect();
+            line.trim_end().to_string()
+        })
+        .collect();
+    lines.join("\n").trim_end().to_string()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The sequence asking the terminal to put `text` on the clipboard.
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (i, &byte)| {
+            word | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(word >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+impl App {
+    /// Puts `text` on the clipboard once control is back in the event loop;
+    /// `what` names it in the notification.
+    pub(crate) fn copy_to_clipboard(&mut self, text: String, what: &str) {
+        self.show_notification(format!("Copied {}", what));
+        self.pending_copy = Some(text);
+    }
+}
+
+impl App {
+    /// Text pasted into the terminal. The commit editor takes it in one go
+    /// at the cursor; when several lines land on the subject, the first
+    /// stays the subject and the rest start the body after a blank line.
+    /// Elsewhere it is typed key by key, as without bracketed paste.
+    pub fn handle_paste(&mut self, text: &str) {
+        if self.mode != AppMode::CommitMessage || self.confirm.is_some() {
+            for c in text.chars() {
+                match c {
+                    '\r' | '\n' => self.handle_input(KeyCode::Enter),
+                    '\t' => self.handle_input(KeyCode::Tab),
+                    c => self.handle_input(KeyCode::Char(c)),
+                }
+            }
+            return;
+        }
+        let mut text = normalize_paste(text);
+        let on_subject = !self.commit_message[..self.cursor_position].contains('\n');
+        if on_subject && let Some((subject, body)) = text.split_once('\n') {
+            let body = body.trim_start_matches('\n');
+            text = format!("{}\n\n{}", subject, body);
+        }
+        if text.is_empty() {
+            return;
+        }
+        self.history.reset();
+        self.commit_message.insert_str(self.cursor_position, &text);
+        self.cursor_position += text.len();
+        self.save_draft();
+    }
+
+    /// `Ctrl+V` in the commit editor.
+    pub(crate) fn paste_clipboard(&mut self) {
+        match paste_from_system() {
+            Ok(text) => self.handle_paste(&text),
+            Err(error) => self.show_notification(format!(
+                "Cannot read the clipboard ({}); use the terminal's paste instead",
+                error
+            )),
+        }
+    }
+}
+
+impl App {
+    /// `y` in the file list.
+    pub(crate) fn handle_copy_menu_input(&mut self, key: KeyCode) {
+        self.mode = AppMode::FileList;
+        match key {
+            KeyCode::Char('p') => {
+                if let Some(file) = self.selected() {
+                    let path = file.path.clone();
+                    self.copy_to_clipboard(path, "path");
+                }
+            }
+            KeyCode::Char('b') => {
+                let branch = self.git_status.current_branch.clone();
+                self.copy_to_clipboard(branch, "branch name");
+            }
+            KeyCode::Char('c') => match self.backend.log(1) {
+                Ok(log) if !log.is_empty() => {
+                    let short_id = log[0].short_id.clone();
+                    self.copy_to_clipboard(log[0].id.clone(), &short_id);
+                }
+                Ok(_) => self.show_notification("No commits yet".to_string()),
+                Err(error) => self.report_error(GitError::new("Reading HEAD", error)),
+            },
+            KeyCode::Char('d') => {
+                let targets = self.target_files();
+                let diff = self.diffs_of(&targets).concat();
+                if !diff.is_empty() {
+                    self.copy_to_clipboard(diff, "diff");
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {}
+            _ => self.mode = AppMode::CopyMenu,
+        }
+    }
+}
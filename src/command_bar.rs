@@ -0,0 +1,132 @@
+//! The `:` command bar: any git command, run in the background, for what
+//! the rest of the UI doesn't cover.
+
+use crate::{
+    app::{App, AppMode, TaskKind},
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct CommandBar {
+    pub input: String,
+    /// The command running, or the last one, as `git ...`.
+    pub last: Option<String>,
+}
+
+/// Splits `text` into words the way a shell would, with single and double
+/// quotes and backslash escapes.
+pub fn split_args(text: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated '".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("unterminated \"".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated \"".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                let word = word.get_or_insert_with(String::new);
+                word.extend(chars.next());
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+impl App {
+    /// `:` in the file list.
+    pub(crate) fn open_command_bar(&mut self) {
+        self.command_bar.input.clear();
+        self.mode = AppMode::CommandBar;
+    }
+
+    pub(crate) fn handle_command_bar_input(&mut self, key: KeyCode) {
+        let input = &mut self.command_bar.input;
+        match key {
+            KeyCode::Esc => self.mode = AppMode::FileList,
+            KeyCode::Enter => self.run_command_bar(),
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(input, input.len());
+                input.truncate(end);
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    fn run_command_bar(&mut self) {
+        let mut args = match split_args(&self.command_bar.input) {
+            Ok(args) => args,
+            Err(error) => {
+                self.show_warning(format!("Cannot run that: {}", error));
+                return;
+            }
+        };
+        // `:git status` works as well as `:status`.
+        if args.first().is_some_and(|first| first == "git") {
+            args.remove(0);
+        }
+        self.mode = AppMode::FileList;
+        if args.is_empty() {
+            return;
+        }
+        if self.task.is_none() {
+            self.command_bar.last = Some(format!("git {}", args.join(" ")));
+        }
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Command, move |cancel| {
+            backend.run_command(&args, cancel)
+        });
+    }
+
+    /// Puts the output in the messages log; the status is refreshed after
+    /// every task.
+    pub(crate) fn finish_command(&mut self, result: Result<String, String>) {
+        let command = self
+            .command_bar
+            .last
+            .clone()
+            .unwrap_or_else(|| "git".to_string());
+        match result {
+            Ok(output) if output.trim().is_empty() => {
+                self.show_notification(format!("{} done", command));
+            }
+            Ok(output) => self.show_notification_with_detail(
+                format!("{} done (m for its output)", command),
+                output,
+            ),
+            Err(error) => self.report_error(GitError::new(command, error)),
+        }
+    }
+}
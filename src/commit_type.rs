@@ -0,0 +1,271 @@
+//! The commit types the editor offers, the picker that puts one in front of
+//! the message, and guessing one from the staged paths, so the editor can
+//! open with `docs: ` already in place when only Markdown changed.
+
+use crate::{
+    app::{App, AppMode},
+    confirm::PendingAction,
+    filter::fuzzy_match,
+    text,
+    undo::EditHistory,
+};
+use crossterm::event::KeyCode;
+use regex::Regex;
+
+/// A type the editor's `Tab` cycles through, e.g. `perf: ` or `✨ feat: `.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitPrefix {
+    /// Ends in a space, ready for the description.
+    pub prefix: String,
+    pub description: String,
+}
+
+impl CommitPrefix {
+    pub fn new(prefix: &str, description: &str) -> CommitPrefix {
+        CommitPrefix {
+            prefix: format!("{} ", prefix.trim()),
+            description: description.trim().to_string(),
+        }
+    }
+
+    /// Reads `perf: | Performance improvements`; the description is
+    /// optional.
+    pub fn parse(value: &str) -> Option<CommitPrefix> {
+        let (prefix, description) = value.split_once('|').unwrap_or((value, ""));
+        (!prefix.trim().is_empty()).then(|| CommitPrefix::new(prefix, description))
+    }
+}
+
+/// The Conventional Commits types most projects use.
+pub fn default_prefixes() -> Vec<CommitPrefix> {
+    [
+        ("feat:", "New feature"),
+        ("fix:", "Bug fix"),
+        ("docs:", "Documentation changes"),
+        ("style:", "Code style/formatting"),
+        ("refactor:", "Code restructuring"),
+        ("test:", "Adding or updating tests"),
+        ("chore:", "Maintenance tasks"),
+    ]
+    .into_iter()
+    .map(|(prefix, description)| CommitPrefix::new(prefix, description))
+    .collect()
+}
+
+/// The repository's own list from `gch.prefix` in its git config, if it
+/// sets one, else the config's.
+pub fn repo_prefixes(values: &[String], config: &[CommitPrefix]) -> Vec<CommitPrefix> {
+    let own: Vec<CommitPrefix> = values
+        .iter()
+        .filter_map(|v| CommitPrefix::parse(v))
+        .collect();
+    if own.is_empty() { config.to_vec() } else { own }
+}
+
+/// The message's current prefix, as the length of the header to swap out
+/// and what goes in its place: a lowercase `type(scope)!: ` header keeps its
+/// scope and `!` when `prefix` is a plain `type: `, a prefix from `known` is
+/// replaced whole, and anything else gets `prefix` in front.
+pub fn replace_prefix(message: &str, prefix: &str, known: &[CommitPrefix]) -> (usize, String) {
+    let new_type = prefix
+        .strip_suffix(": ")
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()));
+    if let Some((header, _)) = message.split_once(": ")
+        && let Some(new_type) = new_type
+    {
+        let old_type = header.split(['(', '!']).next().unwrap_or_default();
+        if !old_type.is_empty()
+            && old_type
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            && !header.contains(char::is_whitespace)
+        {
+            let marks = &header[old_type.len()..];
+            return (header.len() + 2, format!("{}{}: ", new_type, marks));
+        }
+    }
+    let old = known
+        .iter()
+        .filter(|p| message.starts_with(&p.prefix))
+        .map(|p| p.prefix.len())
+        .max()
+        .unwrap_or(0);
+    (old, prefix.to_string())
+}
+
+/// The focused prefix pane of [`AppMode::Prefixes`] in the commit editor.
+#[derive(Debug, Default)]
+pub struct PrefixPicker {
+    pub query: String,
+    /// Over [`PrefixPicker::matches`].
+    pub selected: usize,
+}
+
+impl PrefixPicker {
+    /// Indexes of the prefixes matching the query by prefix and description,
+    /// in list order until something is typed, then best first.
+    pub fn matches(&self, prefixes: &[CommitPrefix]) -> Vec<usize> {
+        let mut scored: Vec<(i64, usize)> = prefixes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| {
+                let text = format!("{} {}", p.prefix, p.description);
+                fuzzy_match(&self.query, &text).map(|score| (score, i))
+            })
+            .collect();
+        if !self.query.is_empty() {
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        }
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+}
+
+/// Offers `prefix` when every staged path matches `pattern`.
+#[derive(Debug, Clone)]
+pub struct TypeRule {
+    pub prefix: String,
+    pub pattern: Regex,
+}
+
+impl TypeRule {
+    pub fn new(prefix: &str, pattern: &str) -> TypeRule {
+        TypeRule {
+            prefix: prefix.to_string(),
+            pattern: Regex::new(pattern).expect("valid regex"),
+        }
+    }
+}
+
+/// Documentation, tests, and CI configuration.
+pub fn default_type_rules() -> Vec<TypeRule> {
+    vec![
+        TypeRule::new("docs: ", r"(?i)(\.(md|rst|adoc)$|^docs?/)"),
+        TypeRule::new(
+            "test: ",
+            r"(^|/)(tests?|__tests__|spec)/|_test\.\w+$|\.(test|spec)\.\w+$",
+        ),
+        TypeRule::new(
+            "chore: ",
+            r"^(\.github/|\.gitlab-ci\.yml$|\.circleci/|\.travis\.yml$|Jenkinsfile$|azure-pipelines\.yml$)",
+        ),
+    ]
+}
+
+/// The prefix of the first rule that covers every path in `paths`.
+pub fn suggest_prefix<'a>(rules: &'a [TypeRule], paths: &[&str]) -> Option<&'a str> {
+    if paths.is_empty() {
+        return None;
+    }
+    rules
+        .iter()
+        .find(|rule| paths.iter().all(|path| rule.pattern.is_match(path)))
+        .map(|rule| rule.prefix.as_str())
+}
+
+impl App {
+    /// Opens the commit editor, starting an empty message with the prefix
+    /// the staged paths suggest.
+    pub(crate) fn start_commit(&mut self) {
+        if !self.has_staged_files() && !self.commit_options.allow_empty {
+            self.ask(
+                "No staged files to commit",
+                "Commit nothing (--allow-empty), e.g. to trigger CI?".to_string(),
+                PendingAction::EmptyCommit,
+            );
+            return;
+        }
+        self.mode = AppMode::CommitMessage;
+        if self.commit_message.is_empty() && !self.prefill_commit_message() {
+            self.suggest_commit_prefix();
+        }
+        self.edits = EditHistory::new(&self.commit_message);
+    }
+
+    /// Starts the message with the prefix the staged paths suggest.
+    fn suggest_commit_prefix(&mut self) {
+        let paths: Vec<&str> = self
+            .all_files
+            .iter()
+            .filter(|f| f.staged)
+            .map(|f| f.path.as_str())
+            .collect();
+        let Some(index) = suggest_prefix(&self.config.type_rules, &paths)
+            .and_then(|prefix| self.commit_prefixes.iter().position(|p| p.prefix == prefix))
+        else {
+            return;
+        };
+        self.selected_prefix = index;
+        self.commit_message = self.commit_prefixes[index].prefix.clone();
+        self.cursor_position = self.commit_message.len();
+    }
+
+    /// `Tab` in the commit editor: focuses the prefix pane on the message's
+    /// current prefix.
+    pub(crate) fn open_prefix_picker(&mut self) {
+        if self.commit_prefixes.is_empty() {
+            self.show_notification("No commit prefixes configured".to_string());
+            return;
+        }
+        let current = self
+            .commit_prefixes
+            .iter()
+            .position(|p| self.commit_message.starts_with(&p.prefix));
+        self.prefix_picker.query.clear();
+        self.prefix_picker.selected = current.unwrap_or(self.selected_prefix);
+        self.mode = AppMode::Prefixes;
+    }
+
+    pub(crate) fn handle_prefix_picker_input(&mut self, key: KeyCode) {
+        let matches = self.prefix_picker.matches(&self.commit_prefixes);
+        let picker = &mut self.prefix_picker;
+        match key {
+            KeyCode::Esc => self.mode = AppMode::CommitMessage,
+            KeyCode::Down | KeyCode::Tab if !matches.is_empty() => {
+                picker.selected = (picker.selected + 1) % matches.len();
+            }
+            KeyCode::Up | KeyCode::BackTab if !matches.is_empty() => {
+                picker.selected = (picker.selected + matches.len() - 1) % matches.len();
+            }
+            KeyCode::Enter => {
+                if let Some(&index) = matches.get(picker.selected) {
+                    self.use_prefix(index);
+                }
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let nth = c as usize - '1' as usize;
+                if let Some(&index) = matches.get(nth) {
+                    self.use_prefix(index);
+                }
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(&picker.query, picker.query.len());
+                picker.query.truncate(end);
+                picker.selected = 0;
+            }
+            KeyCode::Char(c) => {
+                picker.query.push(c);
+                picker.selected = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Puts `commit_prefixes[index]` in front of the message in place of
+    /// its current prefix, keeping the cursor on the same text.
+    fn use_prefix(&mut self, index: usize) {
+        let (old, new) = replace_prefix(
+            &self.commit_message,
+            &self.commit_prefixes[index].prefix,
+            &self.commit_prefixes,
+        );
+        self.commit_message.replace_range(..old, &new);
+        self.cursor_position = if self.cursor_position >= old {
+            self.cursor_position - old + new.len()
+        } else {
+            new.len()
+        };
+        self.selected_prefix = index;
+        self.mode = AppMode::CommitMessage;
+        self.save_draft();
+    }
+}
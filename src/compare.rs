@@ -0,0 +1,231 @@
+//! Comparing two refs: ahead/behind counts against `compare_ref` in the
+//! header, and the compare view with the commits unique to each side and
+//! everything the head changed since it forked.
+
+use crate::{
+    app::{App, AppMode, DiffSource},
+    git::{CommitInfo, GitStatus},
+    lfs,
+    log::LOG_LIMIT,
+    patch::PatchAction,
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// The ref as the header shows it, e.g. `main` for `origin/main`.
+    pub label: String,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// `reference` without the name of one of `remotes` in front.
+pub fn compare_label(reference: &str, remotes: &[String]) -> String {
+    remotes
+        .iter()
+        .find_map(|remote| reference.strip_prefix(&format!("{}/", remote)))
+        .unwrap_or(reference)
+        .to_string()
+}
+
+/// Whether counting against `reference` says nothing new: it is the branch
+/// itself or the upstream already counted.
+fn is_redundant(status: &GitStatus, reference: &str) -> bool {
+    let reference = reference.strip_prefix("refs/heads/").unwrap_or(reference);
+    reference == status.current_branch || status.upstream.as_deref() == Some(reference)
+}
+
+impl App {
+    /// Counts again against `compare_ref`, after the status was read.
+    pub(crate) fn reload_comparison(&mut self) {
+        self.comparison = match &self.config.compare_ref {
+            Some(reference)
+                if self.git_status.head.is_some() && !is_redundant(&self.git_status, reference) =>
+            {
+                self.backend
+                    .ahead_behind(reference)
+                    .ok()
+                    .map(|(ahead, behind)| Comparison {
+                        label: compare_label(reference, &self.backend.remotes()),
+                        ahead,
+                        behind,
+                    })
+            }
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefSide {
+    Base,
+    Head,
+}
+
+#[derive(Debug, Default)]
+pub struct CompareView {
+    pub base: String,
+    pub head: String,
+    /// Commits in `head` but not in `base`, newest first.
+    pub ahead: Vec<CommitInfo>,
+    /// Commits in `base` but not in `head`, newest first.
+    pub behind: Vec<CommitInfo>,
+    /// Over `ahead`, then `behind`.
+    pub selected: usize,
+    /// The ref being typed after `b` or `h`.
+    pub input: Option<(RefSide, String)>,
+}
+
+impl CompareView {
+    pub fn selected_commit(&self) -> Option<&CommitInfo> {
+        self.ahead.iter().chain(&self.behind).nth(self.selected)
+    }
+
+    fn len(&self) -> usize {
+        self.ahead.len() + self.behind.len()
+    }
+}
+
+impl App {
+    /// `=` in the file list: HEAD against `compare_ref`, or `main`.
+    pub(crate) fn open_compare(&mut self) {
+        let base = self
+            .config
+            .compare_ref
+            .clone()
+            .unwrap_or_else(|| "main".to_string());
+        self.compare = CompareView {
+            base,
+            head: "HEAD".to_string(),
+            ..CompareView::default()
+        };
+        self.mode = AppMode::Compare;
+        if !self.reload_compare() {
+            // Let a base that doesn't exist be fixed right away.
+            let base = self.compare.base.clone();
+            self.compare.input = Some((RefSide::Base, base));
+        }
+    }
+
+    fn reload_compare(&mut self) -> bool {
+        let view = &self.compare;
+        let ahead = self
+            .backend
+            .log_range(&format!("{}..{}", view.base, view.head), LOG_LIMIT);
+        let behind = self
+            .backend
+            .log_range(&format!("{}..{}", view.head, view.base), LOG_LIMIT);
+        match ahead.and_then(|ahead| Ok((ahead, behind?))) {
+            Ok((ahead, behind)) => {
+                let view = &mut self.compare;
+                view.ahead = ahead;
+                view.behind = behind;
+                view.selected = view.selected.min(view.len().saturating_sub(1));
+                true
+            }
+            Err(error) => {
+                let view = &mut self.compare;
+                view.ahead.clear();
+                view.behind.clear();
+                let context = format!("Comparing {} with {}", view.head, view.base);
+                self.report_error(GitError::new(context, error));
+                false
+            }
+        }
+    }
+
+    pub(crate) fn handle_compare_input(&mut self, key: KeyCode) {
+        if self.compare.input.is_some() {
+            self.handle_compare_ref_input(key);
+            return;
+        }
+        let view = &mut self.compare;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if view.selected + 1 < view.len() => {
+                view.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Char('b') => view.input = Some((RefSide::Base, view.base.clone())),
+            KeyCode::Char('h') => view.input = Some((RefSide::Head, view.head.clone())),
+            KeyCode::Char('s') => {
+                std::mem::swap(&mut view.base, &mut view.head);
+                view.selected = 0;
+                self.reload_compare();
+            }
+            KeyCode::Char('r') => {
+                self.reload_compare();
+            }
+            KeyCode::Enter => {
+                if let Some(commit) = view.selected_commit() {
+                    let id = commit.id.clone();
+                    self.show_commit(id);
+                }
+            }
+            KeyCode::Char('x') => {
+                if let Some(commit) = view.selected_commit() {
+                    let id = commit.id.clone();
+                    self.open_patch_prompt(PatchAction::ExportCommits(id));
+                }
+            }
+            KeyCode::Char('d') => {
+                let (base, head) = (view.base.clone(), view.head.clone());
+                let diff = self.range_diff(&base, &head);
+                if !diff.is_empty() {
+                    self.open_diff_view(diff, DiffSource::Range { base, head });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_compare_ref_input(&mut self, key: KeyCode) {
+        let Some((side, input)) = &mut self.compare.input else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.compare.input = None,
+            KeyCode::Enter => {
+                let reference = input.trim().to_string();
+                let side = *side;
+                self.compare.input = None;
+                if reference.is_empty() {
+                    return;
+                }
+                match side {
+                    RefSide::Base => self.compare.base = reference,
+                    RefSide::Head => self.compare.head = reference,
+                }
+                self.compare.selected = 0;
+                self.reload_compare();
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(input, input.len());
+                input.truncate(end);
+            }
+            // Refs can't contain spaces.
+            KeyCode::Char(' ') => {}
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// What `head` changed since it forked from `base`; empty after
+    /// reporting a failure or when nothing changed.
+    pub(crate) fn range_diff(&mut self, base: &str, head: &str) -> String {
+        match self.backend.range_diff(base, head, &self.diff_options) {
+            Ok(diff) if diff.is_empty() => {
+                self.show_notification(format!("{} has no changes since {}", head, base));
+                String::new()
+            }
+            Ok(diff) => lfs::describe_pointer_diffs(&diff),
+            Err(error) => {
+                let context = format!("Diffing {}...{}", base, head);
+                self.report_error(GitError::new(context, error));
+                String::new()
+            }
+        }
+    }
+}
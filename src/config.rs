@@ -0,0 +1,294 @@
+use crate::{
+    ai::AiConfig,
+    commit_type::{CommitPrefix, TypeRule, default_prefixes, default_type_rules},
+    git::DiffOptions,
+    identity::Profile,
+    large_files::SizeLimits,
+    panes::{Panes, SplitLayout},
+    protected,
+    pull_request::{Forge, ForgeSettings},
+    repo,
+    secrets::{SecretRule, SecretScan},
+    spell,
+    theme::Theme,
+    ticket::TicketRules,
+    validate::ValidationRules,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// User-tunable settings. Everything has a default so the app works out of
+/// the box.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// What `Tab` picks from in the commit editor; a repository's
+    /// `gch.prefix` git config values replace them.
+    pub commit_prefixes: Vec<CommitPrefix>,
+    pub notification_timeout: Duration,
+    pub validation: ValidationRules,
+    /// Start every commit with sign-off on, for projects that require a
+    /// Developer Certificate of Origin.
+    pub sign_off: bool,
+    /// Skip the hooks for `Ctrl+W` WIP commits.
+    pub wip_no_verify: bool,
+    /// Commands that must pass before each commit, run in order through
+    /// `sh -c` in the working tree, e.g. `cargo fmt --check`.
+    pub checks: Vec<String>,
+    /// What counts as a likely secret in the lines a commit adds; any hit
+    /// asks before committing.
+    pub secret_scan: SecretScan,
+    /// Staging a file over these sizes asks first.
+    pub large_files: SizeLimits,
+    pub ticket: TicketRules,
+    /// Branches that committing or pushing to directly asks about first;
+    /// `*` matches anything, as in `release/*`.
+    pub protected_branches: Vec<String>,
+    /// Where Ctrl+G sends the staged diff for a suggested message. Off
+    /// unless set, since it shares code with a third party.
+    pub ai: Option<AiConfig>,
+    /// Picks the prefix an empty message starts with from the staged
+    /// paths. The prefix must be one of `commit_prefixes`.
+    pub type_rules: Vec<TypeRule>,
+    /// Start with the diff of the selected file shown beside the list.
+    pub split_view: bool,
+    pub split_layout: SplitLayout,
+    /// Pane sizes until they are resized; after that the saved sizes win.
+    pub panes: Panes,
+    /// Context, whitespace, and algorithm for diffs until changed in the
+    /// diff view.
+    pub diff: DiffOptions,
+    /// Refresh the status once files have stopped changing for this long.
+    /// `None` leaves refreshing to `r`.
+    pub watch: Option<Duration>,
+    /// Fetch in the background this often, keeping the ahead/behind
+    /// counts current. `None`, the default, fetches only on `f`.
+    pub auto_fetch: Option<Duration>,
+    /// A ref the header also counts ahead/behind against, e.g.
+    /// `origin/main`, to show whether the branch needs a rebase before a
+    /// pull request.
+    pub compare_ref: Option<String>,
+    /// Which forge each remote is on and the tokens for their APIs, for
+    /// opening pull and merge requests.
+    pub forges: ForgeSettings,
+    /// How often the pushed commit's checks are read from a forge with a
+    /// token. `None` turns the CI status off.
+    pub ci_poll: Option<Duration>,
+    /// Word lists for spell checking the commit message: hunspell `.dic`
+    /// files or one word per line. Empty turns spell checking off.
+    pub dictionaries: Vec<PathBuf>,
+    /// Identities `F6` in the commit editor switches the repository to.
+    pub profiles: Vec<Profile>,
+    /// Where state shared between repositories, such as the recent list,
+    /// is kept.
+    pub state_dir: Option<PathBuf>,
+    /// How many notifications and commands the messages view keeps.
+    pub message_log_size: usize,
+    pub theme: Theme,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            commit_prefixes: default_prefixes(),
+            notification_timeout: Duration::from_secs(3),
+            validation: ValidationRules::default(),
+            sign_off: false,
+            wip_no_verify: false,
+            checks: Vec::new(),
+            secret_scan: SecretScan::default(),
+            large_files: SizeLimits::default(),
+            ticket: TicketRules::default(),
+            protected_branches: protected::default_protected_branches(),
+            ai: None,
+            type_rules: default_type_rules(),
+            split_view: false,
+            split_layout: SplitLayout::SideBySide,
+            panes: Panes { list_percent: 40 },
+            diff: DiffOptions::default(),
+            watch: Some(Duration::from_millis(300)),
+            auto_fetch: None,
+            compare_ref: None,
+            forges: ForgeSettings::default(),
+            ci_poll: Some(Duration::from_secs(30)),
+            dictionaries: spell::default_dictionaries(),
+            profiles: Vec::new(),
+            state_dir: repo::default_state_dir(),
+            message_log_size: 200,
+            theme: Theme::default(),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/gch/config`, or `~/.config/gch/config`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("gch").join("config"))
+}
+
+impl Config {
+    /// Reads `key = value` lines from `path` over the defaults. Blank lines
+    /// and `#` comments are skipped, and a missing file gives the defaults.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        let mut settings = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{}:{}: expected key = value", path.display(), index + 1))?;
+            settings.push((index + 1, key.trim(), value.trim().trim_matches('"')));
+        }
+        // A preset replaces the whole theme, so it goes before any overrides.
+        settings.sort_by_key(|(_, key, _)| *key != "theme");
+
+        let mut config = Config::default();
+        // Listing any prefix or dictionary replaces the built-in ones.
+        if settings.iter().any(|(_, key, _)| *key == "prefix") {
+            config.commit_prefixes.clear();
+        }
+        if settings.iter().any(|(_, key, _)| *key == "dictionary") {
+            config.dictionaries.clear();
+        }
+        for (line, key, value) in settings {
+            config
+                .set(key, value)
+                .map_err(|e| format!("{}:{}: {}", path.display(), line, e))?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if key == "theme" {
+            self.theme =
+                Theme::preset(value).ok_or_else(|| format!("unknown theme '{}'", value))?;
+            return Ok(());
+        }
+        if key == "auto_fetch" {
+            let seconds: u64 = value
+                .parse()
+                .map_err(|_| format!("auto_fetch: expected seconds, got '{}'", value))?;
+            self.auto_fetch = (seconds > 0).then(|| Duration::from_secs(seconds));
+            return Ok(());
+        }
+        if key == "ci_poll" {
+            let seconds: u64 = value
+                .parse()
+                .map_err(|_| format!("ci_poll: expected seconds, got '{}'", value))?;
+            self.ci_poll = (seconds > 0).then(|| Duration::from_secs(seconds));
+            return Ok(());
+        }
+        if key == "wip_no_verify" {
+            self.wip_no_verify = value
+                .parse()
+                .map_err(|_| format!("wip_no_verify: expected true or false, got '{}'", value))?;
+            return Ok(());
+        }
+        if key == "compare_ref" {
+            self.compare_ref = (!value.is_empty()).then(|| value.to_string());
+            return Ok(());
+        }
+        if let Some(forge) = key.strip_suffix("_token").and_then(Forge::parse) {
+            let token = (!value.is_empty()).then(|| value.to_string());
+            match forge {
+                Forge::GitHub => self.forges.github_token = token,
+                Forge::GitLab => self.forges.gitlab_token = token,
+                Forge::Gitea => self.forges.gitea_token = token,
+            }
+            return Ok(());
+        }
+        // `forge.<remote or host> = gitlab`, for hosts their name doesn't
+        // give away.
+        if let Some(remote) = key.strip_prefix("forge.") {
+            let forge = Forge::parse(value).ok_or_else(|| {
+                format!(
+                    "{}: expected github, gitlab, or gitea, got '{}'",
+                    key, value
+                )
+            })?;
+            self.forges.forges.push((remote.to_string(), forge));
+            return Ok(());
+        }
+        // `profile.<label> = Name <email>`, listed in order.
+        if let Some(label) = key.strip_prefix("profile.") {
+            let profile = Profile::parse(label, value)
+                .ok_or_else(|| format!("{}: expected Name <email>, got '{}'", key, value))?;
+            self.profiles.push(profile);
+            return Ok(());
+        }
+        // `prefix = perf: | Performance improvements`, listed in order.
+        if key == "prefix" {
+            let prefix = CommitPrefix::parse(value)
+                .ok_or_else(|| "prefix: expected a prefix, e.g. perf: | Description".to_string())?;
+            self.commit_prefixes.push(prefix);
+            return Ok(());
+        }
+        // One line per word list; an empty value leaves spell checking off.
+        if key == "dictionary" {
+            if !value.is_empty() {
+                self.dictionaries.push(repo::expand_home(value));
+            }
+            return Ok(());
+        }
+        // One line per command, in order.
+        if key == "check" {
+            self.checks.push(value.to_string());
+            return Ok(());
+        }
+        // Added to the built-in rules, one line per pattern.
+        if key == "secret_pattern" {
+            let rule =
+                SecretRule::new(value, value).map_err(|e| format!("secret_pattern: {}", e))?;
+            self.secret_scan.rules.push(rule);
+            return Ok(());
+        }
+        if key == "secret_entropy" {
+            let bits: f64 = value
+                .parse()
+                .map_err(|_| format!("secret_entropy: expected a number, got '{}'", value))?;
+            self.secret_scan.min_entropy = (bits > 0.0).then_some(bits);
+            return Ok(());
+        }
+        if key == "large_file_mb" {
+            self.large_files.any = parse_megabytes(key, value)?;
+            return Ok(());
+        }
+        if key == "large_binary_mb" {
+            self.large_files.binary = parse_megabytes(key, value)?;
+            return Ok(());
+        }
+        if key == "protected_branches" {
+            self.protected_branches = value
+                .split(',')
+                .map(str::trim)
+                .filter(|branch| !branch.is_empty())
+                .map(String::from)
+                .collect();
+            return Ok(());
+        }
+        match key.strip_prefix("theme.") {
+            Some(element) => self.theme.set(element, value),
+            None => Err(format!("unknown setting '{}'", key)),
+        }
+    }
+}
+
+/// A size limit in megabytes, where 0 means none.
+fn parse_megabytes(key: &str, value: &str) -> Result<Option<u64>, String> {
+    let megabytes: u64 = value
+        .parse()
+        .map_err(|_| format!("{}: expected megabytes, got '{}'", key, value))?;
+    Ok((megabytes > 0).then(|| megabytes * 1024 * 1024))
+}
@@ -0,0 +1,126 @@
+//! "Are you sure?" overlays for destructive operations. Callers describe the
+//! operation as a [`PendingAction`] and hand it to [`App::ask`]; nothing runs
+//! until the user answers yes.
+
+use crate::{
+    app::{App, AppMode},
+    git::{Operation, PushOptions},
+    groups::QueueCheck,
+    undo::EditHistory,
+};
+use crossterm::event::KeyCode;
+
+/// An operation held back until the user confirms it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingAction {
+    /// `(path, untracked)` pairs; untracked files are deleted.
+    Discard(Vec<(String, bool)>),
+    /// Paths from the clean panel.
+    Clean(Vec<String>),
+    /// Staging that includes large files.
+    Stage(Vec<String>),
+    StageAll,
+    InvertStaging,
+    /// A push that is forced or goes to a protected branch.
+    Push(PushOptions),
+    /// A commit to a protected branch.
+    Commit,
+    /// Opening the editor for a commit with nothing staged.
+    EmptyCommit,
+    /// A commit from an address outside the repository's domain.
+    CommitAsIdentity,
+    /// A commit whose staged changes look like they hold a secret.
+    CommitWithSecrets,
+    /// Committing the groups, once the question after `QueueCheck` is
+    /// answered.
+    CommitGroups(QueueCheck),
+    Abort(Operation),
+    UndoCommit,
+    /// Putting back a WIP commit that has been pushed.
+    Unwip,
+    DeleteTag(String),
+    /// The release planned in [`crate::release::ReleaseDraft`].
+    Release,
+    /// Moves the branch to the commit, from the reflog.
+    RestoreTo(String),
+    RemoveRemote(String),
+}
+
+impl PendingAction {
+    /// Whether the overlay should warn that there is no going back.
+    pub fn is_undoable(&self) -> bool {
+        matches!(
+            self,
+            PendingAction::Stage(_)
+                | PendingAction::StageAll
+                | PendingAction::InvertStaging
+                | PendingAction::Commit
+                | PendingAction::EmptyCommit
+                | PendingAction::CommitAsIdentity
+                | PendingAction::CommitWithSecrets
+                | PendingAction::CommitGroups(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Confirm {
+    pub title: String,
+    pub message: String,
+    pub action: PendingAction,
+}
+
+impl App {
+    /// Shows a confirmation overlay on top of the current mode.
+    pub fn ask(&mut self, title: &str, message: String, action: PendingAction) {
+        self.confirm = Some(Confirm {
+            title: title.to_string(),
+            message,
+            action,
+        });
+    }
+
+    pub(crate) fn handle_confirm_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                if let Some(confirm) = self.confirm.take() {
+                    self.run_pending_action(confirm.action);
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                self.confirm = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn run_pending_action(&mut self, action: PendingAction) {
+        match action {
+            PendingAction::Discard(files) => self.discard_files(&files),
+            PendingAction::Clean(paths) => self.clean_now(&paths),
+            PendingAction::Stage(paths) => self.stage_paths(&paths),
+            PendingAction::StageAll => self.stage_all_now(),
+            PendingAction::InvertStaging => self.invert_staging_now(),
+            PendingAction::Push(options) => {
+                self.mode = AppMode::FileList;
+                self.push_to_remote(options);
+            }
+            PendingAction::Commit => self.perform_commit(),
+            PendingAction::EmptyCommit => {
+                self.commit_options.allow_empty = true;
+                self.mode = AppMode::CommitMessage;
+                self.edits = EditHistory::new(&self.commit_message);
+            }
+            PendingAction::CommitAsIdentity => self.scan_and_commit(),
+            PendingAction::CommitWithSecrets => self.check_and_commit(),
+            PendingAction::CommitGroups(answered) => self.check_and_commit_groups(Some(answered)),
+            PendingAction::Abort(operation) => self.abort_operation(operation),
+            PendingAction::UndoCommit => self.undo_last_commit(),
+            PendingAction::Unwip => self.unwip(),
+            PendingAction::DeleteTag(name) => self.delete_tag(&name),
+            PendingAction::Release => self.create_release(),
+            PendingAction::RestoreTo(id) => self.restore_to(&id),
+            PendingAction::RemoveRemote(name) => self.remove_remote(&name),
+        }
+    }
+}
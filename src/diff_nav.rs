@@ -0,0 +1,117 @@
+//! Moving through a diff of several files by hunk and by file, and naming
+//! the file on screen.
+
+use crate::app::App;
+
+/// A file's part of a combined diff.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffFile {
+    /// The line its `diff --git` header is on.
+    pub start: usize,
+    pub path: String,
+}
+
+/// The files in `diff`, in order.
+pub fn diff_files(diff: &str) -> Vec<DiffFile> {
+    diff.lines()
+        .enumerate()
+        .filter_map(|(start, line)| {
+            let paths = line.strip_prefix("diff --git a/")?;
+            let path = paths.rsplit_once(" b/").map_or(paths, |(_, new)| new);
+            Some(DiffFile {
+                start,
+                path: path.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The lines each `@@` hunk header is on.
+pub fn hunk_starts(diff: &str) -> Vec<usize> {
+    diff.lines()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("@@"))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Where to open an editor for row `row` of `diff`: the file on that row and
+/// the line, in its new version, of the first change from there on. Rows
+/// after a file's last change give the line they show.
+pub fn file_line_at(diff: &str, row: usize) -> Option<(String, usize)> {
+    let files = diff_files(diff);
+    let file = files
+        .iter()
+        .rev()
+        .find(|file| file.start <= row)
+        .or(files.first())?;
+    // The number, in the new version, of the next line in the hunk.
+    let mut next: Option<usize> = None;
+    let mut at_row = None;
+    for (index, text) in diff.lines().enumerate().skip(file.start + 1) {
+        if text.starts_with("diff --git ") {
+            break;
+        }
+        if text.starts_with("@@") {
+            next = text
+                .split_whitespace()
+                .nth(2)
+                .and_then(|new| new.trim_start_matches('+').split(',').next())
+                .and_then(|start| start.parse().ok());
+            continue;
+        }
+        let Some(line) = next else {
+            continue;
+        };
+        if index >= row {
+            if text.starts_with(['+', '-']) {
+                return Some((file.path.clone(), line.max(1)));
+            }
+            at_row.get_or_insert(line);
+        }
+        if !text.starts_with(['-', '\\']) {
+            next = Some(line + 1);
+        }
+    }
+    Some((file.path.clone(), at_row.unwrap_or(1).max(1)))
+}
+
+/// The first of `starts` after `line`, or the last before it.
+fn neighbour(starts: &[usize], line: usize, forward: bool) -> Option<usize> {
+    if forward {
+        starts.iter().copied().find(|&start| start > line)
+    } else {
+        starts.iter().copied().rev().find(|&start| start < line)
+    }
+}
+
+impl App {
+    /// `]` / `[` in the diff view.
+    pub(crate) fn jump_to_hunk(&mut self, forward: bool) {
+        let starts = hunk_starts(&self.diff_content);
+        self.jump_to(neighbour(&starts, self.diff_scroll as usize, forward));
+    }
+
+    /// `J` / `K` in the diff view.
+    pub(crate) fn jump_to_file(&mut self, forward: bool) {
+        let starts: Vec<usize> = diff_files(&self.diff_content)
+            .iter()
+            .map(|file| file.start)
+            .collect();
+        self.jump_to(neighbour(&starts, self.diff_scroll as usize, forward));
+    }
+
+    fn jump_to(&mut self, line: Option<usize>) {
+        if let Some(line) = line {
+            self.diff_scroll = line as u16;
+            self.scroll_diff(0);
+        }
+    }
+
+    /// Index into [`diff_files`] of the file at the top of the diff view.
+    pub(crate) fn current_diff_file(&self, files: &[DiffFile]) -> Option<usize> {
+        files
+            .iter()
+            .rposition(|file| file.start <= self.diff_scroll as usize)
+    }
+}
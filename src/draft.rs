@@ -0,0 +1,33 @@
+//! The in-progress commit message, saved in the `.git` directory so it
+//! survives quitting or crashing before the commit is made.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const DRAFT_FILE: &str = "GIT_COMMIT_HELPER_DRAFT";
+
+fn draft_path(git_dir: &Path) -> PathBuf {
+    git_dir.join(DRAFT_FILE)
+}
+
+/// The saved draft, if there is a non-empty one.
+pub fn load(git_dir: &Path) -> Option<String> {
+    fs::read_to_string(draft_path(git_dir))
+        .ok()
+        .filter(|draft| !draft.trim().is_empty())
+}
+
+/// Saves `message`, or removes the draft file when it is empty.
+pub fn save(git_dir: &Path, message: &str) -> io::Result<()> {
+    let path = draft_path(git_dir);
+    if message.is_empty() {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    } else {
+        fs::write(path, message)
+    }
+}
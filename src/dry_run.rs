@@ -0,0 +1,91 @@
+//! Previewing a commit without making it: the message as it will be
+//! recorded, the flags, the hooks and checks that will run, and what
+//! `git commit --dry-run` says it would contain.
+
+use crate::app::{App, AppMode};
+use std::{fs::Metadata, path::Path};
+
+/// The hooks `git commit` runs, in order.
+const COMMIT_HOOKS: [&str; 4] = [
+    "pre-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+];
+
+/// The hooks `--no-verify` skips; the others still run.
+const VERIFY_HOOKS: [&str; 2] = ["pre-commit", "commit-msg"];
+
+/// Which of the commit hooks are installed in `dir`, executable on Unix.
+pub fn installed_hooks(dir: &Path) -> Vec<&'static str> {
+    COMMIT_HOOKS
+        .into_iter()
+        .filter(|hook| {
+            dir.join(hook)
+                .metadata()
+                .is_ok_and(|metadata| metadata.is_file() && is_executable(&metadata))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &Metadata) -> bool {
+    true
+}
+
+impl App {
+    /// `F7` in the commit editor.
+    pub(crate) fn preview_commit(&mut self) {
+        let message = self.full_commit_message();
+        let options = self.commit_options.clone();
+        let mut text = String::from("Message:\n");
+        for line in message.lines() {
+            text.push_str(&format!("    {}\n", line));
+        }
+        let flags = options.args().join(" ");
+        if !flags.is_empty() {
+            text.push_str(&format!("\nFlags: {}\n", flags));
+        }
+        if let Some((_, value)) = options.env().first() {
+            text.push_str(&format!("Committer date: {}\n", value));
+        }
+
+        let mut hooks = match self.backend.hooks_dir() {
+            Some(dir) => installed_hooks(&dir),
+            None => Vec::new(),
+        };
+        if options.no_verify {
+            hooks.retain(|hook| !VERIFY_HOOKS.contains(hook));
+        }
+        if !hooks.is_empty() {
+            text.push_str(&format!(
+                "\nHooks: {} (they may still change the message or stop the commit)\n",
+                hooks.join(", ")
+            ));
+        }
+        if options.no_verify {
+            text.push_str("\nSkipped (--no-verify): the pre-commit and commit-msg hooks, checks\n");
+        } else {
+            for check in &self.config.checks {
+                text.push_str(&format!("Check: {}\n", check));
+            }
+        }
+
+        text.push_str("\ngit commit --dry-run:\n");
+        let (title, output) = match self.backend.commit_dry_run(&message, &options) {
+            Ok(output) => ("Dry run", output),
+            Err(output) => ("Dry run: the commit would fail", output),
+        };
+        for line in output.trim_end().lines() {
+            text.push_str(&format!("    {}\n", line));
+        }
+        self.show_output(title, text);
+        self.output.back = Some(AppMode::CommitMessage);
+    }
+}
@@ -0,0 +1,93 @@
+//! Editing text in the user's own editor, the way `git commit` does.
+
+use std::{fs, path::Path, process::Command};
+
+const HELP: &str = "\n\
+# Write the message above. Lines starting with '#' are ignored,
+# and an empty message keeps the current draft.
+";
+
+/// The editor git would use: `GIT_EDITOR`, `core.editor`, `VISUAL`, then
+/// `EDITOR`, falling back to `vi`.
+pub fn resolve_editor() -> String {
+    Command::new("git")
+        .args(["var", "GIT_EDITOR"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|editor| !editor.is_empty())
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// The arguments that open `path` at `line` in `editor`. Most editors take
+/// `+LINE`; a few want `path:line` instead.
+pub fn line_args(editor: &str, path: &Path, line: Option<usize>) -> Vec<String> {
+    let path = path.to_string_lossy().into_owned();
+    let Some(line) = line else {
+        return vec![path];
+    };
+    let program = editor
+        .split_whitespace()
+        .next()
+        .and_then(|program| program.rsplit('/').next())
+        .unwrap_or_default();
+    match program {
+        "code" | "code-insiders" | "codium" => {
+            vec!["--goto".to_string(), format!("{}:{}", path, line)]
+        }
+        "hx" | "helix" | "subl" | "zed" | "micro" => vec![format!("{}:{}", path, line)],
+        _ => vec![format!("+{}", line), path],
+    }
+}
+
+/// Opens `path` in `editor`, at `line` if given, and waits for it to exit.
+pub fn open_file(editor: &str, path: &Path, line: Option<usize>) -> Result<(), String> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor)
+        .args(line_args(editor, path, line))
+        .status()
+        .map_err(|e| format!("could not start {}: {}", editor, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", editor, status))
+    }
+}
+
+/// Writes `text` to `path`, opens it in `editor`, and returns the edited
+/// message with comment lines and trailing blank lines removed. The editor
+/// runs through the shell so values like `code --wait` work.
+pub fn edit_text(editor: &str, text: &str, path: &Path) -> Result<String, String> {
+    fs::write(path, format!("{}\n{}", text, HELP)).map_err(|e| e.to_string())?;
+
+    let result = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor)
+        .arg(path)
+        .status();
+    let edited = fs::read_to_string(path);
+    let _ = fs::remove_file(path);
+
+    let status = result.map_err(|e| format!("could not start {}: {}", editor, e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", editor, status));
+    }
+    Ok(strip_comments(&edited.map_err(|e| e.to_string())?))
+}
+
+/// `text` without `#` comment lines, trailing spaces, or trailing blank
+/// lines, as git cleans up a message.
+pub fn strip_comments(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect();
+    lines.join("\n").trim_end().to_string()
+}
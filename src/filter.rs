@@ -0,0 +1,99 @@
+//! Narrowing the file list: a fuzzy path query plus a status filter.
+
+use crate::git::{FileStatus, GitFile};
+
+/// Which files to show based on their staging state.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    Unstaged,
+    Staged,
+    Untracked,
+}
+
+impl StatusFilter {
+    pub fn next(self) -> StatusFilter {
+        match self {
+            StatusFilter::All => StatusFilter::Unstaged,
+            StatusFilter::Unstaged => StatusFilter::Staged,
+            StatusFilter::Staged => StatusFilter::Untracked,
+            StatusFilter::Untracked => StatusFilter::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StatusFilter::All => "all",
+            StatusFilter::Unstaged => "unstaged",
+            StatusFilter::Staged => "staged",
+            StatusFilter::Untracked => "untracked",
+        }
+    }
+
+    fn matches(self, file: &GitFile) -> bool {
+        match self {
+            StatusFilter::All => true,
+            StatusFilter::Unstaged => !file.staged && file.status != FileStatus::Untracked,
+            StatusFilter::Staged => file.staged,
+            StatusFilter::Untracked => file.status == FileStatus::Untracked,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileFilter {
+    pub query: String,
+    pub status: StatusFilter,
+}
+
+impl FileFilter {
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty() || self.status != StatusFilter::All
+    }
+
+    pub fn matches(&self, file: &GitFile) -> bool {
+        self.status.matches(file) && fuzzy_match(&self.query, &file.path).is_some()
+    }
+}
+
+/// Matches `query` as a subsequence of `text`, returning a score where
+/// higher is better (consecutive runs and matches after `/`, `_`, `-`, or
+/// `.` score extra). Case-insensitive unless the query contains uppercase.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<i64> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+
+    let mut score = 0;
+    let mut query_chars = query.chars().map(normalize).peekable();
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in text.chars() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        if normalize(c) == wanted {
+            query_chars.next();
+            score += 1;
+            if previous_matched {
+                score += 2;
+            }
+            if previous.is_none_or(|p| matches!(p, '/' | '_' | '-' | '.')) {
+                score += 3;
+            }
+            previous_matched = true;
+        } else {
+            previous_matched = false;
+        }
+        previous = Some(c);
+    }
+
+    query_chars.peek().is_none().then_some(score)
+}
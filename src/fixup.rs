@@ -0,0 +1,110 @@
+//! The fixup workflow: commit the staged changes as `fixup!` against a
+//! commit picked from recent history, then fold the fixups in with
+//! `git rebase -i --autosquash`.
+
+use crate::{
+    app::{App, AppMode, TaskKind},
+    git::CommitInfo,
+    rebase::LOG_LIMIT,
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct FixupPicker {
+    /// Recent history, newest first.
+    pub log: Vec<CommitInfo>,
+    pub selected: usize,
+}
+
+/// The commit a `fixup!`, `squash!`, or `amend!` subject points at.
+fn fixup_target(summary: &str) -> Option<&str> {
+    let mut target = summary;
+    while let Some(rest) = ["fixup! ", "squash! ", "amend! "]
+        .iter()
+        .find_map(|prefix| target.strip_prefix(prefix))
+    {
+        target = rest;
+    }
+    (target.len() < summary.len()).then_some(target)
+}
+
+/// Where an autosquash rebase has to start so that it covers every pending
+/// fixup in `log` (newest first): the parent of the oldest target. `None`
+/// when there is nothing to squash; `Some(None)` when the rebase has to
+/// start from the root.
+pub fn autosquash_base(log: &[CommitInfo]) -> Option<Option<String>> {
+    let oldest_target = log
+        .iter()
+        .enumerate()
+        .filter_map(|(index, commit)| {
+            let target = fixup_target(&commit.summary)?;
+            log[index + 1..]
+                .iter()
+                .position(|older| older.summary == target)
+                .map(|offset| index + 1 + offset)
+        })
+        .max()?;
+
+    let is_root = oldest_target + 1 == log.len() && log.len() < LOG_LIMIT;
+    Some((!is_root).then(|| format!("{}^", log[oldest_target].id)))
+}
+
+impl App {
+    pub(crate) fn open_fixup(&mut self) {
+        if !self.has_staged_files() {
+            self.show_notification("Stage the changes for the fixup first".to_string());
+            return;
+        }
+        match self.backend.log(LOG_LIMIT) {
+            Ok(log) if !log.is_empty() => {
+                self.fixup = FixupPicker { log, selected: 0 };
+                self.mode = AppMode::FixupPick;
+            }
+            Ok(_) => self.show_notification("No commits to fix up".to_string()),
+            Err(error) => self.report_error(GitError::new("Reading history", error)),
+        }
+    }
+
+    pub(crate) fn handle_fixup_input(&mut self, key: KeyCode) {
+        let picker = &mut self.fixup;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if picker.selected + 1 < picker.log.len() => {
+                picker.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                picker.selected = picker.selected.saturating_sub(1);
+            }
+            KeyCode::Enter if self.task.is_some() => {
+                self.show_notification("Another git operation is still running".to_string());
+            }
+            KeyCode::Enter => {
+                let target = picker.log[picker.selected].id.clone();
+                let backend = Arc::clone(&self.backend);
+                self.start_task(TaskKind::Fixup, move |cancel| {
+                    backend.commit_fixup(&target, cancel)
+                });
+                self.mode = AppMode::FileList;
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn autosquash(&mut self) {
+        if self.task.is_some() || self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        let log = self.backend.log(LOG_LIMIT).unwrap_or_default();
+        let Some(base) = autosquash_base(&log) else {
+            self.show_notification("No fixup! commits to squash".to_string());
+            return;
+        };
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Autosquash, move |cancel| {
+            backend.autosquash(base.as_deref(), cancel)
+        });
+    }
+}
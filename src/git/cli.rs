@@ -0,0 +1,678 @@
+use super::{
+    CommitInfo, CommitOptions, ConflictSide, DiffOptions, GitBackend, GitStatus, Identity,
+    Operation, PullMode, PushOptions, RebaseAction, RebaseEntry, ReflogEntry, RemoteInfo,
+    SigningConfig, SubmoduleInfo, SubmoduleState, TAG_FORMAT, TagInfo, WorktreeInfo,
+    apply_line_counts, detect_operation, head_signature, parse_clean_preview, parse_file_log,
+    parse_head_commit, parse_log_bodies, parse_numstat_z, parse_reflog, parse_remotes,
+    parse_status_z, parse_submodule_status, parse_tags, parse_worktrees, record_command,
+    remove_untracked, run_git_cancellable, run_git_cancellable_all, run_git_cancellable_with_env,
+    run_git_foreground, run_git_foreground_with_env,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::AtomicBool,
+};
+
+pub struct CliBackend;
+
+/// Quotes `text` for `sh`.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+impl CliBackend {
+    /// `git log` with `extra` arguments, one [`CommitInfo`] per commit.
+    fn log_commits(&self, extra: &[&str]) -> Result<Vec<CommitInfo>, String> {
+        let mut args = vec!["log", "--format=%H%x00%h%x00%an%x00%at%x00%s"];
+        args.extend(extra);
+        let output = self.run(&args)?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(5, '\0');
+                Some(CommitInfo {
+                    id: fields.next()?.to_string(),
+                    short_id: fields.next()?.to_string(),
+                    author: fields.next()?.to_string(),
+                    time: fields.next()?.parse().unwrap_or(0),
+                    summary: fields.next()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    fn run(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .args(args)
+            .output()
+            .map_err(|e| e.to_string())?;
+        record_command(args, output.status.success());
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).to_string())
+        }
+    }
+}
+
+impl GitBackend for CliBackend {
+    fn status(&self) -> GitStatus {
+        let mut status = GitStatus {
+            current_branch: self.current_branch(),
+            upstream: self
+                .run(&["rev-parse", "--abbrev-ref", "@{u}"])
+                .ok()
+                .map(|upstream| upstream.trim().to_string()),
+            operation: self.git_dir().as_deref().and_then(detect_operation),
+            head_signature: self
+                .run(&["rev-parse", "-q", "--verify", "HEAD"])
+                .ok()
+                .and_then(|id| head_signature(id.trim())),
+            head: self
+                .run(&["log", "-1", "--format=%h%x00%s"])
+                .ok()
+                .and_then(|output| parse_head_commit(&output)),
+            stash_count: self
+                .run(&["stash", "list", "--format=%h"])
+                .map(|output| output.lines().count())
+                .unwrap_or(0),
+            ..GitStatus::default()
+        };
+        if status.detached() {
+            status.describe = self
+                .run(&["describe", "--tags"])
+                .ok()
+                .map(|describe| describe.trim().to_string());
+        }
+
+        // Get ahead/behind counts; without an upstream there is nothing to
+        // count against.
+        if status.upstream.is_some()
+            && let Ok(counts) = self.run(&["rev-list", "--left-right", "--count", "HEAD...@{u}"])
+        {
+            let parts: Vec<&str> = counts.trim().split('\t').collect();
+            if parts.len() == 2 {
+                status.ahead = parts[0].parse().unwrap_or(0);
+                status.behind = parts[1].parse().unwrap_or(0);
+            }
+        }
+
+        // Get file status
+        if let Ok(output) = self.run(&["status", "--porcelain", "-z"]) {
+            status.files = parse_status_z(&output);
+        }
+        let numstat = |extra: &[&str]| {
+            let mut args = vec!["diff", "--numstat", "-z"];
+            args.extend(extra);
+            self.run(&args)
+                .map(|output| parse_numstat_z(&output))
+                .unwrap_or_default()
+        };
+        apply_line_counts(&mut status.files, &numstat(&["--cached"]), &numstat(&[]));
+
+        status
+    }
+
+    fn current_branch(&self) -> String {
+        self.run(&["branch", "--show-current"])
+            .map(|branch| branch.trim().to_string())
+            .unwrap_or_else(|_| "unknown".to_string())
+    }
+
+    fn git_dir(&self) -> Option<PathBuf> {
+        self.run(&["rev-parse", "--absolute-git-dir"])
+            .ok()
+            .map(|dir| PathBuf::from(dir.trim()))
+    }
+
+    fn work_dir(&self) -> Option<PathBuf> {
+        self.run(&["rev-parse", "--show-toplevel"])
+            .ok()
+            .map(|dir| PathBuf::from(dir.trim()))
+    }
+
+    fn remotes(&self) -> Vec<String> {
+        self.run(&["remote"])
+            .map(|remotes| remotes.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    fn signing_config(&self) -> SigningConfig {
+        let get = |key: &str| {
+            self.run(&["config", "--get", key])
+                .ok()
+                .map(|value| value.trim().to_string())
+        };
+        SigningConfig {
+            sign_by_default: self
+                .run(&["config", "--type=bool", "--get", "commit.gpgsign"])
+                .is_ok_and(|value| value.trim() == "true"),
+            format: get("gpg.format").unwrap_or_else(|| "openpgp".to_string()),
+            key: get("user.signingkey"),
+        }
+    }
+
+    fn identity(&self) -> Identity {
+        let get = |key: &str| {
+            self.run(&["config", "--get", key])
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Identity {
+            name: get("user.name"),
+            email: get("user.email"),
+            email_domain: get("gch.emailDomain"),
+        }
+    }
+
+    fn set_identity(&self, name: &str, email: &str) -> Result<(), String> {
+        self.run(&["config", "--local", "user.name", name])?;
+        self.run(&["config", "--local", "user.email", email])
+            .map(|_| ())
+    }
+
+    fn lfs_installed(&self) -> bool {
+        self.run(&["config", "--get", "filter.lfs.clean"])
+            .is_ok_and(|value| !value.trim().is_empty())
+    }
+
+    fn stage(&self, path: &str) -> Result<(), String> {
+        // `[id].tsx` would also match `i.tsx` as a glob.
+        let pathspec = format!(":(literal){}", path);
+        self.run(&["add", "--", &pathspec]).map(|_| ())
+    }
+
+    fn intent_to_add(&self, path: &str) -> Result<(), String> {
+        self.run(&["add", "--intent-to-add", "--", path])
+            .map(|_| ())
+    }
+
+    fn unstage(&self, path: &str) -> Result<(), String> {
+        // As in `unstage_all`: with no HEAD, take the path out of the index.
+        if self.run(&["rev-parse", "--verify", "-q", "HEAD"]).is_ok() {
+            self.run(&["reset", "-q", "HEAD", "--", path]).map(|_| ())
+        } else {
+            self.run(&["rm", "-r", "-q", "--cached", "--force", "--", path])
+                .map(|_| ())
+        }
+    }
+
+    fn stage_all(&self) -> Result<(), String> {
+        self.run(&["add", "-A"]).map(|_| ())
+    }
+
+    fn unstage_all(&self) -> Result<(), String> {
+        // `git reset` needs a HEAD; before the first commit, empty the index instead.
+        if self.run(&["rev-parse", "--verify", "-q", "HEAD"]).is_ok() {
+            self.run(&["reset", "-q"]).map(|_| ())
+        } else {
+            self.run(&["rm", "-r", "-q", "--cached", "--ignore-unmatch", "."])
+                .map(|_| ())
+        }
+    }
+
+    fn diff(&self, path: &str, staged: bool, options: &DiffOptions) -> Result<String, String> {
+        let flags = options.args();
+        let mut args = vec!["diff"];
+        if staged {
+            args.push("--staged");
+        }
+        args.extend(flags.iter().map(String::as_str));
+        let pathspec = format!(":(literal){}", path);
+        args.extend(["--", &pathspec]);
+        self.run(&args)
+    }
+
+    fn discard(&self, path: &str, untracked: bool) -> Result<(), String> {
+        if untracked {
+            let toplevel = self.run(&["rev-parse", "--show-toplevel"])?;
+            remove_untracked(Path::new(toplevel.trim()), path)
+        } else {
+            self.run(&["checkout", "--", path]).map(|_| ())
+        }
+    }
+
+    fn commit(
+        &self,
+        message: &str,
+        options: &CommitOptions,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        let mut args = vec!["commit"];
+        args.extend(options.args());
+        args.extend(["-m", message]);
+        run_git_cancellable_with_env(&args, &options.env(), cancel)
+    }
+
+    fn commit_foreground(&self, message: &str, options: &CommitOptions) -> Result<String, String> {
+        let mut args = vec!["commit"];
+        args.extend(options.args());
+        args.extend(["-m", message]);
+        run_git_foreground_with_env(&args, &options.env())
+    }
+
+    fn commit_dry_run(&self, message: &str, options: &CommitOptions) -> Result<String, String> {
+        let mut args = vec!["commit", "--dry-run"];
+        args.extend(options.args());
+        args.extend(["-m", message]);
+        // Nothing to commit exits 1, with the reason on stdout.
+        run_git_cancellable_with_env(&args, &options.env(), &AtomicBool::new(false))
+    }
+
+    fn hooks_dir(&self) -> Option<PathBuf> {
+        let dir = PathBuf::from(self.run(&["rev-parse", "--git-path", "hooks"]).ok()?.trim());
+        if dir.is_relative() {
+            std::env::current_dir().ok().map(|cwd| cwd.join(dir))
+        } else {
+            Some(dir)
+        }
+    }
+
+    fn config_values(&self, key: &str) -> Vec<String> {
+        self.run(&["config", "--get-all", key])
+            .map(|output| output.lines().map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    fn commit_template(&self) -> Option<PathBuf> {
+        let path = self
+            .run(&["config", "--path", "--get", "commit.template"])
+            .ok()?;
+        let path = PathBuf::from(path.trim());
+        // Relative to the top of the working tree, where `git commit` runs.
+        Some(match self.work_dir() {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path,
+        })
+    }
+
+    fn push(&self, options: &PushOptions, cancel: &AtomicBool) -> Result<String, String> {
+        let flags = options.args();
+        let mut args = vec!["push"];
+        args.extend(flags.iter().map(String::as_str));
+        run_git_cancellable(&args, cancel)
+    }
+
+    fn push_foreground(&self, options: &PushOptions) -> Result<String, String> {
+        let flags = options.args();
+        let mut args = vec!["push"];
+        args.extend(flags.iter().map(String::as_str));
+        run_git_foreground(&args)
+    }
+
+    fn fetch(&self, cancel: &AtomicBool) -> Result<String, String> {
+        run_git_cancellable(&["fetch"], cancel)
+    }
+
+    fn pull(&self, mode: PullMode, cancel: &AtomicBool) -> Result<String, String> {
+        run_git_cancellable(&["pull", mode.flag()], cancel)
+    }
+
+    fn resolve_conflict(&self, path: &str, side: ConflictSide) -> Result<(), String> {
+        self.run(&["checkout", side.flag(), "--", path])?;
+        self.stage(path)
+    }
+
+    fn continue_operation(
+        &self,
+        operation: Operation,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        run_git_cancellable(&[operation.command(), "--continue"], cancel)
+    }
+
+    fn abort_operation(&self, operation: Operation) -> Result<(), String> {
+        self.run(&[operation.command(), "--abort"]).map(|_| ())
+    }
+
+    fn commit_fixup(&self, target: &str, cancel: &AtomicBool) -> Result<String, String> {
+        let fixup = format!("--fixup={}", target);
+        run_git_cancellable(&["commit", &fixup], cancel)
+    }
+
+    fn autosquash(&self, base: Option<&str>, cancel: &AtomicBool) -> Result<String, String> {
+        run_git_cancellable_with_env(
+            &["rebase", "-i", "--autosquash", base.unwrap_or("--root")],
+            &[("GIT_SEQUENCE_EDITOR", "true")],
+            cancel,
+        )
+    }
+
+    fn cherry_pick(&self, id: &str, cancel: &AtomicBool) -> Result<String, String> {
+        run_git_cancellable(&["cherry-pick", id], cancel)
+    }
+
+    fn staged_patch(&self) -> Result<String, String> {
+        self.run(&["diff", "--cached", "--binary"])
+    }
+
+    fn format_patch(&self, commits: &str) -> Result<String, String> {
+        if commits.contains("..") {
+            self.run(&["format-patch", "--stdout", commits, "--"])
+        } else {
+            self.run(&["format-patch", "--stdout", "-1", commits, "--"])
+        }
+    }
+
+    fn apply_patch(
+        &self,
+        path: &Path,
+        mailbox: bool,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        let top = self.work_dir().ok_or("repository has no working tree")?;
+        let top = top.to_string_lossy();
+        let path = path.to_string_lossy();
+        let command = if mailbox { "am" } else { "apply" };
+        run_git_cancellable(&["-C", &top, command, "--3way", &path], cancel)
+    }
+
+    fn revert(&self, id: &str, no_commit: bool, cancel: &AtomicBool) -> Result<String, String> {
+        let mode = if no_commit {
+            "--no-commit"
+        } else {
+            "--no-edit"
+        };
+        run_git_cancellable(&["revert", mode, id], cancel)
+    }
+
+    fn remote_list(&self) -> Result<Vec<RemoteInfo>, String> {
+        Ok(parse_remotes(&self.run(&["remote", "-v"])?))
+    }
+
+    fn submodules(&self) -> Result<Vec<SubmoduleInfo>, String> {
+        let top = self.work_dir().ok_or("repository has no working tree")?;
+        let top = top.to_string_lossy();
+        let mut submodules =
+            parse_submodule_status(&self.run(&["-C", &top, "submodule", "status"])?);
+        for submodule in &mut submodules {
+            if submodule.state == SubmoduleState::Uninitialized {
+                continue;
+            }
+            let dir = format!("{}/{}", top, submodule.path);
+            submodule.dirty = self
+                .run(&["-C", &dir, "status", "--porcelain"])
+                .is_ok_and(|output| !output.trim().is_empty());
+            if submodule.state != SubmoduleState::Moved {
+                continue;
+            }
+            // The recorded commit may not have been fetched into the
+            // submodule; then there is nothing to count against.
+            let recorded = self.run(&["-C", &top, "rev-parse", &format!(":{}", submodule.path)])?;
+            let range = format!("{}...HEAD", recorded.trim());
+            if let Ok(counts) =
+                self.run(&["-C", &dir, "rev-list", "--left-right", "--count", &range])
+                && let Some((behind, ahead)) = counts.trim().split_once('\t')
+            {
+                submodule.behind = behind.parse().unwrap_or(0);
+                submodule.ahead = ahead.parse().unwrap_or(0);
+            }
+        }
+        Ok(submodules)
+    }
+
+    fn update_submodules(&self, cancel: &AtomicBool) -> Result<String, String> {
+        run_git_cancellable(&["submodule", "update", "--init", "--recursive"], cancel)
+    }
+
+    fn worktrees(&self) -> Result<Vec<WorktreeInfo>, String> {
+        Ok(parse_worktrees(&self.run(&[
+            "worktree",
+            "list",
+            "--porcelain",
+        ])?))
+    }
+
+    fn add_worktree(&self, path: &Path, branch: &str) -> Result<(), String> {
+        let path = path.to_string_lossy();
+        let local = format!("refs/heads/{}", branch);
+        let remote = format!("refs/remotes/*/{}", branch);
+        let exists = self
+            .run(&["for-each-ref", "--format=%(refname)", &local, &remote])
+            .is_ok_and(|refs| !refs.trim().is_empty());
+        // An existing branch, or one git creates to track the remote's.
+        if exists {
+            self.run(&["worktree", "add", &path, branch]).map(|_| ())
+        } else {
+            self.run(&["worktree", "add", "-b", branch, &path])
+                .map(|_| ())
+        }
+    }
+
+    fn create_branch(&self, name: &str) -> Result<(), String> {
+        self.run(&["switch", "-c", name]).map(|_| ())
+    }
+
+    fn run_command(&self, args: &[String], cancel: &AtomicBool) -> Result<String, String> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run_git_cancellable_all(&args, cancel)
+    }
+
+    fn clean_preview(&self, ignored: bool) -> Result<Vec<String>, String> {
+        let top = self.work_dir().ok_or("repository has no working tree")?;
+        let top = top.to_string_lossy();
+        let mut args = vec![
+            "-C",
+            &top,
+            "-c",
+            "core.quotePath=false",
+            "clean",
+            "-n",
+            "-d",
+        ];
+        if ignored {
+            args.push("-x");
+        }
+        Ok(parse_clean_preview(&self.run(&args)?))
+    }
+
+    fn clean(&self, paths: &[String], ignored: bool) -> Result<(), String> {
+        let top = self.work_dir().ok_or("repository has no working tree")?;
+        let top = top.to_string_lossy();
+        let pathspecs: Vec<String> = paths.iter().map(|p| format!(":(literal){}", p)).collect();
+        let mut args = vec!["-C", &top, "clean", "-f", "-d"];
+        if ignored {
+            args.push("-x");
+        }
+        args.push("--");
+        args.extend(pathspecs.iter().map(String::as_str));
+        self.run(&args).map(|_| ())
+    }
+
+    fn add_remote(&self, name: &str, url: &str) -> Result<(), String> {
+        self.run(&["remote", "add", name, url]).map(|_| ())
+    }
+
+    fn remove_remote(&self, name: &str) -> Result<(), String> {
+        self.run(&["remote", "remove", name]).map(|_| ())
+    }
+
+    fn rename_remote(&self, old: &str, new: &str) -> Result<(), String> {
+        self.run(&["remote", "rename", old, new]).map(|_| ())
+    }
+
+    fn set_remote_url(&self, name: &str, url: &str) -> Result<(), String> {
+        self.run(&["remote", "set-url", name, url]).map(|_| ())
+    }
+
+    fn ahead_behind(&self, base: &str) -> Result<(usize, usize), String> {
+        let range = format!("HEAD...{}", base);
+        let counts = self.run(&["rev-list", "--left-right", "--count", &range, "--"])?;
+        let (ahead, behind) = counts
+            .trim()
+            .split_once('\t')
+            .ok_or_else(|| format!("unexpected rev-list output: {}", counts.trim()))?;
+        Ok((
+            ahead.parse().map_err(|_| "bad ahead count".to_string())?,
+            behind.parse().map_err(|_| "bad behind count".to_string())?,
+        ))
+    }
+
+    fn latest_tag(&self) -> Option<String> {
+        self.run(&["describe", "--tags", "--abbrev=0"])
+            .ok()
+            .map(|tag| tag.trim().to_string())
+    }
+
+    fn log_since(&self, tag: Option<&str>) -> Result<Vec<(CommitInfo, String)>, String> {
+        let range = tag.map(|tag| format!("{}..HEAD", tag));
+        let mut args = vec![
+            "log",
+            "--no-merges",
+            "--format=%x1e%H%x00%h%x00%an%x00%at%x00%s%x00%b",
+        ];
+        args.extend(range.as_deref());
+        Ok(parse_log_bodies(&self.run(&args)?))
+    }
+
+    fn reflog(&self, limit: usize) -> Result<Vec<ReflogEntry>, String> {
+        let limit = format!("-n{}", limit);
+        let output = self.run(&["reflog", &limit, "--format=%H%x00%h%x00%gd%x00%gs%x00%s"])?;
+        Ok(parse_reflog(&output))
+    }
+
+    fn checkout_detached(&self, id: &str) -> Result<(), String> {
+        self.run(&["checkout", "--detach", id]).map(|_| ())
+    }
+
+    fn reset_keep(&self, id: &str) -> Result<(), String> {
+        self.run(&["reset", "--keep", id]).map(|_| ())
+    }
+
+    fn tags(&self) -> Result<Vec<TagInfo>, String> {
+        let format = format!("--format={}", TAG_FORMAT);
+        let output = self.run(&["for-each-ref", "--sort=-creatordate", &format, "refs/tags"])?;
+        Ok(parse_tags(&output))
+    }
+
+    fn create_tag(&self, name: &str, message: Option<&str>) -> Result<(), String> {
+        match message {
+            Some(message) => self.run(&["tag", "-a", name, "-m", message]),
+            None => self.run(&["tag", name]),
+        }
+        .map(|_| ())
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<(), String> {
+        self.run(&["tag", "-d", name]).map(|_| ())
+    }
+
+    fn push_tags(
+        &self,
+        remote: &str,
+        name: Option<&str>,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        match name {
+            Some(name) => {
+                run_git_cancellable(&["push", remote, &format!("refs/tags/{}", name)], cancel)
+            }
+            None => run_git_cancellable(&["push", remote, "--tags"], cancel),
+        }
+    }
+
+    fn undo_last_commit(&self) -> Result<String, String> {
+        let message = self.run(&["log", "-1", "--format=%B"])?;
+        if self.run(&["rev-parse", "--verify", "-q", "HEAD~1"]).is_ok() {
+            self.run(&["reset", "--soft", "HEAD~1"])?;
+        } else {
+            // The root commit has no parent to reset to; unborn the branch
+            // instead, which leaves the index as it is.
+            self.run(&["update-ref", "-d", "HEAD"])?;
+        }
+        Ok(message.trim_end().to_string())
+    }
+
+    fn log(&self, limit: usize) -> Result<Vec<CommitInfo>, String> {
+        self.log_commits(&[&format!("-n{}", limit)])
+    }
+
+    fn log_range(&self, range: &str, limit: usize) -> Result<Vec<CommitInfo>, String> {
+        self.log_commits(&[&format!("-n{}", limit), range, "--"])
+    }
+
+    fn range_diff(&self, base: &str, head: &str, options: &DiffOptions) -> Result<String, String> {
+        let range = format!("{}...{}", base, head);
+        let flags = options.args();
+        let mut args = vec!["diff"];
+        args.extend(flags.iter().map(String::as_str));
+        args.extend([range.as_str(), "--"]);
+        self.run(&args)
+    }
+
+    fn file_log(&self, path: &str, limit: usize) -> Result<Vec<(CommitInfo, String)>, String> {
+        let limit = format!("-n{}", limit);
+        let output = self.run(&[
+            "log",
+            "--follow",
+            &limit,
+            "--format=%x1e%H%x00%h%x00%an%x00%at%x00%s",
+            "--name-only",
+            "--",
+            path,
+        ])?;
+        Ok(parse_file_log(&output))
+    }
+
+    fn show(&self, id: &str, path: Option<&str>, options: &DiffOptions) -> Result<String, String> {
+        let flags = options.args();
+        let mut args = vec!["show"];
+        match path {
+            Some(_) => args.push("--format="),
+            None => args.extend(["--format=fuller", "--decorate=short", "--stat", "-p"]),
+        }
+        args.extend(flags.iter().map(String::as_str));
+        args.push(id);
+        if let Some(path) = path {
+            args.extend(["--", path]);
+        }
+        self.run(&args)
+    }
+
+    fn rebase_interactive(
+        &self,
+        base: Option<&str>,
+        todo: &[RebaseEntry],
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        let dir = std::env::temp_dir().join(format!("gch-rebase-{}", std::process::id()));
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+        // Rewords become a pick followed by an amend with the new message, so
+        // git never needs to open an editor.
+        let mut lines = Vec::new();
+        for (index, entry) in todo.iter().enumerate() {
+            match &entry.action {
+                RebaseAction::Reword(message) => {
+                    let path = dir.join(format!("message-{}", index));
+                    fs::write(&path, message).map_err(|e| e.to_string())?;
+                    lines.push(format!("pick {}", entry.commit.id));
+                    lines.push(format!(
+                        "exec git commit --amend --only --allow-empty --no-verify -q -F {}",
+                        shell_quote(&path.to_string_lossy())
+                    ));
+                }
+                action => lines.push(format!("{} {}", action.label(), entry.commit.id)),
+            }
+        }
+        let todo_path = dir.join("git-rebase-todo");
+        fs::write(&todo_path, lines.join("\n") + "\n").map_err(|e| e.to_string())?;
+
+        let sequence_editor = format!("cp {}", shell_quote(&todo_path.to_string_lossy()));
+        let mut args = vec!["rebase", "-i"];
+        args.push(base.unwrap_or("--root"));
+        let result = run_git_cancellable_with_env(
+            &args,
+            &[("GIT_SEQUENCE_EDITOR", &sequence_editor)],
+            cancel,
+        );
+        // Rewords that haven't run yet still need their message files.
+        if self.git_dir().as_deref().and_then(detect_operation) != Some(Operation::Rebase) {
+            let _ = fs::remove_dir_all(&dir);
+        }
+        result
+    }
+}
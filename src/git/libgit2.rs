@@ -0,0 +1,696 @@
+use super::{
+    CliBackend, CommitInfo, CommitOptions, ConflictSide, DiffAlgorithm, DiffOptions, GitBackend,
+    GitFile, GitStatus, HeadCommit, Identity, Operation, PullMode, PushOptions, RebaseEntry,
+    ReflogEntry, RemoteInfo, SigningConfig, SubmoduleInfo, TagInfo, WorktreeInfo,
+    apply_line_counts, classify_status, head_signature, remove_untracked,
+};
+use git2::{
+    DescribeOptions, DiffFormat, IndexAddOption, IndexEntryExtendedFlag, ObjectType, Repository,
+    RepositoryState, ResetType, StatusOptions, build::CheckoutBuilder,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, atomic::AtomicBool},
+};
+
+/// In-process backend built on libgit2. The repository handle isn't `Sync`,
+/// so it lives behind a mutex.
+pub struct Git2Backend {
+    repo: Mutex<Repository>,
+}
+
+impl Git2Backend {
+    pub fn open() -> Result<Git2Backend, git2::Error> {
+        let repo = Repository::open_from_env()?;
+        if repo.is_bare() {
+            return Err(git2::Error::from_str("bare repositories are not supported"));
+        }
+        Ok(Git2Backend {
+            repo: Mutex::new(repo),
+        })
+    }
+
+    fn repo(&self) -> MutexGuard<'_, Repository> {
+        self.repo.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The upstream's short name and the ahead/behind counts against it.
+    fn upstream(repo: &Repository) -> Result<(String, usize, usize), git2::Error> {
+        let head = repo.head()?;
+        let branch = git2::Branch::wrap(head);
+        let upstream = branch.upstream()?;
+        let name = upstream.name()?.unwrap_or_default().to_string();
+        let local = branch.get().peel_to_commit()?.id();
+        let remote = upstream.get().peel_to_commit()?.id();
+        let (ahead, behind) = repo.graph_ahead_behind(local, remote)?;
+        Ok((name, ahead, behind))
+    }
+
+    fn status_chars(flags: git2::Status) -> (char, char) {
+        if flags.is_conflicted() {
+            return ('U', 'U');
+        }
+        let staged = if flags.is_index_new() {
+            'A'
+        } else if flags.is_index_modified() {
+            'M'
+        } else if flags.is_index_deleted() {
+            'D'
+        } else if flags.is_index_renamed() {
+            'R'
+        } else if flags.is_index_typechange() {
+            'T'
+        } else if flags.is_wt_new() {
+            '?'
+        } else {
+            ' '
+        };
+        let unstaged = if flags.is_wt_new() {
+            '?'
+        } else if flags.is_wt_modified() {
+            'M'
+        } else if flags.is_wt_deleted() {
+            'D'
+        } else if flags.is_wt_renamed() {
+            'R'
+        } else if flags.is_wt_typechange() {
+            'T'
+        } else {
+            ' '
+        };
+        (staged, unstaged)
+    }
+}
+
+impl GitBackend for Git2Backend {
+    fn status(&self) -> GitStatus {
+        let mut status = GitStatus {
+            current_branch: self.current_branch(),
+            ..GitStatus::default()
+        };
+
+        let repo = self.repo();
+        status.operation = match repo.state() {
+            RepositoryState::Merge => Some(Operation::Merge),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(Operation::Rebase),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(Operation::CherryPick)
+            }
+            RepositoryState::Revert | RepositoryState::RevertSequence => Some(Operation::Revert),
+            RepositoryState::ApplyMailbox => Some(Operation::Am),
+            _ => None,
+        };
+        if let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) {
+            status.head = Some(HeadCommit {
+                short_id: commit
+                    .as_object()
+                    .short_id()
+                    .ok()
+                    .and_then(|id| id.as_str().map(str::to_string))
+                    .unwrap_or_else(|| commit.id().to_string()[..7].to_string()),
+                subject: commit.summary().unwrap_or_default().to_string(),
+            });
+        }
+        if status.detached() {
+            let mut options = DescribeOptions::new();
+            options.describe_tags();
+            status.describe = repo
+                .describe(&options)
+                .and_then(|describe| describe.format(None))
+                .ok();
+        }
+        status.stash_count = repo
+            .reflog("refs/stash")
+            .map(|reflog| reflog.len())
+            .unwrap_or(0);
+        if let Ok((upstream, ahead, behind)) = Self::upstream(&repo) {
+            status.upstream = Some(upstream);
+            status.ahead = ahead as i32;
+            status.behind = behind as i32;
+        }
+        // Only pay for verification when HEAD carries a signature at all.
+        let signed = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .filter(|id| repo.extract_signature(id, None).is_ok());
+        if let Some(id) = signed {
+            status.head_signature = head_signature(&id.to_string());
+        }
+
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .renames_head_to_index(true);
+        let index = repo.index().ok();
+        if let Ok(statuses) = repo.statuses(Some(&mut options)) {
+            for entry in statuses.iter() {
+                if entry.status().is_ignored() {
+                    continue;
+                }
+                let rename = entry
+                    .head_to_index()
+                    .filter(|_| entry.status().is_index_renamed());
+                let path = rename
+                    .as_ref()
+                    .and_then(|delta| delta.new_file().path().map(|p| p.to_path_buf()))
+                    .or_else(|| entry.path().map(Into::into));
+                let Some(path) = path else {
+                    continue;
+                };
+                let orig_path = rename
+                    .as_ref()
+                    .and_then(|delta| delta.old_file().path())
+                    .map(|p| p.to_string_lossy().to_string());
+
+                // libgit2 reports an intent-to-add entry as a staged empty
+                // file; git shows it as ` A`.
+                let intent_to_add = index
+                    .as_ref()
+                    .and_then(|index| index.get_path(&path, 0))
+                    .is_some_and(|entry| {
+                        entry.flags_extended & IndexEntryExtendedFlag::INTENT_TO_ADD.bits() != 0
+                    });
+                let (staged_status, unstaged_status) = if intent_to_add {
+                    (' ', 'A')
+                } else {
+                    Self::status_chars(entry.status())
+                };
+                let (file_status, staged) = classify_status(staged_status, unstaged_status);
+                status.files.push(GitFile {
+                    path: path.to_string_lossy().to_string(),
+                    status: file_status,
+                    staged,
+                    orig_path,
+                    marked: false,
+                    insertions: 0,
+                    deletions: 0,
+                });
+            }
+        }
+
+        let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+        let staged = repo
+            .diff_tree_to_index(head_tree.as_ref(), None, None)
+            .map(|mut diff| {
+                // Pairs staged renames up, as `--numstat` does, so they are
+                // counted under their new path rather than as all new lines.
+                let _ = diff.find_similar(None);
+                line_counts(&diff)
+            })
+            .unwrap_or_default();
+        let unstaged = repo
+            .diff_index_to_workdir(None, None)
+            .map(|diff| line_counts(&diff))
+            .unwrap_or_default();
+        apply_line_counts(&mut status.files, &staged, &unstaged);
+
+        status
+    }
+
+    fn current_branch(&self) -> String {
+        // Read HEAD's symbolic target rather than resolving it so that an
+        // unborn branch still reports its name, like `git branch --show-current`.
+        let repo = self.repo();
+        match repo.find_reference("HEAD") {
+            Ok(head) => head
+                .symbolic_target()
+                .map(|target| target.trim_start_matches("refs/heads/").to_string())
+                .unwrap_or_default(),
+            Err(_) => "unknown".to_string(),
+        }
+    }
+
+    fn git_dir(&self) -> Option<PathBuf> {
+        Some(self.repo().path().to_path_buf())
+    }
+
+    fn work_dir(&self) -> Option<PathBuf> {
+        self.repo().workdir().map(Path::to_path_buf)
+    }
+
+    fn remotes(&self) -> Vec<String> {
+        self.repo()
+            .remotes()
+            .map(|remotes| remotes.iter().flatten().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    fn signing_config(&self) -> SigningConfig {
+        let Ok(config) = self.repo().config() else {
+            return SigningConfig::default();
+        };
+        SigningConfig {
+            sign_by_default: config.get_bool("commit.gpgsign").unwrap_or(false),
+            format: config
+                .get_string("gpg.format")
+                .unwrap_or_else(|_| "openpgp".to_string()),
+            key: config.get_string("user.signingkey").ok(),
+        }
+    }
+
+    fn identity(&self) -> Identity {
+        let Ok(config) = self.repo().config() else {
+            return Identity::default();
+        };
+        let get = |key: &str| {
+            config
+                .get_string(key)
+                .ok()
+                .filter(|value| !value.is_empty())
+        };
+        Identity {
+            name: get("user.name"),
+            email: get("user.email"),
+            email_domain: get("gch.emailDomain"),
+        }
+    }
+
+    fn set_identity(&self, name: &str, email: &str) -> Result<(), String> {
+        CliBackend.set_identity(name, email)
+    }
+
+    fn lfs_installed(&self) -> bool {
+        self.repo()
+            .config()
+            .and_then(|config| config.get_string("filter.lfs.clean"))
+            .is_ok_and(|value| !value.is_empty())
+    }
+
+    fn stage(&self, path: &str) -> Result<(), String> {
+        let repo = self.repo();
+        let mut index = repo.index().map_err(|e| e.message().to_string())?;
+        let workdir = repo.workdir().ok_or("repository has no working tree")?;
+        // Paths like `[id].tsx` would otherwise match other files as globs.
+        // libgit2 ignores the flag when picking the files to add, so the
+        // callback skips all but `path` and what is under it.
+        let result = if workdir.join(path).exists() {
+            let mut under_path = |file: &Path, _: &[u8]| i32::from(!file.starts_with(path));
+            index.add_all(
+                [path],
+                IndexAddOption::DISABLE_PATHSPEC_MATCH,
+                Some(&mut under_path),
+            )
+        } else {
+            index.remove_path(Path::new(path))
+        };
+        result
+            .and_then(|_| index.write())
+            .map_err(|e| e.message().to_string())
+    }
+
+    fn intent_to_add(&self, path: &str) -> Result<(), String> {
+        CliBackend.intent_to_add(path)
+    }
+
+    fn unstage(&self, path: &str) -> Result<(), String> {
+        let repo = self.repo();
+        // With no HEAD yet, resetting to `None` drops the path from the index.
+        let head = repo
+            .head()
+            .and_then(|head| head.peel(ObjectType::Commit))
+            .ok();
+        repo.reset_default(head.as_ref(), [path])
+            .map_err(|e| e.message().to_string())
+    }
+
+    fn stage_all(&self) -> Result<(), String> {
+        let repo = self.repo();
+        let mut index = repo.index().map_err(|e| e.message().to_string())?;
+        index
+            .add_all(["*"], IndexAddOption::DEFAULT, None)
+            .and_then(|_| index.update_all(["*"], None))
+            .and_then(|_| index.write())
+            .map_err(|e| e.message().to_string())
+    }
+
+    fn unstage_all(&self) -> Result<(), String> {
+        let repo = self.repo();
+        let head = repo
+            .head()
+            .and_then(|head| head.peel(ObjectType::Commit))
+            .ok();
+        repo.reset_default(head.as_ref(), ["*"])
+            .map_err(|e| e.message().to_string())
+    }
+
+    fn diff(&self, path: &str, staged: bool, options: &DiffOptions) -> Result<String, String> {
+        // libgit2 has no histogram algorithm.
+        if options.algorithm == DiffAlgorithm::Histogram {
+            return CliBackend.diff(path, staged, options);
+        }
+        let repo = self.repo();
+        let mut git_options = git2::DiffOptions::new();
+        git_options
+            .pathspec(path)
+            .disable_pathspec_match(true)
+            .context_lines(options.context)
+            .ignore_whitespace(options.ignore_whitespace)
+            .patience(options.algorithm == DiffAlgorithm::Patience);
+
+        let diff = if staged {
+            let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+            repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut git_options))
+        } else {
+            repo.diff_index_to_workdir(None, Some(&mut git_options))
+        }
+        .map_err(|e| e.message().to_string())?;
+
+        let mut patch = String::new();
+        diff.print(DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })
+        .map_err(|e| e.message().to_string())?;
+        Ok(patch)
+    }
+
+    fn discard(&self, path: &str, untracked: bool) -> Result<(), String> {
+        let repo = self.repo();
+        if untracked {
+            let workdir = repo.workdir().ok_or("repository has no working tree")?;
+            return remove_untracked(workdir, path);
+        }
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force().path(path);
+        repo.checkout_index(None, Some(&mut checkout))
+            .map_err(|e| e.message().to_string())
+    }
+
+    // libgit2 doesn't run hooks or credential helpers, so anything that
+    // creates commits or talks to a remote still goes through the CLI.
+    fn commit(
+        &self,
+        message: &str,
+        options: &CommitOptions,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        CliBackend.commit(message, options, cancel)
+    }
+
+    fn commit_foreground(&self, message: &str, options: &CommitOptions) -> Result<String, String> {
+        CliBackend.commit_foreground(message, options)
+    }
+
+    fn commit_dry_run(&self, message: &str, options: &CommitOptions) -> Result<String, String> {
+        CliBackend.commit_dry_run(message, options)
+    }
+
+    fn hooks_dir(&self) -> Option<PathBuf> {
+        CliBackend.hooks_dir()
+    }
+
+    fn config_values(&self, key: &str) -> Vec<String> {
+        let Ok(config) = self.repo().config() else {
+            return Vec::new();
+        };
+        let Ok(mut entries) = config.multivar(key, None) else {
+            return Vec::new();
+        };
+        let mut values = Vec::new();
+        while let Some(Ok(entry)) = entries.next() {
+            values.extend(entry.value().map(String::from));
+        }
+        values
+    }
+
+    fn commit_template(&self) -> Option<PathBuf> {
+        let path = self
+            .repo()
+            .config()
+            .and_then(|config| config.get_path("commit.template"))
+            .ok()?;
+        Some(match self.work_dir() {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path,
+        })
+    }
+
+    fn push(&self, options: &PushOptions, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.push(options, cancel)
+    }
+
+    fn push_foreground(&self, options: &PushOptions) -> Result<String, String> {
+        CliBackend.push_foreground(options)
+    }
+
+    fn fetch(&self, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.fetch(cancel)
+    }
+
+    fn pull(&self, mode: PullMode, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.pull(mode, cancel)
+    }
+
+    fn resolve_conflict(&self, path: &str, side: ConflictSide) -> Result<(), String> {
+        CliBackend.resolve_conflict(path, side)
+    }
+
+    fn continue_operation(
+        &self,
+        operation: Operation,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        CliBackend.continue_operation(operation, cancel)
+    }
+
+    fn abort_operation(&self, operation: Operation) -> Result<(), String> {
+        CliBackend.abort_operation(operation)
+    }
+
+    fn commit_fixup(&self, target: &str, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.commit_fixup(target, cancel)
+    }
+
+    fn autosquash(&self, base: Option<&str>, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.autosquash(base, cancel)
+    }
+
+    fn cherry_pick(&self, id: &str, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.cherry_pick(id, cancel)
+    }
+
+    fn revert(&self, id: &str, no_commit: bool, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.revert(id, no_commit, cancel)
+    }
+
+    fn staged_patch(&self) -> Result<String, String> {
+        CliBackend.staged_patch()
+    }
+
+    fn format_patch(&self, commits: &str) -> Result<String, String> {
+        CliBackend.format_patch(commits)
+    }
+
+    fn apply_patch(
+        &self,
+        path: &Path,
+        mailbox: bool,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        CliBackend.apply_patch(path, mailbox, cancel)
+    }
+
+    fn remote_list(&self) -> Result<Vec<RemoteInfo>, String> {
+        CliBackend.remote_list()
+    }
+
+    fn submodules(&self) -> Result<Vec<SubmoduleInfo>, String> {
+        CliBackend.submodules()
+    }
+
+    fn update_submodules(&self, cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.update_submodules(cancel)
+    }
+
+    fn worktrees(&self) -> Result<Vec<WorktreeInfo>, String> {
+        CliBackend.worktrees()
+    }
+
+    fn add_worktree(&self, path: &Path, branch: &str) -> Result<(), String> {
+        CliBackend.add_worktree(path, branch)
+    }
+
+    fn create_branch(&self, name: &str) -> Result<(), String> {
+        CliBackend.create_branch(name)
+    }
+
+    fn run_command(&self, args: &[String], cancel: &AtomicBool) -> Result<String, String> {
+        CliBackend.run_command(args, cancel)
+    }
+
+    fn clean_preview(&self, ignored: bool) -> Result<Vec<String>, String> {
+        CliBackend.clean_preview(ignored)
+    }
+
+    fn clean(&self, paths: &[String], ignored: bool) -> Result<(), String> {
+        CliBackend.clean(paths, ignored)
+    }
+
+    fn add_remote(&self, name: &str, url: &str) -> Result<(), String> {
+        CliBackend.add_remote(name, url)
+    }
+
+    fn remove_remote(&self, name: &str) -> Result<(), String> {
+        CliBackend.remove_remote(name)
+    }
+
+    fn rename_remote(&self, old: &str, new: &str) -> Result<(), String> {
+        CliBackend.rename_remote(old, new)
+    }
+
+    fn set_remote_url(&self, name: &str, url: &str) -> Result<(), String> {
+        CliBackend.set_remote_url(name, url)
+    }
+
+    fn ahead_behind(&self, base: &str) -> Result<(usize, usize), String> {
+        let repo = self.repo();
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| e.to_string())?;
+        let base = repo
+            .revparse_single(base)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| e.to_string())?;
+        repo.graph_ahead_behind(head.id(), base.id())
+            .map_err(|e| e.to_string())
+    }
+
+    fn latest_tag(&self) -> Option<String> {
+        CliBackend.latest_tag()
+    }
+
+    fn log_since(&self, tag: Option<&str>) -> Result<Vec<(CommitInfo, String)>, String> {
+        CliBackend.log_since(tag)
+    }
+
+    fn reflog(&self, limit: usize) -> Result<Vec<ReflogEntry>, String> {
+        CliBackend.reflog(limit)
+    }
+
+    fn checkout_detached(&self, id: &str) -> Result<(), String> {
+        CliBackend.checkout_detached(id)
+    }
+
+    fn reset_keep(&self, id: &str) -> Result<(), String> {
+        CliBackend.reset_keep(id)
+    }
+
+    fn tags(&self) -> Result<Vec<TagInfo>, String> {
+        CliBackend.tags()
+    }
+
+    fn create_tag(&self, name: &str, message: Option<&str>) -> Result<(), String> {
+        CliBackend.create_tag(name, message)
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<(), String> {
+        CliBackend.delete_tag(name)
+    }
+
+    fn push_tags(
+        &self,
+        remote: &str,
+        name: Option<&str>,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        CliBackend.push_tags(remote, name, cancel)
+    }
+
+    fn undo_last_commit(&self) -> Result<String, String> {
+        let repo = self.repo();
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| e.message().to_string())?;
+        let message = head.message().unwrap_or_default().trim_end().to_string();
+        match head.parent(0) {
+            Ok(parent) => repo.reset(parent.as_object(), ResetType::Soft, None),
+            // The root commit has no parent; unborn the branch instead,
+            // which leaves the index as it is.
+            Err(_) => repo.head().and_then(|mut head| head.delete()),
+        }
+        .map_err(|e| e.message().to_string())?;
+        Ok(message)
+    }
+
+    fn log(&self, limit: usize) -> Result<Vec<CommitInfo>, String> {
+        let repo = self.repo();
+        let mut walk = repo.revwalk().map_err(|e| e.message().to_string())?;
+        // An unborn branch has no history yet.
+        if walk.push_head().is_err() {
+            return Ok(Vec::new());
+        }
+        walk.take(limit)
+            .map(|oid| {
+                let oid = oid.map_err(|e| e.message().to_string())?;
+                let commit = repo.find_commit(oid).map_err(|e| e.message().to_string())?;
+                let short_id = commit
+                    .as_object()
+                    .short_id()
+                    .ok()
+                    .and_then(|id| id.as_str().map(str::to_string))
+                    .unwrap_or_else(|| oid.to_string()[..7].to_string());
+                Ok(CommitInfo {
+                    id: oid.to_string(),
+                    short_id,
+                    summary: commit.summary().unwrap_or_default().to_string(),
+                    author: commit.author().name().unwrap_or_default().to_string(),
+                    time: commit.time().seconds(),
+                })
+            })
+            .collect()
+    }
+
+    // libgit2 has no rename following, so history comes from git itself.
+    fn file_log(&self, path: &str, limit: usize) -> Result<Vec<(CommitInfo, String)>, String> {
+        CliBackend.file_log(path, limit)
+    }
+
+    fn log_range(&self, range: &str, limit: usize) -> Result<Vec<CommitInfo>, String> {
+        CliBackend.log_range(range, limit)
+    }
+
+    fn range_diff(&self, base: &str, head: &str, options: &DiffOptions) -> Result<String, String> {
+        CliBackend.range_diff(base, head, options)
+    }
+
+    fn show(&self, id: &str, path: Option<&str>, options: &DiffOptions) -> Result<String, String> {
+        CliBackend.show(id, path, options)
+    }
+
+    fn rebase_interactive(
+        &self,
+        base: Option<&str>,
+        todo: &[RebaseEntry],
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        CliBackend.rebase_interactive(base, todo, cancel)
+    }
+}
+
+/// Lines added and removed per new path in `diff`, like `--numstat`.
+fn line_counts(diff: &git2::Diff) -> HashMap<String, (usize, usize)> {
+    let mut counts = HashMap::new();
+    for index in 0..diff.deltas().len() {
+        let Ok(Some(patch)) = git2::Patch::from_diff(diff, index) else {
+            continue;
+        };
+        let Some(path) = patch.delta().new_file().path() else {
+            continue;
+        };
+        if let Ok((_, insertions, deletions)) = patch.line_stats() {
+            counts.insert(path.to_string_lossy().to_string(), (insertions, deletions));
+        }
+    }
+    counts
+}
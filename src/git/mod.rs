@@ -0,0 +1,1403 @@
+//! The git layer: repository types, the [`GitBackend`] abstraction, and its
+//! libgit2 and CLI implementations.
+
+mod cli;
+mod libgit2;
+
+pub use cli::CliBackend;
+pub use libgit2::Git2Backend;
+
+use chrono::{DateTime, Local};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileStatus {
+    Untracked,
+    Modified,
+    Staged,
+    Added,
+    Deleted,
+    Renamed,
+    /// New, added with `git add -N`: in the index with no content yet, so
+    /// `git diff` shows it and its hunks can be staged like any change.
+    IntentToAdd,
+    /// Unmerged after a merge, rebase, cherry-pick, or revert stopped.
+    Conflicted,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitFile {
+    pub path: String,
+    pub status: FileStatus,
+    pub staged: bool,
+    /// For a rename or copy, the path the file came from.
+    pub orig_path: Option<String>,
+    /// Picked for a bulk operation in the file list. Backends always report
+    /// `false`; the app carries marks across refreshes.
+    pub marked: bool,
+    /// Lines added and removed by the change `staged` refers to, as
+    /// `git diff --numstat` counts them. Zero for binary and untracked files.
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct GitStatus {
+    pub current_branch: String,
+    /// The tracking branch, e.g. `origin/main`, if one is configured.
+    pub upstream: Option<String>,
+    pub ahead: i32,
+    pub behind: i32,
+    pub files: Vec<GitFile>,
+    /// A multi-step operation that stopped part way, e.g. on conflicts.
+    pub operation: Option<Operation>,
+    /// How HEAD is signed; `None` when it is unsigned or there is no HEAD.
+    pub head_signature: Option<SignatureState>,
+    /// The commit HEAD points at; `None` before the first commit.
+    pub head: Option<HeadCommit>,
+    /// How many entries `git stash list` has.
+    pub stash_count: usize,
+    /// While detached, HEAD described from the nearest tag, e.g.
+    /// `v1.2.0-3-g1a2b3c4`; `None` on a branch or with no tag behind HEAD.
+    pub describe: Option<String>,
+}
+
+impl GitStatus {
+    /// Whether HEAD is on a branch, rather than detached or unreadable.
+    pub fn on_branch(&self) -> bool {
+        !self.current_branch.is_empty() && self.current_branch != "unknown"
+    }
+
+    /// Whether HEAD points straight at a commit instead of a branch.
+    pub fn detached(&self) -> bool {
+        self.current_branch.is_empty() && self.head.is_some()
+    }
+}
+
+/// The commit HEAD points at, for the header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadCommit {
+    pub short_id: String,
+    pub subject: String,
+}
+
+/// Parses `git log -1 --format=%h%x00%s`.
+pub fn parse_head_commit(output: &str) -> Option<HeadCommit> {
+    let (short_id, subject) = output.trim_end_matches('\n').split_once('\0')?;
+    Some(HeadCommit {
+        short_id: short_id.to_string(),
+        subject: subject.to_string(),
+    })
+}
+
+/// The verdict on a commit signature, from `git log --format=%G?`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignatureState {
+    /// Valid and made with a trusted key.
+    Good,
+    /// Valid, but the key is untrusted, expired, or revoked.
+    Untrusted,
+    Bad,
+    /// Signed, but the key needed to check it is missing.
+    Unchecked,
+}
+
+impl SignatureState {
+    /// Maps a `%G?` code; `None` for unsigned commits.
+    pub fn from_code(code: &str) -> Option<SignatureState> {
+        match code.trim() {
+            "G" => Some(SignatureState::Good),
+            "U" | "X" | "Y" | "R" => Some(SignatureState::Untrusted),
+            "B" => Some(SignatureState::Bad),
+            "E" => Some(SignatureState::Unchecked),
+            _ => None,
+        }
+    }
+}
+
+/// How commit signing is set up in git config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SigningConfig {
+    /// `commit.gpgsign`: git signs every commit unless told not to.
+    pub sign_by_default: bool,
+    /// `gpg.format`: `openpgp`, `x509`, or `ssh`.
+    pub format: String,
+    /// `user.signingkey`, if set.
+    pub key: Option<String>,
+}
+
+impl SigningConfig {
+    /// Whether git has something to sign with. Without a key, OpenPGP
+    /// falls back to the committer's identity, so opting in to
+    /// `commit.gpgsign` counts too.
+    pub fn is_configured(&self) -> bool {
+        self.key.is_some() || self.sign_by_default
+    }
+
+    /// SSH keys are normally served by `ssh-agent`, but GPG and X.509
+    /// signing may ask for a passphrase through pinentry, which needs the
+    /// terminal.
+    pub fn may_prompt(&self) -> bool {
+        self.format != "ssh"
+    }
+}
+
+/// Who commits are made as, from git config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Identity {
+    /// `user.name`, from whichever config level sets it.
+    pub name: Option<String>,
+    /// `user.email`, likewise.
+    pub email: Option<String>,
+    /// `gch.emailDomain`: the domain commits in this repository should be
+    /// made from, e.g. `corp.example`.
+    pub email_domain: Option<String>,
+}
+
+/// A git operation that can stop for conflicts and then be continued or
+/// aborted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    /// `git am`, applying a mailbox of patches.
+    Am,
+}
+
+impl Operation {
+    /// The git subcommand that takes `--continue` and `--abort`.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Operation::Merge => "merge",
+            Operation::Rebase => "rebase",
+            Operation::CherryPick => "cherry-pick",
+            Operation::Revert => "revert",
+            Operation::Am => "am",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Operation::Merge => "Merge",
+            Operation::Rebase => "Rebase",
+            Operation::CherryPick => "Cherry-pick",
+            Operation::Revert => "Revert",
+            Operation::Am => "Patch",
+        }
+    }
+}
+
+/// One commit from the history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitInfo {
+    pub id: String,
+    pub short_id: String,
+    pub summary: String,
+    pub author: String,
+    /// Seconds since the Unix epoch.
+    pub time: i64,
+}
+
+/// A tag and the commit it points at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagInfo {
+    pub name: String,
+    /// Short id of the tagged commit.
+    pub target: String,
+    /// Subject of the tagged commit.
+    pub summary: String,
+    /// Subject of the annotation; `None` for a lightweight tag.
+    pub message: Option<String>,
+}
+
+/// A configured remote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteInfo {
+    pub name: String,
+    pub url: String,
+    /// Set when pushes go somewhere other than `url`.
+    pub push_url: Option<String>,
+}
+
+/// Where a submodule's checkout stands against the commit the superproject
+/// records for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmoduleState {
+    /// Not cloned yet; `git submodule update --init` fetches it.
+    Uninitialized,
+    /// At the recorded commit.
+    Current,
+    /// At another commit; staging the submodule records that one.
+    Moved,
+    Conflicted,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmoduleInfo {
+    /// From the top of the working tree.
+    pub path: String,
+    /// The commit checked out, or the recorded one before initializing.
+    pub id: String,
+    pub state: SubmoduleState,
+    /// Uncommitted changes inside the submodule.
+    pub dirty: bool,
+    /// Commits the checkout has that the recorded commit doesn't, and the
+    /// other way round.
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// A working tree of the repository, from `git worktree list`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorktreeInfo {
+    pub path: PathBuf,
+    /// The commit checked out; `None` for the bare repository.
+    pub head: Option<String>,
+    /// `None` when HEAD is detached, or for the bare repository.
+    pub branch: Option<String>,
+    pub bare: bool,
+    pub locked: bool,
+    /// Its directory is gone; `git worktree prune` would drop it.
+    pub prunable: bool,
+}
+
+/// One movement of HEAD, from `git reflog`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReflogEntry {
+    pub id: String,
+    pub short_id: String,
+    /// Like `HEAD@{2}`.
+    pub selector: String,
+    /// What moved HEAD, like `reset: moving to HEAD~1`.
+    pub action: String,
+    /// Subject of the commit HEAD moved to.
+    pub summary: String,
+}
+
+/// What to do with a commit during an interactive rebase.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebaseAction {
+    Pick,
+    /// Pick, then replace the message with this one.
+    Reword(String),
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword(_) => "reword",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebaseEntry {
+    pub action: RebaseAction,
+    pub commit: CommitInfo,
+}
+
+/// Which side of a conflict to keep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+impl ConflictSide {
+    pub fn flag(&self) -> &'static str {
+        match self {
+            ConflictSide::Ours => "--ours",
+            ConflictSide::Theirs => "--theirs",
+        }
+    }
+}
+
+/// How `git pull` should integrate the fetched upstream changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PullMode {
+    Merge,
+    Rebase,
+    FastForwardOnly,
+}
+
+impl PullMode {
+    pub fn flag(&self) -> &'static str {
+        match self {
+            PullMode::Merge => "--no-rebase",
+            PullMode::Rebase => "--rebase",
+            PullMode::FastForwardOnly => "--ff-only",
+        }
+    }
+}
+
+/// Extra flags for `git commit`, set from the commit editor.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommitOptions {
+    /// Skip the pre-commit and commit-msg hooks.
+    pub no_verify: bool,
+    /// Add a `Signed-off-by` trailer (`--signoff`).
+    pub sign_off: bool,
+    /// Force signing on (`-S`) or off (`--no-gpg-sign`); `None` leaves it
+    /// to `commit.gpgsign`.
+    pub sign: Option<bool>,
+    /// `--author`, as `Name <email>`, for a commit made on someone's behalf.
+    pub author: Option<String>,
+    /// `--date`, for a back-dated commit.
+    pub date: Option<String>,
+    /// Give the committer `date` too, through `GIT_COMMITTER_DATE`.
+    pub committer_date: bool,
+    /// Commit even with nothing staged (`--allow-empty`), e.g. to trigger CI.
+    pub allow_empty: bool,
+}
+
+impl CommitOptions {
+    /// Arguments for `git commit`, without the leading `commit` or the
+    /// message.
+    pub fn args(&self) -> Vec<&str> {
+        let mut args = Vec::new();
+        if self.no_verify {
+            args.push("--no-verify");
+        }
+        if self.sign_off {
+            args.push("--signoff");
+        }
+        if self.allow_empty {
+            args.push("--allow-empty");
+        }
+        match self.sign {
+            Some(true) => args.push("-S"),
+            Some(false) => args.push("--no-gpg-sign"),
+            None => {}
+        }
+        if let Some(author) = &self.author {
+            args.extend(["--author", author]);
+        }
+        if let Some(date) = &self.date {
+            args.extend(["--date", date]);
+        }
+        args
+    }
+
+    /// Environment for `git commit`.
+    pub fn env(&self) -> Vec<(&str, &str)> {
+        match &self.date {
+            Some(date) if self.committer_date => vec![("GIT_COMMITTER_DATE", date)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The diff algorithm, cycled from the diff view.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DiffAlgorithm {
+    /// Whatever `diff.algorithm` says, normally Myers.
+    #[default]
+    Default,
+    Patience,
+    Histogram,
+}
+
+impl DiffAlgorithm {
+    pub fn next(self) -> DiffAlgorithm {
+        match self {
+            DiffAlgorithm::Default => DiffAlgorithm::Patience,
+            DiffAlgorithm::Patience => DiffAlgorithm::Histogram,
+            DiffAlgorithm::Histogram => DiffAlgorithm::Default,
+        }
+    }
+}
+
+/// How diffs are generated. Adjusted from the diff view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    /// Lines of context around each change (`-U`).
+    pub context: u32,
+    /// `--ignore-all-space`.
+    pub ignore_whitespace: bool,
+    pub algorithm: DiffAlgorithm,
+}
+
+impl Default for DiffOptions {
+    fn default() -> DiffOptions {
+        DiffOptions {
+            context: 3,
+            ignore_whitespace: false,
+            algorithm: DiffAlgorithm::Default,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Arguments for `git diff`, without the leading `diff` or the paths.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![format!("-U{}", self.context)];
+        if self.ignore_whitespace {
+            args.push("--ignore-all-space".to_string());
+        }
+        match self.algorithm {
+            DiffAlgorithm::Default => {}
+            DiffAlgorithm::Patience => args.push("--patience".to_string()),
+            DiffAlgorithm::Histogram => args.push("--histogram".to_string()),
+        }
+        args
+    }
+}
+
+/// What to push and how. Built by the push dialog.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PushOptions {
+    pub remote: String,
+    pub branch: String,
+    /// The branch to push to when it isn't named like `branch`.
+    pub remote_branch: Option<String>,
+    pub set_upstream: bool,
+    pub force_with_lease: bool,
+    pub follow_tags: bool,
+    /// Push attached to the terminal, so git and ssh can ask for a
+    /// password or passphrase.
+    pub in_terminal: bool,
+}
+
+/// Index of the remote to push to: the one already tracked, then `origin`,
+/// then the first.
+pub fn default_remote(remotes: &[String], upstream: Option<&str>) -> usize {
+    let tracked = upstream
+        .and_then(|upstream| upstream.split_once('/'))
+        .map(|(remote, _)| remote);
+    remotes
+        .iter()
+        .position(|r| Some(r.as_str()) == tracked)
+        .or_else(|| remotes.iter().position(|r| r == "origin"))
+        .unwrap_or(0)
+}
+
+impl PushOptions {
+    /// Arguments for `git push`, without the leading `push`.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.set_upstream {
+            args.push("--set-upstream".to_string());
+        }
+        if self.force_with_lease {
+            args.push("--force-with-lease".to_string());
+        }
+        if self.follow_tags {
+            args.push("--follow-tags".to_string());
+        }
+        args.push(self.remote.clone());
+        args.push(self.refspec());
+        args
+    }
+
+    /// `branch`, or `branch:target` to push it under another name.
+    pub fn refspec(&self) -> String {
+        match &self.remote_branch {
+            Some(target) if *target != self.branch => format!("{}:{}", self.branch, target),
+            _ => self.branch.clone(),
+        }
+    }
+
+    /// The branch pushed to on the remote.
+    pub fn target(&self) -> &str {
+        self.remote_branch.as_deref().unwrap_or(&self.branch)
+    }
+}
+
+/// Every git operation the app performs goes through this trait, so the UI
+/// can be driven against a fake repository in tests. `Git2Backend` talks to
+/// the repository in-process; `CliBackend` shells out to `git` and is used
+/// when libgit2 can't open the repository.
+///
+/// `commit`, `push`, `fetch`, and `pull` run on a worker thread and should
+/// give up promptly once `cancel` is raised.
+pub trait GitBackend: Send + Sync {
+    fn status(&self) -> GitStatus;
+    fn current_branch(&self) -> String;
+    /// The repository's `.git` directory, where the app keeps its own
+    /// state such as the commit message draft.
+    fn git_dir(&self) -> Option<PathBuf>;
+    /// The top of the working tree; `None` for a bare repository.
+    fn work_dir(&self) -> Option<PathBuf>;
+    fn remotes(&self) -> Vec<String>;
+    fn signing_config(&self) -> SigningConfig;
+    fn identity(&self) -> Identity;
+    /// Sets `user.name` and `user.email` in the repository's own config.
+    fn set_identity(&self, name: &str, email: &str) -> Result<(), String>;
+    /// Whether git-lfs is set up, i.e. its clean filter is configured, so
+    /// files matching an LFS pattern are stored as pointers.
+    fn lfs_installed(&self) -> bool;
+    fn stage(&self, path: &str) -> Result<(), String>;
+    /// Records an untracked file in the index with no content, as `git add
+    /// -N` does; unstaging it makes it untracked again.
+    fn intent_to_add(&self, path: &str) -> Result<(), String>;
+    fn unstage(&self, path: &str) -> Result<(), String>;
+    fn stage_all(&self) -> Result<(), String>;
+    fn unstage_all(&self) -> Result<(), String>;
+    fn diff(&self, path: &str, staged: bool, options: &DiffOptions) -> Result<String, String>;
+    /// A file git doesn't track yet, as a diff that adds every line. `git
+    /// diff` shows nothing for these.
+    fn diff_untracked(&self, path: &str) -> Result<String, String> {
+        let work_dir = self.work_dir().ok_or("repository has no working tree")?;
+        untracked_diff(&work_dir, path)
+    }
+    /// Throws away working-tree changes to `path`, or deletes it if it is
+    /// untracked.
+    fn discard(&self, path: &str, untracked: bool) -> Result<(), String>;
+    fn commit(
+        &self,
+        message: &str,
+        options: &CommitOptions,
+        cancel: &AtomicBool,
+    ) -> Result<String, String>;
+    /// Like [`GitBackend::commit`], but attached to the terminal so gpg can
+    /// ask for a passphrase. Only stderr is captured.
+    fn commit_foreground(&self, message: &str, options: &CommitOptions) -> Result<String, String>;
+    /// What `git commit --dry-run` says the commit would contain.
+    fn commit_dry_run(&self, message: &str, options: &CommitOptions) -> Result<String, String>;
+    /// Where git looks for hooks, following `core.hooksPath`.
+    fn hooks_dir(&self) -> Option<PathBuf>;
+    /// Every value of a multi-valued git config `key`, e.g. `gch.prefix`.
+    fn config_values(&self, key: &str) -> Vec<String>;
+    /// The `commit.template` file, with `~` expanded.
+    fn commit_template(&self) -> Option<PathBuf>;
+    fn push(&self, options: &PushOptions, cancel: &AtomicBool) -> Result<String, String>;
+    /// Like [`GitBackend::push`], but attached to the terminal so
+    /// credentials can be typed in. Only stderr is captured.
+    fn push_foreground(&self, options: &PushOptions) -> Result<String, String>;
+    fn fetch(&self, cancel: &AtomicBool) -> Result<String, String>;
+    fn pull(&self, mode: PullMode, cancel: &AtomicBool) -> Result<String, String>;
+    /// Resolves a conflicted file by keeping one side and staging it.
+    fn resolve_conflict(&self, path: &str, side: ConflictSide) -> Result<(), String>;
+    fn continue_operation(
+        &self,
+        operation: Operation,
+        cancel: &AtomicBool,
+    ) -> Result<String, String>;
+    fn abort_operation(&self, operation: Operation) -> Result<(), String>;
+    /// Commits the staged changes as `fixup! <target's subject>`.
+    fn commit_fixup(&self, target: &str, cancel: &AtomicBool) -> Result<String, String>;
+    /// Folds `fixup!` and `squash!` commits after `base` into their
+    /// targets with `git rebase -i --autosquash`, accepting git's plan.
+    fn autosquash(&self, base: Option<&str>, cancel: &AtomicBool) -> Result<String, String>;
+    /// Applies commit `id` onto HEAD with `git cherry-pick`; a conflict
+    /// leaves the cherry-pick in progress.
+    fn cherry_pick(&self, id: &str, cancel: &AtomicBool) -> Result<String, String>;
+    /// Undoes commit `id` with `git revert`, as a new commit or, with
+    /// `no_commit`, as staged changes; a conflict leaves the revert in
+    /// progress.
+    fn revert(&self, id: &str, no_commit: bool, cancel: &AtomicBool) -> Result<String, String>;
+    /// The staged changes as a patch for `git apply`, binary files included.
+    fn staged_patch(&self) -> Result<String, String>;
+    /// Commit `commits`, or every commit in a `base..head` range, as
+    /// `git format-patch` writes them for `git am`.
+    fn format_patch(&self, commits: &str) -> Result<String, String>;
+    /// Applies the patch file at `path`: a mailbox of commits with `git am
+    /// --3way`, anything else with `git apply --3way`. A conflict leaves the
+    /// `am` in progress, or conflicted files after `apply`.
+    fn apply_patch(
+        &self,
+        path: &Path,
+        mailbox: bool,
+        cancel: &AtomicBool,
+    ) -> Result<String, String>;
+    /// The remotes with their URLs.
+    fn remote_list(&self) -> Result<Vec<RemoteInfo>, String>;
+    fn add_remote(&self, name: &str, url: &str) -> Result<(), String>;
+    fn remove_remote(&self, name: &str) -> Result<(), String>;
+    fn rename_remote(&self, old: &str, new: &str) -> Result<(), String>;
+    fn set_remote_url(&self, name: &str, url: &str) -> Result<(), String>;
+    /// The submodules, with what has changed in each.
+    fn submodules(&self) -> Result<Vec<SubmoduleInfo>, String>;
+    /// Clones missing submodules and checks each out at its recorded commit,
+    /// like `git submodule update --init --recursive`.
+    fn update_submodules(&self, cancel: &AtomicBool) -> Result<String, String>;
+    /// The main working tree first, then the linked ones.
+    fn worktrees(&self) -> Result<Vec<WorktreeInfo>, String>;
+    /// Adds a worktree at `path` with `branch` checked out, creating the
+    /// branch from HEAD unless it exists locally or on one remote.
+    fn add_worktree(&self, path: &Path, branch: &str) -> Result<(), String>;
+    /// Creates branch `name` at HEAD and switches to it, keeping any changes.
+    fn create_branch(&self, name: &str) -> Result<(), String>;
+    /// Runs `git <args>` as typed in the command bar; returns everything it
+    /// printed, as the error too when it fails.
+    fn run_command(&self, args: &[String], cancel: &AtomicBool) -> Result<String, String>;
+    /// What `git clean -d` would delete, relative to the top of the working
+    /// tree; with `ignored`, ignored files as well (`-x`).
+    fn clean_preview(&self, ignored: bool) -> Result<Vec<String>, String>;
+    /// Deletes `paths`, as listed by [`GitBackend::clean_preview`].
+    fn clean(&self, paths: &[String], ignored: bool) -> Result<(), String>;
+    /// The most recent tag reachable from HEAD.
+    fn latest_tag(&self) -> Option<String>;
+    /// How many commits HEAD has that `base` doesn't, and the reverse.
+    fn ahead_behind(&self, base: &str) -> Result<(usize, usize), String>;
+    /// The commits after `tag`, or all of them, newest first and without
+    /// merges, each with its message body.
+    fn log_since(&self, tag: Option<&str>) -> Result<Vec<(CommitInfo, String)>, String>;
+    /// The newest `limit` entries of HEAD's reflog, newest first.
+    fn reflog(&self, limit: usize) -> Result<Vec<ReflogEntry>, String>;
+    /// Checks out commit `id`, detaching HEAD.
+    fn checkout_detached(&self, id: &str) -> Result<(), String>;
+    /// Moves the current branch, or a detached HEAD, to `id` with
+    /// `git reset --keep`, which refuses to overwrite uncommitted changes.
+    fn reset_keep(&self, id: &str) -> Result<(), String>;
+    /// Every tag, newest first.
+    fn tags(&self) -> Result<Vec<TagInfo>, String>;
+    /// Tags HEAD as `name`; annotated when there is a `message`.
+    fn create_tag(&self, name: &str, message: Option<&str>) -> Result<(), String>;
+    fn delete_tag(&self, name: &str) -> Result<(), String>;
+    /// Pushes tag `name` to `remote`, or every tag when `name` is `None`.
+    fn push_tags(
+        &self,
+        remote: &str,
+        name: Option<&str>,
+        cancel: &AtomicBool,
+    ) -> Result<String, String>;
+    /// Moves HEAD back one commit, keeping its changes staged, like
+    /// `git reset --soft HEAD~1`. Returns the undone commit's message.
+    fn undo_last_commit(&self) -> Result<String, String>;
+    /// The newest `limit` commits reachable from HEAD, newest first.
+    fn log(&self, limit: usize) -> Result<Vec<CommitInfo>, String>;
+    /// The newest `limit` commits in `range`, e.g. `main..HEAD`, newest
+    /// first.
+    fn log_range(&self, range: &str, limit: usize) -> Result<Vec<CommitInfo>, String>;
+    /// What `head` changed since it forked from `base`, as `git diff
+    /// base...head` shows it.
+    fn range_diff(&self, base: &str, head: &str, options: &DiffOptions) -> Result<String, String>;
+    /// The newest `limit` commits that touched `path`, following renames,
+    /// each with the name the file had in that commit.
+    fn file_log(&self, path: &str, limit: usize) -> Result<Vec<(CommitInfo, String)>, String>;
+    /// The patch commit `id` introduced, limited to `path` if given;
+    /// without a path, preceded by the commit's metadata, message and stat.
+    fn show(&self, id: &str, path: Option<&str>, options: &DiffOptions) -> Result<String, String>;
+    /// Rewrites the commits after `base` (or all of them when `None`)
+    /// following `todo`, oldest first.
+    fn rebase_interactive(
+        &self,
+        base: Option<&str>,
+        todo: &[RebaseEntry],
+        cancel: &AtomicBool,
+    ) -> Result<String, String>;
+}
+
+/// Opens the repository in the current directory with libgit2, falling back
+/// to the `git` CLI. Set `GCH_GIT_BACKEND=cli` to force the CLI backend.
+/// Fails with git's explanation when there is no repository here.
+pub fn open_backend() -> Result<Arc<dyn GitBackend>, String> {
+    if !std::env::var("GCH_GIT_BACKEND").is_ok_and(|v| v == "cli")
+        && let Ok(backend) = Git2Backend::open()
+    {
+        return Ok(Arc::new(backend));
+    }
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-dir"])
+        .output()
+        .map_err(|e| format!("could not run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(Arc::new(CliBackend))
+}
+
+/// Deletes an untracked file, or a whole untracked directory when `path` is a
+/// collapsed `dir/` entry from status.
+pub(crate) fn remove_untracked(workdir: &Path, path: &str) -> Result<(), String> {
+    let full_path = workdir.join(path);
+    let result = if full_path.is_dir() {
+        fs::remove_dir_all(&full_path)
+    } else {
+        fs::remove_file(&full_path)
+    };
+    result.map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Untracked files bigger than this are cut short in their pseudo-diff.
+const MAX_UNTRACKED_BYTES: usize = 256 * 1024;
+/// Files listed from an untracked directory before giving up.
+const MAX_UNTRACKED_FILES: usize = 100;
+
+/// The diff `git diff --no-index /dev/null <path>` would print, for a file
+/// or every file under an untracked directory.
+pub(crate) fn untracked_diff(workdir: &Path, path: &str) -> Result<String, String> {
+    let mut files = Vec::new();
+    collect_files(workdir, path.trim_end_matches('/'), &mut files)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    files.sort();
+
+    let mut diff = String::new();
+    for file in files.iter().take(MAX_UNTRACKED_FILES) {
+        let bytes = fs::read(workdir.join(file)).map_err(|e| format!("{}: {}", file, e))?;
+        diff.push_str(&format!(
+            "diff --git a/{file} b/{file}\nnew file mode 100644\n--- /dev/null\n+++ b/{file}\n"
+        ));
+        if bytes[..bytes.len().min(8000)].contains(&0) {
+            diff.push_str(&format!("Binary files /dev/null and b/{} differ\n", file));
+            continue;
+        }
+        let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_UNTRACKED_BYTES)]);
+        let lines: Vec<&str> = text.lines().collect();
+        if lines.is_empty() {
+            continue;
+        }
+        diff.push_str(&format!("@@ -0,0 +1,{} @@\n", lines.len()));
+        for line in lines {
+            diff.push('+');
+            diff.push_str(line);
+            diff.push('\n');
+        }
+        if bytes.len() > MAX_UNTRACKED_BYTES {
+            diff.push_str(&format!(
+                "\\ Only the first {} KiB are shown\n",
+                MAX_UNTRACKED_BYTES / 1024
+            ));
+        } else if !bytes.ends_with(b"\n") {
+            diff.push_str("\\ No newline at end of file\n");
+        }
+    }
+    if files.len() > MAX_UNTRACKED_FILES {
+        diff.push_str("... more files not shown\n");
+    }
+    Ok(diff)
+}
+
+/// Paths of the files at or under `path`, relative to `workdir`. Stops once
+/// there are more than [`MAX_UNTRACKED_FILES`], and skips nested
+/// repositories' `.git`.
+fn collect_files(workdir: &Path, path: &str, files: &mut Vec<String>) -> std::io::Result<()> {
+    let full_path = workdir.join(path);
+    if !full_path.is_dir() {
+        files.push(path.to_string());
+        return Ok(());
+    }
+    for entry in fs::read_dir(full_path)? {
+        let name = entry?.file_name();
+        if name == ".git" {
+            continue;
+        }
+        if files.len() > MAX_UNTRACKED_FILES {
+            break;
+        }
+        collect_files(
+            workdir,
+            &format!("{}/{}", path, name.to_string_lossy()),
+            files,
+        )?;
+    }
+    Ok(())
+}
+
+/// Works out which operation is in progress from the marker files git
+/// leaves in `git_dir`.
+pub(crate) fn detect_operation(git_dir: &Path) -> Option<Operation> {
+    // `git am` shares the directory with the old apply-based rebase.
+    if git_dir.join("rebase-apply").join("applying").exists() {
+        Some(Operation::Am)
+    } else if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+        Some(Operation::Rebase)
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        Some(Operation::Merge)
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        Some(Operation::CherryPick)
+    } else if git_dir.join("REVERT_HEAD").exists() {
+        Some(Operation::Revert)
+    } else {
+        None
+    }
+}
+
+/// Maps a porcelain `XY` status pair to the file status shown in the list.
+pub(crate) fn classify_status(staged_status: char, unstaged_status: char) -> (FileStatus, bool) {
+    let file_status = match (staged_status, unstaged_status) {
+        ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => FileStatus::Conflicted,
+        ('A', _) => FileStatus::Added,
+        ('M', _) => FileStatus::Staged,
+        ('D', _) => FileStatus::Deleted,
+        ('R', _) => FileStatus::Renamed,
+        ('?', '?') => FileStatus::Untracked,
+        (' ', 'A') => FileStatus::IntentToAdd,
+        (_, 'M') => FileStatus::Modified,
+        (_, 'D') => FileStatus::Deleted,
+        _ => FileStatus::Modified,
+    };
+
+    let staged =
+        staged_status != ' ' && staged_status != '?' && file_status != FileStatus::Conflicted;
+    (file_status, staged)
+}
+
+/// Parses `git status --porcelain -z`. Entries are `XY path` separated by
+/// NUL bytes, with no quoting; renames and copies are followed by an extra
+/// entry holding the original path.
+pub fn parse_status_z(output: &str) -> Vec<GitFile> {
+    let mut files = Vec::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        let mut chars = entry.chars();
+        let (Some(staged_status), Some(unstaged_status), Some(' ')) =
+            (chars.next(), chars.next(), chars.next())
+        else {
+            continue;
+        };
+        let orig_path =
+            if matches!(staged_status, 'R' | 'C') || matches!(unstaged_status, 'R' | 'C') {
+                entries.next().map(str::to_string)
+            } else {
+                None
+            };
+
+        let (status, staged) = classify_status(staged_status, unstaged_status);
+        files.push(GitFile {
+            path: chars.as_str().to_string(),
+            status,
+            staged,
+            orig_path,
+            marked: false,
+            insertions: 0,
+            deletions: 0,
+        });
+    }
+    files
+}
+
+/// Line counts per path from `git diff --numstat -z`. Entries are
+/// `added\tdeleted\tpath` ended by NUL; a rename leaves the path empty and
+/// follows with the old and new paths. Binary files count as zero.
+pub fn parse_numstat_z(output: &str) -> HashMap<String, (usize, usize)> {
+    let mut counts = HashMap::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        let mut fields = entry.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            entries.next();
+            entries.next().unwrap_or_default()
+        } else {
+            path
+        };
+        counts.insert(
+            path.to_string(),
+            (added.parse().unwrap_or(0), deleted.parse().unwrap_or(0)),
+        );
+    }
+    counts
+}
+
+/// Fills in each file's line counts from the staged or unstaged numstat,
+/// whichever side the file is listed under.
+pub(crate) fn apply_line_counts(
+    files: &mut [GitFile],
+    staged: &HashMap<String, (usize, usize)>,
+    unstaged: &HashMap<String, (usize, usize)>,
+) {
+    for file in files {
+        let counts = if file.staged { staged } else { unstaged };
+        if let Some(&(insertions, deletions)) = counts.get(&file.path) {
+            file.insertions = insertions;
+            file.deletions = deletions;
+        }
+    }
+}
+
+/// Parses `git log --name-only` run with the format
+/// `%x1e%H%x00%h%x00%an%x00%at%x00%s`: each record starts with a record
+/// separator, and the path follows the header after a blank line.
+pub fn parse_file_log(output: &str) -> Vec<(CommitInfo, String)> {
+    output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut fields = lines.next()?.splitn(5, '\0');
+            let commit = CommitInfo {
+                id: fields.next()?.to_string(),
+                short_id: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                time: fields.next()?.parse().unwrap_or(0),
+                summary: fields.next()?.to_string(),
+            };
+            let path = lines.rfind(|line| !line.is_empty())?;
+            Some((commit, path.to_string()))
+        })
+        .collect()
+}
+
+/// The `--format` for `git for-each-ref refs/tags` that [`parse_tags`]
+/// reads.
+pub const TAG_FORMAT: &str = "%(refname:lstrip=2)%00%(objecttype)%00%(objectname:short)%00\
+%(*objectname:short)%00%(contents:subject)%00%(*contents:subject)";
+
+/// Parses `git for-each-ref` output in [`TAG_FORMAT`]. Annotated tags are
+/// `tag` objects whose commit fields come from peeling them.
+pub fn parse_tags(output: &str) -> Vec<TagInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(6, '\0').collect();
+            let [name, kind, id, peeled_id, subject, peeled_subject] = fields[..] else {
+                return None;
+            };
+            Some(if kind == "tag" {
+                TagInfo {
+                    name: name.to_string(),
+                    target: peeled_id.to_string(),
+                    summary: peeled_subject.to_string(),
+                    message: Some(subject.to_string()),
+                }
+            } else {
+                TagInfo {
+                    name: name.to_string(),
+                    target: id.to_string(),
+                    summary: subject.to_string(),
+                    message: None,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Parses `git log` run with the format
+/// `%x1e%H%x00%h%x00%an%x00%at%x00%s%x00%b`: each commit with its body.
+pub fn parse_log_bodies(output: &str) -> Vec<(CommitInfo, String)> {
+    output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.splitn(6, '\0');
+            let commit = CommitInfo {
+                id: fields.next()?.to_string(),
+                short_id: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                time: fields.next()?.parse().unwrap_or(0),
+                summary: fields.next()?.to_string(),
+            };
+            Some((commit, fields.next()?.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Parses `git remote -v`: a fetch and a push line per remote.
+pub fn parse_remotes(output: &str) -> Vec<RemoteInfo> {
+    let mut remotes: Vec<RemoteInfo> = Vec::new();
+    for line in output.lines() {
+        let Some((name, rest)) = line.split_once('\t') else {
+            continue;
+        };
+        let (url, kind) = rest.rsplit_once(' ').unwrap_or((rest, ""));
+        match remotes.iter_mut().find(|remote| remote.name == name) {
+            Some(remote) if kind == "(push)" && remote.url != url => {
+                remote.push_url = Some(url.to_string());
+            }
+            Some(_) => {}
+            None => remotes.push(RemoteInfo {
+                name: name.to_string(),
+                url: url.to_string(),
+                push_url: None,
+            }),
+        }
+    }
+    remotes
+}
+
+/// Parses `git submodule status`. Whether each is dirty, ahead, or behind
+/// takes a look inside it, so those are left at nothing.
+pub fn parse_submodule_status(output: &str) -> Vec<SubmoduleInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let state = match chars.next()? {
+                '-' => SubmoduleState::Uninitialized,
+                '+' => SubmoduleState::Moved,
+                'U' => SubmoduleState::Conflicted,
+                _ => SubmoduleState::Current,
+            };
+            let (id, rest) = chars.as_str().split_once(' ')?;
+            // `git describe` of the checkout, when there is one.
+            let path = match rest.rsplit_once(" (") {
+                Some((path, _)) if rest.ends_with(')') => path,
+                _ => rest,
+            };
+            Some(SubmoduleInfo {
+                path: path.to_string(),
+                id: id.to_string(),
+                state,
+                dirty: false,
+                ahead: 0,
+                behind: 0,
+            })
+        })
+        .collect()
+}
+
+/// Parses `git worktree list --porcelain`: one block of `key value` lines
+/// per worktree, separated by blank lines.
+pub fn parse_worktrees(output: &str) -> Vec<WorktreeInfo> {
+    output
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut worktree = WorktreeInfo {
+                path: PathBuf::from(block.lines().next()?.strip_prefix("worktree ")?),
+                head: None,
+                branch: None,
+                bare: false,
+                locked: false,
+                prunable: false,
+            };
+            for line in block.lines().skip(1) {
+                let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                match key {
+                    "HEAD" => worktree.head = Some(value.to_string()),
+                    "branch" => {
+                        let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                        worktree.branch = Some(branch.to_string());
+                    }
+                    "bare" => worktree.bare = true,
+                    "locked" => worktree.locked = true,
+                    "prunable" => worktree.prunable = true,
+                    _ => {}
+                }
+            }
+            Some(worktree)
+        })
+        .collect()
+}
+
+/// Parses `git clean -n`: the `Would remove` lines, each path unquoted.
+/// Nested repositories it skips are left out.
+pub fn parse_clean_preview(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Would remove "))
+        .map(unquote_path)
+        .collect()
+}
+
+/// Undoes git's C-style quoting of a path in `"…"`; anything else is
+/// returned as it is.
+fn unquote_path(text: &str) -> String {
+    let Some(inner) = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    else {
+        return text.to_string();
+    };
+    let mut bytes = Vec::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some(digit @ '0'..='7') => {
+                let octal: String = std::iter::once(digit)
+                    .chain(chars.by_ref().take(2))
+                    .collect();
+                bytes.push(u8::from_str_radix(&octal, 8).unwrap_or(b'?'));
+            }
+            Some(other) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parses `git reflog` run with the format `%H%x00%h%x00%gd%x00%gs%x00%s`.
+pub fn parse_reflog(output: &str) -> Vec<ReflogEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '\0');
+            Some(ReflogEntry {
+                id: fields.next()?.to_string(),
+                short_id: fields.next()?.to_string(),
+                selector: fields.next()?.to_string(),
+                action: fields.next()?.to_string(),
+                summary: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A git command the app ran, for the messages log.
+#[derive(Debug, Clone)]
+pub struct RanCommand {
+    pub time: DateTime<Local>,
+    /// The command line, e.g. `git add src/main.rs`.
+    pub command: String,
+    pub success: bool,
+}
+
+/// Commands waiting for [`take_commands`]. Capped so nothing piles up when
+/// no one reads them, e.g. in headless mode.
+static RAN_COMMANDS: Mutex<Vec<RanCommand>> = Mutex::new(Vec::new());
+const MAX_PENDING_COMMANDS: usize = 100;
+
+/// Commands that only read. Every refresh runs some, so they stay out of
+/// the log.
+fn is_query(args: &[&str]) -> bool {
+    match args {
+        ["branch", "--show-current"] | ["remote"] | ["remote", "-v"] | [] => true,
+        [command, ..] => [
+            "status",
+            "rev-parse",
+            "rev-list",
+            "log",
+            "diff",
+            "config",
+            "show",
+            "for-each-ref",
+            "describe",
+            "reflog",
+        ]
+        .contains(command),
+    }
+}
+
+pub(crate) fn record_command(args: &[&str], success: bool) {
+    if is_query(args) {
+        return;
+    }
+    let words: Vec<String> = args
+        .iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            } else {
+                arg.to_string()
+            }
+        })
+        .collect();
+    let mut commands = RAN_COMMANDS.lock().unwrap_or_else(|e| e.into_inner());
+    if commands.len() == MAX_PENDING_COMMANDS {
+        commands.remove(0);
+    }
+    commands.push(RanCommand {
+        time: Local::now(),
+        command: format!("git {}", words.join(" ")),
+        success,
+    });
+}
+
+/// The commands run since the last call, oldest first.
+pub fn take_commands() -> Vec<RanCommand> {
+    std::mem::take(&mut *RAN_COMMANDS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Runs `git` with the terminal's stdin and stdout, for commands that may
+/// prompt. Returns stderr on failure.
+pub fn run_git_foreground(args: &[&str]) -> Result<String, String> {
+    run_git_foreground_with_env(args, &[])
+}
+
+/// [`run_git_foreground`] with extra environment variables.
+pub fn run_git_foreground_with_env(args: &[&str], env: &[(&str, &str)]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .envs(env.iter().copied())
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| e.to_string())?;
+    record_command(args, output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if output.status.success() {
+        Ok(stderr)
+    } else {
+        Err(stderr)
+    }
+}
+
+/// What to do about a commit that failed to sign, if `error` looks like a
+/// signing failure.
+pub fn explain_signing_error(error: &str) -> Option<&'static str> {
+    let hints = [
+        (
+            "No secret key",
+            "GPG has no secret key for user.signingkey (or your committer email). \
+             Check `gpg --list-secret-keys`.",
+        ),
+        (
+            "Inappropriate ioctl for device",
+            "GPG could not ask for the passphrase. Add `export GPG_TTY=$(tty)` \
+             to your shell profile, or use a graphical pinentry.",
+        ),
+        (
+            "No pinentry",
+            "GPG could not find a pinentry program to ask for the passphrase.",
+        ),
+        (
+            "Load key",
+            "ssh-keygen could not load user.signingkey. Check the path, or add \
+             the key to ssh-agent.",
+        ),
+        (
+            "cannot run",
+            "The signing program is not installed; see gpg.program or \
+             gpg.ssh.program.",
+        ),
+        (
+            "failed to sign the data",
+            "Signing failed. Run `echo test | gpg --clearsign` to see why.",
+        ),
+    ];
+    hints
+        .iter()
+        .find(|(pattern, _)| error.contains(pattern))
+        .map(|(_, hint)| *hint)
+}
+
+/// What to do about a push that failed for want of credentials, if `error`
+/// looks like that.
+pub fn explain_push_error(error: &str) -> Option<&'static str> {
+    let patterns = [
+        "terminal prompts disabled",
+        "could not read Username",
+        "could not read Password",
+        "Permission denied (publickey",
+    ];
+    patterns
+        .iter()
+        .any(|pattern| error.contains(pattern))
+        .then_some(
+            "Git could not ask for a password or passphrase here. Push again with \
+             i ticked in the push dialog to answer it in the terminal, or set up a \
+             credential helper or ssh-agent.",
+        )
+}
+
+/// The last commit [`head_signature`] verified, and how it was signed.
+static VERIFIED_HEAD: Mutex<Option<(String, Option<SignatureState>)>> = Mutex::new(None);
+
+/// Reads how HEAD, commit `id`, is signed. Verifying runs gpg or
+/// ssh-keygen, so libgit2 defers to git here too, and it is only done again
+/// once HEAD moves.
+pub(crate) fn head_signature(id: &str) -> Option<SignatureState> {
+    let mut verified = VERIFIED_HEAD.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((verified_id, signature)) = verified.as_ref()
+        && verified_id == id
+    {
+        return *signature;
+    }
+    let signature = Command::new("git")
+        .args(["log", "-1", "--format=%G?", id])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .and_then(|output| SignatureState::from_code(&String::from_utf8_lossy(&output.stdout)));
+    *verified = Some((id.to_string(), signature));
+    signature
+}
+
+/// Runs `git` with `args`, killing the child process if `cancel` is raised
+/// before it exits. Returns stdout on success and stderr on failure.
+pub fn run_git_cancellable(args: &[&str], cancel: &AtomicBool) -> Result<String, String> {
+    run_git_cancellable_with_env(args, &[], cancel)
+}
+
+/// [`run_git_cancellable`] with extra environment variables, e.g.
+/// `GIT_SEQUENCE_EDITOR`.
+pub fn run_git_cancellable_with_env(
+    args: &[&str],
+    env: &[(&str, &str)],
+    cancel: &AtomicBool,
+) -> Result<String, String> {
+    let (success, stdout, stderr) = spawn_and_wait(args, env, cancel)?;
+    if success {
+        Ok(stdout)
+    } else {
+        // Merge conflicts and "nothing to commit" are reported on stdout.
+        Err(format!("{}{}", stdout, stderr).trim().to_string())
+    }
+}
+
+/// [`run_git_cancellable`] keeping everything git printed either way, for
+/// commands whose progress and results go to stderr, like `fetch`.
+pub fn run_git_cancellable_all(args: &[&str], cancel: &AtomicBool) -> Result<String, String> {
+    let (success, stdout, stderr) = spawn_and_wait(args, &[], cancel)?;
+    let output = format!("{}{}", stdout, stderr).trim_end().to_string();
+    if success { Ok(output) } else { Err(output) }
+}
+
+/// Runs git to the end, or until `cancel` is set; returns whether it
+/// succeeded, and its stdout and stderr.
+fn spawn_and_wait(
+    args: &[&str],
+    env: &[(&str, &str)],
+    cancel: &AtomicBool,
+) -> Result<(bool, String, String), String> {
+    // There is no terminal for an editor either, so `--continue` and merge
+    // commits keep git's prepared message.
+    let mut child = Command::new("git")
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_EDITOR", "true")
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Drain the pipes on their own threads so a chatty command can't block
+    // on a full pipe while we wait for it.
+    let stdout = child.stdout.take().map(read_to_string_in_background);
+    let stderr = child.stderr.take().map(read_to_string_in_background);
+
+    let status = loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("cancelled".to_string());
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+
+    let stdout = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+    let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+    record_command(args, status.success());
+    Ok((status.success(), stdout, stderr))
+}
+
+fn read_to_string_in_background<R: Read + Send + 'static>(
+    mut reader: R,
+) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = reader.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).to_string()
+    })
+}
@@ -0,0 +1,420 @@
+//! Splitting the working tree into several commits: changed files are
+//! put into named groups, each with its own message, and the groups are
+//! then staged and committed one after another. `F9` in the commit editor
+//! queues the staged files and message as a group instead of committing.
+
+use crate::{
+    app::{App, AppMode, TaskKind},
+    confirm::PendingAction,
+    git::{CommitOptions, GitFile},
+    report::GitError,
+    text,
+    validate::{Severity, validate},
+};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+/// One commit to be: its message and the files that go in it.
+#[derive(Debug, Clone)]
+pub struct CommitGroup {
+    pub message: String,
+    pub files: Vec<GitFile>,
+    /// The editor's options when the group was queued from it; groups made
+    /// in the view take the options set when they are committed.
+    pub options: Option<CommitOptions>,
+}
+
+impl CommitGroup {
+    /// What to stage: each path, and for a rename the path it came from.
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for file in &self.files {
+            paths.extend(file.orig_path.clone());
+            paths.push(file.path.clone());
+        }
+        paths
+    }
+
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// The questions asked before the groups are committed, in order: the
+/// ones a commit from the editor asks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QueueCheck {
+    Protected,
+    Identity,
+    Secrets,
+}
+
+#[derive(Debug, Default)]
+pub struct CommitGroups {
+    pub groups: Vec<CommitGroup>,
+    /// The changed files, in file list order.
+    pub files: Vec<GitFile>,
+    pub selected: usize,
+    /// The message being typed after `n` or `e`, and the group it is for;
+    /// `groups.len()` starts a new group.
+    pub input: Option<(usize, String)>,
+    /// The group being committed, out of the queue until its commit
+    /// finishes.
+    pub running: Option<CommitGroup>,
+    /// Groups committed since `c`, while the rest are still going.
+    pub committed: usize,
+    /// A line per group committed since `c`, shown once they are done.
+    pub report: Vec<String>,
+}
+
+impl CommitGroups {
+    /// The group `path` is in, if any.
+    pub fn group_of(&self, path: &str) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| group.files.iter().any(|file| file.path == path))
+    }
+
+    /// Moves the selected file into `group`, or out of any with `None`.
+    /// Groups left empty are dropped.
+    pub fn assign(&mut self, group: Option<usize>) {
+        let Some(file) = self.files.get(self.selected).cloned() else {
+            return;
+        };
+        for existing in &mut self.groups {
+            existing.files.retain(|f| f.path != file.path);
+        }
+        if let Some(group) = group.and_then(|i| self.groups.get_mut(i)) {
+            group.files.push(file);
+        }
+        self.groups.retain(|group| !group.files.is_empty());
+    }
+
+    /// Adds `group` at the end of the queue, taking its files out of the
+    /// groups they were in.
+    pub fn queue(&mut self, group: CommitGroup) {
+        for existing in &mut self.groups {
+            existing
+                .files
+                .retain(|f| !group.files.iter().any(|file| file.path == f.path));
+        }
+        self.groups.retain(|group| !group.files.is_empty());
+        self.groups.push(group);
+    }
+
+    /// Puts the group whose commit failed or was cancelled back at the
+    /// front of the queue.
+    pub fn requeue(&mut self) {
+        if let Some(group) = self.running.take() {
+            self.groups.insert(0, group);
+        }
+    }
+
+    /// Keeps the groups in step with `files`: files no longer changed leave
+    /// their group, and the rest pick up their new status.
+    fn sync(&mut self, files: &[GitFile]) {
+        let mut seen = std::collections::HashSet::new();
+        self.files = files
+            .iter()
+            .filter(|file| seen.insert(file.path.clone()))
+            .cloned()
+            .collect();
+        for group in &mut self.groups {
+            group.files = group
+                .files
+                .iter()
+                .filter_map(|file| self.files.iter().find(|f| f.path == file.path).cloned())
+                .collect();
+        }
+        self.groups.retain(|group| !group.files.is_empty());
+        self.selected = self.selected.min(self.files.len().saturating_sub(1));
+    }
+}
+
+impl App {
+    /// `g` in the file list.
+    pub(crate) fn open_groups(&mut self) {
+        if self.all_files.is_empty() {
+            self.show_notification("Nothing to split: the working tree is clean".to_string());
+            return;
+        }
+        self.groups.sync(&self.all_files);
+        self.groups.input = None;
+        self.mode = AppMode::Groups;
+    }
+
+    pub(crate) fn handle_groups_input(&mut self, key: KeyCode) {
+        if self.groups.input.is_some() {
+            self.handle_group_message_input(key);
+            return;
+        }
+        let running = self.task.is_some();
+        let view = &mut self.groups;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if view.selected + 1 < view.files.len() => {
+                view.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => view.selected = view.selected.saturating_sub(1),
+            // The queue is being committed; it can change once that is done.
+            _ if running => {}
+            KeyCode::Char('n') => view.input = Some((view.groups.len(), String::new())),
+            KeyCode::Char('e') => {
+                let path = view.files.get(view.selected).map(|file| file.path.clone());
+                match path.and_then(|path| view.group_of(&path)) {
+                    Some(group) => {
+                        let subject = view.groups[group].subject().to_string();
+                        view.input = Some((group, subject));
+                    }
+                    None => self.show_notification(
+                        "This file is in no group; n starts one, 1-9 picks one".to_string(),
+                    ),
+                }
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let group = c as usize - '1' as usize;
+                if group < view.groups.len() {
+                    view.assign(Some(group));
+                    if view.selected + 1 < view.files.len() {
+                        view.selected += 1;
+                    }
+                }
+            }
+            KeyCode::Char('0') | KeyCode::Backspace | KeyCode::Delete => view.assign(None),
+            KeyCode::Char('c') => self.commit_groups(),
+            _ => {}
+        }
+    }
+
+    /// Typing a group's message, or the subject of one queued from the
+    /// editor. A new group takes the selected file.
+    fn handle_group_message_input(&mut self, key: KeyCode) {
+        let view = &mut self.groups;
+        let Some((group, input)) = &mut view.input else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => view.input = None,
+            KeyCode::Enter => {
+                let (group, message) = (*group, input.trim().to_string());
+                if message.is_empty() {
+                    return;
+                }
+                view.input = None;
+                match view.groups.get_mut(group) {
+                    Some(existing) => {
+                        let body = existing.message.find('\n').map(|i| &existing.message[i..]);
+                        existing.message = message + body.unwrap_or_default();
+                    }
+                    None => {
+                        view.groups.push(CommitGroup {
+                            message,
+                            files: Vec::new(),
+                            options: None,
+                        });
+                        view.assign(Some(group));
+                        if view.selected + 1 < view.files.len() {
+                            view.selected += 1;
+                        }
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(input, input.len());
+                input.truncate(end);
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// `c` in the groups view: commits the groups in order.
+    fn commit_groups(&mut self) {
+        if self.groups.groups.is_empty() {
+            self.show_notification("No groups yet; n starts one for the selected file".to_string());
+            return;
+        }
+        if self.signs_commit() && self.signing.may_prompt() {
+            self.show_notification(
+                "Signing asks for a passphrase, so commit the groups from the editor one at a time"
+                    .to_string(),
+            );
+            return;
+        }
+        if self.config.validation.block_on_error {
+            for group in &self.groups.groups {
+                let issues = validate(&group.message, &self.config.validation);
+                if let Some(issue) = issues.iter().find(|i| i.severity == Severity::Error) {
+                    let message =
+                        format!("Commit blocked: \"{}\": {}", group.message, issue.message);
+                    self.show_notification(message);
+                    return;
+                }
+            }
+        }
+        self.check_and_commit_groups(None);
+    }
+
+    /// Asks what a commit from the editor would ask, past the question
+    /// `answered`, then commits the groups.
+    pub(crate) fn check_and_commit_groups(&mut self, answered: Option<QueueCheck>) {
+        let asked = |check| answered.is_some_and(|answered| answered >= check);
+        if !asked(QueueCheck::Protected) && self.on_protected_branch() {
+            let message = format!(
+                "{} is protected. Commit the {} group(s) to it directly?",
+                self.git_status.current_branch,
+                self.groups.groups.len()
+            );
+            let then = PendingAction::CommitGroups(QueueCheck::Protected);
+            self.ask("Protected branch", message, then);
+            return;
+        }
+        if !asked(QueueCheck::Identity)
+            && self.confirm_identity(PendingAction::CommitGroups(QueueCheck::Identity))
+        {
+            return;
+        }
+        if !asked(QueueCheck::Secrets) && self.scans_for_secrets() {
+            let files: Vec<GitFile> = self
+                .groups
+                .groups
+                .iter()
+                .flat_map(|group| group.files.clone())
+                .collect();
+            let diff = files
+                .iter()
+                .map(|file| self.diff_file(file))
+                .collect::<Result<String, String>>();
+            let then = PendingAction::CommitGroups(QueueCheck::Secrets);
+            if self.ask_about_secrets(diff, "Possible secrets in the groups", then) {
+                return;
+            }
+        }
+        self.groups.committed = 0;
+        self.groups.report.clear();
+        self.commit_next_group();
+    }
+
+    /// `F9` in the commit editor: queues the staged files with the message
+    /// and unstages them, ready for the next commit.
+    pub(crate) fn queue_commit(&mut self) {
+        if self.task.is_some() {
+            self.show_notification("Another git operation is still running".to_string());
+            return;
+        }
+        let files: Vec<GitFile> = self
+            .all_files
+            .iter()
+            .filter(|f| f.staged)
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            self.show_notification("Stage the files for this commit first".to_string());
+            return;
+        }
+        if self.commit_message.trim().is_empty() {
+            self.show_notification("Commit message cannot be empty".to_string());
+            return;
+        }
+        if let Err(error) = self.backend.unstage_all() {
+            self.report_error(GitError::new("Unstaging the queued files", error));
+            return;
+        }
+        let group = CommitGroup {
+            message: self.full_commit_message(),
+            files,
+            options: Some(self.commit_options.clone()),
+        };
+        let subject = group.subject().to_string();
+        self.groups.queue(group);
+        self.commit_message.clear();
+        self.cursor_position = 0;
+        self.save_draft();
+        self.commit_options = CommitOptions {
+            sign_off: self.config.sign_off,
+            ..CommitOptions::default()
+        };
+        self.skip_ticket = false;
+        if let Err(e) = self.trailers.finish_commit(self.git_dir.as_deref()) {
+            self.show_warning(format!("Could not save co-authors: {}", e));
+        }
+        self.mode = AppMode::FileList;
+        self.refresh_git_status();
+        self.show_notification(format!(
+            "Queued \"{}\" ({} in the queue; g to review and commit them)",
+            subject,
+            self.groups.groups.len()
+        ));
+    }
+
+    /// Takes the first group out of the queue, and stages exactly its files
+    /// and commits them.
+    fn commit_next_group(&mut self) {
+        if self.groups.groups.is_empty() {
+            return;
+        }
+        let group = self.groups.groups.remove(0);
+        self.groups.running = Some(group.clone());
+        let backend = Arc::clone(&self.backend);
+        let options = group
+            .options
+            .clone()
+            .unwrap_or_else(|| self.commit_options.clone());
+        self.start_task(TaskKind::CommitGroup, move |cancel| {
+            backend.unstage_all()?;
+            for path in group.paths() {
+                backend.stage(&path)?;
+            }
+            backend.commit(&group.message, &options, cancel)
+        });
+    }
+
+    pub(crate) fn finish_commit_group(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(_) => {
+                let Some(group) = self.groups.running.take() else {
+                    return;
+                };
+                let _ = self.history.record(self.git_dir.as_deref(), &group.message);
+                let view = &mut self.groups;
+                view.committed += 1;
+                view.report.push(format!("✓ {}", group.subject()));
+                let total = view.committed + view.groups.len();
+                if !view.groups.is_empty() {
+                    let progress = format!("Committed {} of {}", view.committed, total);
+                    self.show_notification(progress);
+                    self.commit_next_group();
+                    return;
+                }
+                let mut text = view.report.join("\n");
+                text.push_str(&format!("\n\nAll {} committed; H shows the last.\n", total));
+                self.show_output("Commit queue", text);
+            }
+            // The rest wait in the groups view, to be fixed and committed again
+            Err(error) => {
+                let view = &mut self.groups;
+                view.requeue();
+                let Some(failed) = view.groups.first() else {
+                    return;
+                };
+                let context = format!("Committing \"{}\"", failed.subject());
+                view.report.push(format!("✗ {}", failed.subject()));
+                for group in &view.groups[1..] {
+                    view.report.push(format!("  {} (not run)", group.subject()));
+                }
+                let mut text = view.report.join("\n");
+                text.push_str(&format!(
+                    "\n\nStopped after {} of {}; the rest wait in the groups view.\n\n{}\n",
+                    view.committed,
+                    view.committed + view.groups.len(),
+                    error.trim_end()
+                ));
+                self.log_error(GitError::new(context, error));
+                self.refresh_git_status();
+                self.groups.sync(&self.all_files);
+                self.show_output("Commit queue stopped", text);
+                self.output.back = Some(AppMode::Groups);
+            }
+        }
+    }
+}
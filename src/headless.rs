@@ -0,0 +1,170 @@
+//! The subcommands that run without the UI, so the same checks and
+//! conventions apply when committing from scripts and CI.
+
+use crate::{
+    args::Command,
+    config::Config,
+    git::{
+        CommitOptions, FileStatus, GitBackend, GitFile, GitStatus, PushOptions, SignatureState,
+        default_remote,
+    },
+    validate::{Severity, validate},
+};
+use serde_json::{Value, json};
+use std::sync::atomic::AtomicBool;
+
+/// Runs `command` and returns what to print.
+pub fn run(command: &Command, backend: &dyn GitBackend, config: &Config) -> Result<String, String> {
+    match command {
+        Command::Status { json: true } => Ok(format!("{:#}\n", status_json(&backend.status()))),
+        Command::Status { json: false } => Ok(status_text(&backend.status())),
+        Command::Commit {
+            message,
+            stage_all,
+            push,
+            no_verify,
+            signoff,
+            allow_empty,
+        } => {
+            let options = CommitOptions {
+                no_verify: *no_verify,
+                sign_off: *signoff || config.sign_off,
+                allow_empty: *allow_empty,
+                ..CommitOptions::default()
+            };
+            commit(backend, config, message, *stage_all, &options, *push)
+        }
+    }
+}
+
+fn commit(
+    backend: &dyn GitBackend,
+    config: &Config,
+    message: &str,
+    stage_all: bool,
+    options: &CommitOptions,
+    push: bool,
+) -> Result<String, String> {
+    if stage_all {
+        backend.stage_all()?;
+    }
+    let status = backend.status();
+    if !options.allow_empty && !status.files.iter().any(|file| file.staged) {
+        return Err("nothing staged to commit".to_string());
+    }
+    if message.trim().is_empty() {
+        return Err("the commit message is empty".to_string());
+    }
+    if config.validation.block_on_error {
+        let errors: Vec<String> = validate(message, &config.validation)
+            .into_iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .map(|issue| issue.message)
+            .collect();
+        if !errors.is_empty() {
+            return Err(format!("commit message rejected: {}", errors.join("; ")));
+        }
+    }
+    let message = match config.ticket.find(&status.current_branch) {
+        Some(ticket) => config.ticket.apply(message, &ticket),
+        None => message.to_string(),
+    };
+
+    let cancel = AtomicBool::new(false);
+    let mut output = backend.commit(&message, options, &cancel)?;
+    if push {
+        let remotes = backend.remotes();
+        if remotes.is_empty() {
+            return Err("no remotes configured".to_string());
+        }
+        let remote = &remotes[default_remote(&remotes, status.upstream.as_deref())];
+        let options = PushOptions {
+            remote: remote.clone(),
+            branch: status.current_branch.clone(),
+            set_upstream: status.upstream.is_none(),
+            ..PushOptions::default()
+        };
+        output.push_str(&backend.push(&options, &cancel)?);
+    }
+    Ok(output)
+}
+
+fn status_letter(status: &FileStatus) -> char {
+    match status {
+        FileStatus::Untracked => '?',
+        FileStatus::Modified | FileStatus::Staged => 'M',
+        FileStatus::Added | FileStatus::IntentToAdd => 'A',
+        FileStatus::Deleted => 'D',
+        FileStatus::Renamed => 'R',
+        FileStatus::Conflicted => 'U',
+    }
+}
+
+fn status_name(status: &FileStatus) -> &'static str {
+    match status {
+        FileStatus::Untracked => "untracked",
+        FileStatus::Modified => "modified",
+        FileStatus::Staged => "staged",
+        FileStatus::Added => "added",
+        FileStatus::Deleted => "deleted",
+        FileStatus::Renamed => "renamed",
+        FileStatus::IntentToAdd => "intent-to-add",
+        FileStatus::Conflicted => "conflicted",
+    }
+}
+
+/// Like `git status --short --branch`: a `##` branch line, then one line per
+/// file with the staged and unstaged columns.
+pub fn status_text(status: &GitStatus) -> String {
+    let mut text = format!("## {}", status.current_branch);
+    if let Some(upstream) = &status.upstream {
+        text.push_str(&format!("...{}", upstream));
+    }
+    if status.ahead > 0 || status.behind > 0 {
+        text.push_str(&format!(
+            " [ahead {}, behind {}]",
+            status.ahead, status.behind
+        ));
+    }
+    text.push('\n');
+    for file in &status.files {
+        let letter = status_letter(&file.status);
+        let columns = match (&file.status, file.staged) {
+            (FileStatus::Untracked, _) => "??".to_string(),
+            (_, true) => format!("{} ", letter),
+            (_, false) => format!(" {}", letter),
+        };
+        match &file.orig_path {
+            Some(orig) => text.push_str(&format!("{} {} -> {}\n", columns, orig, file.path)),
+            None => text.push_str(&format!("{} {}\n", columns, file.path)),
+        }
+    }
+    text
+}
+
+fn file_json(file: &GitFile) -> Value {
+    json!({
+        "path": file.path,
+        "status": status_name(&file.status),
+        "staged": file.staged,
+        "orig_path": file.orig_path,
+    })
+}
+
+pub fn status_json(status: &GitStatus) -> Value {
+    let signature = status.head_signature.map(|signature| match signature {
+        SignatureState::Good => "good",
+        SignatureState::Untrusted => "untrusted",
+        SignatureState::Bad => "bad",
+        SignatureState::Unchecked => "unchecked",
+    });
+    json!({
+        "branch": status.current_branch,
+        "upstream": status.upstream,
+        "ahead": status.ahead,
+        "behind": status.behind,
+        "operation": status.operation.map(|operation| operation.command()),
+        "head_signature": signature,
+        "files": status.files.iter().map(file_json).collect::<Vec<_>>(),
+    })
+}
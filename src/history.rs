@@ -0,0 +1,98 @@
+//! Commit messages written through the app, recalled with Up/Down in the
+//! commit editor like shell history. Kept per repository in the `.git`
+//! directory.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const HISTORY_FILE: &str = "GIT_COMMIT_HELPER_HISTORY";
+const MAX_ENTRIES: usize = 100;
+
+fn history_path(git_dir: &Path) -> PathBuf {
+    git_dir.join(HISTORY_FILE)
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    /// Oldest first.
+    pub entries: Vec<String>,
+    /// The entry being shown while browsing, if any.
+    position: Option<usize>,
+    /// The draft that was in the editor when browsing started.
+    stash: String,
+}
+
+impl History {
+    /// Reads the history file. Entries are separated by NUL bytes since
+    /// messages can span lines.
+    pub fn load(git_dir: &Path) -> History {
+        let entries = fs::read_to_string(history_path(git_dir))
+            .map(|text| {
+                text.split('\0')
+                    .filter(|entry| !entry.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        History {
+            entries,
+            ..History::default()
+        }
+    }
+
+    /// Adds `message` as the newest entry, dropping an older copy of it, and
+    /// saves the history when `git_dir` is known.
+    pub fn record(&mut self, git_dir: Option<&Path>, message: &str) -> io::Result<()> {
+        self.reset();
+        self.entries.retain(|entry| entry != message);
+        self.entries.push(message.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+        match git_dir {
+            Some(git_dir) => fs::write(history_path(git_dir), self.entries.join("\0")),
+            None => Ok(()),
+        }
+    }
+
+    /// Steps back to an older message. `current` is remembered when browsing
+    /// starts so [`History::newer`] can return to it.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.stash = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => 0,
+            Some(position) => position - 1,
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    /// Steps forward to a newer message, ending at the stashed draft.
+    pub fn newer(&mut self) -> Option<&str> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            Some(&self.entries[position + 1])
+        } else {
+            self.position = None;
+            Some(&self.stash)
+        }
+    }
+
+    /// Stops browsing, e.g. once the recalled message is edited.
+    pub fn reset(&mut self) {
+        self.position = None;
+    }
+
+    /// The 1-based number of the entry being shown, counting from the
+    /// newest.
+    pub fn browsing(&self) -> Option<usize> {
+        self.position.map(|position| self.entries.len() - position)
+    }
+}
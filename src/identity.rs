@@ -0,0 +1,129 @@
+//! Who commits are made as: the effective `user.name` and `user.email`
+//! shown in the commit editor, profiles from the config that set them for
+//! the repository, and a question before committing from an address outside
+//! the repository's `gch.emailDomain`.
+
+use crate::{
+    app::{App, AppMode},
+    confirm::PendingAction,
+    git::Identity,
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+
+/// A named identity from the config, e.g. `profile.work = Ann <ann@corp.example>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub label: String,
+    pub name: String,
+    pub email: String,
+}
+
+impl Profile {
+    /// Reads `Name <email>`.
+    pub fn parse(label: &str, value: &str) -> Option<Profile> {
+        let (name, email) = value.trim().strip_suffix('>')?.split_once('<')?;
+        let (name, email) = (name.trim(), email.trim());
+        (!name.is_empty() && email.contains('@')).then(|| Profile {
+            label: label.to_string(),
+            name: name.to_string(),
+            email: email.to_string(),
+        })
+    }
+
+    pub fn is_current(&self, identity: &Identity) -> bool {
+        identity.name.as_deref() == Some(&self.name)
+            && identity.email.as_deref() == Some(&self.email)
+    }
+}
+
+/// Whether `email` is at `domain` or one of its subdomains, ignoring case.
+pub fn email_in_domain(email: &str, domain: &str) -> bool {
+    let Some((_, host)) = email.rsplit_once('@') else {
+        return false;
+    };
+    let (host, domain) = (
+        host.to_lowercase(),
+        domain.trim_start_matches('@').to_lowercase(),
+    );
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// The address commits would be made from when it is outside the
+/// repository's domain.
+pub fn foreign_email(identity: &Identity) -> Option<&str> {
+    let email = identity.email.as_deref()?;
+    let domain = identity.email_domain.as_deref()?;
+    (!email_in_domain(email, domain)).then_some(email)
+}
+
+impl App {
+    /// `F6` in the commit editor.
+    pub(crate) fn open_profiles(&mut self) {
+        let profiles = &self.config.profiles;
+        if profiles.is_empty() {
+            self.show_notification(
+                "No profiles; add one to the config, e.g. profile.work = Ann <ann@corp.example>"
+                    .to_string(),
+            );
+            return;
+        }
+        self.profile_selected = profiles
+            .iter()
+            .position(|profile| profile.is_current(&self.identity))
+            .unwrap_or(0);
+        self.mode = AppMode::Profiles;
+    }
+
+    pub(crate) fn handle_profiles_input(&mut self, key: KeyCode) {
+        let count = self.config.profiles.len();
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::CommitMessage,
+            KeyCode::Down | KeyCode::Char('j') if self.profile_selected + 1 < count => {
+                self.profile_selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.profile_selected = self.profile_selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                if let Some(profile) = self.config.profiles.get(self.profile_selected).cloned() {
+                    self.use_profile(&profile);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn use_profile(&mut self, profile: &Profile) {
+        self.mode = AppMode::CommitMessage;
+        match self.backend.set_identity(&profile.name, &profile.email) {
+            Ok(()) => {
+                self.identity = self.backend.identity();
+                self.show_notification(format!(
+                    "Committing as {} <{}> in this repository",
+                    profile.name, profile.email
+                ));
+            }
+            Err(error) => self.report_error(GitError::new("Setting the identity", error)),
+        }
+    }
+
+    /// Asks before committing from an address outside `gch.emailDomain`,
+    /// going on with `then` if told to; `true` while the question is open.
+    pub(crate) fn confirm_identity(&mut self, then: PendingAction) -> bool {
+        let (Some(email), Some(domain)) = (
+            foreign_email(&self.identity),
+            self.identity.email_domain.as_deref(),
+        ) else {
+            return false;
+        };
+        let message = format!(
+            "This repository expects @{} addresses, but you would commit as {}. \
+             F6 switches profiles. Commit anyway?",
+            domain.trim_start_matches('@'),
+            email
+        );
+        self.ask("Email outside the domain", message, then);
+        true
+    }
+}
@@ -0,0 +1,160 @@
+//! Ignoring an untracked file from the file list: a pattern for the file,
+//! its extension, or its directory is added to the top-level `.gitignore`,
+//! which is then staged.
+
+use crate::{
+    app::{App, AppMode},
+    git::FileStatus,
+    report::GitError,
+    tree::RowKind,
+};
+use crossterm::event::KeyCode;
+use std::{fs, io, path::Path};
+
+#[derive(Debug, Default)]
+pub struct IgnoreMenu {
+    /// The patterns offered, most specific first.
+    pub choices: Vec<String>,
+    pub selected: usize,
+}
+
+/// The patterns that would ignore `path`, as git lists it: the path itself,
+/// anchored to the top; every file with its extension; and its directory.
+/// Untracked directories end in `/`.
+pub fn ignore_patterns(path: &str) -> Vec<String> {
+    let is_dir = path.ends_with('/');
+    let path = path.trim_end_matches('/');
+    let mut choices = vec![format!(
+        "/{}{}",
+        escape(path),
+        if is_dir { "/" } else { "" }
+    )];
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if !is_dir
+        && let Some((stem, extension)) = name.rsplit_once('.')
+        && !stem.is_empty()
+        && !extension.is_empty()
+    {
+        choices.push(format!("*.{}", escape(extension)));
+    }
+    if let Some((parent, _)) = path.rsplit_once('/') {
+        choices.push(format!("/{}/", escape(parent)));
+    }
+    choices
+}
+
+/// `text` with the characters gitignore would read as a glob escaped, and a
+/// trailing space kept.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    if escaped.ends_with(' ') {
+        escaped.pop();
+        escaped.push_str("\\ ");
+    }
+    escaped
+}
+
+/// Adds `pattern` as a line of `root/.gitignore`, creating it if needed.
+/// Returns false, changing nothing, when the line is already there.
+pub fn append_pattern(root: &Path, pattern: &str) -> io::Result<bool> {
+    let path = root.join(".gitignore");
+    let mut text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    if text.lines().any(|line| line.trim_end() == pattern) {
+        return Ok(false);
+    }
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(pattern);
+    text.push('\n');
+    fs::write(&path, text)?;
+    Ok(true)
+}
+
+impl App {
+    /// `I` in the file list, on an untracked file or a directory of them.
+    pub(crate) fn open_ignore_menu(&mut self) {
+        let path = match self.rows.get(self.selected_row).map(|row| &row.kind) {
+            Some(RowKind::File(index)) => {
+                let file = &self.files[*index];
+                (file.status == FileStatus::Untracked).then(|| file.path.clone())
+            }
+            Some(RowKind::Dir { path, .. }) => {
+                let prefix = format!("{}/", path);
+                self.files
+                    .iter()
+                    .filter(|f| f.path.starts_with(&prefix))
+                    .all(|f| f.status == FileStatus::Untracked)
+                    .then_some(prefix)
+            }
+            None => return,
+        };
+        let Some(path) = path else {
+            self.show_notification("Only untracked files can be ignored".to_string());
+            return;
+        };
+        self.ignore_menu = IgnoreMenu {
+            choices: ignore_patterns(&path),
+            selected: 0,
+        };
+        self.mode = AppMode::IgnoreMenu;
+    }
+
+    pub(crate) fn handle_ignore_menu_input(&mut self, key: KeyCode) {
+        let menu = &mut self.ignore_menu;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if menu.selected + 1 < menu.choices.len() => {
+                menu.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => menu.selected = menu.selected.saturating_sub(1),
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                if let Some(pattern) = menu.choices.get(index).cloned() {
+                    self.ignore(&pattern);
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(pattern) = menu.choices.get(menu.selected).cloned() {
+                    self.ignore(&pattern);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds `pattern` to `.gitignore` and stages it.
+    fn ignore(&mut self, pattern: &str) {
+        self.mode = AppMode::FileList;
+        let Some(root) = self.backend.work_dir() else {
+            return;
+        };
+        match append_pattern(&root, pattern) {
+            Ok(true) => {}
+            Ok(false) => {
+                self.show_notification(format!("{} is already in .gitignore", pattern));
+                return;
+            }
+            Err(e) => {
+                self.report_error(GitError::new("Writing .gitignore", e.to_string()));
+                return;
+            }
+        }
+        let staged = self.backend.stage(".gitignore");
+        self.refresh_git_status();
+        match staged {
+            Ok(()) => self.show_notification(format!("Added {} to .gitignore", pattern)),
+            Err(error) => self.report_error(GitError::new("Staging .gitignore", error)),
+        }
+    }
+}
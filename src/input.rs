@@ -0,0 +1,565 @@
+use crate::{
+    app::{App, AppMode, DiffSource, External, Focus},
+    confirm::PendingAction,
+    filter::FileFilter,
+    git::{ConflictSide, PullMode},
+    patch::PatchAction,
+    search::DiffSearch,
+    text,
+    tree::RowKind,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::sync::atomic::Ordering;
+
+impl App {
+    /// Handles a key press including modifiers. Keys without a Ctrl binding
+    /// go to [`App::handle_input`].
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('z') && self.mode != AppMode::CommitMessage {
+            self.pending_external = Some(External::Suspend);
+            return;
+        }
+        // An open confirmation takes the keys until it is answered.
+        let editing = self.mode == AppMode::CommitMessage && self.confirm.is_none();
+        if editing && self.handle_word_edit(key) {
+            return;
+        }
+        if ctrl && editing {
+            match key.code {
+                KeyCode::Char('e') => {
+                    self.pending_external = Some(External::CommitEditor);
+                    return;
+                }
+                KeyCode::Char('g') => {
+                    self.suggest_message();
+                    return;
+                }
+                KeyCode::Char('d') => {
+                    self.show_staged_diff();
+                    return;
+                }
+                KeyCode::Char('r') => {
+                    self.open_issues();
+                    return;
+                }
+                KeyCode::Char('s') => {
+                    self.open_spelling();
+                    return;
+                }
+                KeyCode::Char('v') => {
+                    self.paste_clipboard();
+                    return;
+                }
+                KeyCode::Char('z') => {
+                    self.undo_edit();
+                    return;
+                }
+                KeyCode::Char('y') => {
+                    self.redo_edit();
+                    return;
+                }
+                _ => {}
+            }
+        }
+        if ctrl && self.mode == AppMode::FileList && self.confirm.is_none() {
+            match key.code {
+                KeyCode::Char('w') => {
+                    self.wip_commit();
+                    return;
+                }
+                KeyCode::Char('u') => {
+                    self.confirm_unwip();
+                    return;
+                }
+                _ => {}
+            }
+        }
+        if ctrl && self.mode == AppMode::Tags && key.code == KeyCode::Char('e') {
+            self.edit_tag_message();
+            return;
+        }
+        self.handle_input(key.code);
+    }
+
+    /// Readline's word and line editing in the commit editor: `Ctrl+←/→`
+    /// jump words, `Ctrl+W` and `Alt+Backspace` delete the word before the
+    /// cursor, and `Ctrl+U`/`Ctrl+K` delete to the start or end of the line.
+    fn handle_word_edit(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let (message, cursor) = (&self.commit_message, self.cursor_position);
+        let delete = match key.code {
+            KeyCode::Left if ctrl || alt => {
+                self.cursor_position = text::prev_word(message, cursor);
+                return true;
+            }
+            KeyCode::Right if ctrl || alt => {
+                self.cursor_position = text::next_word(message, cursor);
+                return true;
+            }
+            KeyCode::Char('w') if ctrl => text::prev_whitespace_word(message, cursor)..cursor,
+            KeyCode::Backspace if alt => text::prev_word(message, cursor)..cursor,
+            KeyCode::Char('u') if ctrl => text::line_start(message, cursor)..cursor,
+            KeyCode::Char('k') if ctrl => cursor..text::line_end(message, cursor),
+            _ => return false,
+        };
+        if !delete.is_empty() {
+            self.history.reset();
+            self.commit_message.replace_range(delete.clone(), "");
+            self.cursor_position = delete.start;
+            self.save_draft();
+        }
+        true
+    }
+
+    pub fn handle_input(&mut self, key: KeyCode) {
+        if key == KeyCode::Esc
+            && let Some(task) = &self.task
+        {
+            task.cancel.store(true, Ordering::Relaxed);
+            return;
+        }
+
+        if self.confirm.is_some() {
+            self.handle_confirm_input(key);
+            return;
+        }
+
+        match self.mode {
+            AppMode::FileList => self.handle_file_list_input(key),
+            AppMode::DiffView => self.handle_diff_view_input(key),
+            AppMode::CommitMessage => {
+                let before = self.commit_message.clone();
+                if !matches!(key, KeyCode::Up | KeyCode::Down) {
+                    self.history.reset();
+                }
+                self.handle_commit_message_input(key);
+                if self.commit_message != before {
+                    self.save_draft();
+                }
+            }
+            AppMode::Help => self.handle_help_input(key),
+            AppMode::PullMenu => self.handle_pull_menu_input(key),
+            AppMode::PushDialog => self.handle_push_dialog_input(key),
+            AppMode::FilterInput => self.handle_filter_input(key),
+            AppMode::Output => self.handle_output_input(key),
+            AppMode::Checks => self.handle_checks_input(key),
+            AppMode::RebasePick => self.handle_rebase_pick_input(key),
+            AppMode::Rebase => self.handle_rebase_input(key),
+            AppMode::FixupPick => self.handle_fixup_input(key),
+            AppMode::CommitOptions => self.handle_trailer_input(key),
+            AppMode::NoRepo | AppMode::RepoSwitch => self.handle_repo_input(key),
+            AppMode::Messages => self.handle_messages_input(key),
+            AppMode::DiffSearch => self.handle_diff_search_input(key),
+            AppMode::Log => self.handle_log_input(key),
+            AppMode::Tags => self.handle_tags_input(key),
+            AppMode::Release => self.handle_release_input(key),
+            AppMode::Reflog => self.handle_reflog_input(key),
+            AppMode::Remotes => self.handle_remotes_input(key),
+            AppMode::Submodules => self.handle_submodules_input(key),
+            AppMode::Worktrees => self.handle_worktrees_input(key),
+            AppMode::IgnoreMenu => self.handle_ignore_menu_input(key),
+            AppMode::Clean => self.handle_clean_input(key),
+            AppMode::CopyMenu => self.handle_copy_menu_input(key),
+            AppMode::CommandBar => self.handle_command_bar_input(key),
+            AppMode::NewBranch => self.handle_new_branch_input(key),
+            AppMode::Compare => self.handle_compare_input(key),
+            AppMode::Patch => self.handle_patch_input(key),
+            AppMode::PullRequest => self.handle_pull_request_input(key),
+            AppMode::Ci => self.handle_ci_input(key),
+            AppMode::Issues => self.handle_issues_input(key),
+            AppMode::Profiles => self.handle_profiles_input(key),
+            AppMode::Prefixes => self.handle_prefix_picker_input(key),
+            AppMode::Spelling => self.handle_spelling_input(key),
+            AppMode::Groups => self.handle_groups_input(key),
+        }
+    }
+
+    fn handle_file_list_input(&mut self, key: KeyCode) {
+        if self.split_view && self.focus == Focus::Diff {
+            match key {
+                KeyCode::Tab | KeyCode::Esc => {
+                    self.focus = Focus::Files;
+                    return;
+                }
+                key if self.handle_diff_scroll(key) || self.handle_diff_options(key) => return,
+                // Everything else still acts on the selected file.
+                _ => {}
+            }
+        }
+        match key {
+            KeyCode::Tab if self.split_view => self.focus = Focus::Diff,
+            KeyCode::Char('s') => self.toggle_split_view(),
+            KeyCode::Char('[') => self.resize_panes(-1),
+            KeyCode::Char(']') => self.resize_panes(1),
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Char('h') | KeyCode::F(1) => self.mode = AppMode::Help,
+            KeyCode::Char('r') => self.refresh_git_status(),
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Char('v') => self.toggle_mark(),
+            KeyCode::Char('J') => self.extend_marks(true),
+            KeyCode::Char('K') => self.extend_marks(false),
+            KeyCode::Esc if self.filter.is_active() => {
+                self.filter = FileFilter::default();
+                self.apply_filter();
+            }
+            KeyCode::Esc => self.clear_marks(),
+            KeyCode::Char('/') => self.mode = AppMode::FilterInput,
+            KeyCode::Char(':') => self.open_command_bar(),
+            KeyCode::Char('!') => self.pending_external = Some(External::Shell),
+            KeyCode::Char('O') => self.open_repo_switcher(),
+            KeyCode::Char('m') => self.show_messages(),
+            KeyCode::Char('t') => self.toggle_tree_view(),
+            KeyCode::Left if self.tree_view => self.set_selected_dir_collapsed(true),
+            KeyCode::Right if self.tree_view => self.set_selected_dir_collapsed(false),
+            KeyCode::Enter if self.tree_view => {
+                if let Some(RowKind::Dir { collapsed, .. }) =
+                    self.rows.get(self.selected_row).map(|row| &row.kind)
+                {
+                    self.set_selected_dir_collapsed(!collapsed);
+                }
+            }
+            KeyCode::Char('F') => {
+                self.filter.status = self.filter.status.next();
+                self.apply_filter();
+            }
+            KeyCode::Char(' ') => self.toggle_stage_file(),
+            KeyCode::Char('N') => self.toggle_intent_to_add(),
+            KeyCode::Char('A') => self.stage_all(),
+            KeyCode::Char('U') => self.unstage_all(),
+            KeyCode::Char('i') => self.invert_staging(),
+            KeyCode::Char('x') => self.confirm_discard(),
+            KeyCode::Char('I') => self.open_ignore_menu(),
+            KeyCode::Char('G') => self.open_clean(),
+            KeyCode::Char('g') => self.open_groups(),
+            KeyCode::Char('<') => self.resolve_conflicts(ConflictSide::Ours),
+            KeyCode::Char('>') => self.resolve_conflicts(ConflictSide::Theirs),
+            KeyCode::Char('M') => self.open_external_tool(),
+            KeyCode::Char('e') => self.edit_selected_file(),
+            KeyCode::Char('y') => self.mode = AppMode::CopyMenu,
+            KeyCode::Char('C') if self.git_status.operation.is_some() => {
+                self.continue_operation();
+            }
+            KeyCode::Char('X') if self.git_status.operation.is_some() => self.confirm_abort(),
+            KeyCode::Char('d') if !self.rows.is_empty() => {
+                self.show_diff();
+            }
+            KeyCode::Char('D') => self.show_staged_diff(),
+            KeyCode::Char('H') => self.show_head_commit(),
+            KeyCode::Char('T') => self.open_tags(),
+            KeyCode::Char('V') => self.open_release(),
+            KeyCode::Char('L') => self.open_reflog(),
+            KeyCode::Char('E') => self.open_remotes(),
+            KeyCode::Char('S') => self.focus_submodules(),
+            KeyCode::Char('W') => self.open_worktrees(),
+            KeyCode::Char('B') => self.publish_branch(),
+            KeyCode::Char('b') => self.open_new_branch(false),
+            KeyCode::Char('=') => self.open_compare(),
+            KeyCode::Char('a') => self.open_patch_prompt(PatchAction::Apply),
+            KeyCode::Char('l') if !self.rows.is_empty() => self.open_file_log(),
+            KeyCode::Char('c') => self.start_commit(),
+            KeyCode::Char('p') => self.open_push_dialog(),
+            KeyCode::Char('o') => self.open_pull_request(),
+            KeyCode::Char('w') => self.open_ci(),
+            KeyCode::Char('f') => self.fetch(),
+            KeyCode::Char('P') => self.mode = AppMode::PullMenu,
+            KeyCode::Char('R') => self.open_rebase(),
+            KeyCode::Char('u') => self.confirm_undo_commit(),
+            KeyCode::Char('z') => self.open_fixup(),
+            KeyCode::Char('Z') => self.autosquash(),
+            _ => {}
+        }
+    }
+
+    fn handle_push_dialog_input(&mut self, key: KeyCode) {
+        let dialog = &mut self.push_dialog;
+        if let Some(input) = &mut dialog.branch_input {
+            match key {
+                KeyCode::Esc => dialog.branch_input = None,
+                KeyCode::Enter => {
+                    let target = input.trim().to_string();
+                    dialog.options.remote_branch = (!target.is_empty()).then_some(target);
+                    dialog.branch_input = None;
+                }
+                KeyCode::Backspace => {
+                    let end = text::prev_boundary(input, input.len());
+                    input.truncate(end);
+                }
+                // Branch names can't contain spaces.
+                KeyCode::Char(' ') => {}
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return;
+        }
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Tab | KeyCode::Right => dialog.cycle_remote(true),
+            KeyCode::BackTab | KeyCode::Left => dialog.cycle_remote(false),
+            KeyCode::Char('u') => dialog.options.set_upstream = !dialog.options.set_upstream,
+            KeyCode::Char('b') => dialog.branch_input = Some(dialog.options.target().to_string()),
+            KeyCode::Char('f') => {
+                dialog.options.force_with_lease = !dialog.options.force_with_lease;
+            }
+            KeyCode::Char('t') => dialog.options.follow_tags = !dialog.options.follow_tags,
+            KeyCode::Char('i') => dialog.options.in_terminal = !dialog.options.in_terminal,
+            KeyCode::Enter => {
+                let options = dialog.options.clone();
+                self.confirm_push(options);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_filter_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Enter => self.mode = AppMode::FileList,
+            KeyCode::Esc => {
+                self.filter.query.clear();
+                self.apply_filter();
+                self.mode = AppMode::FileList;
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(&self.filter.query, self.filter.query.len());
+                self.filter.query.truncate(end);
+                self.apply_filter();
+            }
+            KeyCode::Char(c) => {
+                self.filter.query.push(c);
+                self.apply_filter();
+            }
+            KeyCode::Down => self.select_next(),
+            KeyCode::Up => self.select_previous(),
+            _ => {}
+        }
+    }
+
+    fn handle_diff_search_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Enter => self.mode = AppMode::DiffView,
+            KeyCode::Esc => {
+                self.diff_search = DiffSearch::default();
+                self.mode = AppMode::DiffView;
+            }
+            KeyCode::Backspace => {
+                let query = &mut self.diff_search.query;
+                query.truncate(text::prev_boundary(query, query.len()));
+                self.update_diff_search();
+            }
+            KeyCode::Char(c) => {
+                self.diff_search.query.push(c);
+                self.update_diff_search();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_pull_menu_input(&mut self, key: KeyCode) {
+        let mode = match key {
+            KeyCode::Char('m') | KeyCode::Enter => PullMode::Merge,
+            KeyCode::Char('r') => PullMode::Rebase,
+            KeyCode::Char('f') => PullMode::FastForwardOnly,
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = AppMode::FileList;
+                return;
+            }
+            _ => return,
+        };
+        self.mode = AppMode::FileList;
+        self.pull(mode);
+    }
+
+    fn handle_diff_view_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.close_diff_view(),
+            KeyCode::Char('/') => {
+                self.diff_search = DiffSearch::default();
+                self.mode = AppMode::DiffSearch;
+            }
+            KeyCode::Char('n') => self.step_diff_search(true),
+            KeyCode::Char('N') => self.step_diff_search(false),
+            KeyCode::Char(']') => self.jump_to_hunk(true),
+            KeyCode::Char('[') => self.jump_to_hunk(false),
+            KeyCode::Char('J') => self.jump_to_file(true),
+            KeyCode::Char('K') => self.jump_to_file(false),
+            KeyCode::Char('e') => self.edit_diff_file(),
+            KeyCode::Char('x') => self.export_shown_diff(),
+            KeyCode::Char('Y') => {
+                let diff = self.diff_content.clone();
+                self.copy_to_clipboard(diff, "diff");
+            }
+            KeyCode::Char('y') => {
+                if let DiffSource::Commit { id, .. } = &self.diff_source {
+                    let id = id.clone();
+                    let short_id = id[..id.len().min(7)].to_string();
+                    self.copy_to_clipboard(id, &short_id);
+                }
+            }
+            KeyCode::Char('c') => {
+                if let DiffSource::Commit { id, .. } = &self.diff_source {
+                    let id = id.clone();
+                    self.cherry_pick(id);
+                }
+            }
+            KeyCode::Char(key @ ('r' | 'R')) => {
+                if let DiffSource::Commit { id, .. } = &self.diff_source {
+                    let id = id.clone();
+                    self.revert(id, key == 'R');
+                }
+            }
+            key => {
+                if !self.handle_diff_scroll(key) {
+                    self.handle_diff_options(key);
+                }
+            }
+        }
+    }
+
+    /// `+`/`-` for more or less context, `w` to ignore whitespace, and `a`
+    /// to cycle the algorithm; returns whether `key` was one of them.
+    fn handle_diff_options(&mut self, key: KeyCode) -> bool {
+        let mut options = self.diff_options;
+        match key {
+            KeyCode::Char('+') | KeyCode::Char('=') => options.context += 1,
+            KeyCode::Char('-') => options.context = options.context.saturating_sub(1),
+            KeyCode::Char('w') => options.ignore_whitespace = !options.ignore_whitespace,
+            KeyCode::Char('a') => options.algorithm = options.algorithm.next(),
+            _ => return false,
+        }
+        self.set_diff_options(options);
+        true
+    }
+
+    /// Scrolls the diff for the usual paging keys; returns whether `key`
+    /// was one of them.
+    fn handle_diff_scroll(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_diff(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_diff(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_diff(10),
+            KeyCode::PageUp => self.scroll_diff(-10),
+            KeyCode::Home | KeyCode::Char('g') => self.diff_scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => self.scroll_diff(i32::MAX / 2),
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_output_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => {
+                self.mode = self.output.back.take().unwrap_or(AppMode::FileList);
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.output.scroll_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.output.scroll_by(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.output.scroll_by(10),
+            KeyCode::PageUp => self.output.scroll_by(-10),
+            KeyCode::Home | KeyCode::Char('g') => self.output.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => self.output.scroll_by(i32::MAX / 2),
+            _ => {}
+        }
+    }
+
+    fn handle_messages_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('m') => {
+                self.mode = AppMode::FileList;
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_messages(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_messages(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_messages(10),
+            KeyCode::PageUp => self.scroll_messages(-10),
+            KeyCode::Home | KeyCode::Char('g') => self.messages.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => self.scroll_messages(i32::MAX / 2),
+            _ => {}
+        }
+    }
+
+    fn handle_commit_message_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Up => {
+                if let Some(message) = self.history.older(&self.commit_message) {
+                    self.commit_message = message.to_string();
+                    self.cursor_position = self.commit_message.len();
+                }
+            }
+            KeyCode::Down => {
+                if let Some(message) = self.history.newer() {
+                    self.commit_message = message.to_string();
+                    self.cursor_position = self.commit_message.len();
+                }
+            }
+            KeyCode::F(2) => {
+                self.commit_options.no_verify = !self.commit_options.no_verify;
+            }
+            KeyCode::F(3) => self.toggle_signing(),
+            KeyCode::F(4) => self.mode = AppMode::CommitOptions,
+            KeyCode::F(5) => self.toggle_ticket(),
+            KeyCode::F(6) => self.open_profiles(),
+            KeyCode::F(7) => self.preview_commit(),
+            KeyCode::F(8) => self.toggle_breaking(),
+            KeyCode::F(9) => self.queue_commit(),
+            KeyCode::Esc => self.mode = AppMode::FileList,
+            KeyCode::Enter => {
+                if self.commit_message.trim().is_empty() {
+                    self.show_notification("Commit message cannot be empty".to_string());
+                } else if self.task.is_some() {
+                    self.show_notification("Another git operation is still running".to_string());
+                } else if let Some(issue) = self.blocking_issue() {
+                    self.show_notification(format!("Commit blocked: {}", issue.message));
+                } else if self.on_protected_branch() {
+                    self.ask(
+                        "Protected branch",
+                        format!(
+                            "{} is protected. Commit to it directly?",
+                            self.git_status.current_branch
+                        ),
+                        PendingAction::Commit,
+                    );
+                } else {
+                    self.perform_commit();
+                }
+            }
+            KeyCode::Char(c) => {
+                self.commit_message.insert(self.cursor_position, c);
+                self.cursor_position += c.len_utf8();
+            }
+            KeyCode::Backspace if self.cursor_position > 0 => {
+                let start = text::prev_boundary(&self.commit_message, self.cursor_position);
+                self.commit_message
+                    .replace_range(start..self.cursor_position, "");
+                self.cursor_position = start;
+            }
+            KeyCode::Delete if self.cursor_position < self.commit_message.len() => {
+                let end = text::next_boundary(&self.commit_message, self.cursor_position);
+                self.commit_message
+                    .replace_range(self.cursor_position..end, "");
+            }
+            KeyCode::Left => {
+                self.cursor_position =
+                    text::prev_boundary(&self.commit_message, self.cursor_position);
+            }
+            KeyCode::Right => {
+                self.cursor_position =
+                    text::next_boundary(&self.commit_message, self.cursor_position);
+            }
+            KeyCode::Home => {
+                self.cursor_position = text::line_start(&self.commit_message, self.cursor_position);
+            }
+            KeyCode::End => {
+                self.cursor_position = text::line_end(&self.commit_message, self.cursor_position);
+            }
+            KeyCode::Tab => self.open_prefix_picker(),
+            _ => {}
+        }
+    }
+
+    fn handle_help_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => self.mode = AppMode::FileList,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Referencing issues from the commit editor: `Ctrl+R` lists the open issues
+//! of the branch's forge, filtered as you type, and inserts `#123` or
+//! `Closes #123` at the cursor.
+
+use crate::{
+    app::{App, AppMode},
+    filter::fuzzy_match,
+    pull_request::{Forge, Forges, api_repo_url, remote_repo},
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use serde_json::Value;
+use std::{
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+}
+
+/// Issues from a forge's API or CLI; GitLab numbers them per project as
+/// `iid`, and GitHub lists pull requests among them.
+pub fn parse_issues(forge: Forge, reply: &Value) -> Vec<Issue> {
+    let number = match forge {
+        Forge::GitLab => "iid",
+        Forge::GitHub | Forge::Gitea => "number",
+    };
+    let items = reply.as_array();
+    items
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("pull_request").is_none())
+        .filter_map(|item| {
+            Some(Issue {
+                number: item.get(number)?.as_u64()?,
+                title: item
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `#123`, or `Closes #123` to close it once the commit lands.
+pub fn issue_reference(number: u64, closes: bool) -> String {
+    if closes {
+        format!("Closes #{}", number)
+    } else {
+        format!("#{}", number)
+    }
+}
+
+/// Somewhere to list a repository's open issues.
+pub trait IssueProvider: Send + Sync {
+    fn open_issues(&self, forge: Forge, remote_url: &str) -> Result<Vec<Issue>, String>;
+}
+
+impl IssueProvider for Forges {
+    fn open_issues(&self, forge: Forge, remote_url: &str) -> Result<Vec<Issue>, String> {
+        if !self.has_token(forge) {
+            let (host, repo) = remote_repo(remote_url)
+                .ok_or_else(|| format!("cannot tell the repository of {}", remote_url))?;
+            let output = match forge {
+                Forge::GitHub => self
+                    .run_cli(
+                        "gh",
+                        &[
+                            "issue",
+                            "list",
+                            "--repo",
+                            &format!("{}/{}", host, repo),
+                            "--state",
+                            "open",
+                            "--limit",
+                            "200",
+                            "--json",
+                            "number,title",
+                        ],
+                        &[("GH_PROMPT_DISABLED", "1")],
+                    )
+                    .map_err(|e| format!("{}; or set github_token in the config", e))?,
+                Forge::GitLab => self
+                    .run_cli(
+                        "glab",
+                        &[
+                            "issue",
+                            "list",
+                            "--repo",
+                            &format!("https://{}/{}", host, repo),
+                            "--per-page",
+                            "100",
+                            "--output",
+                            "json",
+                        ],
+                        &[("GITLAB_HOST", &host), ("NO_PROMPT", "1")],
+                    )
+                    .map_err(|e| format!("{}; or set gitlab_token in the config", e))?,
+                Forge::Gitea => return Err("set gitea_token in the config".to_string()),
+            };
+            let reply: Value =
+                serde_json::from_str(&output).map_err(|e| format!("unexpected output: {}", e))?;
+            return Ok(parse_issues(forge, &reply));
+        }
+        let repo = api_repo_url(forge, remote_url)?;
+        let url = match forge {
+            Forge::GitHub => format!("{}/issues?state=open&per_page=100", repo),
+            Forge::GitLab => format!("{}/issues?state=opened&per_page=100", repo),
+            Forge::Gitea => format!("{}/issues?state=open&type=issues&limit=50", repo),
+        };
+        Ok(parse_issues(forge, &self.api(forge, &url, None)?))
+    }
+}
+
+/// The popup of [`AppMode::Issues`] over the commit editor.
+#[derive(Debug, Default)]
+pub struct IssueBrowser {
+    pub forge: Forge,
+    /// The last list read; kept while the next one loads.
+    pub issues: Vec<Issue>,
+    pub query: String,
+    /// Over [`IssueBrowser::matches`].
+    pub selected: usize,
+    loading: Option<Receiver<Result<Vec<Issue>, String>>>,
+}
+
+impl IssueBrowser {
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// The issues matching the query by number and title, best first.
+    pub fn matches(&self) -> Vec<&Issue> {
+        let mut scored: Vec<(i64, &Issue)> = self
+            .issues
+            .iter()
+            .filter_map(|issue| {
+                let text = format!("#{} {}", issue.number, issue.title);
+                fuzzy_match(&self.query, &text).map(|score| (score, issue))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, issue)| issue).collect()
+    }
+}
+
+impl App {
+    /// `Ctrl+R` in the commit editor: the issues of the upstream's remote,
+    /// else `origin`'s, else the first remote's.
+    pub(crate) fn open_issues(&mut self) {
+        let remotes = self.backend.remote_list().unwrap_or_default();
+        let upstream = self.git_status.upstream.as_deref().unwrap_or_default();
+        let remote = remotes
+            .iter()
+            .find(|remote| upstream.starts_with(&format!("{}/", remote.name)))
+            .or_else(|| remotes.iter().find(|remote| remote.name == "origin"))
+            .or_else(|| remotes.first());
+        let Some(remote) = remote else {
+            self.show_notification("No remote to list issues from".to_string());
+            return;
+        };
+        let remote_url = remote.url.clone();
+        let host = remote_repo(&remote_url).map(|(host, _)| host);
+        let forge = host
+            .as_deref()
+            .and_then(|host| self.config.forges.forge_for(&remote.name, host));
+        let Some(forge) = forge else {
+            self.show_notification(format!(
+                "Cannot tell where {} is hosted; set forge.{} = github, gitlab, or gitea",
+                remote.name, remote.name
+            ));
+            return;
+        };
+        let provider = Arc::clone(&self.issue_provider);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(provider.open_issues(forge, &remote_url));
+        });
+        let browser = &mut self.issues;
+        if browser.forge != forge {
+            browser.issues.clear();
+        }
+        browser.forge = forge;
+        browser.query.clear();
+        browser.selected = 0;
+        browser.loading = Some(receiver);
+        self.mode = AppMode::Issues;
+    }
+
+    pub(crate) fn poll_issues(&mut self) {
+        let Some(receiver) = &self.issues.loading else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("worker thread exited".to_string()),
+        };
+        let browser = &mut self.issues;
+        browser.loading = None;
+        match result {
+            Ok(issues) => {
+                browser.issues = issues;
+                browser.selected = 0;
+            }
+            Err(error) => {
+                if self.mode == AppMode::Issues {
+                    self.mode = AppMode::CommitMessage;
+                }
+                self.report_error(GitError::new("Listing issues", error));
+            }
+        }
+    }
+
+    pub(crate) fn handle_issues_input(&mut self, key: KeyCode) {
+        let browser = &mut self.issues;
+        let count = browser.matches().len();
+        match key {
+            KeyCode::Esc => self.mode = AppMode::CommitMessage,
+            KeyCode::Down if browser.selected + 1 < count => browser.selected += 1,
+            KeyCode::Up => browser.selected = browser.selected.saturating_sub(1),
+            KeyCode::Enter | KeyCode::Tab => {
+                let Some(number) = browser.matches().get(browser.selected).map(|i| i.number) else {
+                    return;
+                };
+                self.insert_reference(&issue_reference(number, key == KeyCode::Tab));
+                self.mode = AppMode::CommitMessage;
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(&browser.query, browser.query.len());
+                browser.query.truncate(end);
+                browser.selected = 0;
+            }
+            KeyCode::Char(c) => {
+                browser.query.push(c);
+                browser.selected = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Inserts `reference` at the cursor, spaced from the word before it.
+    fn insert_reference(&mut self, reference: &str) {
+        let message = &mut self.commit_message;
+        let before = message[..self.cursor_position].chars().next_back();
+        let reference = match before {
+            Some(c) if !c.is_whitespace() => format!(" {}", reference),
+            _ => reference.to_string(),
+        };
+        message.insert_str(self.cursor_position, &reference);
+        self.cursor_position += reference.len();
+        self.save_draft();
+    }
+}
@@ -0,0 +1,172 @@
+//! A warning before staging large files. A big build artifact is easy to
+//! stage by accident, and once it is pushed it stays in every clone.
+
+use crate::{
+    app::App,
+    confirm::PendingAction,
+    git::{FileStatus, GitFile},
+};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+const MB: u64 = 1024 * 1024;
+
+/// How big a file may get before staging it asks first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    /// For any file, in bytes. `None` never asks.
+    pub any: Option<u64>,
+    /// Lower, for binary files, which git can neither diff nor compress well.
+    pub binary: Option<u64>,
+}
+
+impl Default for SizeLimits {
+    fn default() -> SizeLimits {
+        SizeLimits {
+            any: Some(50 * MB),
+            binary: Some(5 * MB),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeFile {
+    pub path: String,
+    pub size: u64,
+    pub binary: bool,
+}
+
+/// The files under `paths`, relative to `root`, that go over `limits`.
+/// Directories, as untracked ones are listed, are searched all the way down.
+pub fn find_large_files(root: &Path, paths: &[String], limits: SizeLimits) -> Vec<LargeFile> {
+    let over = |size: u64, limit: Option<u64>| limit.is_some_and(|limit| size > limit);
+    files_under(root, paths)
+        .into_iter()
+        .filter(|(_, size)| over(*size, limits.any) || over(*size, limits.binary))
+        .filter_map(|(path, size)| {
+            let binary = is_binary(&root.join(&path));
+            (over(size, limits.any) || binary).then_some(LargeFile { path, size, binary })
+        })
+        .collect()
+}
+
+/// Every file at or below `paths`, relative to `root`, with its size.
+pub fn files_under(root: &Path, paths: &[String]) -> Vec<(String, u64)> {
+    let mut found = Vec::new();
+    for path in paths {
+        collect(root, &root.join(path), &mut found);
+    }
+    found
+}
+
+fn collect(root: &Path, path: &Path, found: &mut Vec<(String, u64)>) {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        // Deleted; nothing to add.
+        return;
+    };
+    if metadata.is_dir() {
+        // Git doesn't add what is inside another repository, such as a
+        // submodule.
+        if path.file_name().is_some_and(|name| name == ".git")
+            || (path != root && path.join(".git").exists())
+        {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+        let mut children: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        children.sort();
+        for child in children {
+            collect(root, &child, found);
+        }
+    } else if metadata.is_file() {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        found.push((relative.to_string_lossy().into_owned(), metadata.len()));
+    }
+}
+
+/// Git's own test: a NUL byte near the start.
+fn is_binary(path: &Path) -> bool {
+    let mut head = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(8000).read_to_end(&mut head))
+        .is_ok_and(|_| head.contains(&0))
+}
+
+/// `312.4 MB`, `980 KB`, `12 B`.
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..MB => format!("{} KB", bytes / 1024),
+        _ => format!("{:.1} MB", bytes as f64 / MB as f64),
+    }
+}
+
+/// How many files the warning lists before summing up the rest.
+const LISTED: usize = 8;
+
+impl App {
+    /// Asks before `action` when any of `files` that it would stage is over
+    /// the size limits, or is meant for LFS while LFS isn't set up; returns
+    /// whether it asked.
+    pub(crate) fn confirm_large_files(&mut self, files: &[GitFile], action: PendingAction) -> bool {
+        let Some(root) = self.backend.work_dir() else {
+            return false;
+        };
+        let paths: Vec<String> = files
+            .iter()
+            .filter(|f| !f.staged && f.status != FileStatus::Deleted)
+            .map(|f| f.path.clone())
+            .collect();
+        let lfs_ready = self.lfs.is_empty() || self.backend.lfs_installed();
+        let mut warnings: Vec<String> = Vec::new();
+        if !lfs_ready {
+            warnings.extend(
+                files_under(&root, &paths)
+                    .into_iter()
+                    .filter(|(path, _)| self.lfs.matches(path))
+                    .map(|(path, size)| {
+                        format!(
+                            "{}  {}  LFS, but git-lfs isn't set up",
+                            path,
+                            format_size(size)
+                        )
+                    }),
+            );
+        }
+        // LFS keeps big files out of the history anyway.
+        warnings.extend(
+            find_large_files(&root, &paths, self.config.large_files)
+                .into_iter()
+                .filter(|file| !self.lfs.matches(&file.path))
+                .map(|file| {
+                    let kind = if file.binary { "  binary" } else { "" };
+                    format!("{}  {}{}", file.path, format_size(file.size), kind)
+                }),
+        );
+        if warnings.is_empty() {
+            return false;
+        }
+
+        let mut message = String::new();
+        for warning in warnings.iter().take(LISTED) {
+            message.push_str(warning);
+            message.push('\n');
+        }
+        if warnings.len() > LISTED {
+            message.push_str(&format!("…and {} more\n", warnings.len() - LISTED));
+        }
+        message.push_str("\nOnce pushed, these stay in the history for good. Stage anyway?");
+        let title = if warnings.len() == 1 {
+            "Large file"
+        } else {
+            "Large files"
+        };
+        self.ask(title, message, action);
+        true
+    }
+}
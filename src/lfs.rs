@@ -0,0 +1,185 @@
+//! Git LFS: which paths `.gitattributes` sends through LFS, and pointer
+//! files shown as the objects they stand for rather than as hashes.
+
+use crate::large_files::format_size;
+use regex::Regex;
+use std::{fs, path::Path};
+
+/// The `filter=lfs` lines of the top-level `.gitattributes`. Attributes
+/// files further down the tree are not read.
+#[derive(Debug, Clone, Default)]
+pub struct LfsPatterns {
+    /// In file order, each with whether it turns LFS on or off; the last
+    /// match wins, as in git.
+    rules: Vec<(Regex, bool)>,
+}
+
+impl LfsPatterns {
+    pub fn load(work_dir: &Path) -> LfsPatterns {
+        fs::read_to_string(work_dir.join(".gitattributes"))
+            .map(|text| LfsPatterns::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(text: &str) -> LfsPatterns {
+        let rules = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let pattern = fields.next()?;
+                if pattern.starts_with('#') || pattern.starts_with("[attr]") {
+                    return None;
+                }
+                let lfs = fields.rev().find_map(|field| match field {
+                    "filter=lfs" => Some(true),
+                    "-filter" | "!filter" => Some(false),
+                    _ if field.starts_with("filter=") => Some(false),
+                    _ => None,
+                })?;
+                Some((pattern_regex(pattern)?, lfs))
+            })
+            .collect();
+        LfsPatterns { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path`, relative to the top of the working tree, goes
+    /// through LFS.
+    pub fn matches(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(regex, _)| regex.is_match(path))
+            .is_some_and(|(_, lfs)| *lfs)
+    }
+}
+
+/// A gitattributes pattern as a regex over the whole path. Without a `/` it
+/// matches the file name in any directory.
+fn pattern_regex(pattern: &str) -> Option<Regex> {
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' if chars.peek() == Some(&'!') => {
+                chars.next();
+                regex.push_str("[^");
+            }
+            '[' => regex.push('['),
+            ']' => regex.push(']'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+/// What a pointer file records about the object it stands for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LfsPointer {
+    /// The SHA-256 of the content.
+    pub oid: String,
+    pub size: u64,
+}
+
+const POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+
+impl LfsPointer {
+    pub fn parse(text: &str) -> Option<LfsPointer> {
+        let mut lines = text.lines();
+        if lines.next()? != POINTER_VERSION {
+            return None;
+        }
+        let (mut oid, mut size) = (None, None);
+        for line in lines {
+            if let Some(value) = line.strip_prefix("oid sha256:") {
+                oid = Some(value.to_string());
+            } else if let Some(value) = line.strip_prefix("size ") {
+                size = value.parse().ok();
+            }
+        }
+        Some(LfsPointer {
+            oid: oid?,
+            size: size?,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        let short: String = self.oid.chars().take(12).collect();
+        format!("Git LFS object, {} ({})", format_size(self.size), short)
+    }
+}
+
+/// `diff` with each hunk that changes a pointer file replaced by a line for
+/// the old object and one for the new; everything else is left alone.
+pub fn describe_pointer_diffs(diff: &str) -> String {
+    let mut out = String::new();
+    let mut hunk: Vec<&str> = Vec::new();
+    for line in diff.lines() {
+        let in_hunk = !hunk.is_empty();
+        if (in_hunk && !line.starts_with([' ', '+', '-', '\\'])) || line.starts_with("@@") {
+            flush_hunk(&mut out, &mut hunk);
+        }
+        if line.starts_with("@@") || !hunk.is_empty() {
+            hunk.push(line);
+        } else {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    flush_hunk(&mut out, &mut hunk);
+    out
+}
+
+/// Writes out a hunk, header first, summarised if it is a pointer change.
+fn flush_hunk(out: &mut String, hunk: &mut Vec<&str>) {
+    let Some((header, body)) = hunk.split_first() else {
+        return;
+    };
+    let side = |skip: char| -> String {
+        body.iter()
+            .filter(|line| !line.starts_with(skip) && !line.starts_with('\\'))
+            .map(|line| format!("{}\n", line.get(1..).unwrap_or_default()))
+            .collect()
+    };
+    let (old, new) = (side('+'), side('-'));
+    let pointers = (LfsPointer::parse(&old), LfsPointer::parse(&new));
+    let is_pointer_hunk = match &pointers {
+        (Some(_), Some(_)) => true,
+        (Some(_), None) => new.is_empty(),
+        (None, Some(_)) => old.is_empty(),
+        (None, None) => false,
+    };
+    out.push_str(header);
+    out.push('\n');
+    if is_pointer_hunk {
+        if let Some(pointer) = pointers.0 {
+            out.push_str(&format!("-{}\n", pointer.describe()));
+        }
+        if let Some(pointer) = pointers.1 {
+            out.push_str(&format!("+{}\n", pointer.describe()));
+        }
+    } else {
+        for line in body {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    hunk.clear();
+}
@@ -0,0 +1,88 @@
+//! Git Commit Helper: a terminal UI for staging, reviewing, and committing
+//! changes. The binary is a thin wrapper around [`App`]; the git layer in
+//! [`git`] can be used on its own to build other frontends.
+
+pub mod ai;
+pub mod app;
+pub mod args;
+pub mod auto_fetch;
+pub mod branch;
+pub mod breaking;
+pub mod checks;
+pub mod ci;
+pub mod clean;
+pub mod clipboard;
+pub mod command_bar;
+pub mod commit_type;
+pub mod compare;
+pub mod config;
+pub mod confirm;
+pub mod diff_nav;
+pub mod draft;
+pub mod dry_run;
+pub mod editor;
+pub mod filter;
+pub mod fixup;
+pub mod git;
+pub mod groups;
+pub mod headless;
+pub mod history;
+pub mod identity;
+pub mod ignore;
+pub mod input;
+pub mod issues;
+pub mod large_files;
+pub mod lfs;
+pub mod log;
+pub mod mouse;
+pub mod panes;
+pub mod patch;
+pub mod protected;
+pub mod pull_request;
+pub mod rebase;
+pub mod reflog;
+pub mod release;
+pub mod remotes;
+pub mod repo;
+pub mod report;
+pub mod search;
+pub mod secrets;
+pub mod spell;
+pub mod submodules;
+pub mod tags;
+pub mod template;
+pub mod terminal;
+pub mod text;
+pub mod theme;
+pub mod ticket;
+pub mod trailers;
+pub mod tree;
+pub mod ui;
+pub mod undo;
+pub mod validate;
+pub mod watch;
+pub mod wip;
+pub mod word_diff;
+pub mod worktrees;
+
+pub use ai::{AiConfig, MessageProvider, Provider};
+pub use app::{App, AppMode, BackgroundTask, DiffSource, External, Focus, TaskKind};
+pub use commit_type::TypeRule;
+pub use config::Config;
+pub use confirm::{Confirm, PendingAction};
+pub use filter::{FileFilter, StatusFilter};
+pub use git::{
+    CliBackend, CommitInfo, CommitOptions, ConflictSide, DiffAlgorithm, DiffOptions, FileStatus,
+    Git2Backend, GitBackend, GitFile, GitStatus, HeadCommit, Identity, Operation, PullMode,
+    PushOptions, RanCommand, RebaseAction, RebaseEntry, ReflogEntry, RemoteInfo, SignatureState,
+    SigningConfig, SubmoduleInfo, SubmoduleState, TagInfo, WorktreeInfo, open_backend,
+    take_commands,
+};
+pub use panes::{Panes, SplitLayout};
+pub use report::{GitError, Level, LogEntry, MessageLog, Notification};
+pub use search::DiffSearch;
+pub use theme::Theme;
+pub use ticket::TicketRules;
+pub use trailers::{Trailer, TrailerPanel};
+pub use validate::{Issue, Severity, ValidationRules};
+pub use watch::Watcher;
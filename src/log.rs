@@ -0,0 +1,133 @@
+//! Browsing history: the commits that touched a file, following renames,
+//! with the file's diff at each.
+
+use crate::{
+    app::{App, AppMode, DiffSource, TaskKind},
+    git::{CommitInfo, FileStatus},
+    patch::PatchAction,
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+/// How far back the history view reads.
+pub const LOG_LIMIT: usize = 500;
+
+#[derive(Debug, Default)]
+pub struct LogView {
+    /// The file whose history this is, as it is named now.
+    pub path: String,
+    /// Newest first, each with the file's name in that commit.
+    pub commits: Vec<(CommitInfo, String)>,
+    pub selected: usize,
+}
+
+impl App {
+    /// `l` in the file list: the selected file's history.
+    pub(crate) fn open_file_log(&mut self) {
+        let Some(file) = self.selected() else {
+            return;
+        };
+        if file.status == FileStatus::Untracked {
+            self.show_notification(format!("{} has no history yet", file.path));
+            return;
+        }
+        let path = file.path.clone();
+        match self.backend.file_log(&path, LOG_LIMIT) {
+            Ok(commits) if !commits.is_empty() => {
+                self.log = LogView {
+                    path,
+                    commits,
+                    selected: 0,
+                };
+                self.mode = AppMode::Log;
+            }
+            Ok(_) => self.show_notification(format!("{} has no history yet", path)),
+            Err(error) => {
+                self.report_error(GitError::new(format!("Reading history of {}", path), error))
+            }
+        }
+    }
+
+    /// `c` in the history or a commit's details: applies the commit onto
+    /// the current branch. Conflicts leave the cherry-pick in progress for
+    /// the file list's resolve, continue, and abort keys.
+    pub(crate) fn cherry_pick(&mut self, id: String) {
+        if self.task.is_some() || self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::CherryPick, move |cancel| {
+            backend.cherry_pick(&id, cancel)
+        });
+        self.mode = AppMode::FileList;
+    }
+
+    /// `r` / `R` in the history or a commit's details: undoes the commit
+    /// with a new commit, or with `no_commit` as staged changes to inspect
+    /// first.
+    pub(crate) fn revert(&mut self, id: String, no_commit: bool) {
+        if self.task.is_some() || self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Revert { no_commit }, move |cancel| {
+            backend.revert(&id, no_commit, cancel)
+        });
+        self.mode = AppMode::FileList;
+    }
+
+    pub(crate) fn handle_log_input(&mut self, key: KeyCode) {
+        let log = &mut self.log;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if log.selected + 1 < log.commits.len() => {
+                log.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => log.selected = log.selected.saturating_sub(1),
+            KeyCode::Home | KeyCode::Char('g') => log.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => log.selected = log.commits.len().saturating_sub(1),
+            KeyCode::Enter => {
+                if let Some((commit, _)) = log.commits.get(log.selected) {
+                    let id = commit.id.clone();
+                    self.show_commit(id);
+                }
+            }
+            KeyCode::Char('x') => {
+                if let Some((commit, _)) = log.commits.get(log.selected) {
+                    let id = commit.id.clone();
+                    self.open_patch_prompt(PatchAction::ExportCommits(id));
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some((commit, _)) = log.commits.get(log.selected) {
+                    let id = commit.id.clone();
+                    self.cherry_pick(id);
+                }
+            }
+            KeyCode::Char(key @ ('r' | 'R')) => {
+                if let Some((commit, _)) = log.commits.get(log.selected) {
+                    let id = commit.id.clone();
+                    self.revert(id, key == 'R');
+                }
+            }
+            KeyCode::Char('d') => {
+                let Some((commit, path)) = log.commits.get(log.selected) else {
+                    return;
+                };
+                let (id, path) = (commit.id.clone(), path.clone());
+                let diff = self.show_commit_diff(&id, Some(&path));
+                self.open_diff_view(
+                    diff,
+                    DiffSource::Commit {
+                        id,
+                        path: Some(path),
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Mouse input: clicking and scrolling the file list and diff, and the
+//! clickable key hints in the status bar.
+
+use crate::{
+    app::{App, AppMode, Focus},
+    ci::{CiState, CiStatus},
+    git::FileStatus,
+};
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use std::time::{Duration, Instant};
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+const WHEEL_LINES: i32 = 3;
+/// Columns before the indent on a file row: the highlight symbol, then the
+/// mark.
+const ROW_PREFIX: u16 = 4;
+
+/// Where the last frame put the parts that react to the mouse.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ClickAreas {
+    pub files: Rect,
+    /// Empty unless the split view is on.
+    pub diff: Rect,
+    pub status_bar: Rect,
+}
+
+/// The key hints shown in the status bar for `mode`, when they don't depend
+/// on the selection.
+fn mode_hints(mode: &AppMode) -> &'static [(&'static str, KeyCode)] {
+    match mode {
+        AppMode::CommitMessage => &[
+            ("[Enter] commit", KeyCode::Enter),
+            ("[F4] options", KeyCode::F(4)),
+            ("[Esc] cancel", KeyCode::Esc),
+        ],
+        AppMode::DiffView | AppMode::Help | AppMode::Output | AppMode::Messages => {
+            &[("[Esc] back", KeyCode::Esc)]
+        }
+        AppMode::Log => &[
+            ("[Enter] commit", KeyCode::Enter),
+            ("[d] diff", KeyCode::Char('d')),
+            ("[c] cherry-pick", KeyCode::Char('c')),
+            ("[r] revert", KeyCode::Char('r')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Checks => &[
+            ("[c] commit anyway", KeyCode::Char('c')),
+            ("[r] rerun", KeyCode::Char('r')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Tags => &[
+            ("[n] new", KeyCode::Char('n')),
+            ("[a] annotated", KeyCode::Char('a')),
+            ("[p] push", KeyCode::Char('p')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Reflog => &[
+            ("[Enter] details", KeyCode::Enter),
+            ("[o] check out", KeyCode::Char('o')),
+            ("[r] restore", KeyCode::Char('r')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Remotes => &[
+            ("[a] add", KeyCode::Char('a')),
+            ("[e] edit URL", KeyCode::Char('e')),
+            ("[p] push", KeyCode::Char('p')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Submodules => &[
+            ("[u] update", KeyCode::Char('u')),
+            ("[Space] stage", KeyCode::Char(' ')),
+            ("[Enter] open", KeyCode::Enter),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Clean => &[
+            ("[Space] mark", KeyCode::Char(' ')),
+            ("[a] all", KeyCode::Char('a')),
+            ("[d] delete", KeyCode::Char('d')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Groups => &[
+            ("[n] new group", KeyCode::Char('n')),
+            ("[e] edit message", KeyCode::Char('e')),
+            ("[c] commit groups", KeyCode::Char('c')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Compare => &[
+            ("[Enter] commit", KeyCode::Enter),
+            ("[d] diff", KeyCode::Char('d')),
+            ("[b] base", KeyCode::Char('b')),
+            ("[s] swap", KeyCode::Char('s')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Ci => &[
+            ("[y] copy link", KeyCode::Char('y')),
+            ("[r] reload", KeyCode::Char('r')),
+            ("[Esc] close", KeyCode::Esc),
+        ],
+        AppMode::Spelling => &[
+            ("[Enter] replace", KeyCode::Enter),
+            ("[a] add word", KeyCode::Char('a')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Prefixes => &[
+            ("[Enter] use", KeyCode::Enter),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Profiles => &[
+            ("[Enter] use", KeyCode::Enter),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Issues => &[
+            ("[Enter] insert #N", KeyCode::Enter),
+            ("[Tab] insert Closes #N", KeyCode::Tab),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::PullRequest => &[
+            ("[Enter] create", KeyCode::Enter),
+            ("[Tab] next field", KeyCode::Tab),
+            ("[Esc] cancel", KeyCode::Esc),
+        ],
+        AppMode::Worktrees => &[
+            ("[Enter] switch", KeyCode::Enter),
+            ("[n] new", KeyCode::Char('n')),
+            ("[Esc] back", KeyCode::Esc),
+        ],
+        AppMode::Release => &[
+            ("[Enter] create", KeyCode::Enter),
+            ("[b] bump", KeyCode::Char('b')),
+            ("[f] changelog file", KeyCode::Char('f')),
+            ("[Esc] cancel", KeyCode::Esc),
+        ],
+        AppMode::RepoSwitch => &[
+            ("[Enter] open", KeyCode::Enter),
+            ("[o] type a path", KeyCode::Char('o')),
+            ("[Esc] cancel", KeyCode::Esc),
+        ],
+        AppMode::NoRepo => &[
+            ("[o] open", KeyCode::Char('o')),
+            ("[i] git init", KeyCode::Char('i')),
+            ("[q] quit", KeyCode::Char('q')),
+        ],
+        _ => &[("[Esc] cancel", KeyCode::Esc)],
+    }
+}
+
+/// Separates the hints from each other and from the mode label.
+pub(crate) const HINT_GAP: &str = "  ";
+
+impl App {
+    /// The key hints shown in the status bar; clicking one acts like
+    /// pressing its key.
+    pub(crate) fn status_hints(&self) -> Vec<(&'static str, KeyCode)> {
+        if self.mode == AppMode::FileList {
+            self.file_list_hints()
+        } else {
+            mode_hints(&self.mode).to_vec()
+        }
+    }
+
+    /// The keys most likely wanted next for what is selected in the file
+    /// list, and the state of the repository.
+    fn file_list_hints(&self) -> Vec<(&'static str, KeyCode)> {
+        let mut hints = Vec::new();
+        if self.git_status.operation.is_some() {
+            hints.push(("[C] continue", KeyCode::Char('C')));
+            hints.push(("[X] abort", KeyCode::Char('X')));
+        }
+        if self.split_view && self.focus == Focus::Diff {
+            hints.push(("[Tab] files", KeyCode::Tab));
+        }
+        let targets = self.target_files();
+        if targets.iter().any(|f| f.status == FileStatus::Conflicted) {
+            hints.push(("[<] ours", KeyCode::Char('<')));
+            hints.push(("[>] theirs", KeyCode::Char('>')));
+            hints.push(("[M] merge tool", KeyCode::Char('M')));
+        } else if !targets.is_empty() {
+            if targets.iter().all(|f| f.staged) {
+                hints.push(("[Space] unstage", KeyCode::Char(' ')));
+            } else {
+                hints.push(("[Space] stage", KeyCode::Char(' ')));
+            }
+            hints.push(("[d] diff", KeyCode::Char('d')));
+            if targets.iter().all(|f| f.status == FileStatus::Untracked) {
+                hints.push(("[N] intent to add", KeyCode::Char('N')));
+                hints.push(("[I] ignore", KeyCode::Char('I')));
+            } else if targets.iter().all(|f| f.status == FileStatus::IntentToAdd) {
+                hints.push(("[N] untrack", KeyCode::Char('N')));
+            }
+        }
+        if self.all_files.iter().any(|f| f.staged) {
+            hints.push(("[c] commit", KeyCode::Char('c')));
+        }
+        if self.git_status.detached() {
+            hints.push(("[b] branch here", KeyCode::Char('b')));
+        }
+        if self.git_status.ahead > 0 {
+            hints.push(("[p] push", KeyCode::Char('p')));
+        }
+        if self
+            .ci
+            .as_ref()
+            .and_then(|ci| ci.status.as_ref())
+            .and_then(CiStatus::overall)
+            == Some(CiState::Failure)
+        {
+            hints.push(("[w] CI failed", KeyCode::Char('w')));
+        }
+        if self.offers_pull_request() {
+            hints.push(("[o] pull request", KeyCode::Char('o')));
+        }
+        if self.git_status.behind > 0 {
+            hints.push(("[P] pull", KeyCode::Char('P')));
+        }
+        if self.all_files.is_empty() && self.git_status.operation.is_none() {
+            hints.push(("[f] fetch", KeyCode::Char('f')));
+        }
+        hints.push(("[h] help", KeyCode::Char('h')));
+        hints.push(("[q] quit", KeyCode::Char('q')));
+        hints
+    }
+
+    pub(crate) fn status_prefix(&self) -> String {
+        format!("Mode: {} |{}", self.mode_label(), HINT_GAP)
+    }
+
+    /// The hint under `column` of the status bar.
+    fn hint_at(&self, column: u16) -> Option<KeyCode> {
+        let mut x = self.areas.status_bar.x + self.status_prefix().chars().count() as u16;
+        for (label, key) in self.status_hints() {
+            let width = label.chars().count() as u16;
+            if (x..x + width).contains(&column) {
+                return Some(key);
+            }
+            x += width + HINT_GAP.len() as u16;
+        }
+        None
+    }
+
+    pub fn handle_mouse(&mut self, event: MouseEvent) {
+        let position = Position::new(event.column, event.row);
+        match event.kind {
+            MouseEventKind::ScrollDown => self.scroll_at(position, WHEEL_LINES),
+            MouseEventKind::ScrollUp => self.scroll_at(position, -WHEEL_LINES),
+            MouseEventKind::Down(MouseButton::Left) => self.click_at(position),
+            _ => {}
+        }
+    }
+
+    fn scroll_at(&mut self, position: Position, lines: i32) {
+        match self.mode {
+            AppMode::DiffView => self.scroll_diff(lines),
+            AppMode::Output => self.output.scroll_by(lines),
+            AppMode::Messages => self.scroll_messages(lines),
+            AppMode::FileList if self.areas.diff.contains(position) => self.scroll_diff(lines),
+            AppMode::FileList if self.areas.files.contains(position) && !self.rows.is_empty() => {
+                let last = self.rows.len() as i32 - 1;
+                let row = (self.selected_row as i32 + lines).clamp(0, last);
+                self.select_row(row as usize);
+            }
+            _ => {}
+        }
+    }
+
+    fn click_at(&mut self, position: Position) {
+        if self.task.is_some() {
+            return;
+        }
+        if self.areas.status_bar.contains(position) {
+            if let Some(key) = self.hint_at(position.x) {
+                self.handle_input(key);
+            }
+            return;
+        }
+        if self.mode != AppMode::FileList || self.confirm.is_some() {
+            return;
+        }
+        if self.areas.diff.contains(position) {
+            self.focus = Focus::Diff;
+            return;
+        }
+
+        let files = self.areas.files;
+        // Skip the border.
+        if !files.contains(position) || position.y == files.y || position.y + 1 >= files.bottom() {
+            return;
+        }
+        let index = self.file_list_state.offset() + (position.y - files.y - 1) as usize;
+        let Some(row) = self.rows.get(index) else {
+            return;
+        };
+        let circle = files.x + 1 + ROW_PREFIX + 2 * row.depth as u16;
+
+        self.focus = Focus::Files;
+        let double = self
+            .last_click
+            .is_some_and(|(time, row)| row == index && time.elapsed() < DOUBLE_CLICK);
+        self.select_row(index);
+        if double || position.x == circle {
+            self.toggle_stage_file();
+            self.last_click = None;
+        } else {
+            self.last_click = Some((Instant::now(), index));
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Sizing of the split view, resized from the keyboard and remembered per
+//! repository in the `.git` directory.
+
+use crate::app::App;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const PANES_FILE: &str = "GIT_COMMIT_HELPER_PANES";
+const MIN_PERCENT: u16 = 15;
+const MAX_PERCENT: u16 = 85;
+const STEP: i16 = 5;
+
+fn panes_path(git_dir: &Path) -> PathBuf {
+    git_dir.join(PANES_FILE)
+}
+
+/// How the split view arranges the file list and the diff.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitLayout {
+    /// List on the left, diff on the right. Suits wide terminals.
+    SideBySide,
+    /// List above the diff. Suits narrow ones.
+    Stacked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Panes {
+    /// Share of the split given to the file list, in percent.
+    pub list_percent: u16,
+}
+
+impl Panes {
+    /// Reads the saved sizes, falling back to `default` for anything
+    /// missing. The file holds `key=value` lines.
+    pub fn load(git_dir: &Path, default: Panes) -> Panes {
+        let Ok(text) = fs::read_to_string(panes_path(git_dir)) else {
+            return default;
+        };
+        let mut panes = default;
+        for line in text.lines() {
+            if let Some(("list_percent", value)) = line.split_once('=')
+                && let Ok(percent) = value.trim().parse::<u16>()
+            {
+                panes.list_percent = percent.clamp(MIN_PERCENT, MAX_PERCENT);
+            }
+        }
+        panes
+    }
+
+    pub fn save(&self, git_dir: &Path) -> io::Result<()> {
+        fs::write(
+            panes_path(git_dir),
+            format!("list_percent={}\n", self.list_percent),
+        )
+    }
+
+    /// Grows (`steps > 0`) or shrinks the file list.
+    pub fn resize(&mut self, steps: i16) {
+        let percent = self.list_percent as i16 + steps * STEP;
+        self.list_percent = percent.clamp(MIN_PERCENT as i16, MAX_PERCENT as i16) as u16;
+    }
+}
+
+impl App {
+    pub(crate) fn resize_panes(&mut self, steps: i16) {
+        if !self.split_view {
+            return;
+        }
+        self.panes.resize(steps);
+        if let Some(git_dir) = &self.git_dir
+            && let Err(e) = self.panes.save(git_dir)
+        {
+            self.show_warning(format!("Could not save layout: {}", e));
+        }
+    }
+}
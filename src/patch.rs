@@ -0,0 +1,188 @@
+//! Patch files: exporting the staged changes, a commit, or a compared range
+//! to disk, and applying one with `git apply` or `git am`.
+
+use crate::{
+    app::{App, AppMode, DiffSource, TaskKind},
+    git::Operation,
+    repo::expand_home,
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchAction {
+    ExportStaged,
+    /// A commit id, or a `base..head` range.
+    ExportCommits(String),
+    Apply,
+}
+
+#[derive(Debug)]
+pub struct PatchPrompt {
+    pub action: PatchAction,
+    /// The file, from the top of the working tree unless absolute.
+    pub input: String,
+    /// Where `Esc` and a finished export go back to.
+    pub back: AppMode,
+}
+
+/// Whether `text` is a mailbox from `git format-patch`, for `git am`,
+/// rather than a plain diff.
+pub fn is_mailbox(text: &str) -> bool {
+    text.starts_with("From ") && text.lines().any(|line| line.starts_with("Subject: "))
+}
+
+/// A file name for the patch of `commits`, e.g. `feature-since-main.patch`
+/// for `main..feature`.
+pub fn patch_file_name(commits: &str) -> String {
+    let name = match commits.split_once("..") {
+        Some((base, head)) => format!("{}-since-{}", head, base),
+        None => commits[..commits.len().min(7)].to_string(),
+    };
+    format!("{}.patch", name.replace('/', "-"))
+}
+
+impl App {
+    /// `x` in a diff: what it shows, as a patch file.
+    pub(crate) fn export_shown_diff(&mut self) {
+        match self.diff_source.clone() {
+            DiffSource::Staged => self.open_patch_prompt(PatchAction::ExportStaged),
+            DiffSource::Commit { id, .. } => {
+                self.open_patch_prompt(PatchAction::ExportCommits(id));
+            }
+            DiffSource::Range { base, head } => {
+                let range = format!("{}..{}", base, head);
+                self.open_patch_prompt(PatchAction::ExportCommits(range));
+            }
+            DiffSource::Selection => self.show_notification(
+                "Export the staged changes (D) or a commit as a patch".to_string(),
+            ),
+        }
+    }
+
+    pub(crate) fn open_patch_prompt(&mut self, action: PatchAction) {
+        if action == PatchAction::ExportStaged && !self.all_files.iter().any(|f| f.staged) {
+            self.show_notification("Nothing staged to export".to_string());
+            return;
+        }
+        let input = match &action {
+            PatchAction::ExportStaged => "staged.patch".to_string(),
+            PatchAction::ExportCommits(commits) => patch_file_name(commits),
+            PatchAction::Apply => String::new(),
+        };
+        let back = std::mem::replace(&mut self.mode, AppMode::Patch);
+        self.patch = Some(PatchPrompt {
+            action,
+            input,
+            back,
+        });
+    }
+
+    pub(crate) fn handle_patch_input(&mut self, key: KeyCode) {
+        let Some(prompt) = &mut self.patch else {
+            self.mode = AppMode::FileList;
+            return;
+        };
+        let input = &mut prompt.input;
+        match key {
+            KeyCode::Esc => self.close_patch_prompt(),
+            KeyCode::Enter if !input.trim().is_empty() => {
+                let (typed, action) = (input.trim().to_string(), prompt.action.clone());
+                let path = self.patch_path(&typed);
+                match action {
+                    PatchAction::Apply => self.apply_patch(path),
+                    action => self.export_patch(&action, &path),
+                }
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(input, input.len());
+                input.truncate(end);
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    fn close_patch_prompt(&mut self) {
+        self.mode = match self.patch.take() {
+            Some(prompt) => prompt.back,
+            None => AppMode::FileList,
+        };
+    }
+
+    fn patch_path(&self, typed: &str) -> PathBuf {
+        let path = expand_home(typed);
+        match self.backend.work_dir() {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path,
+        }
+    }
+
+    /// Writes the patch; an existing file is left alone and the prompt
+    /// stays open for another name.
+    fn export_patch(&mut self, action: &PatchAction, path: &Path) {
+        if path.exists() {
+            self.show_warning(format!("{} already exists", path.display()));
+            return;
+        }
+        let patch = match action {
+            PatchAction::ExportCommits(commits) => self.backend.format_patch(commits),
+            _ => self.backend.staged_patch(),
+        };
+        let written = patch.and_then(|patch| {
+            fs::write(path, patch).map_err(|e| format!("{}: {}", path.display(), e))
+        });
+        self.close_patch_prompt();
+        match written {
+            Ok(()) => self.show_notification(format!("Wrote {}", path.display())),
+            Err(error) => self.report_error(GitError::new("Exporting a patch", error)),
+        }
+    }
+
+    fn apply_patch(&mut self, path: PathBuf) {
+        let mailbox = match fs::read_to_string(&path) {
+            Ok(text) => is_mailbox(&text),
+            Err(e) => {
+                self.show_warning(format!("Cannot read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        if self.task.is_some() || self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        self.close_patch_prompt();
+        self.mode = AppMode::FileList;
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::ApplyPatch, move |cancel| {
+            backend.apply_patch(&path, mailbox, cancel)
+        });
+    }
+
+    /// Conflicts stop `git am` part way, or leave `git apply`'s files
+    /// conflicted; either way the file list takes over from there.
+    pub(crate) fn finish_apply_patch(&mut self, result: Result<String, String>) {
+        self.refresh_git_status();
+        let conflicts = self.conflict_count();
+        match result {
+            Ok(_) => self.show_notification("Patch applied".to_string()),
+            Err(_) if self.git_status.operation == Some(Operation::Am) => self.show_warning(
+                "Patch stopped: resolve conflicts, then C to continue or X to abort".to_string(),
+            ),
+            Err(_) if conflicts > 0 => self.show_warning(format!(
+                "Patch applied with {} conflict(s); resolve them and stage the files",
+                conflicts
+            )),
+            Err(error) => {
+                self.log_error(GitError::new("Applying a patch", error.clone()));
+                self.show_output("Applying the patch failed", error);
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Protected branches: committing or pushing straight to one asks first,
+//! so work meant for a feature branch doesn't land on `main` by accident.
+
+use crate::{
+    app::{App, AppMode},
+    confirm::PendingAction,
+    git::PushOptions,
+};
+
+/// `main`, `master`, and release branches.
+pub fn default_protected_branches() -> Vec<String> {
+    ["main", "master", "release/*"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Whether `branch` matches one of `patterns`, where `*` stands for any
+/// run of characters, `/` included.
+pub fn is_protected(patterns: &[String], branch: &str) -> bool {
+    patterns.iter().any(|pattern| glob_match(pattern, branch))
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` at all.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+impl App {
+    pub(crate) fn on_protected_branch(&self) -> bool {
+        is_protected(
+            &self.config.protected_branches,
+            &self.git_status.current_branch,
+        )
+    }
+
+    /// Pushes, asking first when the remote branch is protected or the push
+    /// is forced.
+    pub(crate) fn confirm_push(&mut self, options: PushOptions) {
+        let protected = is_protected(&self.config.protected_branches, options.target());
+        let target = format!("{}/{}", options.remote, options.target());
+        match (options.force_with_lease, protected) {
+            (true, true) => self.ask(
+                "Force push to protected branch",
+                format!(
+                    "{} is protected, and a force push may overwrite it. Continue?",
+                    target
+                ),
+                PendingAction::Push(options),
+            ),
+            (true, false) => self.ask(
+                "Force push",
+                format!(
+                    "Force push may overwrite {} on the remote. Continue?",
+                    target
+                ),
+                PendingAction::Push(options),
+            ),
+            (false, true) => self.ask(
+                "Protected branch",
+                format!("{} is protected. Push to it directly?", target),
+                PendingAction::Push(options),
+            ),
+            (false, false) => {
+                self.mode = AppMode::FileList;
+                self.push_to_remote(options);
+            }
+        }
+    }
+}
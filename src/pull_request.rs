@@ -0,0 +1,579 @@
+//! Opening a pull request for the pushed branch, or a merge request on
+//! GitLab. GitHub goes through `gh` and GitLab through `glab` unless a token
+//! for their REST API is configured; Gitea always uses its API. The title
+//! and body start from the branch's commits and are reviewed before
+//! anything is sent.
+
+use crate::{
+    app::{App, AppMode, TaskKind},
+    compare::compare_label,
+    git::CommitInfo,
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use serde_json::{Value, json};
+use std::{
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Where a remote is hosted, which decides how requests are created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Forge {
+    #[default]
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl Forge {
+    pub fn parse(name: &str) -> Option<Forge> {
+        match name.to_lowercase().as_str() {
+            "github" => Some(Forge::GitHub),
+            "gitlab" => Some(Forge::GitLab),
+            "gitea" | "forgejo" => Some(Forge::Gitea),
+            _ => None,
+        }
+    }
+
+    /// The forge a host name gives away, e.g. `gitlab.example.com`.
+    pub fn detect(host: &str) -> Option<Forge> {
+        let host = host.to_lowercase();
+        if host.contains("github") {
+            Some(Forge::GitHub)
+        } else if host.contains("gitlab") {
+            Some(Forge::GitLab)
+        } else if host.contains("gitea") || host.contains("forgejo") || host == "codeberg.org" {
+            Some(Forge::Gitea)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Forge::GitHub => "GitHub",
+            Forge::GitLab => "GitLab",
+            Forge::Gitea => "Gitea",
+        }
+    }
+
+    /// What the forge calls a request to merge a branch.
+    pub fn request_name(self) -> &'static str {
+        match self {
+            Forge::GitLab => "merge request",
+            Forge::GitHub | Forge::Gitea => "pull request",
+        }
+    }
+}
+
+/// Which forge each remote is on, where its URL doesn't say, and the
+/// tokens for their APIs.
+#[derive(Debug, Clone, Default)]
+pub struct ForgeSettings {
+    /// A remote's name or host, and its forge.
+    pub forges: Vec<(String, Forge)>,
+    pub github_token: Option<String>,
+    pub gitlab_token: Option<String>,
+    pub gitea_token: Option<String>,
+}
+
+impl ForgeSettings {
+    /// The configured forge of remote `name` at `host`, else the one the
+    /// host name suggests.
+    pub fn forge_for(&self, name: &str, host: &str) -> Option<Forge> {
+        self.forges
+            .iter()
+            .find(|(remote, _)| remote == name)
+            .or_else(|| self.forges.iter().find(|(known, _)| known == host))
+            .map(|(_, forge)| *forge)
+            .or_else(|| Forge::detect(host))
+    }
+
+    pub fn token(&self, forge: Forge) -> Option<&str> {
+        match forge {
+            Forge::GitHub => self.github_token.as_deref(),
+            Forge::GitLab => self.gitlab_token.as_deref(),
+            Forge::Gitea => self.gitea_token.as_deref(),
+        }
+    }
+}
+
+/// What is sent to the forge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PullRequest {
+    pub forge: Forge,
+    /// The URL of the remote the branch was pushed to.
+    pub remote_url: String,
+    /// The branch to merge into.
+    pub base: String,
+    /// The pushed branch, as the remote names it.
+    pub head: String,
+    pub title: String,
+    pub body: String,
+}
+
+/// Somewhere that turns a pushed branch into a pull request.
+pub trait PullRequestHost: Send + Sync {
+    /// Creates the pull request and returns its URL.
+    fn create(&self, request: &PullRequest) -> Result<String, String>;
+}
+
+/// The host and repository path of a remote URL, for the `https`,
+/// `ssh://`, and `git@host:` forms. GitLab paths may hold subgroups.
+pub fn remote_repo(url: &str) -> Option<(String, String)> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .or_else(|| url.strip_prefix("ssh://"));
+    let (host, path) = match rest {
+        Some(rest) => rest.split_once('/')?,
+        None => url.split_once(':')?,
+    };
+    // Drop a user, and a port for `ssh://`.
+    let host = host.rsplit('@').next()?.split(':').next()?;
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    if host.is_empty() || !path.contains('/') || path.starts_with('/') {
+        return None;
+    }
+    Some((host.to_string(), path.to_string()))
+}
+
+/// A title from a branch name: `feature/add-login` gives `Add login`.
+fn title_from_branch(branch: &str) -> String {
+    let name = branch
+        .rsplit('/')
+        .next()
+        .unwrap_or(branch)
+        .replace(['-', '_'], " ");
+    let mut chars = name.trim().chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The title and body a pull request starts with, as `gh pr create --fill`
+/// would: a single commit's subject and body, or else the branch name and
+/// the subjects oldest first. `commits` are newest first.
+pub fn prefill(branch: &str, commits: &[(CommitInfo, String)]) -> (String, String) {
+    match commits {
+        [(commit, body)] => (commit.summary.clone(), body.trim().to_string()),
+        _ => {
+            let body = commits
+                .iter()
+                .rev()
+                .map(|(commit, _)| format!("- {}", commit.summary))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (title_from_branch(branch), body)
+        }
+    }
+}
+
+/// The message in an API's error reply: GitHub's first validation error,
+/// GitLab's `message` string or list, or a plain `error`.
+fn error_detail(reply: &Value) -> String {
+    ["/errors/0/message", "/message", "/message/0", "/error"]
+        .iter()
+        .find_map(|pointer| reply.pointer(pointer).and_then(Value::as_str))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// The root of `forge`'s REST API on `host`. GitHub Enterprise serves it
+/// under `/api/v3`.
+fn api_url(forge: Forge, host: &str) -> String {
+    match forge {
+        Forge::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+        Forge::GitHub => format!("https://{}/api/v3", host),
+        Forge::GitLab => format!("https://{}/api/v4", host),
+        Forge::Gitea => format!("https://{}/api/v1", host),
+    }
+}
+
+/// Where `repo` lives under [`api_url`]; GitLab wants the path as one
+/// encoded project id.
+pub(crate) fn api_repo_url(forge: Forge, remote_url: &str) -> Result<String, String> {
+    let (host, repo) = remote_repo(remote_url)
+        .ok_or_else(|| format!("cannot tell the repository of {}", remote_url))?;
+    Ok(match forge {
+        Forge::GitLab => format!(
+            "{}/projects/{}",
+            api_url(forge, &host),
+            repo.replace('/', "%2F")
+        ),
+        Forge::GitHub | Forge::Gitea => format!("{}/repos/{}", api_url(forge, &host), repo),
+    })
+}
+
+/// The URL a CLI printed last, after any warnings.
+fn last_url(output: &str, program: &str) -> Result<String, String> {
+    output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with("http"))
+        .map(str::to_string)
+        .ok_or_else(|| format!("{} printed no URL", program))
+}
+
+/// Talks to each request's forge, through its CLI or its API.
+pub struct Forges {
+    settings: ForgeSettings,
+    /// Where `gh` and `glab` run, so they find the repository.
+    dir: Option<PathBuf>,
+    agent: ureq::Agent,
+}
+
+impl Forges {
+    pub fn new(settings: ForgeSettings, dir: Option<PathBuf>) -> Forges {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Forges {
+            settings,
+            dir,
+            agent,
+        }
+    }
+
+    pub(crate) fn has_token(&self, forge: Forge) -> bool {
+        self.settings.token(forge).is_some()
+    }
+
+    /// Runs a forge's CLI in the working tree and returns what it printed.
+    pub(crate) fn run_cli(
+        &self,
+        program: &str,
+        args: &[&str],
+        env: &[(&str, &str)],
+    ) -> Result<String, String> {
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .envs(env.iter().copied())
+            .stdin(Stdio::null());
+        if let Some(dir) = &self.dir {
+            command.current_dir(dir);
+        }
+        let output = command
+            .output()
+            .map_err(|e| format!("{}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Calls `forge`'s API with its token: a POST of `body`, else a GET.
+    pub(crate) fn api(
+        &self,
+        forge: Forge,
+        url: &str,
+        body: Option<Value>,
+    ) -> Result<Value, String> {
+        let token = self
+            .settings
+            .token(forge)
+            .ok_or_else(|| format!("set {}_token in the config", forge.name().to_lowercase()))?;
+        let (name, value) = match forge {
+            Forge::GitHub => ("Authorization", format!("Bearer {}", token)),
+            Forge::GitLab => ("PRIVATE-TOKEN", token.to_string()),
+            Forge::Gitea => ("Authorization", format!("token {}", token)),
+        };
+        let response = match body {
+            Some(body) => self
+                .agent
+                .post(url)
+                .header("Accept", "application/json")
+                .header("Content-Type", "application/json")
+                .header(name, &value)
+                .header("User-Agent", "gch")
+                .send(body.to_string()),
+            None => self
+                .agent
+                .get(url)
+                .header("Accept", "application/json")
+                .header(name, &value)
+                .header("User-Agent", "gch")
+                .call(),
+        };
+        let mut response = response.map_err(|e| e.to_string())?;
+        let status = response.status();
+        let text = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| e.to_string())?;
+        let reply: Value =
+            serde_json::from_str(&text).map_err(|e| format!("unexpected reply: {}", e))?;
+        if !status.is_success() {
+            return Err(format!(
+                "{} answered {}: {}",
+                url,
+                status,
+                error_detail(&reply)
+            ));
+        }
+        Ok(reply)
+    }
+}
+
+impl PullRequestHost for Forges {
+    fn create(&self, request: &PullRequest) -> Result<String, String> {
+        let forge = request.forge;
+        if self.settings.token(forge).is_none() {
+            let (host, repo) = remote_repo(&request.remote_url)
+                .ok_or_else(|| format!("cannot tell the repository of {}", request.remote_url))?;
+            return match forge {
+                Forge::GitHub => self
+                    .run_cli(
+                        "gh",
+                        &[
+                            "pr",
+                            "create",
+                            "--title",
+                            &request.title,
+                            "--body",
+                            &request.body,
+                            "--base",
+                            &request.base,
+                            "--head",
+                            &request.head,
+                            "--repo",
+                            &format!("{}/{}", host, repo),
+                        ],
+                        &[("GH_PROMPT_DISABLED", "1")],
+                    )
+                    .and_then(|output| last_url(&output, "gh"))
+                    .map_err(|e| format!("{}; or set github_token in the config", e)),
+                Forge::GitLab => self
+                    .run_cli(
+                        "glab",
+                        &[
+                            "mr",
+                            "create",
+                            "--title",
+                            &request.title,
+                            "--description",
+                            &request.body,
+                            "--target-branch",
+                            &request.base,
+                            "--source-branch",
+                            &request.head,
+                            "--repo",
+                            &format!("https://{}/{}", host, repo),
+                            "--yes",
+                        ],
+                        &[("GITLAB_HOST", &host), ("NO_PROMPT", "1")],
+                    )
+                    .and_then(|output| last_url(&output, "glab"))
+                    .map_err(|e| format!("{}; or set gitlab_token in the config", e)),
+                Forge::Gitea => Err("set gitea_token in the config".to_string()),
+            };
+        }
+        let repo_url = api_repo_url(forge, &request.remote_url)?;
+        let (url, body, url_field) = match forge {
+            Forge::GitLab => (
+                format!("{}/merge_requests", repo_url),
+                json!({
+                    "title": request.title,
+                    "description": request.body,
+                    "target_branch": request.base,
+                    "source_branch": request.head,
+                }),
+                "web_url",
+            ),
+            Forge::GitHub | Forge::Gitea => (
+                format!("{}/pulls", repo_url),
+                json!({
+                    "title": request.title,
+                    "body": request.body,
+                    "base": request.base,
+                    "head": request.head,
+                }),
+                "html_url",
+            ),
+        };
+        self.api(forge, &url, Some(body))?
+            .get(url_field)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| "the reply held no URL".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PullRequestField {
+    #[default]
+    Title,
+    Body,
+    Base,
+}
+
+impl PullRequestField {
+    fn next(self) -> PullRequestField {
+        match self {
+            PullRequestField::Title => PullRequestField::Body,
+            PullRequestField::Body => PullRequestField::Base,
+            PullRequestField::Base => PullRequestField::Title,
+        }
+    }
+}
+
+/// The request being written in [`AppMode::PullRequest`].
+#[derive(Debug, Default)]
+pub struct PullRequestForm {
+    pub request: PullRequest,
+    pub field: PullRequestField,
+    /// Set once it was created; the form then only offers copying it.
+    pub url: Option<String>,
+}
+
+impl App {
+    /// The branch pull requests go into: `compare_ref` without its remote,
+    /// else `main`.
+    fn pull_request_base(&self, remotes: &[String]) -> String {
+        self.config
+            .compare_ref
+            .as_deref()
+            .map_or_else(|| "main".to_string(), |base| compare_label(base, remotes))
+    }
+
+    /// Whether the branch is pushed with nothing left to push and has
+    /// commits the base lacks, as far as the header's comparison tells.
+    pub(crate) fn offers_pull_request(&self) -> bool {
+        let status = &self.git_status;
+        let ahead_of_base = match (&self.config.compare_ref, &self.comparison) {
+            (Some(_), Some(comparison)) => comparison.ahead > 0,
+            // Comparing with the branch itself or its upstream.
+            (Some(_), None) => false,
+            (None, _) => status.current_branch != "main",
+        };
+        status.upstream.is_some() && status.ahead == 0 && ahead_of_base
+    }
+
+    /// `o` in the file list, once the branch is pushed.
+    pub(crate) fn open_pull_request(&mut self) {
+        let Some(upstream) = self.git_status.upstream.clone() else {
+            self.show_notification("Push the branch first (p or B)".to_string());
+            return;
+        };
+        let remotes = self.backend.remote_list().unwrap_or_default();
+        let Some(remote) = remotes
+            .iter()
+            .find(|remote| upstream.starts_with(&format!("{}/", remote.name)))
+        else {
+            self.show_notification(format!("{} is not on a remote", upstream));
+            return;
+        };
+        let remote_url = remote
+            .push_url
+            .clone()
+            .unwrap_or_else(|| remote.url.clone());
+        let host = remote_repo(&remote_url).map(|(host, _)| host);
+        let Some(forge) = host
+            .as_deref()
+            .and_then(|host| self.config.forges.forge_for(&remote.name, host))
+        else {
+            self.show_notification(format!(
+                "Cannot tell where {} is hosted; set forge.{} = github, gitlab, or gitea",
+                remote.name, remote.name
+            ));
+            return;
+        };
+        let names: Vec<String> = remotes.iter().map(|remote| remote.name.clone()).collect();
+        let head = compare_label(&upstream, &names);
+        let base = self.pull_request_base(&names);
+        // The commits the remote's base lacks, else the local one's.
+        let since = format!("{}/{}", remote.name, base);
+        let commits = self
+            .backend
+            .log_since(Some(&since))
+            .or_else(|_| self.backend.log_since(Some(&base)))
+            .unwrap_or_default();
+        let (title, body) = prefill(&self.git_status.current_branch, &commits);
+        self.pull_request = PullRequestForm {
+            request: PullRequest {
+                forge,
+                remote_url,
+                base,
+                head,
+                title,
+                body,
+            },
+            ..PullRequestForm::default()
+        };
+        self.mode = AppMode::PullRequest;
+    }
+
+    pub(crate) fn handle_pull_request_input(&mut self, key: KeyCode) {
+        let form = &mut self.pull_request;
+        if let Some(url) = &form.url {
+            match key {
+                KeyCode::Char('y') => {
+                    let url = url.clone();
+                    let what = format!("{} URL", form.request.forge.request_name());
+                    self.copy_to_clipboard(url, &what);
+                }
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => {
+                    self.mode = AppMode::FileList;
+                }
+                _ => {}
+            }
+            return;
+        }
+        let request = &mut form.request;
+        let input = match form.field {
+            PullRequestField::Title => &mut request.title,
+            PullRequestField::Body => &mut request.body,
+            PullRequestField::Base => &mut request.base,
+        };
+        match key {
+            KeyCode::Esc => self.mode = AppMode::FileList,
+            KeyCode::Tab => form.field = form.field.next(),
+            KeyCode::BackTab => form.field = form.field.next().next(),
+            KeyCode::Enter if form.field == PullRequestField::Body => input.push('\n'),
+            KeyCode::Enter => self.create_pull_request(),
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(input, input.len());
+                input.truncate(end);
+            }
+            // Branch names can't contain spaces.
+            KeyCode::Char(' ') if form.field == PullRequestField::Base => {}
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    fn create_pull_request(&mut self) {
+        let request = self.pull_request.request.clone();
+        if request.title.trim().is_empty() || request.base.trim().is_empty() {
+            self.show_notification(format!(
+                "A {} needs a title and a base",
+                request.forge.request_name()
+            ));
+            return;
+        }
+        let host = Arc::clone(&self.pull_request_host);
+        self.start_task(TaskKind::PullRequest, move |_| host.create(&request));
+    }
+
+    pub(crate) fn finish_pull_request(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(url) => self.pull_request.url = Some(url),
+            Err(error) => {
+                let context = format!(
+                    "Creating the {}",
+                    self.pull_request.request.forge.request_name()
+                );
+                self.report_error(GitError::new(context, error));
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! The interactive rebase editor: pick where to start from recent history,
+//! then reorder and mark the commits after it before handing the plan to
+//! `git rebase -i`.
+
+use crate::{
+    app::{App, AppMode, TaskKind},
+    git::{CommitInfo, RebaseAction, RebaseEntry},
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+/// How much history the base picker offers.
+pub(crate) const LOG_LIMIT: usize = 50;
+
+#[derive(Debug, Default)]
+pub struct RebaseEditor {
+    /// Recent history, newest first, for choosing the oldest commit to
+    /// rewrite.
+    pub log: Vec<CommitInfo>,
+    /// Cursor in `log` while picking, then in `todo`.
+    pub selected: usize,
+    /// Where the rebase starts; `None` rewrites from the root commit.
+    pub base: Option<String>,
+    /// The plan, oldest first like git's todo list.
+    pub todo: Vec<RebaseEntry>,
+    /// The new message while rewording the selected commit.
+    pub reword: Option<String>,
+}
+
+impl RebaseEditor {
+    fn move_selected(&mut self, down: bool) {
+        let target = if down {
+            self.selected + 1
+        } else {
+            self.selected.wrapping_sub(1)
+        };
+        if target < self.todo.len() {
+            self.todo.swap(self.selected, target);
+            self.selected = target;
+        }
+    }
+
+    /// Why the plan can't run as is, if there is a reason.
+    fn problem(&self) -> Option<&'static str> {
+        let first = self
+            .todo
+            .iter()
+            .find(|entry| entry.action != RebaseAction::Drop)?;
+        if matches!(first.action, RebaseAction::Squash | RebaseAction::Fixup) {
+            return Some("The first commit has nothing to squash into");
+        }
+        None
+    }
+}
+
+impl App {
+    pub(crate) fn open_rebase(&mut self) {
+        if self.task.is_some() || self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        let log = match self.backend.log(LOG_LIMIT) {
+            Ok(log) if !log.is_empty() => log,
+            Ok(_) => {
+                self.show_notification("No commits to rebase".to_string());
+                return;
+            }
+            Err(error) => {
+                self.report_error(GitError::new("Reading history", error));
+                return;
+            }
+        };
+
+        // Start on the oldest unpushed commit when there are any.
+        let ahead = self.git_status.ahead.max(1) as usize;
+        self.rebase = RebaseEditor {
+            selected: ahead.min(log.len()) - 1,
+            log,
+            ..RebaseEditor::default()
+        };
+        self.mode = AppMode::RebasePick;
+    }
+
+    pub(crate) fn handle_rebase_pick_input(&mut self, key: KeyCode) {
+        let rebase = &mut self.rebase;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if rebase.selected + 1 < rebase.log.len() => {
+                rebase.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                rebase.selected = rebase.selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                let oldest = &rebase.log[rebase.selected];
+                // A short log that ends at the selection reached the root.
+                let is_root =
+                    rebase.selected + 1 == rebase.log.len() && rebase.log.len() < LOG_LIMIT;
+                rebase.base = (!is_root).then(|| format!("{}^", oldest.id));
+                rebase.todo = rebase.log[..=rebase.selected]
+                    .iter()
+                    .rev()
+                    .map(|commit| RebaseEntry {
+                        action: RebaseAction::Pick,
+                        commit: commit.clone(),
+                    })
+                    .collect();
+                rebase.selected = 0;
+                self.mode = AppMode::Rebase;
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn handle_rebase_input(&mut self, key: KeyCode) {
+        let rebase = &mut self.rebase;
+        if let Some(message) = &mut rebase.reword {
+            match key {
+                KeyCode::Enter if !message.trim().is_empty() => {
+                    let message = rebase.reword.take().unwrap_or_default();
+                    rebase.todo[rebase.selected].action = RebaseAction::Reword(message);
+                }
+                KeyCode::Esc => rebase.reword = None,
+                KeyCode::Backspace => {
+                    let end = text::prev_boundary(message, message.len());
+                    message.truncate(end);
+                }
+                KeyCode::Char(c) => message.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        let set_action = |rebase: &mut RebaseEditor, action: RebaseAction| {
+            if let Some(entry) = rebase.todo.get_mut(rebase.selected) {
+                entry.action = action;
+            }
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if rebase.selected + 1 < rebase.todo.len() => {
+                rebase.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                rebase.selected = rebase.selected.saturating_sub(1);
+            }
+            KeyCode::Char('J') => rebase.move_selected(true),
+            KeyCode::Char('K') => rebase.move_selected(false),
+            KeyCode::Char('p') => set_action(rebase, RebaseAction::Pick),
+            KeyCode::Char('s') => set_action(rebase, RebaseAction::Squash),
+            KeyCode::Char('f') => set_action(rebase, RebaseAction::Fixup),
+            KeyCode::Char('d') => set_action(rebase, RebaseAction::Drop),
+            KeyCode::Char('r') => {
+                if let Some(entry) = rebase.todo.get(rebase.selected) {
+                    rebase.reword = Some(match &entry.action {
+                        RebaseAction::Reword(message) => message.clone(),
+                        _ => entry.commit.summary.clone(),
+                    });
+                }
+            }
+            KeyCode::Enter => self.start_rebase(),
+            _ => {}
+        }
+    }
+
+    fn start_rebase(&mut self) {
+        if let Some(problem) = self.rebase.problem() {
+            self.show_notification(problem.to_string());
+            return;
+        }
+        if self.task.is_some() {
+            self.show_notification("Another git operation is still running".to_string());
+            return;
+        }
+
+        let backend = Arc::clone(&self.backend);
+        let base = self.rebase.base.clone();
+        let todo = self.rebase.todo.clone();
+        self.start_task(TaskKind::Rebase, move |cancel| {
+            backend.rebase_interactive(base.as_deref(), &todo, cancel)
+        });
+        self.mode = AppMode::FileList;
+    }
+}
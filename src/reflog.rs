@@ -0,0 +1,109 @@
+//! The reflog browser: everywhere HEAD has been, for finding commits lost
+//! to a reset, a rebase, or a deleted branch, and getting them back.
+
+use crate::{
+    app::{App, AppMode},
+    confirm::PendingAction,
+    git::ReflogEntry,
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+
+/// How far back the reflog view reads.
+pub const REFLOG_LIMIT: usize = 500;
+
+#[derive(Debug, Default)]
+pub struct ReflogView {
+    /// Newest first.
+    pub entries: Vec<ReflogEntry>,
+    pub selected: usize,
+}
+
+impl App {
+    /// `L` in the file list.
+    pub(crate) fn open_reflog(&mut self) {
+        match self.backend.reflog(REFLOG_LIMIT) {
+            Ok(entries) if !entries.is_empty() => {
+                self.reflog = ReflogView {
+                    entries,
+                    selected: 0,
+                };
+                self.mode = AppMode::Reflog;
+            }
+            Ok(_) => self.show_notification("The reflog is empty".to_string()),
+            Err(error) => self.report_error(GitError::new("Reading the reflog", error)),
+        }
+    }
+
+    pub(crate) fn handle_reflog_input(&mut self, key: KeyCode) {
+        let view = &mut self.reflog;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if view.selected + 1 < view.entries.len() => {
+                view.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Home | KeyCode::Char('g') => view.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => {
+                view.selected = view.entries.len().saturating_sub(1);
+            }
+            _ => {
+                let Some(entry) = view.entries.get(view.selected).cloned() else {
+                    return;
+                };
+                match key {
+                    KeyCode::Enter => self.show_commit(entry.id),
+                    KeyCode::Char('c') => self.cherry_pick(entry.id),
+                    KeyCode::Char('o') => self.checkout_entry(&entry),
+                    KeyCode::Char('r') => self.confirm_restore(entry),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// `o`: looks around at the entry with a detached HEAD.
+    fn checkout_entry(&mut self, entry: &ReflogEntry) {
+        match self.backend.checkout_detached(&entry.id) {
+            Ok(()) => {
+                self.mode = AppMode::FileList;
+                self.show_notification(format!(
+                    "HEAD detached at {}; create a branch there to keep it",
+                    entry.short_id
+                ));
+                self.refresh_git_status();
+            }
+            Err(error) => self.report_error(GitError::new(
+                format!("Checking out {}", entry.short_id),
+                error,
+            )),
+        }
+    }
+
+    /// `r`: asks before moving the branch back to the entry.
+    fn confirm_restore(&mut self, entry: ReflogEntry) {
+        let branch = match self.git_status.current_branch.as_str() {
+            "" => "HEAD".to_string(),
+            branch => branch.to_string(),
+        };
+        self.ask(
+            "Restore branch",
+            format!(
+                "Move {} to {} ({}: {})? Commits after it stay in the reflog, and uncommitted changes are kept (git reset --keep).",
+                branch, entry.short_id, entry.selector, entry.action
+            ),
+            PendingAction::RestoreTo(entry.id),
+        );
+    }
+
+    pub(crate) fn restore_to(&mut self, id: &str) {
+        match self.backend.reset_keep(id) {
+            Ok(()) => {
+                self.mode = AppMode::FileList;
+                self.show_notification(format!("Restored to {}", &id[..id.len().min(7)]));
+                self.refresh_git_status();
+            }
+            Err(error) => self.report_error(GitError::new("Restoring", error)),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Cutting a release: the Conventional Commits since the last tag decide
+//! the next version and make up a changelog section, and nothing is
+//! written until the whole plan has been reviewed.
+
+use crate::{
+    app::{App, AppMode, TaskKind},
+    confirm::PendingAction,
+    git::{CommitInfo, CommitOptions},
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+use regex::Regex;
+use std::{fmt, fs, sync::Arc};
+
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Splits a tag like `v1.2.3` into its prefix and version.
+    pub fn parse(tag: &str) -> Option<(&str, Version)> {
+        let start = tag.find(|c: char| c.is_ascii_digit())?;
+        let mut parts = tag[start..].splitn(3, '.');
+        let version = Version {
+            major: parts.next()?.parse().ok()?,
+            minor: parts.next()?.parse().ok()?,
+            patch: parts.next()?.parse().ok()?,
+        };
+        Some((&tag[..start], version))
+    }
+
+    pub fn bump(self, bump: Bump) -> Version {
+        match bump {
+            Bump::Major => Version {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            Bump::Minor => Version {
+                minor: self.minor + 1,
+                patch: 0,
+                ..self
+            },
+            Bump::Patch => Version {
+                patch: self.patch + 1,
+                ..self
+            },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    pub fn next(self) -> Bump {
+        match self {
+            Bump::Patch => Bump::Minor,
+            Bump::Minor => Bump::Major,
+            Bump::Major => Bump::Patch,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+/// One commit, read as a Conventional Commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// `feat`, `fix`, ...; empty when the subject doesn't follow the format.
+    pub kind: String,
+    pub scope: Option<String>,
+    /// A `!` after the type, or a `BREAKING CHANGE:` footer.
+    pub breaking: bool,
+    pub description: String,
+    pub short_id: String,
+}
+
+impl Change {
+    pub fn parse(commit: &CommitInfo, body: &str) -> Change {
+        let pattern = Regex::new(r"^(\w+)(?:\(([^()]+)\))?(!)?: (.+)$").expect("valid regex");
+        let breaking_footer = body.lines().any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+        match pattern.captures(&commit.summary) {
+            Some(captures) => Change {
+                kind: captures[1].to_lowercase(),
+                scope: captures.get(2).map(|scope| scope.as_str().to_string()),
+                breaking: captures.get(3).is_some() || breaking_footer,
+                description: captures[4].to_string(),
+                short_id: commit.short_id.clone(),
+            },
+            None => Change {
+                kind: String::new(),
+                scope: None,
+                breaking: breaking_footer,
+                description: commit.summary.clone(),
+                short_id: commit.short_id.clone(),
+            },
+        }
+    }
+}
+
+/// Breaking changes bump the major version, or the minor one before 1.0.0;
+/// features bump the minor version; anything else the patch.
+pub fn suggest_bump(changes: &[Change], current: Version) -> Bump {
+    if changes.iter().any(|change| change.breaking) {
+        if current.major == 0 {
+            Bump::Minor
+        } else {
+            Bump::Major
+        }
+    } else if changes.iter().any(|change| change.kind == "feat") {
+        Bump::Minor
+    } else {
+        Bump::Patch
+    }
+}
+
+/// Changelog headings, in order.
+const HEADINGS: [&str; 5] = [
+    "Breaking Changes",
+    "Features",
+    "Bug Fixes",
+    "Performance",
+    "Other Changes",
+];
+
+/// The heading `change` is listed under.
+fn heading(change: &Change) -> &'static str {
+    match change.kind.as_str() {
+        _ if change.breaking => "Breaking Changes",
+        "feat" => "Features",
+        "fix" => "Bug Fixes",
+        "perf" => "Performance",
+        _ => "Other Changes",
+    }
+}
+
+/// The changes grouped under their headings, skipping empty groups.
+fn sections(changes: &[Change]) -> Vec<(&'static str, Vec<String>)> {
+    HEADINGS
+        .into_iter()
+        .map(|title| {
+            let entries = changes
+                .iter()
+                .filter(|change| heading(change) == title)
+                .map(|change| match &change.scope {
+                    Some(scope) => format!(
+                        "- **{}:** {} ({})",
+                        scope, change.description, change.short_id
+                    ),
+                    None => format!("- {} ({})", change.description, change.short_id),
+                })
+                .collect::<Vec<_>>();
+            (title, entries)
+        })
+        .filter(|(_, entries)| !entries.is_empty())
+        .collect()
+}
+
+/// The Markdown section for `CHANGELOG.md`.
+pub fn changelog(tag: &str, date: &str, changes: &[Change]) -> String {
+    let mut text = format!("## {} ({})\n", tag, date);
+    for (title, entries) in sections(changes) {
+        text.push_str(&format!("\n### {}\n\n{}\n", title, entries.join("\n")));
+    }
+    text
+}
+
+/// The annotated tag's message. Plain text, since git would strip the
+/// Markdown headings as comments.
+pub fn tag_message(tag: &str, changes: &[Change]) -> String {
+    let mut text = tag.to_string();
+    for (title, entries) in sections(changes) {
+        text.push_str(&format!("\n\n{}:\n{}", title, entries.join("\n")));
+    }
+    text.replace("**", "")
+}
+
+/// `existing` with `section` inserted after its title, or at the top when
+/// it has none.
+pub fn prepend_changelog(existing: &str, section: &str) -> String {
+    if existing.trim().is_empty() {
+        return format!("# Changelog\n\n{}", section);
+    }
+    // A title and introduction stay above the releases.
+    let split = if existing.starts_with("# ") {
+        existing
+            .find("\n## ")
+            .map_or(existing.len(), |index| index + 1)
+    } else {
+        0
+    };
+    let (head, tail) = existing.split_at(split);
+    let mut text = String::new();
+    if !head.is_empty() {
+        text.push_str(head.trim_end());
+        text.push_str("\n\n");
+    }
+    text.push_str(section);
+    if !tail.is_empty() {
+        text.push('\n');
+        text.push_str(tail);
+    }
+    text
+}
+
+/// The release being prepared in [`AppMode::Release`].
+#[derive(Debug, Default)]
+pub struct ReleaseDraft {
+    /// The tag the changes are counted from; `None` before the first one.
+    pub previous: Option<String>,
+    /// What comes before the version in tag names, like `v`.
+    pub prefix: String,
+    pub current: Version,
+    /// Newest first.
+    pub changes: Vec<Change>,
+    pub suggested: Option<Bump>,
+    pub bump: Option<Bump>,
+    pub date: String,
+    /// Prepend the section to `CHANGELOG.md` and commit it before tagging.
+    pub write_changelog: bool,
+    pub scroll: u16,
+}
+
+impl ReleaseDraft {
+    pub fn bump(&self) -> Bump {
+        self.bump.or(self.suggested).unwrap_or(Bump::Patch)
+    }
+
+    /// The tag this release will get.
+    pub fn tag(&self) -> String {
+        format!("{}{}", self.prefix, self.current.bump(self.bump()))
+    }
+
+    pub fn changelog(&self) -> String {
+        changelog(&self.tag(), &self.date, &self.changes)
+    }
+}
+
+impl App {
+    /// `V` in the file list: plans the next release from the commits since
+    /// the last tag.
+    pub(crate) fn open_release(&mut self) {
+        let previous = self.backend.latest_tag();
+        let commits = match self.backend.log_since(previous.as_deref()) {
+            Ok(commits) => commits,
+            Err(error) => {
+                self.report_error(GitError::new("Reading history", error));
+                return;
+            }
+        };
+        if commits.is_empty() {
+            self.show_notification(match &previous {
+                Some(tag) => format!("No commits since {}", tag),
+                None => "No commits to release".to_string(),
+            });
+            return;
+        }
+        let (prefix, current) = previous
+            .as_deref()
+            .and_then(Version::parse)
+            .map(|(prefix, version)| (prefix.to_string(), version))
+            .unwrap_or_else(|| ("v".to_string(), Version::default()));
+        let changes: Vec<Change> = commits
+            .iter()
+            .map(|(commit, body)| Change::parse(commit, body))
+            .collect();
+        let write_changelog = self
+            .backend
+            .work_dir()
+            .is_some_and(|dir| dir.join(CHANGELOG_FILE).exists());
+        self.release = ReleaseDraft {
+            previous,
+            prefix,
+            current,
+            suggested: Some(suggest_bump(&changes, current)),
+            changes,
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            write_changelog,
+            ..ReleaseDraft::default()
+        };
+        self.mode = AppMode::Release;
+    }
+
+    pub(crate) fn handle_release_input(&mut self, key: KeyCode) {
+        let draft = &mut self.release;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Char('b') => draft.bump = Some(draft.bump().next()),
+            KeyCode::Char('f') => draft.write_changelog = !draft.write_changelog,
+            KeyCode::Down | KeyCode::Char('j') => draft.scroll = draft.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => draft.scroll = draft.scroll.saturating_sub(1),
+            KeyCode::Enter => {
+                let tag = draft.tag();
+                let message = if draft.write_changelog {
+                    format!(
+                        "Prepend the changelog to {}, commit it, and tag that commit {}?",
+                        CHANGELOG_FILE, tag
+                    )
+                } else {
+                    format!("Tag HEAD {} with the changelog as its message?", tag)
+                };
+                self.ask("Create release", message, PendingAction::Release);
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes what the draft describes: the changelog and its commit if
+    /// asked for, then the tag.
+    pub(crate) fn create_release(&mut self) {
+        let draft = &self.release;
+        let tag = draft.tag();
+        let message = tag_message(&tag, &draft.changes);
+        if !draft.write_changelog {
+            self.mode = AppMode::FileList;
+            match self.backend.create_tag(&tag, Some(&message)) {
+                Ok(()) => self.show_notification(format!("Tagged {}; T to push it", tag)),
+                Err(error) => {
+                    self.report_error(GitError::new(format!("Creating tag {}", tag), error))
+                }
+            }
+            return;
+        }
+
+        if self.has_staged_files() {
+            self.show_notification(
+                "Commit or unstage the staged changes first; the release commit holds only the changelog"
+                    .to_string(),
+            );
+            return;
+        }
+        let Some(path) = self.backend.work_dir().map(|dir| dir.join(CHANGELOG_FILE)) else {
+            return;
+        };
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        if let Err(e) = fs::write(&path, prepend_changelog(&existing, &draft.changelog())) {
+            self.show_warning(format!("Could not write {}: {}", CHANGELOG_FILE, e));
+            return;
+        }
+        self.mode = AppMode::FileList;
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::Release, move |cancel| {
+            backend.stage(CHANGELOG_FILE)?;
+            let subject = format!("chore(release): {}", tag);
+            backend.commit(&subject, &CommitOptions::default(), cancel)?;
+            backend.create_tag(&tag, Some(&message))?;
+            Ok(tag)
+        });
+    }
+}
@@ -0,0 +1,210 @@
+//! The remotes panel: the configured remotes with their URLs, and adding,
+//! renaming, re-pointing, and removing them.
+
+use crate::{
+    app::{App, AppMode},
+    confirm::PendingAction,
+    git::RemoteInfo,
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Default)]
+pub struct RemotesView {
+    pub remotes: Vec<RemoteInfo>,
+    pub selected: usize,
+    /// The change being typed.
+    pub input: Option<RemoteInput>,
+}
+
+/// What the typed text is for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteEdit {
+    /// A new remote: its name, then its URL.
+    Add,
+    /// A new name for the remote with this name.
+    Rename(String),
+    /// A new URL for the remote with this name.
+    SetUrl(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteInput {
+    pub edit: RemoteEdit,
+    pub name: String,
+    pub url: String,
+    /// Whether the URL is being typed rather than the name.
+    pub on_url: bool,
+}
+
+impl RemoteInput {
+    /// The text being typed.
+    pub fn field(&self) -> &str {
+        if self.on_url { &self.url } else { &self.name }
+    }
+
+    fn field_mut(&mut self) -> &mut String {
+        if self.on_url {
+            &mut self.url
+        } else {
+            &mut self.name
+        }
+    }
+}
+
+impl App {
+    /// `E` in the file list.
+    pub(crate) fn open_remotes(&mut self) {
+        if self.reload_remotes() {
+            self.remotes.selected = 0;
+            self.remotes.input = None;
+            self.mode = AppMode::Remotes;
+        }
+    }
+
+    /// Reads the remotes again, keeping the selection in range; returns
+    /// whether that worked.
+    fn reload_remotes(&mut self) -> bool {
+        match self.backend.remote_list() {
+            Ok(remotes) => {
+                self.remotes.selected = self.remotes.selected.min(remotes.len().saturating_sub(1));
+                self.remotes.remotes = remotes;
+                true
+            }
+            Err(error) => {
+                self.report_error(GitError::new("Reading remotes", error));
+                false
+            }
+        }
+    }
+
+    pub(crate) fn handle_remotes_input(&mut self, key: KeyCode) {
+        if self.remotes.input.is_some() {
+            self.handle_remote_input(key);
+            return;
+        }
+        let view = &mut self.remotes;
+        let selected = view.remotes.get(view.selected).cloned();
+        match (key, selected) {
+            (KeyCode::Esc | KeyCode::Char('q'), _) => self.mode = AppMode::FileList,
+            (KeyCode::Down | KeyCode::Char('j'), _) if view.selected + 1 < view.remotes.len() => {
+                view.selected += 1;
+            }
+            (KeyCode::Up | KeyCode::Char('k'), _) => {
+                view.selected = view.selected.saturating_sub(1);
+            }
+            (KeyCode::Char('a'), _) => {
+                view.input = Some(RemoteInput {
+                    edit: RemoteEdit::Add,
+                    name: String::new(),
+                    url: String::new(),
+                    on_url: false,
+                });
+            }
+            (KeyCode::Char('r'), Some(remote)) => {
+                view.input = Some(RemoteInput {
+                    edit: RemoteEdit::Rename(remote.name.clone()),
+                    name: remote.name,
+                    url: remote.url,
+                    on_url: false,
+                });
+            }
+            (KeyCode::Char('e'), Some(remote)) => {
+                view.input = Some(RemoteInput {
+                    edit: RemoteEdit::SetUrl(remote.name.clone()),
+                    name: remote.name,
+                    url: remote.url,
+                    on_url: true,
+                });
+            }
+            (KeyCode::Char('d'), Some(remote)) => {
+                self.ask(
+                    "Remove remote",
+                    format!(
+                        "Remove remote {} ({})? Its remote-tracking branches go with it.",
+                        remote.name, remote.url
+                    ),
+                    PendingAction::RemoveRemote(remote.name),
+                );
+            }
+            (KeyCode::Char('p'), Some(remote)) => {
+                self.open_push_dialog();
+                let dialog = &mut self.push_dialog;
+                if let Some(index) = dialog.remotes.iter().position(|r| *r == remote.name) {
+                    dialog.selected_remote = index;
+                    dialog.options.remote = remote.name;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Typing a remote's name or URL.
+    fn handle_remote_input(&mut self, key: KeyCode) {
+        let Some(input) = &mut self.remotes.input else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.remotes.input = None,
+            KeyCode::Backspace => {
+                let field = input.field_mut();
+                let end = text::prev_boundary(field, field.len());
+                field.truncate(end);
+            }
+            // Neither names nor URLs can contain spaces.
+            KeyCode::Char(' ') => {}
+            KeyCode::Char(c) => input.field_mut().push(c),
+            KeyCode::Enter if input.field().trim().is_empty() => {}
+            KeyCode::Enter if input.edit == RemoteEdit::Add && !input.on_url => {
+                input.on_url = true;
+            }
+            KeyCode::Enter => {
+                let input = input.clone();
+                self.apply_remote_edit(input);
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_remote_edit(&mut self, input: RemoteInput) {
+        let (name, url) = (input.name.trim(), input.url.trim());
+        let (result, done) = match &input.edit {
+            RemoteEdit::Add => (
+                self.backend.add_remote(name, url),
+                format!("Added remote {}", name),
+            ),
+            RemoteEdit::Rename(old) => (
+                self.backend.rename_remote(old, name),
+                format!("Renamed remote {} to {}", old, name),
+            ),
+            RemoteEdit::SetUrl(name) => (
+                self.backend.set_remote_url(name, url),
+                format!("Remote {} now points at {}", name, url),
+            ),
+        };
+        match result {
+            Ok(()) => {
+                self.remotes.input = None;
+                self.show_notification(done);
+                self.reload_remotes();
+                if let Some(index) = self.remotes.remotes.iter().position(|r| r.name == name) {
+                    self.remotes.selected = index;
+                }
+                self.refresh_git_status();
+            }
+            Err(error) => self.report_error(GitError::new("Changing remote", error)),
+        }
+    }
+
+    pub(crate) fn remove_remote(&mut self, name: &str) {
+        match self.backend.remove_remote(name) {
+            Ok(()) => {
+                self.show_notification(format!("Removed remote {}", name));
+                self.reload_remotes();
+                self.refresh_git_status();
+            }
+            Err(error) => self.report_error(GitError::new(format!("Removing {}", name), error)),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Opening repositories: the screen shown when the tool starts outside a
+//! repository, the switcher for jumping between projects, and the list of
+//! recently opened ones kept in the state directory.
+
+use crate::{
+    app::{App, AppMode},
+    git::{CliBackend, open_backend},
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+
+const RECENT_FILE: &str = "recent_repos";
+/// Set by `--git-dir` and `--work-tree`; they would keep pointing every
+/// repository opened later at the first one.
+const GIT_ENV: [&str; 2] = ["GIT_DIR", "GIT_WORK_TREE"];
+const MAX_RECENT: usize = 20;
+
+/// `$XDG_STATE_HOME/gch`, or `~/.local/state/gch`.
+pub fn default_state_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join("gch"))
+}
+
+/// Recently opened working trees, most recent first.
+pub fn load_recent(state_dir: &Path) -> Vec<PathBuf> {
+    fs::read_to_string(state_dir.join(RECENT_FILE))
+        .map(|text| text.lines().map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// Moves `repo` to the top of the recent list.
+pub fn remember(state_dir: &Path, repo: &Path) -> io::Result<()> {
+    let mut recent = load_recent(state_dir);
+    recent.retain(|path| path != repo);
+    recent.insert(0, repo.to_path_buf());
+    recent.truncate(MAX_RECENT);
+    fs::create_dir_all(state_dir)?;
+    let text: String = recent
+        .iter()
+        .map(|path| format!("{}\n", path.display()))
+        .collect();
+    fs::write(state_dir.join(RECENT_FILE), text)
+}
+
+/// State of the screen shown outside a repository.
+#[derive(Debug, Default)]
+pub struct RepoPicker {
+    /// Why the current directory couldn't be opened.
+    pub reason: String,
+    pub recent: Vec<PathBuf>,
+    pub selected: usize,
+    /// The directory being typed after `o`.
+    pub path_input: Option<String>,
+}
+
+/// Expands a leading `~/`.
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl App {
+    /// An app for when the current directory isn't in a repository; it
+    /// starts on the screen that offers to open or create one.
+    pub fn without_repo(config: crate::config::Config, reason: String) -> App {
+        let mut app = App::new(Arc::new(CliBackend), config);
+        app.mode = AppMode::NoRepo;
+        app.repo_picker = RepoPicker {
+            reason,
+            recent: app
+                .config
+                .state_dir
+                .as_deref()
+                .map(load_recent)
+                .unwrap_or_default(),
+            ..RepoPicker::default()
+        };
+        app
+    }
+
+    /// Adds the open repository to the recent list.
+    pub(crate) fn remember_repo(&mut self) {
+        if let (Some(state_dir), Some(work_dir)) =
+            (self.config.state_dir.clone(), self.backend.work_dir())
+            && let Err(e) = remember(&state_dir, &work_dir)
+        {
+            self.show_warning(format!("Could not save recent repositories: {}", e));
+        }
+    }
+
+    /// Lists recent repositories to switch to, leaving out the open one.
+    pub(crate) fn open_repo_switcher(&mut self) {
+        if self.task.is_some() {
+            self.show_notification("Wait for the running operation to finish".to_string());
+            return;
+        }
+        let current = self.backend.work_dir();
+        let mut recent = self
+            .config
+            .state_dir
+            .as_deref()
+            .map(load_recent)
+            .unwrap_or_default();
+        recent.retain(|path| Some(path) != current.as_ref());
+        self.repo_picker = RepoPicker {
+            recent,
+            ..RepoPicker::default()
+        };
+        self.mode = AppMode::RepoSwitch;
+    }
+
+    /// Moves to `path` and opens the repository there, starting afresh with
+    /// its files, draft, and history. When switching away from an open
+    /// repository and `path` isn't one, stays where it was.
+    pub fn open_repo(&mut self, path: &Path) {
+        let previous = std::env::current_dir().ok();
+        if let Err(e) = std::env::set_current_dir(path) {
+            self.show_warning(format!("Cannot open {}: {}", path.display(), e));
+            return;
+        }
+        let git_env = GIT_ENV.map(|var| (var, std::env::var_os(var)));
+        for var in GIT_ENV {
+            // SAFETY: only this thread opens repositories from them, and the
+            // git processes of other threads are spawned through std, which
+            // holds its environment lock meanwhile.
+            unsafe { std::env::remove_var(var) };
+        }
+        match open_backend() {
+            Ok(backend) => {
+                *self = App::new(backend, self.config.clone());
+                self.refresh_git_status();
+                self.remember_repo();
+                self.start_watcher();
+                self.start_auto_fetch();
+            }
+            Err(_) if self.mode == AppMode::RepoSwitch => {
+                if let Some(previous) = previous {
+                    let _ = std::env::set_current_dir(previous);
+                }
+                // The repository still open was found through them.
+                for (var, value) in git_env {
+                    if let Some(value) = value {
+                        // SAFETY: as for removing them above.
+                        unsafe { std::env::set_var(var, value) };
+                    }
+                }
+                self.show_notification(format!("{} is not a git repository", path.display()));
+            }
+            Err(reason) => {
+                self.repo_picker.reason = reason;
+                self.show_notification(format!(
+                    "{} is not a git repository; press i to create one",
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    /// Runs `git init` in the current directory and opens the result.
+    pub(crate) fn init_repo(&mut self) {
+        let Ok(dir) = std::env::current_dir() else {
+            return;
+        };
+        match Command::new("git").arg("init").output() {
+            Ok(output) if output.status.success() => self.open_repo(&dir),
+            Ok(output) => self.report_error(GitError::new(
+                "git init",
+                String::from_utf8_lossy(&output.stderr),
+            )),
+            Err(e) => self.report_error(GitError::new("git init", e.to_string())),
+        }
+    }
+
+    pub(crate) fn handle_repo_input(&mut self, key: KeyCode) {
+        let picker = &mut self.repo_picker;
+        if let Some(input) = &mut picker.path_input {
+            match key {
+                KeyCode::Enter => {
+                    let path = expand_home(input.trim());
+                    picker.path_input = None;
+                    self.open_repo(&path);
+                }
+                KeyCode::Esc => picker.path_input = None,
+                KeyCode::Backspace => {
+                    let end = text::prev_boundary(input, input.len());
+                    input.truncate(end);
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return;
+        }
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') if self.mode == AppMode::RepoSwitch => {
+                self.mode = AppMode::FileList;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Down | KeyCode::Char('j') if picker.selected + 1 < picker.recent.len() => {
+                picker.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                picker.selected = picker.selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                if let Some(path) = picker.recent.get(picker.selected).cloned() {
+                    self.open_repo(&path);
+                }
+            }
+            KeyCode::Char('o') => picker.path_input = Some(String::new()),
+            KeyCode::Char('i') if self.mode == AppMode::NoRepo => self.init_repo(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Telling the user what happened: notifications with a severity, and the
+//! messages log that keeps them, the git commands that ran, and the full
+//! output of failed operations after the notification has gone.
+
+use crate::{
+    app::{App, AppMode},
+    git::take_commands,
+};
+use chrono::{DateTime, Local};
+use std::{fmt, time::Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub message: String,
+    pub level: Level,
+    pub shown: Instant,
+}
+
+/// A git operation that failed: what was being done, and git's full error
+/// output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitError {
+    /// E.g. `Staging src/main.rs`.
+    pub action: String,
+    pub stderr: String,
+}
+
+impl GitError {
+    pub fn new(action: impl Into<String>, stderr: impl Into<String>) -> GitError {
+        GitError {
+            action: action.into(),
+            stderr: stderr.into(),
+        }
+    }
+
+    /// One line for the notification: the action and the first thing git
+    /// said, without its `fatal:`/`error:` tag.
+    pub fn summary(&self) -> String {
+        let first = self
+            .stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("");
+        let first = ["fatal: ", "error: "]
+            .iter()
+            .fold(first, |line, tag| line.strip_prefix(tag).unwrap_or(line));
+        format!("{} failed: {}", self.action, first)
+    }
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} failed:\n{}", self.action, self.stderr.trim_end())
+    }
+}
+
+/// One line of the messages log.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    /// The notification, or `$ git ...` for a command that ran.
+    pub text: String,
+    /// Everything git printed, for failures and commands from the command
+    /// bar.
+    pub detail: Option<String>,
+}
+
+/// The messages view, drawn newest first.
+#[derive(Debug, Default)]
+pub struct MessageLog {
+    /// Oldest first.
+    pub entries: Vec<LogEntry>,
+    pub scroll: u16,
+}
+
+impl MessageLog {
+    /// Lines the view takes, details included.
+    pub fn line_count(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|entry| 1 + entry.detail.as_deref().map_or(0, |d| d.lines().count()))
+            .sum()
+    }
+}
+
+impl App {
+    pub(crate) fn show_notification(&mut self, message: String) {
+        self.notify(Level::Info, message, None);
+    }
+
+    /// Shows `message`; the log keeps `detail` under it.
+    pub(crate) fn show_notification_with_detail(&mut self, message: String, detail: String) {
+        self.notify(Level::Info, message, Some(detail));
+    }
+
+    pub(crate) fn show_warning(&mut self, message: String) {
+        self.notify(Level::Warning, message, None);
+    }
+
+    fn notify(&mut self, level: Level, message: String, detail: Option<String>) {
+        self.log(level, message.clone(), detail);
+        self.notification = Some(Notification {
+            message,
+            level,
+            shown: Instant::now(),
+        });
+    }
+
+    fn log(&mut self, level: Level, text: String, detail: Option<String>) {
+        // Commands first, so they sit before the message they led to.
+        self.collect_commands();
+        self.push_entry(LogEntry {
+            time: Local::now(),
+            level,
+            text,
+            detail,
+        });
+    }
+
+    fn push_entry(&mut self, entry: LogEntry) {
+        let log = &mut self.messages.entries;
+        log.push(entry);
+        if log.len() > self.config.message_log_size {
+            log.remove(0);
+        }
+    }
+
+    /// Moves the git commands run since the last call into the log.
+    pub(crate) fn collect_commands(&mut self) {
+        for command in take_commands() {
+            self.push_entry(LogEntry {
+                time: command.time,
+                level: if command.success {
+                    Level::Info
+                } else {
+                    Level::Error
+                },
+                text: format!("$ {}", command.command),
+                detail: None,
+            });
+        }
+    }
+
+    /// Keeps `error` in the log without interrupting, for failures already
+    /// shown in full some other way.
+    pub(crate) fn log_error(&mut self, error: GitError) {
+        self.log(Level::Error, error.summary(), Some(error.stderr));
+    }
+
+    /// Shows the summary of `error`; the log keeps the rest.
+    pub(crate) fn report_error(&mut self, error: GitError) {
+        let message = format!("{} (m for messages)", error.summary());
+        self.notify(Level::Error, message, Some(error.stderr));
+    }
+
+    pub(crate) fn show_messages(&mut self) {
+        self.collect_commands();
+        self.messages.scroll = 0;
+        self.mode = AppMode::Messages;
+    }
+
+    pub(crate) fn scroll_messages(&mut self, lines: i32) {
+        let max = self.messages.line_count().saturating_sub(1) as i32;
+        self.messages.scroll = (self.messages.scroll as i32 + lines).clamp(0, max) as u16;
+    }
+}
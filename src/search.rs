@@ -0,0 +1,79 @@
+//! Searching the diff view: every match of a query and which one is
+//! current.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffSearch {
+    pub query: String,
+    /// Line index and byte range of each match, in order.
+    pub matches: Vec<(usize, Range<usize>)>,
+    /// Index into `matches`.
+    pub current: usize,
+}
+
+impl DiffSearch {
+    /// Finds every match of the query in `text`. The search ignores ASCII
+    /// case unless the query has a capital letter.
+    pub fn update(&mut self, text: &str) {
+        self.matches.clear();
+        self.current = 0;
+        if self.query.is_empty() {
+            return;
+        }
+        let ignore_case = !self.query.chars().any(|c| c.is_uppercase());
+        let query = if ignore_case {
+            self.query.to_ascii_lowercase()
+        } else {
+            self.query.clone()
+        };
+        for (index, line) in text.lines().enumerate() {
+            // ASCII lowercasing keeps byte offsets valid for the original.
+            let line = if ignore_case {
+                line.to_ascii_lowercase()
+            } else {
+                line.to_string()
+            };
+            for (start, found) in line.match_indices(&query) {
+                self.matches.push((index, start..start + found.len()));
+            }
+        }
+    }
+
+    /// Makes the first match at or below `line` current, wrapping to the
+    /// top.
+    pub fn select_from(&mut self, line: usize) {
+        self.current = self
+            .matches
+            .iter()
+            .position(|(index, _)| *index >= line)
+            .unwrap_or(0);
+    }
+
+    /// Moves to the next or previous match, wrapping around.
+    pub fn step(&mut self, forward: bool) {
+        let len = self.matches.len();
+        if len == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % len
+        } else {
+            (self.current + len - 1) % len
+        };
+    }
+
+    /// The line the current match is on.
+    pub fn current_line(&self) -> Option<usize> {
+        self.matches.get(self.current).map(|(line, _)| *line)
+    }
+
+    /// `3/17`, or `no matches`.
+    pub fn counter(&self) -> String {
+        if self.matches.is_empty() {
+            "no matches".to_string()
+        } else {
+            format!("{}/{}", self.current + 1, self.matches.len())
+        }
+    }
+}
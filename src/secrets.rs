@@ -0,0 +1,216 @@
+//! A last look for credentials before they are committed: the lines the
+//! staged changes add are matched against known secret formats and checked
+//! for long random-looking tokens.
+
+use crate::{app::App, confirm::PendingAction, report::GitError};
+use regex::Regex;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct SecretRule {
+    /// What a match is reported as, e.g. `AWS access key`.
+    pub name: String,
+    pub pattern: Regex,
+}
+
+impl SecretRule {
+    pub fn new(name: &str, pattern: &str) -> Result<SecretRule, String> {
+        Ok(SecretRule {
+            name: name.to_string(),
+            pattern: Regex::new(pattern).map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SecretScan {
+    pub rules: Vec<SecretRule>,
+    /// Also flag tokens of 20 or more characters whose Shannon entropy, in
+    /// bits per character, reaches this. `None` turns that off.
+    pub min_entropy: Option<f64>,
+}
+
+impl Default for SecretScan {
+    fn default() -> SecretScan {
+        SecretScan {
+            rules: default_secret_rules(),
+            min_entropy: Some(4.5),
+        }
+    }
+}
+
+/// Private keys and the access keys and tokens of the common services.
+pub fn default_secret_rules() -> Vec<SecretRule> {
+    [
+        ("Private key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+        ("AWS access key", r"\b(AKIA|ASIA)[0-9A-Z]{16}\b"),
+        ("GitHub token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+        ("Slack token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+        ("Stripe key", r"\b[sr]k_live_[A-Za-z0-9]{20,}\b"),
+        ("Google API key", r"\bAIza[0-9A-Za-z_-]{35}\b"),
+    ]
+    .iter()
+    .map(|(name, pattern)| SecretRule::new(name, pattern).expect("valid regex"))
+    .collect()
+}
+
+/// Something in the staged changes that looks like a secret.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub path: String,
+    /// In the new version of the file.
+    pub line: usize,
+    pub rule: String,
+    /// The start of the match, so the warning doesn't show it whole.
+    pub excerpt: String,
+}
+
+/// The findings in the added lines of `diff`, a unified diff of one or more
+/// files.
+pub fn scan_diff(diff: &str, scan: &SecretScan) -> Vec<Finding> {
+    let hunk = Regex::new(r"^@@ -\d+(?:,\d+)? \+(\d+)").expect("valid regex");
+    let mut findings = Vec::new();
+    let mut path = String::new();
+    let mut line = 0;
+    for text in diff.lines() {
+        if let Some(header) = text.strip_prefix("diff --git ") {
+            path = header
+                .rsplit_once(" b/")
+                .map_or(header, |(_, path)| path)
+                .to_string();
+            line = 1;
+        } else if let Some(new_path) = text.strip_prefix("+++ b/") {
+            path = new_path.to_string();
+        } else if text.starts_with("+++ ") || text.starts_with("--- ") {
+        } else if let Some(captures) = hunk.captures(text) {
+            line = captures[1].parse().unwrap_or(1);
+        } else if let Some(added) = text.strip_prefix('+') {
+            findings.extend(
+                scan_line(added, scan, &path)
+                    .into_iter()
+                    .map(|(rule, excerpt)| Finding {
+                        path: path.clone(),
+                        line,
+                        rule,
+                        excerpt,
+                    }),
+            );
+            line += 1;
+        } else if !text.starts_with('-') && !text.starts_with('\\') {
+            line += 1;
+        }
+    }
+    findings
+}
+
+/// `(rule, excerpt)` for each thing on `line` that looks like a secret.
+fn scan_line(line: &str, scan: &SecretScan, path: &str) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = scan
+        .rules
+        .iter()
+        .filter_map(|rule| {
+            rule.pattern
+                .find(line)
+                .map(|m| (rule.name.clone(), m.as_str()))
+        })
+        .map(|(name, text)| (name, excerpt(text)))
+        .collect();
+    // Lock files are full of checksums.
+    if found.is_empty()
+        && let Some(min_entropy) = scan.min_entropy
+        && !is_lock_file(path)
+    {
+        let token = Regex::new(r"[A-Za-z0-9+/=_-]{20,}").expect("valid regex");
+        if let Some(m) = token
+            .find_iter(line)
+            .find(|m| entropy(m.as_str()) >= min_entropy)
+        {
+            found.push(("High-entropy string".to_string(), excerpt(m.as_str())));
+        }
+    }
+    found
+}
+
+fn excerpt(text: &str) -> String {
+    let shown: String = text.chars().take(8).collect();
+    format!("{}…", shown)
+}
+
+fn is_lock_file(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.ends_with(".lock") || name.contains("-lock.") || name.ends_with(".sum")
+}
+
+/// Shannon entropy of `text`, in bits per character.
+pub fn entropy(text: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in text.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = text.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// How many findings the warning lists before summing up the rest.
+const LISTED: usize = 8;
+
+impl App {
+    /// Scans the staged changes and asks before committing anything that
+    /// looks like a secret; returns whether it asked.
+    pub(crate) fn confirm_secrets(&mut self) -> bool {
+        if !self.scans_for_secrets() {
+            return false;
+        }
+        let diff = self.staged_diff();
+        self.ask_about_secrets(
+            diff,
+            "Possible secrets in the staged changes",
+            PendingAction::CommitWithSecrets,
+        )
+    }
+
+    pub(crate) fn scans_for_secrets(&self) -> bool {
+        let scan = &self.config.secret_scan;
+        !scan.rules.is_empty() || scan.min_entropy.is_some()
+    }
+
+    /// Asks `title` before going on with `then` if `diff` holds anything
+    /// that looks like a secret; returns whether it asked.
+    pub(crate) fn ask_about_secrets(
+        &mut self,
+        diff: Result<String, String>,
+        title: &str,
+        then: PendingAction,
+    ) -> bool {
+        let diff = match diff {
+            Ok(diff) => diff,
+            Err(error) => {
+                self.log_error(GitError::new("Scanning for secrets", error));
+                return false;
+            }
+        };
+        let findings = scan_diff(&diff, &self.config.secret_scan);
+        if findings.is_empty() {
+            return false;
+        }
+        let mut message = String::new();
+        for finding in findings.iter().take(LISTED) {
+            message.push_str(&format!(
+                "{}:{}  {}  {}\n",
+                finding.path, finding.line, finding.rule, finding.excerpt
+            ));
+        }
+        if findings.len() > LISTED {
+            message.push_str(&format!("…and {} more\n", findings.len() - LISTED));
+        }
+        message.push_str("\nOnce pushed, a secret must be revoked. Commit anyway?");
+        self.ask(title, message, then);
+        true
+    }
+}
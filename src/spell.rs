@@ -0,0 +1,349 @@
+//! Spell checking in the commit editor: words missing from the dictionary
+//! are underlined, and `Ctrl+S` offers corrections for the one at the
+//! cursor. Dictionaries are hunspell `.dic` files or plain word lists, with
+//! a personal list of added words in the state directory.
+
+use crate::{
+    app::{App, AppMode},
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+use std::{
+    collections::HashSet,
+    fs,
+    io::Write,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Words added with `a` in the corrections popup, one per line.
+const PERSONAL_FILE: &str = "words";
+
+/// Where dictionaries are usually installed; the ones present are used.
+pub fn default_dictionaries() -> Vec<PathBuf> {
+    [
+        "/usr/share/hunspell/en_US.dic",
+        "/usr/share/myspell/en_US.dic",
+        "/usr/share/dict/words",
+    ]
+    .into_iter()
+    .map(PathBuf::from)
+    .filter(|path| path.is_file())
+    .collect()
+}
+
+/// Known words, lowercased.
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    pub fn from_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Dictionary {
+        let mut dictionary = Dictionary::default();
+        dictionary.extend(words);
+        dictionary
+    }
+
+    /// Reads each of `paths`; a hunspell `.dic` has a count on its first
+    /// line and affix flags after a `/`, which are dropped.
+    pub fn load(paths: &[PathBuf]) -> Result<Dictionary, String> {
+        let mut dictionary = Dictionary::default();
+        for path in paths {
+            let text =
+                fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let words = text.lines().map(|line| {
+                let word = line.split(['/', '\t']).next().unwrap_or_default();
+                word.trim()
+            });
+            dictionary.extend(words.filter(|word| !word.chars().all(|c| c.is_ascii_digit())));
+        }
+        Ok(dictionary)
+    }
+
+    fn extend<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) {
+        self.words.extend(
+            words
+                .into_iter()
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase),
+        );
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Whether `word` or a stem of it is known, so `walked` passes when a
+    /// hunspell dictionary lists only `walk`.
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        let word = word.strip_suffix("'s").unwrap_or(&word);
+        self.words.contains(word) || stems(word).iter().any(|stem| self.words.contains(stem))
+    }
+
+    /// Up to `limit` known words within two edits of `word`, closest first,
+    /// capitalised like it.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let length = lower.chars().count();
+        let mut scored: Vec<(usize, bool, &String)> = self
+            .words
+            .iter()
+            .filter(|candidate| candidate.chars().count().abs_diff(length) <= 2)
+            .filter_map(|candidate| {
+                let distance = edit_distance(&lower, candidate);
+                let first_differs = candidate.chars().next() != lower.chars().next();
+                (distance <= 2).then_some((distance, first_differs, candidate))
+            })
+            .collect();
+        scored.sort();
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, candidate)| match word.chars().next() {
+                Some(first) if first.is_uppercase() => {
+                    let mut chars = candidate.chars();
+                    let first = chars.next().map(|c| c.to_uppercase().to_string());
+                    first.unwrap_or_default() + chars.as_str()
+                }
+                _ => candidate.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Candidate stems of an inflected word: `tries` → `try`, `making` →
+/// `make`, `stopped` → `stop`.
+fn stems(word: &str) -> Vec<String> {
+    let mut stems = Vec::new();
+    for (suffix, replacement) in [
+        ("ies", "y"),
+        ("ied", "y"),
+        ("es", ""),
+        ("s", ""),
+        ("ed", ""),
+        ("ed", "e"),
+        ("ing", ""),
+        ("ing", "e"),
+        ("er", ""),
+        ("ers", ""),
+        ("ly", ""),
+    ] {
+        if let Some(stem) = word.strip_suffix(suffix)
+            && stem.len() >= 2
+        {
+            stems.push(format!("{}{}", stem, replacement));
+            // `stopped` → `stopp` → `stop`
+            let mut chars = stem.chars().rev();
+            if replacement.is_empty() && chars.next() == chars.next() {
+                stems.push(stem[..stem.len() - 1].to_string());
+            }
+        }
+    }
+    stems
+}
+
+/// Optimal string alignment distance: insertions, deletions,
+/// substitutions, and swaps of neighbours each count one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// The byte ranges of the words in `message` that `dictionary` doesn't
+/// know. Code is left alone: anything in backticks, a `type(scope):`
+/// header, and words with digits, `_`, dots, slashes, or inner capitals.
+/// So are words under three letters.
+pub fn misspelled(message: &str, dictionary: &Dictionary) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut in_code = false;
+    let header = match message.split_once(": ") {
+        Some((header, _)) if !header.contains(char::is_whitespace) => header.len() + 1,
+        _ => 0,
+    };
+    let mut start = None;
+    for (i, c) in message.char_indices().chain([(message.len(), ' ')]) {
+        if !c.is_whitespace() {
+            start.get_or_insert(i);
+            continue;
+        }
+        let Some(token_start) = start.take() else {
+            continue;
+        };
+        let token = &message[token_start..i];
+        let code = in_code || token.contains('`');
+        in_code ^= token.matches('`').count() % 2 == 1;
+        if code || token_start < header {
+            continue;
+        }
+        let trimmed = token.trim_start_matches(|c: char| !c.is_alphanumeric());
+        let offset = token_start + token.len() - trimmed.len();
+        let word = trimmed.trim_end_matches(|c: char| !c.is_alphanumeric());
+        let mut part_start = offset;
+        for part in word.split('-') {
+            let range = part_start..part_start + part.len();
+            part_start += part.len() + 1;
+            if is_checked(part) && !dictionary.contains(part) {
+                ranges.push(range);
+            }
+        }
+    }
+    ranges
+}
+
+/// Plain words only: letters with inner apostrophes, at least three of
+/// them, capitalised at most on the first.
+fn is_checked(word: &str) -> bool {
+    word.chars().count() >= 3
+        && word.chars().all(|c| c.is_alphabetic() || c == '\'')
+        && !word.chars().skip(1).any(char::is_uppercase)
+}
+
+/// The corrections popup of [`AppMode::Spelling`].
+#[derive(Debug, Default)]
+pub struct Spelling {
+    /// `None` turns spell checking off, when no dictionary was found.
+    pub dictionary: Option<Dictionary>,
+    /// The word being corrected, as a byte range of the message.
+    pub word: Range<usize>,
+    pub suggestions: Vec<String>,
+    pub selected: usize,
+}
+
+impl Spelling {
+    /// The configured dictionaries and the personal word list.
+    pub fn load(dictionaries: &[PathBuf], state_dir: Option<&Path>) -> Result<Spelling, String> {
+        if dictionaries.is_empty() {
+            return Ok(Spelling::default());
+        }
+        let mut dictionary = Dictionary::load(dictionaries)?;
+        if let Some(dir) = state_dir
+            && let Ok(text) = fs::read_to_string(dir.join(PERSONAL_FILE))
+        {
+            dictionary.extend(text.lines().map(str::trim));
+        }
+        Ok(Spelling {
+            dictionary: Some(dictionary),
+            ..Spelling::default()
+        })
+    }
+
+    pub fn misspelled(&self, message: &str) -> Vec<Range<usize>> {
+        match &self.dictionary {
+            Some(dictionary) => misspelled(message, dictionary),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl App {
+    /// `Ctrl+S` in the commit editor: corrections for the misspelled word at
+    /// the cursor, else the next one.
+    pub(crate) fn open_spelling(&mut self) {
+        let Some(dictionary) = &self.spelling.dictionary else {
+            self.show_notification(
+                "No dictionary found; set one in the config, e.g. dictionary = /usr/share/dict/words"
+                    .to_string(),
+            );
+            return;
+        };
+        let words = misspelled(&self.commit_message, dictionary);
+        let cursor = self.cursor_position;
+        let word = words
+            .iter()
+            .find(|word| word.end >= cursor)
+            .or_else(|| words.first());
+        let Some(word) = word.cloned() else {
+            self.show_notification("No misspelled words".to_string());
+            return;
+        };
+        self.spelling.suggestions = dictionary.suggest(&self.commit_message[word.clone()], 8);
+        self.spelling.word = word;
+        self.spelling.selected = 0;
+        self.mode = AppMode::Spelling;
+    }
+
+    pub(crate) fn handle_spelling_input(&mut self, key: KeyCode) {
+        let spelling = &mut self.spelling;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::CommitMessage,
+            KeyCode::Down | KeyCode::Char('j')
+                if spelling.selected + 1 < spelling.suggestions.len() =>
+            {
+                spelling.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                spelling.selected = spelling.selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                let Some(replacement) = spelling.suggestions.get(spelling.selected).cloned() else {
+                    return;
+                };
+                let word = spelling.word.clone();
+                self.commit_message
+                    .replace_range(word.clone(), &replacement);
+                if self.cursor_position >= word.end {
+                    self.cursor_position = self.cursor_position - word.len() + replacement.len();
+                } else if self.cursor_position > word.start {
+                    self.cursor_position = word.start + replacement.len();
+                }
+                self.mode = AppMode::CommitMessage;
+                self.save_draft();
+            }
+            KeyCode::Char('i') => {
+                let word = self.commit_message[spelling.word.clone()].to_string();
+                if let Some(dictionary) = &mut spelling.dictionary {
+                    dictionary.extend([word.as_str()]);
+                }
+                self.mode = AppMode::CommitMessage;
+            }
+            KeyCode::Char('a') => {
+                let word = self.commit_message[spelling.word.clone()].to_string();
+                if let Some(dictionary) = &mut spelling.dictionary {
+                    dictionary.extend([word.as_str()]);
+                }
+                self.mode = AppMode::CommitMessage;
+                match self.add_personal_word(&word) {
+                    Ok(()) => self.show_notification(format!("Added '{}' to your words", word)),
+                    Err(error) => self.report_error(GitError::new("Saving the word", error)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_personal_word(&self, word: &str) -> Result<(), String> {
+        let dir = self
+            .config
+            .state_dir
+            .as_deref()
+            .ok_or("no state directory")?;
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(PERSONAL_FILE))
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", word).map_err(|e| e.to_string())
+    }
+}
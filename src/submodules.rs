@@ -0,0 +1,151 @@
+//! Submodules, listed in their own section under the file list: updating
+//! them, staging a new recorded commit, and opening one in a nested gch.
+
+use crate::{
+    app::{App, AppMode, External, TaskKind},
+    git::{SubmoduleInfo, SubmoduleState},
+    report::GitError,
+};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct SubmodulesView {
+    pub submodules: Vec<SubmoduleInfo>,
+    /// Selected while the section has the keyboard, after `S`.
+    pub selected: usize,
+}
+
+impl SubmodulesView {
+    pub fn contains(&self, path: &str) -> bool {
+        self.submodules
+            .iter()
+            .any(|submodule| submodule.path == path)
+    }
+}
+
+/// What has changed in `submodule`, e.g. `↑3 ↓1, modified`; empty when
+/// nothing has.
+pub fn describe(submodule: &SubmoduleInfo) -> String {
+    let mut parts = Vec::new();
+    match submodule.state {
+        SubmoduleState::Uninitialized => parts.push("not initialized".to_string()),
+        SubmoduleState::Conflicted => parts.push("conflict".to_string()),
+        SubmoduleState::Moved if submodule.ahead + submodule.behind == 0 => {
+            parts.push("moved".to_string());
+        }
+        SubmoduleState::Moved => {
+            let mut counts = Vec::new();
+            if submodule.ahead > 0 {
+                counts.push(format!("↑{}", submodule.ahead));
+            }
+            if submodule.behind > 0 {
+                counts.push(format!("↓{}", submodule.behind));
+            }
+            parts.push(counts.join(" "));
+        }
+        SubmoduleState::Current => {}
+    }
+    if submodule.dirty {
+        parts.push("modified".to_string());
+    }
+    parts.join(", ")
+}
+
+impl App {
+    /// Reads the submodules again, for repositories that have any.
+    pub(crate) fn reload_submodules(&mut self) {
+        let has_gitmodules = self
+            .backend
+            .work_dir()
+            .is_some_and(|dir| dir.join(".gitmodules").exists());
+        let submodules = if has_gitmodules {
+            self.backend.submodules().unwrap_or_else(|error| {
+                self.log_error(GitError::new("Reading submodules", error));
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        let view = &mut self.submodules;
+        view.selected = view.selected.min(submodules.len().saturating_sub(1));
+        view.submodules = submodules;
+    }
+
+    /// `S` in the file list.
+    pub(crate) fn focus_submodules(&mut self) {
+        if self.submodules.submodules.is_empty() {
+            self.show_notification("No submodules".to_string());
+        } else {
+            self.mode = AppMode::Submodules;
+        }
+    }
+
+    pub(crate) fn handle_submodules_input(&mut self, key: KeyCode) {
+        let view = &mut self.submodules;
+        let selected = view.submodules.get(view.selected).cloned();
+        match (key, selected) {
+            (KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S'), _) => {
+                self.mode = AppMode::FileList;
+            }
+            (KeyCode::Down | KeyCode::Char('j'), _)
+                if view.selected + 1 < view.submodules.len() =>
+            {
+                view.selected += 1;
+            }
+            (KeyCode::Up | KeyCode::Char('k'), _) => {
+                view.selected = view.selected.saturating_sub(1);
+            }
+            (KeyCode::Char('u'), _) => self.update_submodules(),
+            (KeyCode::Char(' '), Some(submodule)) => self.toggle_submodule_stage(&submodule),
+            (KeyCode::Enter | KeyCode::Char('o'), Some(submodule)) => {
+                self.open_submodule(&submodule);
+            }
+            _ => {}
+        }
+    }
+
+    fn update_submodules(&mut self) {
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::UpdateSubmodules, move |cancel| {
+            backend.update_submodules(cancel)
+        });
+    }
+
+    /// Stages the commit the submodule is at as its new recorded commit, or
+    /// unstages one already staged.
+    fn toggle_submodule_stage(&mut self, submodule: &SubmoduleInfo) {
+        let staged = self
+            .all_files
+            .iter()
+            .any(|f| f.path == submodule.path && f.staged);
+        let result = if staged {
+            self.backend.unstage(&submodule.path)
+        } else if submodule.state == SubmoduleState::Moved {
+            self.backend.stage(&submodule.path)
+        } else {
+            self.show_notification(format!("{} is at its recorded commit", submodule.path));
+            return;
+        };
+        match result {
+            Ok(()) => self.refresh_git_status(),
+            Err(error) => {
+                let action = if staged { "Unstaging" } else { "Staging" };
+                self.report_error(GitError::new(
+                    format!("{} {}", action, submodule.path),
+                    error,
+                ));
+            }
+        }
+    }
+
+    fn open_submodule(&mut self, submodule: &SubmoduleInfo) {
+        if submodule.state == SubmoduleState::Uninitialized {
+            self.show_notification("Not initialized yet; u to update".to_string());
+            return;
+        }
+        if let Some(dir) = self.backend.work_dir() {
+            self.pending_external = Some(External::Submodule(dir.join(&submodule.path)));
+        }
+    }
+}
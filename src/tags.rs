@@ -0,0 +1,186 @@
+//! The tags panel: listing tags, tagging HEAD with a lightweight or
+//! annotated tag, deleting tags, and pushing them.
+
+use crate::{
+    app::{App, AppMode, External, TaskKind},
+    confirm::PendingAction,
+    git::{TagInfo, default_remote},
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct TagsView {
+    /// Newest first.
+    pub tags: Vec<TagInfo>,
+    pub selected: usize,
+    /// The tag being written after `n` or `a`.
+    pub input: Option<TagInput>,
+}
+
+/// A new tag on HEAD, as it is typed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TagInput {
+    pub name: String,
+    pub annotated: bool,
+    /// The annotation, once the name is entered; `Ctrl+E` edits it in the
+    /// editor.
+    pub message: Option<String>,
+}
+
+impl App {
+    /// `T` in the file list.
+    pub(crate) fn open_tags(&mut self) {
+        if self.reload_tags() {
+            self.tags.selected = 0;
+            self.tags.input = None;
+            self.mode = AppMode::Tags;
+        }
+    }
+
+    /// Reads the tags again, keeping the selection in range; returns whether
+    /// that worked.
+    fn reload_tags(&mut self) -> bool {
+        match self.backend.tags() {
+            Ok(tags) => {
+                self.tags.selected = self.tags.selected.min(tags.len().saturating_sub(1));
+                self.tags.tags = tags;
+                true
+            }
+            Err(error) => {
+                self.report_error(GitError::new("Reading tags", error));
+                false
+            }
+        }
+    }
+
+    pub(crate) fn handle_tags_input(&mut self, key: KeyCode) {
+        if self.tags.input.is_some() {
+            self.handle_tag_input(key);
+            return;
+        }
+        let view = &mut self.tags;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::FileList,
+            KeyCode::Down | KeyCode::Char('j') if view.selected + 1 < view.tags.len() => {
+                view.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Home | KeyCode::Char('g') => view.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => {
+                view.selected = view.tags.len().saturating_sub(1);
+            }
+            KeyCode::Char(key @ ('n' | 'a')) => {
+                view.input = Some(TagInput {
+                    annotated: key == 'a',
+                    ..TagInput::default()
+                });
+            }
+            KeyCode::Char('d') => {
+                if let Some(tag) = view.tags.get(view.selected) {
+                    let name = tag.name.clone();
+                    self.ask(
+                        "Delete tag",
+                        format!(
+                            "Delete tag {}? It stays on any remote it was pushed to.",
+                            name
+                        ),
+                        PendingAction::DeleteTag(name),
+                    );
+                }
+            }
+            KeyCode::Char('p') => {
+                if let Some(tag) = view.tags.get(view.selected) {
+                    let name = tag.name.clone();
+                    self.push_tags(Some(name));
+                }
+            }
+            KeyCode::Char('P') => self.push_tags(None),
+            _ => {}
+        }
+    }
+
+    /// Typing the new tag's name, then for an annotated tag its message.
+    fn handle_tag_input(&mut self, key: KeyCode) {
+        let Some(input) = &mut self.tags.input else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.tags.input = None,
+            KeyCode::Enter if input.name.trim().is_empty() => {}
+            KeyCode::Enter if input.annotated && input.message.is_none() => {
+                input.message = Some(String::new());
+            }
+            KeyCode::Enter => {
+                let input = input.clone();
+                self.create_tag(&input);
+            }
+            // Tag names can't contain spaces.
+            KeyCode::Char(' ') if input.message.is_none() => {}
+            KeyCode::Char(c) => input.message.as_mut().unwrap_or(&mut input.name).push(c),
+            KeyCode::Backspace => {
+                let field = input.message.as_mut().unwrap_or(&mut input.name);
+                let end = text::prev_boundary(field, field.len());
+                field.truncate(end);
+            }
+            _ => {}
+        }
+    }
+
+    /// `Ctrl+E` while writing a tag message.
+    pub(crate) fn edit_tag_message(&mut self) {
+        if let Some(TagInput {
+            message: Some(_), ..
+        }) = &self.tags.input
+        {
+            self.pending_external = Some(External::TagEditor);
+        }
+    }
+
+    fn create_tag(&mut self, input: &TagInput) {
+        let name = input.name.trim();
+        let message = input.message.as_deref().map(str::trim);
+        if message == Some("") {
+            self.show_notification("An annotated tag needs a message".to_string());
+            return;
+        }
+        match self.backend.create_tag(name, message) {
+            Ok(()) => {
+                self.tags.input = None;
+                self.show_notification(format!("Tagged HEAD as {}", name));
+                self.reload_tags();
+                if let Some(index) = self.tags.tags.iter().position(|tag| tag.name == name) {
+                    self.tags.selected = index;
+                }
+            }
+            Err(error) => self.report_error(GitError::new(format!("Creating tag {}", name), error)),
+        }
+    }
+
+    pub(crate) fn delete_tag(&mut self, name: &str) {
+        match self.backend.delete_tag(name) {
+            Ok(()) => {
+                self.show_notification(format!("Deleted tag {}", name));
+                self.reload_tags();
+            }
+            Err(error) => self.report_error(GitError::new(format!("Deleting tag {}", name), error)),
+        }
+    }
+
+    /// Pushes tag `name`, or all of them, to the remote the branch tracks,
+    /// else `origin`, else the first.
+    fn push_tags(&mut self, name: Option<String>) {
+        let remotes = self.backend.remotes();
+        if remotes.is_empty() {
+            self.show_notification("No remote to push tags to".to_string());
+            return;
+        }
+        let remote = remotes[default_remote(&remotes, self.git_status.upstream.as_deref())].clone();
+        let backend = Arc::clone(&self.backend);
+        self.start_task(TaskKind::PushTags, move |cancel| {
+            backend.push_tags(&remote, name.as_deref(), cancel)
+        });
+    }
+}
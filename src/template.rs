@@ -0,0 +1,72 @@
+//! Where a new commit message starts: the message git prepared for a merge
+//! or squash being concluded, else the `commit.template` file, then passed
+//! through the `prepare-commit-msg` hook as `git commit` would.
+
+use crate::{app::App, dry_run::installed_hooks, editor::strip_comments, report::GitError};
+use std::{fs, process::Command};
+
+impl App {
+    /// The starting message and where it came from, in the terms
+    /// `prepare-commit-msg` is told: `merge`, `squash`, or `template`.
+    fn prepared_message(&self) -> Option<(String, &'static str)> {
+        if let Some(dir) = self.backend.git_dir() {
+            for (file, source) in [("MERGE_MSG", "merge"), ("SQUASH_MSG", "squash")] {
+                if let Ok(text) = fs::read_to_string(dir.join(file)) {
+                    return Some((text, source));
+                }
+            }
+        }
+        let template = fs::read_to_string(self.backend.commit_template()?).ok()?;
+        Some((template, "template"))
+    }
+
+    /// Runs `prepare-commit-msg` on `text` if it is installed; the hook's
+    /// version of it, with comments dropped.
+    fn run_prepare_hook(&mut self, text: String, source: Option<&str>) -> String {
+        let (Some(hooks), Some(git_dir)) = (self.backend.hooks_dir(), self.backend.git_dir())
+        else {
+            return strip_comments(&text);
+        };
+        if !installed_hooks(&hooks).contains(&"prepare-commit-msg") {
+            return strip_comments(&text);
+        }
+        let file = git_dir.join("COMMIT_EDITMSG");
+        let result = fs::write(&file, &text)
+            .map_err(|e| e.to_string())
+            .and_then(|()| {
+                let mut hook = Command::new(hooks.join("prepare-commit-msg"));
+                hook.arg(&file).args(source);
+                if let Some(root) = self.backend.work_dir() {
+                    hook.current_dir(root);
+                }
+                let output = hook.output().map_err(|e| e.to_string())?;
+                if !output.status.success() {
+                    return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+                }
+                fs::read_to_string(&file).map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(prepared) => strip_comments(&prepared),
+            Err(error) => {
+                self.report_error(GitError::new("Running prepare-commit-msg", error));
+                strip_comments(&text)
+            }
+        }
+    }
+
+    /// Fills the empty commit editor the way `git commit` would fill its
+    /// file; `false` if nothing did.
+    pub(crate) fn prefill_commit_message(&mut self) -> bool {
+        let (text, source) = match self.prepared_message() {
+            Some((text, source)) => (text, Some(source)),
+            None => (String::new(), None),
+        };
+        let message = self.run_prepare_hook(text, source);
+        if message.is_empty() {
+            return false;
+        }
+        self.cursor_position = message.find('\n').unwrap_or(message.len());
+        self.commit_message = message;
+        true
+    }
+}
@@ -0,0 +1,114 @@
+//! Switching the terminal between the TUI and normal line mode, so external
+//! programs like `$EDITOR` can take over the screen, and putting it back
+//! however the app exits.
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use std::{
+    io::{self, Write},
+    panic,
+    path::Path,
+    process::Command,
+    thread,
+};
+
+/// Puts the terminal into raw mode on the alternate screen.
+pub fn enter() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )
+}
+
+/// Restores the terminal to the state it was in before [`enter`].
+pub fn leave() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        Show
+    )
+}
+
+/// Stops the process the way Ctrl+Z does in a shell, returning once it is
+/// continued with `fg`. Raw mode swallows the key, so the app asks for this
+/// itself, after [`leave`].
+#[cfg(unix)]
+pub fn suspend() -> io::Result<()> {
+    // SAFETY: raise has no preconditions; the signal goes to this process.
+    if unsafe { libc::raise(libc::SIGTSTP) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+pub fn suspend() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "suspending needs a Unix shell",
+    ))
+}
+
+/// `$SHELL`, or `sh`.
+pub fn user_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "sh".to_string())
+}
+
+/// Runs an interactive shell in `dir` until it exits. Call after [`leave`].
+pub fn run_shell(dir: &Path) -> io::Result<()> {
+    let shell = user_shell();
+    let mut stdout = io::stdout();
+    writeln!(
+        stdout,
+        "{} in {}; exit to return to gch",
+        shell,
+        dir.display()
+    )?;
+    stdout.flush()?;
+    Command::new(&shell).current_dir(dir).status().map(|_| ())
+}
+
+/// Holds the terminal in TUI mode and leaves it when dropped, so returning
+/// early with an error doesn't strand the shell in raw mode.
+pub struct Guard(());
+
+impl Guard {
+    pub fn enter() -> io::Result<Guard> {
+        enter()?;
+        Ok(Guard(()))
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let _ = leave();
+    }
+}
+
+/// Restores the terminal before a panic on the main thread is reported, so
+/// the message lands on the normal screen and the shell still works.
+/// Panics on worker threads leave the UI running; the task reports them as
+/// failed.
+pub fn install_panic_hook() {
+    let report = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            let _ = leave();
+        }
+        report(info);
+    }));
+}
@@ -0,0 +1,133 @@
+//! Cursor movement, soft wrapping, and measurement for the text inputs.
+//! Positions are byte offsets that always sit on a grapheme cluster
+//! boundary, so combining marks and emoji sequences move and delete as one
+//! character.
+
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// The boundary before `cursor`, or 0.
+pub fn prev_boundary(text: &str, cursor: usize) -> usize {
+    text[..cursor]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index)
+}
+
+/// The boundary after `cursor`, or the end of `text`.
+pub fn next_boundary(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .graphemes(true)
+        .next()
+        .map_or(text.len(), |grapheme| cursor + grapheme.len())
+}
+
+/// Whether a grapheme belongs to a word, as readline counts them: letters
+/// and digits.
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// Start of the word before `cursor`, skipping punctuation and spaces first
+/// (readline's `backward-word`).
+pub fn prev_word(text: &str, cursor: usize) -> usize {
+    let mut graphemes = text[..cursor].grapheme_indices(true).rev().peekable();
+    while graphemes.next_if(|(_, g)| !is_word(g)).is_some() {}
+    let mut start = 0;
+    while let Some((index, _)) = graphemes.next_if(|(_, g)| is_word(g)) {
+        start = index;
+    }
+    start
+}
+
+/// End of the word after `cursor` (readline's `forward-word`).
+pub fn next_word(text: &str, cursor: usize) -> usize {
+    let mut graphemes = text[cursor..].grapheme_indices(true).peekable();
+    while graphemes.next_if(|(_, g)| !is_word(g)).is_some() {}
+    while graphemes.next_if(|(_, g)| is_word(g)).is_some() {}
+    graphemes
+        .peek()
+        .map_or(text.len(), |(index, _)| cursor + index)
+}
+
+/// Start of the whitespace-separated word before `cursor`, what `Ctrl+W`
+/// deletes back to (readline's `unix-word-rubout`).
+pub fn prev_whitespace_word(text: &str, cursor: usize) -> usize {
+    let before = text[..cursor].trim_end_matches(char::is_whitespace);
+    before.rfind(char::is_whitespace).map_or(0, |index| {
+        index + before[index..].chars().next().map_or(1, char::len_utf8)
+    })
+}
+
+/// Start of the line containing `cursor`.
+pub fn line_start(text: &str, cursor: usize) -> usize {
+    text[..cursor].rfind('\n').map_or(0, |index| index + 1)
+}
+
+/// End of the line containing `cursor`, before its newline.
+pub fn line_end(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .find('\n')
+        .map_or(text.len(), |index| cursor + index)
+}
+
+/// The rows `text` takes up when soft-wrapped to `width` columns, as byte
+/// ranges without their newlines. Lines break after the last space that
+/// fits, or mid-word when a word is wider than the row; spaces at a break
+/// hang off the end of the row instead of starting the next.
+pub fn wrap(text: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let mut start = line_start;
+        let mut used = 0;
+        let mut after_space = None;
+        for (offset, grapheme) in line.grapheme_indices(true) {
+            let index = line_start + offset;
+            let cells = grapheme.width();
+            if grapheme.chars().all(char::is_whitespace) {
+                used += cells;
+                after_space = Some((index + grapheme.len(), used));
+                continue;
+            }
+            if used + cells > width && index > start {
+                match after_space.filter(|(at, _)| *at > start) {
+                    Some((at, before)) => {
+                        rows.push(start..at);
+                        start = at;
+                        used -= before;
+                    }
+                    None => {
+                        rows.push(start..index);
+                        start = index;
+                        used = 0;
+                    }
+                }
+            }
+            used += cells;
+        }
+        rows.push(start..line_start + line.len());
+        line_start += line.len() + 1;
+    }
+    rows
+}
+
+/// Where `cursor` is drawn in `text` soft-wrapped to `width`: the column,
+/// kept inside the row, and the row. A cursor where one row ends and the
+/// next starts is drawn at the start of the next.
+pub fn wrapped_cell(text: &str, cursor: usize, width: usize) -> (u16, usize) {
+    let rows = wrap(text, width);
+    let row = rows
+        .iter()
+        .rposition(|row| row.start <= cursor)
+        .unwrap_or(0);
+    let column = text[rows[row].start..cursor].width();
+    (column.min(width.saturating_sub(1)) as u16, row)
+}
+
+/// Number of user-perceived characters in `text`.
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
@@ -0,0 +1,153 @@
+//! Colours for everything the renderer draws, with built-in presets for
+//! dark, light, and high-contrast terminals.
+
+use ratatui::style::Color;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub header: Color,
+    pub status_fg: Color,
+    pub status_bg: Color,
+    /// Ordinary text, e.g. info messages.
+    pub text: Color,
+    /// Secondary text: counts, authors, timestamps.
+    pub muted: Color,
+    /// The focused pane's border, the chosen prefix, commit IDs.
+    pub highlight: Color,
+    pub staged: Color,
+    pub unstaged: Color,
+    /// A directory with only some files staged.
+    pub partial: Color,
+    pub mark: Color,
+    pub directory: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub info: Color,
+    /// Text on the coloured operation banner.
+    pub banner_fg: Color,
+    pub diff_add: Color,
+    pub diff_remove: Color,
+    /// `@@` hunk headers.
+    pub diff_hunk: Color,
+    /// `diff --git`, `index`, `---` and `+++` lines.
+    pub diff_meta: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            header: Color::Yellow,
+            status_fg: Color::White,
+            status_bg: Color::Blue,
+            text: Color::White,
+            muted: Color::DarkGray,
+            highlight: Color::Yellow,
+            staged: Color::Green,
+            unstaged: Color::Red,
+            partial: Color::Yellow,
+            mark: Color::Magenta,
+            directory: Color::Blue,
+            success: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            info: Color::Cyan,
+            banner_fg: Color::Black,
+            diff_add: Color::Green,
+            diff_remove: Color::Red,
+            diff_hunk: Color::Cyan,
+            diff_meta: Color::Yellow,
+        }
+    }
+
+    /// Darker accents that stay readable on a white background.
+    pub fn light() -> Theme {
+        let amber = Color::Indexed(130);
+        Theme {
+            header: Color::Blue,
+            text: Color::Reset,
+            muted: Color::Indexed(244),
+            highlight: Color::Magenta,
+            partial: amber,
+            warning: amber,
+            info: Color::Blue,
+            banner_fg: Color::White,
+            diff_hunk: Color::Blue,
+            diff_meta: Color::Magenta,
+            ..Theme::dark()
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Theme {
+            header: Color::LightYellow,
+            status_fg: Color::Black,
+            status_bg: Color::White,
+            text: Color::White,
+            muted: Color::Gray,
+            highlight: Color::LightYellow,
+            staged: Color::LightGreen,
+            unstaged: Color::LightRed,
+            partial: Color::LightYellow,
+            mark: Color::LightMagenta,
+            directory: Color::LightBlue,
+            success: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            info: Color::LightCyan,
+            banner_fg: Color::Black,
+            diff_add: Color::LightGreen,
+            diff_remove: Color::LightRed,
+            diff_hunk: Color::LightCyan,
+            diff_meta: Color::LightYellow,
+        }
+    }
+
+    /// `dark`, `light`, or `high-contrast`.
+    pub fn preset(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Overrides one element, e.g. `set("staged", "#00aa00")`. Colours are
+    /// names like `light-red`, `#rrggbb`, or a 0-255 palette index.
+    pub fn set(&mut self, element: &str, color: &str) -> Result<(), String> {
+        let color = Color::from_str(color).map_err(|_| format!("unknown color '{}'", color))?;
+        let slot = match element {
+            "header" => &mut self.header,
+            "status_fg" => &mut self.status_fg,
+            "status_bg" => &mut self.status_bg,
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "highlight" => &mut self.highlight,
+            "staged" => &mut self.staged,
+            "unstaged" => &mut self.unstaged,
+            "partial" => &mut self.partial,
+            "mark" => &mut self.mark,
+            "directory" => &mut self.directory,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            "banner_fg" => &mut self.banner_fg,
+            "diff_add" => &mut self.diff_add,
+            "diff_remove" => &mut self.diff_remove,
+            "diff_hunk" => &mut self.diff_hunk,
+            "diff_meta" => &mut self.diff_meta,
+            _ => return Err(format!("unknown theme element '{}'", element)),
+        };
+        *slot = color;
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+//! Ticket IDs taken from the branch name, e.g. `feature/PROJ-123-login`,
+//! and worked into the commit subject through a template.
+
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+pub struct TicketRules {
+    /// Finds the ticket in the branch name. `None` turns insertion off.
+    pub pattern: Option<Regex>,
+    /// How the ticket joins the subject, with `{ticket}` and `{message}`
+    /// placeholders, e.g. `{ticket}: {message}` or `{message} ({ticket})`.
+    pub template: String,
+}
+
+impl Default for TicketRules {
+    fn default() -> TicketRules {
+        TicketRules {
+            pattern: None,
+            template: "{ticket}: {message}".to_string(),
+        }
+    }
+}
+
+/// JIRA-style keys such as `PROJ-123`.
+pub fn jira_ticket_pattern() -> Regex {
+    Regex::new(r"[A-Z][A-Z0-9]+-\d+").expect("valid regex")
+}
+
+impl TicketRules {
+    /// The ticket named by `branch`, if the pattern finds one.
+    pub fn find(&self, branch: &str) -> Option<String> {
+        let found = self.pattern.as_ref()?.find(branch)?;
+        Some(found.as_str().to_string())
+    }
+
+    /// Rewrites the subject of `message` through the template. Messages that
+    /// already mention the ticket are left alone.
+    pub fn apply(&self, message: &str, ticket: &str) -> String {
+        if message.contains(ticket) {
+            return message.to_string();
+        }
+        let (subject, body) = match message.split_once('\n') {
+            Some((subject, body)) => (subject, Some(body)),
+            None => (message, None),
+        };
+        let subject = self
+            .template
+            .replace("{ticket}", ticket)
+            .replace("{message}", subject);
+        match body {
+            Some(body) => format!("{}\n{}", subject, body),
+            None => subject,
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Trailers appended to the commit message: `Co-authored-by:` lines picked
+//! from an address book of recent co-authors, and arbitrary `Key: value`
+//! pairs. `Signed-off-by` is left to `git commit --signoff`. The same panel
+//! overrides the next commit's author and date.
+
+use crate::{
+    app::{App, AppMode},
+    text,
+};
+use crossterm::event::KeyCode;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const ADDRESS_BOOK_FILE: &str = "GIT_COMMIT_HELPER_COAUTHORS";
+const MAX_CO_AUTHORS: usize = 20;
+pub const CO_AUTHORED_BY: &str = "Co-authored-by";
+
+/// The toggles above the trailers: sign-off and allow-empty.
+pub const FLAG_ROWS: usize = 2;
+
+fn address_book_path(git_dir: &Path) -> PathBuf {
+    git_dir.join(ADDRESS_BOOK_FILE)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+impl Trailer {
+    /// Parses `Key: value`. Keys are a single word of letters, digits, and
+    /// dashes, as `git interpret-trailers` expects.
+    pub fn parse(line: &str) -> Option<Trailer> {
+        let (key, value) = line.split_once(':')?;
+        let key = key.trim();
+        let value = value.trim();
+        let valid_key =
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        (valid_key && !value.is_empty()).then(|| Trailer {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    pub fn line(&self) -> String {
+        format!("{}: {}", self.key, self.value)
+    }
+}
+
+/// Appends `trailers` to `message`, skipping any it already has. They join
+/// an existing trailer block, or start one after a blank line.
+pub fn append_trailers(message: &str, trailers: &[Trailer]) -> String {
+    let message = message.trim_end();
+    let mut lines: Vec<String> = Vec::new();
+    for line in trailers.iter().map(Trailer::line) {
+        if !message.lines().any(|existing| existing.trim() == line) && !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+    if lines.is_empty() {
+        return message.to_string();
+    }
+
+    // The subject on its own is never a trailer block.
+    let in_trailer_block = message
+        .rsplit_once("\n\n")
+        .is_some_and(|(_, last)| last.lines().all(|line| Trailer::parse(line).is_some()));
+    let separator = if in_trailer_block { "\n" } else { "\n\n" };
+    format!("{}{}{}", message, separator, lines.join("\n"))
+}
+
+/// What is being typed in the commit options panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PanelInput {
+    Trailer,
+    /// `--author`; empty goes back to your own identity.
+    Author,
+    /// `--date`; empty goes back to now.
+    Date,
+}
+
+/// Whether `author` is `Name <email>`, as `--author` needs to use it as
+/// given rather than search earlier commits for it.
+fn is_author(author: &str) -> bool {
+    author
+        .strip_suffix('>')
+        .and_then(|rest| rest.split_once('<'))
+        .is_some_and(|(name, email)| !name.trim().is_empty() && !email.trim().is_empty())
+}
+
+/// The commit options panel: sign-off, co-authors, and custom trailers for
+/// the next commit.
+#[derive(Debug, Default)]
+pub struct TrailerPanel {
+    /// The address book, most recent first, and whether each co-author is
+    /// on the next commit.
+    pub co_authors: Vec<(String, bool)>,
+    /// Other trailers for the next commit.
+    pub custom: Vec<Trailer>,
+    /// Sign-off and allow-empty come first (see [`FLAG_ROWS`]), then
+    /// co-authors, then custom trailers.
+    pub selected: usize,
+    /// A trailer, author, or date being typed.
+    pub input: Option<(PanelInput, String)>,
+}
+
+impl TrailerPanel {
+    /// Reads the co-author address book, one `Name <email>` per line.
+    pub fn load(git_dir: &Path) -> TrailerPanel {
+        let co_authors = fs::read_to_string(address_book_path(git_dir))
+            .map(|text| {
+                text.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| (line.to_string(), false))
+                    .collect()
+            })
+            .unwrap_or_default();
+        TrailerPanel {
+            co_authors,
+            ..TrailerPanel::default()
+        }
+    }
+
+    /// Every trailer on the next commit, co-authors first.
+    pub fn trailers(&self) -> Vec<Trailer> {
+        self.co_authors
+            .iter()
+            .filter(|(_, on)| *on)
+            .map(|(name, _)| Trailer {
+                key: CO_AUTHORED_BY.to_string(),
+                value: name.clone(),
+            })
+            .chain(self.custom.iter().cloned())
+            .collect()
+    }
+
+    fn rows(&self) -> usize {
+        FLAG_ROWS + self.co_authors.len() + self.custom.len()
+    }
+
+    /// Adds a typed trailer. Co-authors go to the top of the address book.
+    fn add(&mut self, trailer: Trailer) {
+        if trailer.key.eq_ignore_ascii_case(CO_AUTHORED_BY) {
+            self.co_authors.retain(|(name, _)| *name != trailer.value);
+            self.co_authors.insert(0, (trailer.value, true));
+            self.selected = FLAG_ROWS;
+        } else {
+            self.custom.push(trailer);
+            self.selected = self.rows() - 1;
+        }
+    }
+
+    /// Moves this commit's co-authors to the front of the address book,
+    /// saves it when `git_dir` is known, and clears the commit's trailers.
+    pub fn finish_commit(&mut self, git_dir: Option<&Path>) -> io::Result<()> {
+        self.co_authors.sort_by_key(|(_, on)| !*on);
+        self.co_authors.truncate(MAX_CO_AUTHORS);
+        for (_, on) in &mut self.co_authors {
+            *on = false;
+        }
+        self.custom.clear();
+        self.selected = 0;
+        self.save(git_dir)
+    }
+
+    fn save(&self, git_dir: Option<&Path>) -> io::Result<()> {
+        let Some(git_dir) = git_dir else {
+            return Ok(());
+        };
+        let names: Vec<&str> = self
+            .co_authors
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        fs::write(address_book_path(git_dir), names.join("\n"))
+    }
+}
+
+impl App {
+    pub(crate) fn handle_trailer_input(&mut self, key: KeyCode) {
+        let panel = &mut self.trailers;
+        if let Some((kind, input)) = &mut panel.input {
+            match key {
+                KeyCode::Enter if *kind == PanelInput::Trailer => match Trailer::parse(input) {
+                    Some(trailer) => {
+                        panel.input = None;
+                        panel.add(trailer);
+                    }
+                    None => {
+                        self.show_notification("Trailers look like Key: value".to_string());
+                    }
+                },
+                KeyCode::Enter => {
+                    let value = input.trim().to_string();
+                    if *kind == PanelInput::Author && !value.is_empty() && !is_author(&value) {
+                        self.show_notification("The author looks like Name <email>".to_string());
+                        return;
+                    }
+                    let options = &mut self.commit_options;
+                    let value = (!value.is_empty()).then_some(value);
+                    if *kind == PanelInput::Author {
+                        options.author = value;
+                    } else {
+                        // A back-dated commit usually wants both dates.
+                        options.committer_date = value.is_some();
+                        options.date = value;
+                    }
+                    panel.input = None;
+                }
+                KeyCode::Esc => panel.input = None,
+                KeyCode::Backspace => {
+                    let end = text::prev_boundary(input, input.len());
+                    input.truncate(end);
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        // The rows after the co-authors.
+        let custom = FLAG_ROWS + panel.co_authors.len();
+        match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::F(4) => self.mode = AppMode::CommitMessage,
+            KeyCode::Down | KeyCode::Char('j') if panel.selected + 1 < panel.rows() => {
+                panel.selected += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                panel.selected = panel.selected.saturating_sub(1);
+            }
+            KeyCode::Char(' ') => match panel.selected {
+                0 => self.commit_options.sign_off = !self.commit_options.sign_off,
+                1 => self.commit_options.allow_empty = !self.commit_options.allow_empty,
+                row if row < custom => {
+                    let (_, on) = &mut panel.co_authors[row - FLAG_ROWS];
+                    *on = !*on;
+                }
+                _ => {}
+            },
+            KeyCode::Char('c') => {
+                panel.input = Some((PanelInput::Trailer, format!("{}: ", CO_AUTHORED_BY)));
+            }
+            KeyCode::Char('a') => panel.input = Some((PanelInput::Trailer, String::new())),
+            KeyCode::Char('A') => {
+                let author = self.commit_options.author.clone().unwrap_or_default();
+                panel.input = Some((PanelInput::Author, author));
+            }
+            KeyCode::Char('D') => {
+                let date = self.commit_options.date.clone().unwrap_or_default();
+                panel.input = Some((PanelInput::Date, date));
+            }
+            KeyCode::Char('C') if self.commit_options.date.is_some() => {
+                self.commit_options.committer_date = !self.commit_options.committer_date;
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                match panel.selected {
+                    row if row < FLAG_ROWS => return,
+                    row if row < custom => {
+                        panel.co_authors.remove(row - FLAG_ROWS);
+                        if let Err(e) = panel.save(self.git_dir.as_deref()) {
+                            self.show_warning(format!("Could not save co-authors: {}", e));
+                        }
+                    }
+                    row => {
+                        panel.custom.remove(row - custom);
+                    }
+                }
+                let panel = &mut self.trailers;
+                panel.selected = panel.selected.min(panel.rows() - 1);
+            }
+            _ => {}
+        }
+    }
+}
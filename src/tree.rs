@@ -0,0 +1,124 @@
+//! Rows for the file list. In flat mode there is one row per file; in tree
+//! mode files are grouped under collapsible directory rows.
+
+use crate::git::GitFile;
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowKind {
+    /// `path` is the directory's path from the repository root, without a
+    /// trailing slash.
+    Dir { path: String, collapsed: bool },
+    /// Index into the visible file list.
+    File(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub depth: usize,
+    pub name: String,
+    pub kind: RowKind,
+}
+
+impl Row {
+    /// A stable identity for keeping the selection across rebuilds.
+    pub fn key<'a>(&'a self, files: &'a [GitFile]) -> &'a str {
+        match &self.kind {
+            RowKind::Dir { path, .. } => path,
+            RowKind::File(index) => &files[*index].path,
+        }
+    }
+}
+
+pub fn flat_rows(files: &[GitFile]) -> Vec<Row> {
+    files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| Row {
+            depth: 0,
+            name: match &file.orig_path {
+                Some(orig_path) => format!("{} → {}", orig_path, file.path),
+                None => file.path.clone(),
+            },
+            kind: RowKind::File(index),
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Node {
+    dirs: BTreeMap<String, Node>,
+    files: Vec<(String, usize)>,
+}
+
+/// Builds directory-grouped rows, directories first, both sorted by name.
+/// Children of directories listed in `collapsed` are left out.
+pub fn tree_rows(files: &[GitFile], collapsed: &HashSet<String>) -> Vec<Row> {
+    let mut root = Node::default();
+    for (index, file) in files.iter().enumerate() {
+        // Untracked directories arrive as a single `dir/` entry; keep those
+        // as leaves rather than inventing an empty directory node.
+        let (parents, name) = match file.path.trim_end_matches('/').rsplit_once('/') {
+            Some((parents, _)) => (Some(parents), &file.path[parents.len() + 1..]),
+            None => (None, file.path.as_str()),
+        };
+        let mut node = &mut root;
+        for part in parents.into_iter().flat_map(|p| p.split('/')) {
+            node = node.dirs.entry(part.to_string()).or_default();
+        }
+        let name = match &file.orig_path {
+            Some(orig_path) => format!("{} ← {}", name, orig_path),
+            None => name.to_string(),
+        };
+        node.files.push((name, index));
+    }
+
+    let mut rows = Vec::new();
+    push_rows(&root, "", 0, collapsed, &mut rows);
+    rows
+}
+
+fn push_rows(
+    node: &Node,
+    prefix: &str,
+    depth: usize,
+    collapsed: &HashSet<String>,
+    rows: &mut Vec<Row>,
+) {
+    for (name, child) in &node.dirs {
+        let path = format!("{}{}", prefix, name);
+        let is_collapsed = collapsed.contains(&path);
+        rows.push(Row {
+            depth,
+            name: name.clone(),
+            kind: RowKind::Dir {
+                path: path.clone(),
+                collapsed: is_collapsed,
+            },
+        });
+        if !is_collapsed {
+            push_rows(child, &format!("{}/", path), depth + 1, collapsed, rows);
+        }
+    }
+
+    let mut files = node.files.clone();
+    files.sort();
+    for (name, index) in files {
+        rows.push(Row {
+            depth,
+            name,
+            kind: RowKind::File(index),
+        });
+    }
+}
+
+/// Indices of the files that live under directory `dir`.
+pub fn files_under(files: &[GitFile], dir: &str) -> Vec<usize> {
+    let prefix = format!("{}/", dir);
+    files
+        .iter()
+        .enumerate()
+        .filter(|(_, file)| file.path.starts_with(&prefix))
+        .map(|(index, _)| index)
+        .collect()
+}
@@ -0,0 +1,2929 @@
+use crate::{
+    app::{App, AppMode, DiffSource, Focus, SPINNER_FRAMES},
+    auto_fetch, breaking,
+    checks::CheckState,
+    ci::{CiState, CiStatus, CiWatch},
+    compare::RefSide,
+    confirm::Confirm,
+    diff_nav::diff_files,
+    filter::StatusFilter,
+    git::{
+        CommitInfo, DiffOptions, FileStatus, Operation, RebaseAction, SignatureState,
+        SubmoduleState,
+    },
+    identity::foreign_email,
+    mouse::HINT_GAP,
+    panes::SplitLayout,
+    patch::PatchAction,
+    pull_request::PullRequestField,
+    release::CHANGELOG_FILE,
+    remotes::RemoteEdit,
+    report::{Level, Notification},
+    submodules,
+    tags::TagInput,
+    text,
+    theme::Theme,
+    trailers::{CO_AUTHORED_BY, PanelInput},
+    tree::{self, RowKind},
+    validate::{self, Severity},
+    word_diff,
+};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+use std::{
+    ops::Range,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use unicode_width::UnicodeWidthStr;
+
+impl App {
+    pub fn draw(&mut self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Header
+                Constraint::Min(0),    // Main content
+                Constraint::Length(1), // Status bar
+            ])
+            .split(f.area());
+
+        self.render_header(f, chunks[0]);
+        self.areas.status_bar = chunks[2];
+        self.areas.diff = Rect::default();
+
+        let mut content = chunks[1];
+        if let Some(operation) = self.git_status.operation {
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(content);
+            self.render_operation_banner(f, parts[0], operation);
+            content = parts[1];
+        }
+
+        match self.mode {
+            AppMode::FileList => self.render_file_list(f, content),
+            AppMode::DiffView => self.render_diff_view(f, content),
+            AppMode::CommitMessage => self.render_commit_message(f, content),
+            AppMode::Help => self.render_help(f, content),
+            AppMode::Output => self.render_output(f, content),
+            AppMode::Checks => self.render_checks(f, content),
+            AppMode::RebasePick => self.render_rebase_pick(f, content),
+            AppMode::Rebase => self.render_rebase(f, content),
+            AppMode::FixupPick => self.render_fixup_pick(f, content),
+            AppMode::Log => self.render_log(f, content),
+            AppMode::Tags => self.render_tags(f, content),
+            AppMode::Release => self.render_release(f, content),
+            AppMode::Reflog => self.render_reflog(f, content),
+            AppMode::Compare => self.render_compare(f, content),
+            AppMode::Remotes => self.render_remotes(f, content),
+            AppMode::Submodules => self.render_file_list(f, content),
+            AppMode::Worktrees => self.render_worktrees(f, content),
+            AppMode::Clean => self.render_clean(f, content),
+            AppMode::Groups => self.render_groups(f, content),
+            AppMode::NoRepo => self.render_no_repo(f, content),
+            AppMode::Messages => self.render_messages(f, content),
+            AppMode::RepoSwitch => {
+                self.render_file_list(f, content);
+                self.render_repo_switch(f, content);
+            }
+            AppMode::CommitOptions => {
+                self.render_commit_message(f, content);
+                self.render_commit_options(f, content);
+            }
+            AppMode::PullMenu => {
+                self.render_file_list(f, content);
+                self.render_pull_menu(f, content);
+            }
+            AppMode::IgnoreMenu => {
+                self.render_file_list(f, content);
+                self.render_ignore_menu(f, content);
+            }
+            AppMode::CopyMenu => {
+                self.render_file_list(f, content);
+                self.render_copy_menu(f, content);
+            }
+            AppMode::PushDialog => {
+                self.render_file_list(f, content);
+                self.render_push_dialog(f, content);
+            }
+            AppMode::PullRequest => {
+                self.render_file_list(f, content);
+                self.render_pull_request(f, content);
+            }
+            AppMode::Ci => {
+                self.render_file_list(f, content);
+                self.render_ci(f, content);
+            }
+            AppMode::Issues => {
+                self.render_commit_message(f, content);
+                self.render_issues(f, content);
+            }
+            AppMode::Profiles => {
+                self.render_commit_message(f, content);
+                self.render_profiles(f, content);
+            }
+            AppMode::Prefixes => self.render_commit_message(f, content),
+            AppMode::Spelling => {
+                self.render_commit_message(f, content);
+                self.render_spelling(f, content);
+            }
+            AppMode::DiffSearch => {
+                let parts = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(content);
+                self.render_diff_view(f, parts[0]);
+                self.render_diff_search_input(f, parts[1]);
+            }
+            AppMode::FilterInput => {
+                let parts = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(content);
+                self.render_file_list(f, parts[0]);
+                self.render_filter_input(f, parts[1]);
+            }
+            AppMode::CommandBar => {
+                let parts = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(content);
+                self.render_file_list(f, parts[0]);
+                self.render_command_bar(f, parts[1]);
+            }
+            AppMode::Patch => {
+                let parts = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(content);
+                match self.patch.as_ref().map(|prompt| &prompt.back) {
+                    Some(AppMode::DiffView) => self.render_diff_view(f, parts[0]),
+                    Some(AppMode::Log) => self.render_log(f, parts[0]),
+                    Some(AppMode::Compare) => self.render_compare(f, parts[0]),
+                    _ => self.render_file_list(f, parts[0]),
+                }
+                self.render_patch_prompt(f, parts[1]);
+            }
+            AppMode::NewBranch => {
+                let parts = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(content);
+                self.render_file_list(f, parts[0]);
+                self.render_new_branch(f, parts[1]);
+            }
+        }
+
+        if let Some(confirm) = &self.confirm {
+            self.render_confirm(f, content, confirm);
+        }
+
+        self.render_status_bar(f, chunks[2]);
+
+        if let Some(notification) = &self.notification {
+            self.render_notification(f, notification);
+        }
+    }
+
+    fn render_header(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let mut ahead_behind = if self.git_status.ahead > 0 || self.git_status.behind > 0 {
+            format!(" (↑{} ↓{})", self.git_status.ahead, self.git_status.behind)
+        } else {
+            String::new()
+        };
+        if let Some(comparison) = &self.comparison {
+            ahead_behind.push_str(&format!(
+                " | {}: ↑{} ↓{}",
+                comparison.label, comparison.ahead, comparison.behind
+            ));
+        }
+
+        let mut file_count = if self.filter.is_active() {
+            format!("{}/{}", self.files.len(), self.all_files.len())
+        } else {
+            self.files.len().to_string()
+        };
+        let insertions: usize = self.all_files.iter().map(|f| f.insertions).sum();
+        let deletions: usize = self.all_files.iter().map(|f| f.deletions).sum();
+        if insertions + deletions > 0 {
+            file_count.push_str(&format!(" (+{} -{})", insertions, deletions));
+        }
+
+        let status = &self.git_status;
+        let branch = match (&status.head, &status.describe) {
+            (_, Some(describe)) if status.detached() => format!("(detached at {})", describe),
+            (Some(head), _) if status.detached() => format!("(detached at {})", head.short_id),
+            _ => status.current_branch.clone(),
+        };
+        let header_text = if self.mode == AppMode::NoRepo {
+            "Git Commit Helper - No repository".to_string()
+        } else {
+            format!(
+                "Git Commit Helper - Branch: {}{} - Files: {}",
+                branch, ahead_behind, file_count
+            )
+        };
+
+        let mut spans = vec![Span::raw(header_text)];
+        if self.mode != AppMode::NoRepo
+            && self.git_status.on_branch()
+            && self.git_status.upstream.is_none()
+        {
+            spans.push(Span::styled(
+                " - no upstream (B to publish)",
+                Style::default().fg(theme.warning),
+            ));
+        }
+        let fetching = self.auto_fetch.as_ref().is_some_and(|f| f.is_running());
+        if self.fetch_error.is_some() {
+            spans.push(Span::styled(
+                " - fetch failed (m for messages)",
+                Style::default().fg(theme.error),
+            ));
+        } else if fetching {
+            spans.push(Span::styled(
+                " - fetching…",
+                Style::default().fg(theme.muted),
+            ));
+        } else if let Some(time) = self.last_fetch {
+            spans.push(Span::styled(
+                format!(" - fetched {}", auto_fetch::ago(time.elapsed())),
+                Style::default().fg(theme.muted),
+            ));
+        }
+        if let Some(ci) = &self.ci {
+            let status = ci.status.as_ref();
+            let (text, color) = match status.and_then(CiStatus::overall) {
+                Some(CiState::Success) => (" - CI ✓ passed".to_string(), theme.success),
+                Some(CiState::Failure) => (
+                    format!(
+                        " - CI ✗ {} failed",
+                        status.map_or(0, |s| s.count(CiState::Failure))
+                    ),
+                    theme.error,
+                ),
+                Some(_) => (
+                    format!(
+                        " - CI ● {} running",
+                        status.map_or(0, |s| s.count(CiState::Pending))
+                    ),
+                    theme.warning,
+                ),
+                None if ci.error.is_some() => (" - CI ?".to_string(), theme.muted),
+                None => (String::new(), theme.muted),
+            };
+            if !text.is_empty() {
+                spans.push(Span::styled(text, Style::default().fg(color)));
+            }
+        }
+        if let Some(signature) = self.git_status.head_signature {
+            let (text, color) = match signature {
+                SignatureState::Good => (" - HEAD ✓ signed", theme.success),
+                SignatureState::Untrusted => (" - HEAD ✓ signed (untrusted key)", theme.warning),
+                SignatureState::Unchecked => (" - HEAD ? signed (key not found)", theme.warning),
+                SignatureState::Bad => (" - HEAD ✗ bad signature", theme.error),
+            };
+            spans.push(Span::styled(text, Style::default().fg(color)));
+        }
+
+        let mut block = Block::default().borders(Borders::ALL);
+        if self.mode != AppMode::NoRepo {
+            block = block.title_bottom(self.repo_state_line().centered());
+        }
+        let header = Paragraph::new(Line::from(spans))
+            .style(Style::default().fg(theme.header))
+            .alignment(Alignment::Center)
+            .block(block);
+
+        f.render_widget(header, area);
+    }
+
+    /// The header's bottom border: HEAD's commit, the stash count, and an
+    /// operation that stopped part way.
+    fn repo_state_line(&self) -> Line<'static> {
+        let theme = self.config.theme;
+        let status = &self.git_status;
+        let mut spans = Vec::new();
+        match &status.head {
+            Some(head) => {
+                let label = if status.detached() {
+                    " HEAD detached at "
+                } else {
+                    " HEAD "
+                };
+                spans.push(Span::raw(label));
+                spans.push(Span::styled(
+                    head.short_id.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+                spans.push(Span::raw(format!(" {} ", head.subject)));
+            }
+            None => spans.push(Span::styled(
+                " no commits yet ",
+                Style::default().fg(theme.muted),
+            )),
+        }
+        if status.stash_count > 0 {
+            let noun = if status.stash_count == 1 {
+                "stash"
+            } else {
+                "stashes"
+            };
+            spans.push(Span::styled(
+                format!("· {} {} ", status.stash_count, noun),
+                Style::default().fg(theme.muted),
+            ));
+        }
+        if let Some(operation) = status.operation {
+            spans.push(Span::styled(
+                format!("· {} in progress ", operation.label().to_lowercase()),
+                Style::default().fg(theme.warning),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn render_operation_banner(&self, f: &mut Frame, area: Rect, operation: Operation) {
+        let theme = self.config.theme;
+        let conflicts = self.conflict_count();
+        let (text, color) = if conflicts > 0 {
+            (
+                format!(
+                    " {} stopped: {} conflict(s) · < ours  > theirs  M mergetool  Space mark resolved  X abort",
+                    operation.label(),
+                    conflicts
+                ),
+                theme.error,
+            )
+        } else {
+            (
+                format!(" {} in progress · C continue  X abort", operation.label()),
+                theme.warning,
+            )
+        };
+        let banner = Paragraph::new(text).style(
+            Style::default()
+                .fg(theme.banner_fg)
+                .bg(color)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_widget(banner, area);
+    }
+
+    fn render_file_list(&mut self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let area = if self.split_view {
+            let direction = match self.config.split_layout {
+                SplitLayout::SideBySide => Direction::Horizontal,
+                SplitLayout::Stacked => Direction::Vertical,
+            };
+            let percent = self.panes.list_percent;
+            let panes = Layout::default()
+                .direction(direction)
+                .constraints([
+                    Constraint::Percentage(percent),
+                    Constraint::Percentage(100 - percent),
+                ])
+                .split(area);
+            self.render_diff_pane(f, panes[1]);
+            self.areas.diff = panes[1];
+            panes[0]
+        } else {
+            area
+        };
+        let area = if self.submodules.submodules.is_empty() {
+            area
+        } else {
+            let height = self.submodules.submodules.len().min(5) as u16 + 2;
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(height)])
+                .split(area);
+            self.render_submodules(f, parts[1]);
+            parts[0]
+        };
+
+        // Inside the borders, after the highlight symbol.
+        let row_width = area.width.saturating_sub(4) as usize;
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let indent = "  ".repeat(row.depth);
+                match &row.kind {
+                    RowKind::File(index) => {
+                        let file = &self.files[*index];
+                        let status_char = match file.status {
+                            FileStatus::Untracked => "?",
+                            FileStatus::Modified => "M",
+                            FileStatus::Added | FileStatus::IntentToAdd => "A",
+                            FileStatus::Deleted => "D",
+                            FileStatus::Renamed => "R",
+                            FileStatus::Staged => "M",
+                            FileStatus::Conflicted => "U",
+                        };
+
+                        let conflicted = file.status == FileStatus::Conflicted;
+                        let staged_char = if conflicted {
+                            "!"
+                        } else if file.staged {
+                            "●"
+                        } else if file.status == FileStatus::IntentToAdd {
+                            "◌"
+                        } else {
+                            "○"
+                        };
+                        let color = if file.staged {
+                            theme.staged
+                        } else {
+                            theme.unstaged
+                        };
+                        let name_style = if conflicted {
+                            Style::default()
+                                .fg(theme.error)
+                                .add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default()
+                        };
+
+                        let mark = if file.marked { "◆ " } else { "  " };
+
+                        let mut spans = vec![
+                            Span::styled(mark, Style::default().fg(theme.mark)),
+                            Span::raw(indent),
+                            Span::styled(
+                                format!("{} {} ", staged_char, status_char),
+                                Style::default().fg(color),
+                            ),
+                            Span::styled(row.name.as_str(), name_style),
+                        ];
+                        if self.lfs.matches(&file.path) {
+                            spans.push(Span::styled(" LFS", Style::default().fg(theme.info)));
+                        }
+                        push_line_counts(
+                            &mut spans,
+                            (file.insertions, file.deletions),
+                            row_width,
+                            &theme,
+                        );
+                        ListItem::new(Line::from(spans))
+                    }
+                    RowKind::Dir { path, collapsed } => {
+                        let children = tree::files_under(&self.files, path);
+                        let staged = children.iter().filter(|&&i| self.files[i].staged).count();
+                        let (staged_char, color) = if staged == children.len() {
+                            ("●", theme.staged)
+                        } else if staged == 0 {
+                            ("○", theme.unstaged)
+                        } else {
+                            ("◐", theme.partial)
+                        };
+                        let arrow = if *collapsed { "▸" } else { "▾" };
+
+                        let counts = children.iter().fold((0, 0), |(added, removed), &i| {
+                            (
+                                added + self.files[i].insertions,
+                                removed + self.files[i].deletions,
+                            )
+                        });
+
+                        let mut spans = vec![
+                            Span::raw("  "),
+                            Span::raw(indent),
+                            Span::styled(
+                                format!("{} {} ", staged_char, arrow),
+                                Style::default().fg(color),
+                            ),
+                            Span::styled(
+                                format!("{}/", row.name),
+                                Style::default()
+                                    .fg(theme.directory)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                            Span::styled(
+                                format!(" ({})", children.len()),
+                                Style::default().fg(theme.muted),
+                            ),
+                        ];
+                        push_line_counts(&mut spans, counts, row_width, &theme);
+                        ListItem::new(Line::from(spans))
+                    }
+                }
+            })
+            .collect();
+
+        let mut title = if self.tree_view {
+            "Files (tree)".to_string()
+        } else {
+            "Files".to_string()
+        };
+        let marked = self.files.iter().filter(|f| f.marked).count();
+        if marked > 0 {
+            title.push_str(&format!(" ({} marked)", marked));
+        }
+        if !self.filter.query.is_empty() {
+            title.push_str(&format!(" [/{}]", self.filter.query));
+        }
+        if self.filter.status != StatusFilter::All {
+            title.push_str(&format!(" [{}]", self.filter.status.label()));
+        }
+
+        let files_list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(self.pane_border(Focus::Files))
+                    .title(title),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+
+        f.render_stateful_widget(files_list, area, &mut self.file_list_state);
+        self.areas.files = area;
+    }
+
+    /// The section under the file list; selectable after `S`.
+    fn render_submodules(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let items: Vec<ListItem> = self
+            .submodules
+            .submodules
+            .iter()
+            .map(|submodule| {
+                let staged = self
+                    .all_files
+                    .iter()
+                    .any(|file| file.path == submodule.path && file.staged);
+                let (marker, color) = match submodule.state {
+                    _ if staged => ("●", theme.staged),
+                    SubmoduleState::Moved => ("○", theme.unstaged),
+                    SubmoduleState::Uninitialized => ("-", theme.muted),
+                    SubmoduleState::Conflicted => ("!", theme.error),
+                    SubmoduleState::Current => (" ", theme.text),
+                };
+                let short_id = &submodule.id[..submodule.id.len().min(7)];
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("  {} ", marker), Style::default().fg(color)),
+                    Span::styled(
+                        submodule.path.as_str(),
+                        Style::default().fg(theme.directory),
+                    ),
+                    Span::styled(format!(" {} ", short_id), Style::default().fg(theme.muted)),
+                    Span::styled(
+                        submodules::describe(submodule),
+                        Style::default().fg(theme.warning),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let focused = self.mode == AppMode::Submodules;
+        let (title, border) = if focused {
+            (
+                "Submodules - u update, Space stage, Enter open, Esc back",
+                Style::default().fg(theme.highlight),
+            )
+        } else {
+            ("Submodules (S)", Style::default())
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border)
+                    .title(title),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state =
+            ListState::default().with_selected(focused.then_some(self.submodules.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_filter_input(&self, f: &mut Frame, area: Rect) {
+        let input = Paragraph::new(format!("/{}", self.filter.query)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Filter (Enter keep · Esc clear)"),
+        );
+        f.render_widget(input, area);
+        f.set_cursor_position((area.x + self.filter.query.width() as u16 + 2, area.y + 1));
+    }
+
+    fn render_command_bar(&self, f: &mut Frame, area: Rect) {
+        let input = &self.command_bar.input;
+        let bar = Paragraph::new(format!(":{}", input)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("git ... (Enter run · Esc cancel)"),
+        );
+        f.render_widget(bar, area);
+        f.set_cursor_position((area.x + input.width() as u16 + 2, area.y + 1));
+    }
+
+    fn render_patch_prompt(&self, f: &mut Frame, area: Rect) {
+        let Some(prompt) = &self.patch else {
+            return;
+        };
+        let title = match &prompt.action {
+            PatchAction::ExportStaged => {
+                "Write the staged changes to (Enter · Esc cancel)".to_string()
+            }
+            PatchAction::ExportCommits(commits) if commits.contains("..") => {
+                format!("Write {} as patches to (Enter · Esc cancel)", commits)
+            }
+            PatchAction::ExportCommits(id) => format!(
+                "Write {} as a patch to (Enter · Esc cancel)",
+                &id[..id.len().min(7)]
+            ),
+            PatchAction::Apply => {
+                "Patch file to apply, with git am if it has commits (Enter · Esc cancel)"
+                    .to_string()
+            }
+        };
+        let bar = Paragraph::new(prompt.input.as_str())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(bar, area);
+        f.set_cursor_position((area.x + prompt.input.width() as u16 + 1, area.y + 1));
+    }
+
+    fn render_new_branch(&self, f: &mut Frame, area: Rect) {
+        let input = &self.new_branch.input;
+        let at = match &self.git_status.head {
+            Some(head) => format!(" at {}", head.short_id),
+            None => String::new(),
+        };
+        let title = if self.new_branch.then_push {
+            format!(
+                "HEAD is detached; name a branch{} to push (Enter · Esc cancel)",
+                at
+            )
+        } else {
+            format!("New branch{} (Enter create · Esc cancel)", at)
+        };
+        let bar = Paragraph::new(input.as_str())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(bar, area);
+        f.set_cursor_position((area.x + input.width() as u16 + 1, area.y + 1));
+    }
+
+    fn render_diff_view(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let mut lines = diff_lines(&self.diff_content, &theme);
+        let search = &self.diff_search;
+        for (index, (line, range)) in search.matches.iter().enumerate() {
+            let style = if index == search.current {
+                Style::default().fg(theme.banner_fg).bg(theme.highlight)
+            } else {
+                Style::default().bg(theme.muted)
+            };
+            let highlighted = highlight(std::mem::take(&mut lines[*line]), range, style);
+            lines[*line] = highlighted;
+        }
+
+        let mut title = self.diff_title(match &self.diff_source {
+            DiffSource::Selection => "Diff".to_string(),
+            DiffSource::Staged => {
+                let count = self.all_files.iter().filter(|f| f.staged).count();
+                format!("Staged changes ({} files)", count)
+            }
+            DiffSource::Range { base, head } => format!("{}...{}", base, head),
+            DiffSource::Commit { id, path } => {
+                let short_id = &id[..id.len().min(7)];
+                match path {
+                    Some(path) => format!("{}: {}", short_id, path),
+                    None => format!("Commit {}", short_id),
+                }
+            }
+        });
+        if !search.query.is_empty() {
+            title.push_str(&format!(" [/{} {}]", search.query, search.counter()));
+        }
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        // With more than one file, the first row names them all and
+        // highlights the one on screen.
+        let files = diff_files(&self.diff_content);
+        let inner = if files.len() > 1 {
+            let current = self.current_diff_file(&files);
+            let mut crumbs = Vec::new();
+            for (index, file) in files.iter().enumerate() {
+                if index > 0 {
+                    crumbs.push(Span::styled(" · ", Style::default().fg(theme.muted)));
+                }
+                let style = if Some(index) == current {
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.muted)
+                };
+                crumbs.push(Span::styled(file.path.as_str(), style));
+            }
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(inner);
+            f.render_widget(Paragraph::new(Line::from(crumbs)), parts[0]);
+            parts[1]
+        } else {
+            inner
+        };
+
+        let diff = Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .scroll((self.diff_scroll, 0));
+        f.render_widget(diff, inner);
+    }
+
+    fn render_diff_search_input(&self, f: &mut Frame, area: Rect) {
+        let query = &self.diff_search.query;
+        let input = Paragraph::new(format!("/{}", query)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Search (Enter keep · Esc clear · n/N next/previous)"),
+        );
+        f.render_widget(input, area);
+        f.set_cursor_position((area.x + query.width() as u16 + 2, area.y + 1));
+    }
+
+    fn render_diff_pane(&self, f: &mut Frame, area: Rect) {
+        let title = match self.selected() {
+            Some(file) => format!("Diff: {}", file.path),
+            None => "Diff".to_string(),
+        };
+        let text = if self.diff_content.is_empty() {
+            Text::raw("No diff to show")
+        } else {
+            Text::from(diff_lines(&self.diff_content, &self.config.theme))
+        };
+        let diff = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(self.pane_border(Focus::Diff))
+                    .title(self.diff_title(title)),
+            )
+            .wrap(Wrap { trim: true })
+            .scroll((self.diff_scroll, 0));
+
+        f.render_widget(diff, area);
+    }
+
+    /// `title` followed by the diff flags, once they differ from the
+    /// defaults.
+    fn diff_title(&self, title: String) -> String {
+        if self.diff_options == DiffOptions::default() {
+            return title;
+        }
+        format!("{} ({})", title, self.diff_options.args().join(" "))
+    }
+
+    /// Highlights the focused pane of the split view.
+    fn pane_border(&self, pane: Focus) -> Style {
+        let theme = self.config.theme;
+        if self.split_view && self.focus == pane {
+            Style::default().fg(theme.highlight)
+        } else {
+            Style::default()
+        }
+    }
+
+    fn render_output(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let lines = self.output.text.lines().count();
+        let title = format!(
+            "{} ({}/{}) - j/k to scroll, Esc to close",
+            self.output.title,
+            (self.output.scroll as usize + 1).min(lines),
+            lines
+        );
+        let output = Paragraph::new(self.output.text.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.error))
+                    .title(title),
+            )
+            .scroll((self.output.scroll, 0));
+
+        f.render_widget(output, area);
+    }
+
+    fn render_checks(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let Some(run) = &self.checks else {
+            return;
+        };
+        let command = &run.commands[run.current];
+        let progress = format!("{}/{}", run.current + 1, run.commands.len());
+        let (title, color) = match run.state {
+            CheckState::Running => {
+                let frame = run.started.elapsed().as_millis() / 100;
+                let spinner = SPINNER_FRAMES[frame as usize % SPINNER_FRAMES.len()];
+                (
+                    format!("{} Checks {}: {} - Esc to stop", spinner, progress, command),
+                    theme.info,
+                )
+            }
+            CheckState::Passed => ("Checks passed".to_string(), theme.success),
+            CheckState::Failed => (
+                format!(
+                    "Check {} failed: {} - c commit anyway, r run again, Esc back to the message",
+                    progress, command
+                ),
+                theme.error,
+            ),
+        };
+        let lines = run.output.lines().count() as u16;
+        let scroll = if run.follow {
+            lines.saturating_sub(area.height.saturating_sub(2))
+        } else {
+            run.scroll
+        };
+        let output = Paragraph::new(run.output.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color))
+                    .title(title),
+            )
+            .scroll((scroll, 0));
+        f.render_widget(output, area);
+    }
+
+    fn render_commit_message(&mut self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let issues = self.commit_issues();
+        let picking = self.mode == AppMode::Prefixes;
+        let matches = if picking {
+            self.prefix_picker.matches(&self.commit_prefixes)
+        } else {
+            (0..self.commit_prefixes.len()).collect()
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(if picking {
+                    matches.len().clamp(1, 9) as u16 + 2
+                } else {
+                    3
+                }),
+                Constraint::Min(3),
+                Constraint::Length(if issues.is_empty() {
+                    0
+                } else {
+                    issues.len() as u16 + 2
+                }),
+            ])
+            .split(area);
+
+        // Prefixes: the chosen one, or while focused the list to pick from,
+        // numbered for 1-9
+        let width = self
+            .commit_prefixes
+            .iter()
+            .map(|p| p.prefix.width())
+            .max()
+            .unwrap_or(0);
+        let (selected, prefix_title) = if picking {
+            let title = format!("Prefixes: {}", self.prefix_picker.query);
+            (self.prefix_picker.selected, title)
+        } else {
+            let title = format!(
+                "Prefixes (Tab to pick, {}/{})",
+                self.selected_prefix + 1,
+                self.commit_prefixes.len()
+            );
+            (self.selected_prefix, title)
+        };
+        let prefixes: Vec<ListItem> = matches
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| {
+                let prefix = &self.commit_prefixes[index];
+                let style = if i == selected {
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                let number = match i {
+                    0..9 if picking => format!("{} ", i + 1),
+                    _ if picking => "  ".to_string(),
+                    _ => String::new(),
+                };
+                let pad = " ".repeat(width - prefix.prefix.width());
+                ListItem::new(Line::from(vec![
+                    Span::styled(number, Style::default().fg(theme.muted)),
+                    Span::styled(format!("{}{}", prefix.prefix, pad), style),
+                    Span::styled(prefix.description.clone(), Style::default().fg(theme.muted)),
+                ]))
+            })
+            .collect();
+
+        let border = if picking {
+            Style::default().fg(theme.highlight)
+        } else {
+            Style::default()
+        };
+        let prefix_list = List::new(prefixes).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border)
+                .title(prefix_title.as_str()),
+        );
+        let mut state = ListState::default().with_selected(Some(selected));
+        f.render_stateful_widget(prefix_list, chunks[0], &mut state);
+
+        // Commit message input
+        let (subject, body) = self
+            .commit_message
+            .split_once('\n')
+            .unwrap_or((&self.commit_message, ""));
+        let mut title = match self.config.validation.subject_soft_limit {
+            0 => format!("Commit Message (subject {}", text::grapheme_count(subject)),
+            limit => format!(
+                "Commit Message (subject {}/{}",
+                text::grapheme_count(subject),
+                limit
+            ),
+        };
+        let body = body.trim_start_matches('\n');
+        if !body.is_empty() {
+            title.push_str(&format!(", body {}", text::grapheme_count(body)));
+        }
+        title.push(')');
+        if let Some(n) = self.history.browsing() {
+            title.push_str(&format!(" [history {}/{}]", n, self.history.entries.len()));
+        }
+        if self.commit_options.no_verify {
+            title.push_str(" [--no-verify]");
+        }
+        if self.signs_commit() {
+            title.push_str(" [signed]");
+        }
+        if self.commit_options.sign_off {
+            title.push_str(" [signed-off]");
+        }
+        if self.commit_options.allow_empty {
+            title.push_str(" [allow-empty]");
+        }
+        if breaking::is_breaking(&self.commit_message) {
+            title.push_str(" [breaking]");
+        }
+        if let Some(ticket) = self.ticket() {
+            title.push_str(&format!(" [{}]", ticket));
+        }
+        if let Some(author) = &self.commit_options.author {
+            title.push_str(&format!(" [author: {}]", author));
+        }
+        if let Some(date) = &self.commit_options.date {
+            title.push_str(&format!(" [dated {}]", date));
+        }
+        let trailers = self.trailers.trailers().len();
+        if trailers > 0 {
+            title.push_str(&format!(" [+{} trailer(s)]", trailers));
+        }
+        let message_border = match issues.first().map(|issue| issue.severity) {
+            Some(Severity::Error) => Style::default().fg(theme.error),
+            Some(Severity::Warning) => Style::default().fg(theme.warning),
+            None => Style::default(),
+        };
+
+        let identity = &self.identity;
+        let author = match (&identity.name, &identity.email) {
+            (Some(name), Some(email)) => format!(" {} <{}> ", name, email),
+            (None, Some(email)) => format!(" <{}> ", email),
+            _ => " no user.email set (F6 profiles) ".to_string(),
+        };
+        let author = match (foreign_email(identity), &identity.email_domain) {
+            (Some(_), Some(domain)) => Span::styled(
+                format!("{}- not @{} ", author, domain.trim_start_matches('@')),
+                Style::default().fg(theme.warning),
+            ),
+            _ if identity.email.is_none() => {
+                Span::styled(author, Style::default().fg(theme.warning))
+            }
+            _ => Span::styled(author, Style::default().fg(theme.muted)),
+        };
+
+        // Soft-wrapped to the box, scrolled just enough to keep the cursor
+        // in view.
+        let (width, height) = (
+            chunks[1].width.saturating_sub(2) as usize,
+            chunks[1].height.saturating_sub(2),
+        );
+        let rows = text::wrap(&self.commit_message, width);
+        let (column, row) = text::wrapped_cell(&self.commit_message, self.cursor_position, width);
+        let row = row as u16;
+        if row < self.commit_scroll {
+            self.commit_scroll = row;
+        } else if height > 0 && row >= self.commit_scroll + height {
+            self.commit_scroll = row + 1 - height;
+        }
+        let total = rows.len() as u16;
+        self.commit_scroll = self.commit_scroll.min(total.saturating_sub(height.max(1)));
+        let position = if total > height {
+            let top = self.commit_scroll;
+            format!(" [{}-{}/{}] ", top + 1, (top + height).min(total), total)
+        } else {
+            String::new()
+        };
+
+        let input = Paragraph::new(self.styled_message(&rows))
+            .scroll((self.commit_scroll, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(message_border)
+                    .title(title)
+                    .title_bottom(Line::from(position))
+                    .title_bottom(Line::from(author).right_aligned()),
+            );
+
+        f.render_widget(input, chunks[1]);
+        self.draw_guides(f, chunks[1].inner(Margin::new(1, 1)), &rows);
+
+        if !issues.is_empty() {
+            let lines: Vec<Line> = issues
+                .iter()
+                .map(|issue| {
+                    let (icon, color) = match issue.severity {
+                        Severity::Error => ("✗ ", theme.error),
+                        Severity::Warning => ("! ", theme.warning),
+                    };
+                    Line::from(vec![
+                        Span::styled(icon, Style::default().fg(color)),
+                        Span::raw(issue.message.as_str()),
+                    ])
+                })
+                .collect();
+            let title =
+                if self.config.validation.block_on_error && issues[0].severity == Severity::Error {
+                    "Checks (errors block the commit)"
+                } else {
+                    "Checks"
+                };
+            let checks =
+                Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+            f.render_widget(checks, chunks[2]);
+        }
+
+        // Set cursor position
+        if picking {
+            f.set_cursor_position((chunks[0].x + 1 + prefix_title.width() as u16, chunks[0].y));
+            return;
+        }
+        f.set_cursor_position((
+            chunks[1].x + column + 1,
+            chunks[1].y + row - self.commit_scroll + 1,
+        ));
+    }
+
+    /// The `rows` of the commit message, with text past the length limits
+    /// coloured and misspelled words underlined.
+    fn styled_message(&self, rows: &[Range<usize>]) -> Text<'_> {
+        let message = self.commit_message.as_str();
+        let theme = self.config.theme;
+        let underline = Style::default()
+            .add_modifier(Modifier::UNDERLINED)
+            .underline_color(theme.error);
+        let mut marks: Vec<(Range<usize>, Style)> =
+            validate::overflow(message, &self.config.validation)
+                .into_iter()
+                .map(|(range, severity)| {
+                    let color = match severity {
+                        Severity::Error => theme.error,
+                        Severity::Warning => theme.warning,
+                    };
+                    (range, Style::default().fg(color))
+                })
+                .collect();
+        marks.extend(
+            self.spelling
+                .misspelled(message)
+                .into_iter()
+                .map(|word| (word, underline)),
+        );
+        let lines = rows.iter().map(|row| {
+            // Split the row wherever a mark starts or ends
+            let mut cuts = vec![row.start, row.end];
+            for (range, _) in &marks {
+                cuts.extend(
+                    [range.start, range.end]
+                        .into_iter()
+                        .filter(|&at| row.contains(&at)),
+                );
+            }
+            cuts.sort_unstable();
+            cuts.dedup();
+            let spans = cuts.windows(2).map(|cut| {
+                let style = marks
+                    .iter()
+                    .filter(|(range, _)| range.start <= cut[0] && cut[1] <= range.end)
+                    .fold(Style::default(), |style, (_, mark)| style.patch(*mark));
+                Span::styled(&message[cut[0]..cut[1]], style)
+            });
+            Line::from(spans.collect::<Vec<_>>())
+        });
+        Text::from(lines.collect::<Vec<_>>())
+    }
+
+    /// Marks column 50 on the subject and 72 on body lines, where the line
+    /// starts a row and stops short of the guide.
+    fn draw_guides(&self, f: &mut Frame, area: Rect, rows: &[Range<usize>]) {
+        let message = self.commit_message.as_str();
+        let rules = &self.config.validation;
+        let style = Style::default().fg(self.config.theme.muted);
+        let visible = rows
+            .iter()
+            .skip(self.commit_scroll as usize)
+            .take(area.height as usize);
+        for (y, row) in (area.y..).zip(visible) {
+            if row.start > 0 && !message[..row.start].ends_with('\n') {
+                continue;
+            }
+            let limit = if row.start == 0 {
+                rules.subject_soft_limit
+            } else {
+                rules.body_line_limit
+            };
+            if limit == 0 || limit >= area.width as usize || message[row.clone()].width() > limit {
+                continue;
+            }
+            let cell = &mut f.buffer_mut()[(area.x + limit as u16, y)];
+            if cell.symbol() == " " {
+                cell.set_symbol("┊").set_style(style);
+            }
+        }
+    }
+
+    fn render_spelling(&self, f: &mut Frame, area: Rect) {
+        let spelling = &self.spelling;
+        let word = &self.commit_message[spelling.word.clone()];
+        let items: Vec<ListItem> = if spelling.suggestions.is_empty() {
+            vec![ListItem::new(Span::styled(
+                "No suggestions",
+                Style::default().fg(self.config.theme.muted),
+            ))]
+        } else {
+            spelling
+                .suggestions
+                .iter()
+                .map(|s| ListItem::new(s.as_str()))
+                .collect()
+        };
+        let popup = centered_rect(56, items.len() as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "'{}' (Enter replace · a add · i ignore · Esc back)",
+                word
+            )))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let selected = (!spelling.suggestions.is_empty()).then_some(spelling.selected);
+        let mut state = ListState::default().with_selected(selected);
+        f.render_stateful_widget(list, popup, &mut state);
+    }
+
+    fn render_help(&self, f: &mut Frame, area: Rect) {
+        let help_text = vec![
+            "Git Commit Helper - Keyboard Shortcuts",
+            "",
+            "File List Mode:",
+            "  ↑/k, ↓/j     - Navigate files",
+            "  Space        - Stage/unstage file",
+            "  A / U        - Stage all / unstage all",
+            "  i            - Invert staged and unstaged files",
+            "  d            - View diff of selected file",
+            "  D            - Review everything staged as one diff",
+            "  l            - History of the selected file",
+            "  H            - Show the HEAD commit, e.g. the one just made",
+            "  =            - Compare HEAD with main (or compare_ref): commits and diff",
+            "  T            - Tags: create, delete, and push",
+            "  V            - Release: next version, changelog, and tag from the history",
+            "  L            - Reflog: find and recover lost commits",
+            "  E            - Remotes: add, rename, edit URLs, remove",
+            "  S            - Submodules: update, stage a new commit, open",
+            "  W            - Worktrees: switch to another, or add one for a branch",
+            "  s            - Split view: diff of the selected file beside the list",
+            "  Tab          - Move focus between list and diff (split view)",
+            "  [ / ]        - Shrink / grow the file list (split view)",
+            "  x            - Discard changes (deletes untracked files)",
+            "  N            - Intent to add an untracked file (git add -N), or untrack it again",
+            "  I            - Add an untracked file, its extension, or its directory to .gitignore",
+            "  G            - Clean: preview untracked files and delete the ones picked",
+            "  g            - Split the changes into several commits by grouping files",
+            "  < / >        - Resolve conflict with ours / theirs",
+            "  M            - Open mergetool on a conflicted file, difftool on any other",
+            "  e            - Open the file in the editor at its first change",
+            "  y            - Copy the path, branch, last commit's SHA, or diff",
+            "  a            - Apply a patch file (git am or git apply, 3-way)",
+            "  C / X        - Continue / abort merge, rebase, cherry-pick",
+            "  v            - Mark/unmark file for bulk actions",
+            "  J / K        - Extend marks down / up",
+            "  /            - Fuzzy filter by path",
+            "  :            - Run any git command; its output goes to the messages",
+            "  !            - Open a shell in the repository; exit it to come back",
+            "  Ctrl+Z       - Suspend (undo in the commit editor); fg in the shell resumes",
+            "  F            - Cycle status filter (all/unstaged/staged/untracked)",
+            "  Esc          - Clear filter, then marks",
+            "  t            - Toggle directory tree view",
+            "  ←/→ Enter    - Collapse / expand directory (tree view)",
+            "  c            - Start commit (if files are staged)",
+            "  p            - Push (remote, upstream, force, tags, in the terminal)",
+            "  B            - Publish a branch with no upstream (push -u)",
+            "  o            - Open a pull or merge request for the pushed branch",
+            "  w            - CI checks of the pushed commit (with a forge token)",
+            "  b            - Create a branch at HEAD and switch to it",
+            "  f            - Fetch from remote",
+            "  P            - Pull (merge, rebase, or fast-forward only)",
+            "  R            - Interactive rebase of recent commits",
+            "  u            - Undo last commit (keeps changes staged)",
+            "  z            - Commit staged changes as fixup! of a recent commit",
+            "  Z            - Squash fixup! commits into their targets",
+            "  Ctrl+W       - Commit everything as WIP: <branch>",
+            "  Ctrl+U       - Put the last WIP commit back into the working tree",
+            "  O            - Switch to a recently opened repository",
+            "  m            - Messages: past notifications, git commands run, and errors",
+            "  r            - Refresh git status (also automatic when files change)",
+            "  h/F1         - Show this help",
+            "  q            - Quit",
+            "",
+            "Commit Message Mode:",
+            "  Tab          - Pick a prefix: type to filter, ↑/↓ or 1-9, Enter to use",
+            "  Enter        - Commit changes, after the configured checks pass",
+            "  F2           - Toggle --no-verify (skip hooks and checks)",
+            "  F3           - Toggle signing the commit (-S)",
+            "  F4           - Sign-off, co-authors, trailers, author, and date",
+            "  F5           - Leave the branch's ticket ID out of this commit",
+            "  F6           - Switch the repository's identity to a profile",
+            "  F7           - Dry run: what the commit would record, without committing",
+            "  F8           - Toggle a breaking change: feat!: and a BREAKING CHANGE: footer",
+            "  F9           - Queue the staged files and message; g commits the queue",
+            "  Ctrl+E       - Edit message in $EDITOR",
+            "  Ctrl+G       - Suggest a message from the staged diff",
+            "  Ctrl+D       - Review the staged diff; Esc comes back here",
+            "  Ctrl+R       - Reference an open issue (#N, or Closes #N with Tab)",
+            "  Ctrl+S       - Correct the underlined word at the cursor",
+            "  Ctrl+V       - Paste from the clipboard",
+            "  Ctrl+Z/Y     - Undo/redo",
+            "  ↑/↓          - Recall previous commit messages",
+            "  Ctrl+←/→     - Jump by word",
+            "  Ctrl+W       - Delete back to the previous space (Alt+Backspace: word)",
+            "  Ctrl+U/K     - Delete to the start/end of the line",
+            "  Esc          - Cancel commit",
+            "",
+            "Checks (when a check fails):",
+            "  c            - Commit anyway",
+            "  r            - Run the checks again",
+            "  j/k, g/G     - Scroll; G follows new output",
+            "  Esc          - Stop, or go back to the message",
+            "",
+            "Rebase Mode:",
+            "  Enter        - Choose oldest commit, then start the rebase",
+            "  p/r/s/f/d    - Pick / reword / squash / fixup / drop",
+            "  J / K        - Move commit down / up",
+            "",
+            "History Mode:",
+            "  Enter        - Commit details: metadata, message, and full patch",
+            "  d            - What the commit changed in this file",
+            "  c            - Cherry-pick the commit onto the current branch",
+            "  x            - Export the commit as a patch file",
+            "  r / R        - Revert the commit / revert it as staged changes (--no-commit)",
+            "",
+            "Compare Mode:",
+            "  Enter        - Commit details",
+            "  d            - Everything the head changed since it forked (base...head)",
+            "  b / h        - Type another base / head ref",
+            "  s            - Swap base and head",
+            "  x            - Export the selected commit as a patch file",
+            "",
+            "Tags Mode:",
+            "  n / a        - Tag HEAD with a lightweight / annotated tag",
+            "  Ctrl+E       - Write the annotated tag's message in $EDITOR",
+            "  d            - Delete the selected tag",
+            "  p / P        - Push the selected tag / all tags",
+            "",
+            "Reflog Mode:",
+            "  Enter        - Commit details",
+            "  o            - Check out the entry (detached HEAD)",
+            "  c            - Cherry-pick the entry onto the current branch",
+            "  r            - Restore the branch to the entry (reset --keep), after confirmation",
+            "",
+            "Remotes Mode:",
+            "  a            - Add a remote: name, then URL",
+            "  r / e        - Rename the remote / edit its URL",
+            "  d            - Remove the remote, after confirmation",
+            "  p            - Push the current branch to the remote",
+            "",
+            "Clean Mode:",
+            "  Space        - Mark/unmark the entry for deletion",
+            "  a            - Mark all / none",
+            "  x            - Also list ignored files (git clean -x)",
+            "  d/Enter      - Delete the marked entries, after confirmation",
+            "",
+            "Groups Mode:",
+            "  n            - New group with the selected file; type its message",
+            "  1-9 / 0      - Put the file in that group / in none",
+            "  e            - Edit the message of the file's group",
+            "  c            - Stage and commit each group in turn",
+            "",
+            "Worktrees Mode:",
+            "  Enter        - Switch to the worktree",
+            "  n            - Add a worktree for a branch beside the main one",
+            "",
+            "Submodules Mode:",
+            "  u            - Update all (git submodule update --init --recursive)",
+            "  Space        - Stage / unstage the commit the submodule is at",
+            "  Enter/o      - Open the submodule in another gch; quit it to come back",
+            "",
+            "Release Mode:",
+            "  b            - Cycle the bump: patch, minor, major",
+            "  f            - Toggle prepending the changelog to CHANGELOG.md",
+            "  Enter        - Create the release, after confirmation",
+            "",
+            "Diff View Mode:",
+            "  /            - Search the diff; n / N for next / previous match",
+            "  ] / [        - Next / previous hunk",
+            "  J / K        - Next / previous file",
+            "  e            - Open the file on screen in the editor, at the change at the top",
+            "  +/-          - More / less context",
+            "  w            - Ignore whitespace changes",
+            "  a            - Cycle algorithm: default, patience, histogram",
+            "  y            - Copy the commit's SHA (commit details)",
+            "  Y            - Copy the whole diff",
+            "  x            - Export the staged changes, commit, or range as a patch",
+            "  c            - Cherry-pick the commit (commit details)",
+            "  r / R        - Revert the commit, or stage the revert (commit details)",
+            "  Esc/q        - Return to file list",
+            "",
+            "Mouse:",
+            "  Click        - Select a file; double-click or click its circle to stage",
+            "  Wheel        - Move through files, or scroll the diff under the pointer",
+            "  Status bar   - Click a [key] hint to press that key",
+            "",
+            "Press Esc or q to close this help",
+        ];
+
+        let help = Paragraph::new(help_text.join("\n"))
+            .block(Block::default().borders(Borders::ALL).title("Help"))
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(help, area);
+    }
+
+    fn render_rebase_pick(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let rebase = &self.rebase;
+        let items: Vec<ListItem> = rebase
+            .log
+            .iter()
+            .enumerate()
+            .map(|(i, commit)| {
+                let style = if i <= rebase.selected {
+                    Style::default().fg(theme.highlight)
+                } else {
+                    Style::default().fg(theme.muted)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", commit.short_id), style),
+                    Span::raw(commit.summary.as_str()),
+                    Span::styled(
+                        format!("  {}, {}", commit.author, relative_time(commit.time)),
+                        Style::default().fg(theme.muted),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let title = format!(
+            "Rebase: choose the oldest commit to rewrite ({} selected) - Enter to edit, Esc to cancel",
+            rebase.selected + 1
+        );
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(rebase.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_fixup_pick(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let fixup = &self.fixup;
+        let items: Vec<ListItem> = fixup
+            .log
+            .iter()
+            .map(|commit| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", commit.short_id),
+                        Style::default().fg(theme.highlight),
+                    ),
+                    Span::raw(commit.summary.as_str()),
+                    Span::styled(
+                        format!("  {}, {}", commit.author, relative_time(commit.time)),
+                        Style::default().fg(theme.muted),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Fixup: choose the commit to fix - Enter to commit, Esc to cancel"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(fixup.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_log(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let log = &self.log;
+        let items: Vec<ListItem> = log
+            .commits
+            .iter()
+            .map(|(commit, path)| {
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{} ", commit.short_id),
+                        Style::default().fg(theme.highlight),
+                    ),
+                    Span::raw(commit.summary.as_str()),
+                    Span::styled(
+                        format!("  {}, {}", commit.author, relative_time(commit.time)),
+                        Style::default().fg(theme.muted),
+                    ),
+                ];
+                if *path != log.path {
+                    spans.push(Span::styled(
+                        format!("  (as {})", path),
+                        Style::default().fg(theme.directory),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "History: {} ({} commits) - Enter for the commit, d for the diff, Esc to go back",
+                log.path,
+                log.commits.len()
+            )))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(log.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_tags(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let view = &self.tags;
+        let input_height = match &view.input {
+            Some(TagInput {
+                message: Some(message),
+                ..
+            }) => message.lines().count().clamp(1, 8) as u16 + 2,
+            Some(_) => 3,
+            None => 0,
+        };
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(input_height)])
+            .split(area);
+
+        let items: Vec<ListItem> = view
+            .tags
+            .iter()
+            .map(|tag| {
+                let mut spans = vec![
+                    Span::styled(tag.name.as_str(), Style::default().fg(theme.highlight)),
+                    Span::styled(
+                        format!("  {} ", tag.target),
+                        Style::default().fg(theme.muted),
+                    ),
+                    Span::raw(tag.summary.as_str()),
+                ];
+                if let Some(message) = &tag.message {
+                    spans.push(Span::styled(
+                        format!("  \"{}\"", message),
+                        Style::default().fg(theme.info),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let title = if items.is_empty() {
+            "Tags (none yet) - n new, a annotated, Esc to go back".to_string()
+        } else {
+            format!(
+                "Tags ({}) - n new, a annotated, d delete, p push, P push all, Esc to go back",
+                items.len()
+            )
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(view.selected));
+        f.render_stateful_widget(list, parts[0], &mut state);
+
+        let Some(input) = &view.input else {
+            return;
+        };
+        let area = parts[1];
+        let (text, title) = match &input.message {
+            Some(message) => (
+                message.as_str(),
+                format!(
+                    "Message for {} (Enter create · Ctrl+E editor · Esc cancel)",
+                    input.name.trim()
+                ),
+            ),
+            None if input.annotated => (
+                input.name.as_str(),
+                "Annotated tag on HEAD (Enter next · Esc cancel)".to_string(),
+            ),
+            None => (
+                input.name.as_str(),
+                "Tag HEAD as (Enter create · Esc cancel)".to_string(),
+            ),
+        };
+        let prompt =
+            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(prompt, area);
+        // The cursor goes after the last line, when that line is on screen.
+        let lines = text.lines().count().max(1) as u16;
+        let last = text.lines().last().unwrap_or("");
+        if lines + 2 <= area.height {
+            f.set_cursor_position((area.x + last.width() as u16 + 1, area.y + lines));
+        }
+    }
+
+    fn render_release(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let draft = &self.release;
+        let bump = draft.bump();
+        let suggestion = if draft.suggested == Some(bump) {
+            "suggested".to_string()
+        } else {
+            format!(
+                "suggested: {}",
+                draft.suggested.map(|bump| bump.label()).unwrap_or("-")
+            )
+        };
+        let from = match &draft.previous {
+            Some(tag) => format!("since {}", tag),
+            None => "since the first commit".to_string(),
+        };
+        let write = if draft.write_changelog {
+            format!(
+                "[x] Prepend to {} and commit it as \"chore(release): {}\"",
+                CHANGELOG_FILE,
+                draft.tag()
+            )
+        } else {
+            format!("[ ] Prepend to {} (tag HEAD only)", CHANGELOG_FILE)
+        };
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw("Version: "),
+                Span::styled(
+                    draft.tag(),
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(
+                        "  {} bump ({}) · {} commits {}",
+                        bump.label(),
+                        suggestion,
+                        draft.changes.len(),
+                        from
+                    ),
+                    Style::default().fg(theme.muted),
+                ),
+            ]),
+            Line::from(write),
+            Line::from(""),
+        ];
+        lines.extend(draft.changelog().lines().map(|line| {
+            let style = if line.starts_with('#') {
+                Style::default()
+                    .fg(theme.header)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(line.to_string(), style))
+        }));
+
+        let release = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(
+                "Release - b change bump, f toggle changelog file, Enter to create, Esc to cancel",
+            ))
+            .wrap(Wrap { trim: false })
+            .scroll((draft.scroll, 0));
+        f.render_widget(release, area);
+    }
+
+    fn render_reflog(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let view = &self.reflog;
+        let items: Vec<ListItem> = view
+            .entries
+            .iter()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", entry.short_id),
+                        Style::default().fg(theme.highlight),
+                    ),
+                    Span::styled(
+                        format!("{:<10} ", entry.selector),
+                        Style::default().fg(theme.muted),
+                    ),
+                    Span::raw(entry.action.as_str()),
+                    Span::styled(
+                        format!("  {}", entry.summary),
+                        Style::default().fg(theme.muted),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "Reflog ({} entries) - Enter details, o check out, c cherry-pick, r restore branch here, Esc to go back",
+                view.entries.len()
+            )))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(view.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_clean(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let view = &self.clean;
+        let items: Vec<ListItem> = view
+            .entries
+            .iter()
+            .map(|entry| {
+                let (mark, style) = if entry.marked {
+                    ("[x] ", Style::default().fg(theme.error))
+                } else {
+                    ("[ ] ", Style::default())
+                };
+                let slash = if entry.is_dir { "/" } else { "" };
+                ListItem::new(Line::from(vec![
+                    Span::styled(mark, style),
+                    Span::styled(format!("{}{}", entry.path, slash), style),
+                ]))
+            })
+            .collect();
+        let marked = view.entries.iter().filter(|entry| entry.marked).count();
+        let scope = if view.ignored {
+            "untracked and ignored"
+        } else {
+            "untracked"
+        };
+        let title = if items.is_empty() {
+            format!(
+                "Clean - nothing {} to delete (x toggles ignored, Esc to go back)",
+                scope
+            )
+        } else {
+            format!(
+                "Clean - {} of {} {} marked (Space mark, a all, x ignored, d delete, Esc back)",
+                marked,
+                items.len(),
+                scope
+            )
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(view.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_groups(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let view = &self.groups;
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(if view.input.is_some() { 3 } else { 0 }),
+            ])
+            .split(area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[0]);
+
+        // Files, each with the number of its group
+        let items: Vec<ListItem> = view
+            .files
+            .iter()
+            .map(|file| {
+                let group = match view.group_of(&file.path) {
+                    Some(i) => {
+                        Span::styled(format!("{} ", i + 1), Style::default().fg(theme.staged))
+                    }
+                    None => Span::styled("· ", Style::default().fg(theme.muted)),
+                };
+                ListItem::new(Line::from(vec![group, Span::raw(file.path.as_str())]))
+            })
+            .collect();
+        let loose = view
+            .files
+            .iter()
+            .filter(|file| view.group_of(&file.path).is_none())
+            .count();
+        let title = format!(
+            "Files - {} in no group (1-9 group, 0 none, n new group, e edit message)",
+            loose
+        );
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(view.selected));
+        f.render_stateful_widget(list, columns[0], &mut state);
+
+        // Groups, in the order they will be committed
+        let selected_group = view
+            .files
+            .get(view.selected)
+            .and_then(|file| view.group_of(&file.path));
+        let mut lines = Vec::new();
+        for (i, group) in view.groups.iter().enumerate() {
+            let style = if Some(i) == selected_group {
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} ", i + 1), Style::default().fg(theme.staged)),
+                Span::styled(group.subject(), style),
+            ]));
+            for file in &group.files {
+                lines.push(Line::styled(
+                    format!("    {}", file.path),
+                    Style::default().fg(theme.muted),
+                ));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::styled(
+                "No groups yet: n starts one with the selected file",
+                Style::default().fg(theme.muted),
+            ));
+        }
+        let title = format!(
+            "Queue - {} commit(s) (c commits them in order)",
+            view.groups.len()
+        );
+        let groups = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false });
+        f.render_widget(groups, columns[1]);
+
+        let Some((group, input)) = &view.input else {
+            return;
+        };
+        let title = if *group < view.groups.len() {
+            format!("Message for group {} (Enter save · Esc cancel)", group + 1)
+        } else {
+            "Message for a new group with the selected file (Enter add · Esc cancel)".to_string()
+        };
+        let input_widget = Paragraph::new(input.as_str())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(input_widget, rows[1]);
+        f.set_cursor_position((rows[1].x + input.width() as u16 + 1, rows[1].y + 1));
+    }
+
+    fn render_worktrees(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let view = &self.worktrees;
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(if view.branch_input.is_some() { 3 } else { 0 }),
+            ])
+            .split(area);
+
+        let items: Vec<ListItem> = view
+            .worktrees
+            .iter()
+            .map(|worktree| {
+                let current = self.is_current_worktree(worktree);
+                let head = match (&worktree.branch, &worktree.head) {
+                    _ if worktree.bare => "(bare)".to_string(),
+                    (Some(branch), _) => branch.clone(),
+                    (None, Some(id)) => format!("(detached {})", &id[..id.len().min(7)]),
+                    (None, None) => String::new(),
+                };
+                let mut spans = vec![
+                    Span::styled(
+                        if current { "● " } else { "  " },
+                        Style::default().fg(theme.staged),
+                    ),
+                    Span::styled(
+                        format!("{:<24} ", head),
+                        Style::default().fg(theme.highlight),
+                    ),
+                    Span::raw(worktree.path.display().to_string()),
+                ];
+                for (flag, label) in [(worktree.locked, "locked"), (worktree.prunable, "missing")] {
+                    if flag {
+                        spans.push(Span::styled(
+                            format!("  {}", label),
+                            Style::default().fg(theme.warning),
+                        ));
+                    }
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let title = format!(
+            "Worktrees ({}) - Enter switch, n new for a branch, Esc to go back",
+            items.len()
+        );
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(view.selected));
+        f.render_stateful_widget(list, parts[0], &mut state);
+
+        let Some(input) = &view.branch_input else {
+            return;
+        };
+        let input_widget = Paragraph::new(input.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Branch for the new worktree (Enter add · Esc cancel)"),
+        );
+        f.render_widget(input_widget, parts[1]);
+        f.set_cursor_position((parts[1].x + input.width() as u16 + 1, parts[1].y + 1));
+    }
+
+    fn render_compare(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let view = &self.compare;
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(if view.input.is_some() { 3 } else { 0 }),
+            ])
+            .split(area);
+
+        let section = |text: String| {
+            ListItem::new(Line::styled(
+                text,
+                Style::default()
+                    .fg(theme.directory)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        };
+        let commit = |commit: &CommitInfo| {
+            ListItem::new(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("{} ", commit.short_id),
+                    Style::default().fg(theme.highlight),
+                ),
+                Span::raw(commit.summary.clone()),
+                Span::styled(
+                    format!("  {}, {}", commit.author, relative_time(commit.time)),
+                    Style::default().fg(theme.muted),
+                ),
+            ]))
+        };
+        let mut items = vec![section(format!(
+            "↑{} only in {}",
+            view.ahead.len(),
+            view.head
+        ))];
+        items.extend(view.ahead.iter().map(commit));
+        items.push(section(format!(
+            "↓{} only in {}",
+            view.behind.len(),
+            view.base
+        )));
+        items.extend(view.behind.iter().map(commit));
+
+        // Past the section lines above the selected commit.
+        let selected = view.selected_commit().map(|_| {
+            if view.selected < view.ahead.len() {
+                view.selected + 1
+            } else {
+                view.selected + 2
+            }
+        });
+        let title = format!(
+            "Compare {} with {} - Enter commit, d diff, b/h change base/head, s swap, Esc back",
+            view.head, view.base
+        );
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(selected);
+        f.render_stateful_widget(list, parts[0], &mut state);
+
+        let Some((side, input)) = &view.input else {
+            return;
+        };
+        let title = match side {
+            RefSide::Base => "Base ref, e.g. origin/main (Enter compare · Esc cancel)",
+            RefSide::Head => "Head ref, e.g. HEAD or a branch (Enter compare · Esc cancel)",
+        };
+        let input_widget = Paragraph::new(input.as_str())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(input_widget, parts[1]);
+        f.set_cursor_position((parts[1].x + input.width() as u16 + 1, parts[1].y + 1));
+    }
+
+    fn render_remotes(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let view = &self.remotes;
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(if view.input.is_some() { 3 } else { 0 }),
+            ])
+            .split(area);
+
+        let tracked = self
+            .git_status
+            .upstream
+            .as_deref()
+            .and_then(|upstream| upstream.split_once('/'))
+            .map(|(remote, _)| remote);
+        let items: Vec<ListItem> = view
+            .remotes
+            .iter()
+            .map(|remote| {
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:<12} ", remote.name),
+                        Style::default().fg(theme.highlight),
+                    ),
+                    Span::raw(remote.url.as_str()),
+                ];
+                if let Some(push_url) = &remote.push_url {
+                    spans.push(Span::styled(
+                        format!("  (push: {})", push_url),
+                        Style::default().fg(theme.muted),
+                    ));
+                }
+                if tracked == Some(remote.name.as_str()) {
+                    spans.push(Span::styled("  tracked", Style::default().fg(theme.info)));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let title = if items.is_empty() {
+            "Remotes (none) - a add, Esc to go back".to_string()
+        } else {
+            format!(
+                "Remotes ({}) - a add, r rename, e edit URL, d remove, p push here, Esc to go back",
+                items.len()
+            )
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(view.selected));
+        f.render_stateful_widget(list, parts[0], &mut state);
+
+        let Some(input) = &view.input else {
+            return;
+        };
+        let title = match (&input.edit, input.on_url) {
+            (RemoteEdit::Add, false) => "New remote name (Enter next · Esc cancel)".to_string(),
+            (RemoteEdit::Add, true) => format!("URL for {} (Enter add · Esc cancel)", input.name),
+            (RemoteEdit::Rename(old), _) => {
+                format!("Rename {} to (Enter rename · Esc cancel)", old)
+            }
+            (RemoteEdit::SetUrl(name), _) => format!("URL for {} (Enter save · Esc cancel)", name),
+        };
+        let field = input.field();
+        let prompt =
+            Paragraph::new(field).block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(prompt, parts[1]);
+        f.set_cursor_position((parts[1].x + field.width() as u16 + 1, parts[1].y + 1));
+    }
+
+    fn render_messages(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let mut lines = Vec::new();
+        for entry in self.messages.entries.iter().rev() {
+            let color = match entry.level {
+                Level::Info => theme.text,
+                Level::Warning => theme.warning,
+                Level::Error => theme.error,
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    entry.time.format("%H:%M:%S ").to_string(),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(entry.text.as_str(), Style::default().fg(color)),
+            ]));
+            for detail in entry.detail.iter().flat_map(|detail| detail.lines()) {
+                lines.push(Line::from(Span::styled(
+                    format!("         {}", detail),
+                    Style::default().fg(theme.muted),
+                )));
+            }
+        }
+        let title = if lines.is_empty() {
+            "Messages (none yet)"
+        } else {
+            "Messages - newest first, Esc to close"
+        };
+        let messages = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .scroll((self.messages.scroll, 0));
+        f.render_widget(messages, area);
+    }
+
+    fn render_no_repo(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let picker = &self.repo_picker;
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(5), Constraint::Min(0)])
+            .split(area);
+
+        let here = std::env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let intro = Paragraph::new(vec![
+            Line::from(Span::styled(
+                format!("{} is not a git repository", here),
+                Style::default().fg(theme.error),
+            )),
+            Line::from(Span::styled(
+                picker.reason.as_str(),
+                Style::default().fg(theme.muted),
+            )),
+            Line::from("Enter: open recent · o: open a directory · i: git init here · q: quit"),
+        ])
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Not a git repository"),
+        )
+        .wrap(Wrap { trim: true });
+        f.render_widget(intro, parts[0]);
+        self.render_repo_list(f, parts[1], "Recent repositories");
+    }
+
+    fn render_repo_switch(&self, f: &mut Frame, area: Rect) {
+        let popup = centered_rect(72, 14, area);
+        f.render_widget(Clear, popup);
+        self.render_repo_list(
+            f,
+            popup,
+            "Switch repository (Enter open · o type a path · Esc cancel)",
+        );
+    }
+
+    /// The recent repositories, or the path being typed after `o`.
+    fn render_repo_list(&self, f: &mut Frame, area: Rect, title: &str) {
+        let picker = &self.repo_picker;
+        if let Some(input) = &picker.path_input {
+            let input_area = Rect {
+                height: 3.min(area.height),
+                ..area
+            };
+            let prompt = Paragraph::new(input.as_str()).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Open directory (Enter open · Esc cancel)"),
+            );
+            f.render_widget(prompt, input_area);
+            f.set_cursor_position((input_area.x + input.width() as u16 + 1, input_area.y + 1));
+            return;
+        }
+
+        let items: Vec<ListItem> = picker
+            .recent
+            .iter()
+            .map(|path| ListItem::new(path.display().to_string()))
+            .collect();
+        let title = if items.is_empty() {
+            format!("{} (none yet)", title)
+        } else {
+            title.to_string()
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(picker.selected));
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_rebase(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let rebase = &self.rebase;
+        let items: Vec<ListItem> = rebase
+            .todo
+            .iter()
+            .map(|entry| {
+                let color = match entry.action {
+                    RebaseAction::Pick => theme.success,
+                    RebaseAction::Reword(_) => theme.info,
+                    RebaseAction::Squash | RebaseAction::Fixup => theme.warning,
+                    RebaseAction::Drop => theme.error,
+                };
+                let summary = match &entry.action {
+                    RebaseAction::Reword(message) => message.as_str(),
+                    _ => entry.commit.summary.as_str(),
+                };
+                let mut summary_style = Style::default();
+                if entry.action == RebaseAction::Drop {
+                    summary_style = summary_style
+                        .fg(theme.muted)
+                        .add_modifier(Modifier::CROSSED_OUT);
+                }
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<7}", entry.action.label()),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{} ", entry.commit.short_id),
+                        Style::default().fg(theme.muted),
+                    ),
+                    Span::styled(summary, summary_style),
+                ]))
+            })
+            .collect();
+
+        let onto = match &rebase.base {
+            Some(base) => base.chars().take(7).collect::<String>() + "^",
+            None => "root".to_string(),
+        };
+        let title = format!(
+            "Rebase onto {} (oldest first) - p pick  r reword  s squash  f fixup  d drop  J/K move  Enter start",
+            onto
+        );
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(rebase.selected));
+        f.render_stateful_widget(list, area, &mut state);
+
+        if let Some(message) = &rebase.reword {
+            let popup = centered_rect(area.width.saturating_sub(4), 3, area);
+            f.render_widget(Clear, popup);
+            f.render_widget(
+                Paragraph::new(message.as_str()).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("New message (Enter to keep, Esc to cancel)"),
+                ),
+                popup,
+            );
+            f.set_cursor_position((popup.x + message.width() as u16 + 1, popup.y + 1));
+        }
+    }
+
+    fn render_pull_menu(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let behind = match (&self.git_status.upstream, self.git_status.behind) {
+            (None, _) => "No upstream to pull from".to_string(),
+            (Some(_), 0) => "Up to date with upstream".to_string(),
+            (Some(_), n) => format!("{} commit(s) behind upstream", n),
+        };
+        let text = vec![
+            Line::from(Span::styled(behind, Style::default().fg(theme.warning))),
+            Line::from(""),
+            Line::from("  m/Enter  Merge (default)"),
+            Line::from("  r        Rebase onto upstream"),
+            Line::from("  f        Fast-forward only"),
+            Line::from(""),
+            Line::from("  Esc      Cancel"),
+        ];
+
+        let popup = centered_rect(44, text.len() as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Pull")),
+            popup,
+        );
+    }
+
+    fn render_copy_menu(&self, f: &mut Frame, area: Rect) {
+        let text = vec![
+            Line::from("  p        Path of the selected file"),
+            Line::from(format!(
+                "  b        Branch name ({})",
+                self.git_status.current_branch
+            )),
+            Line::from("  c        SHA of the last commit"),
+            Line::from("  d        Diff of the selection"),
+            Line::from(""),
+            Line::from("  Esc      Cancel"),
+        ];
+
+        let popup = centered_rect(44, text.len() as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Copy")),
+            popup,
+        );
+    }
+
+    fn render_ignore_menu(&self, f: &mut Frame, area: Rect) {
+        let menu = &self.ignore_menu;
+        let mut text: Vec<Line> = menu
+            .choices
+            .iter()
+            .enumerate()
+            .map(|(index, pattern)| {
+                let line = format!("  {}  {}", index + 1, pattern);
+                if index == menu.selected {
+                    Line::styled(line, Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    Line::from(line)
+                }
+            })
+            .collect();
+        text.push(Line::from(""));
+        text.push(Line::from("  Enter    Add to .gitignore and stage it"));
+        text.push(Line::from("  Esc      Cancel"));
+
+        let width = text.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 4;
+        let popup = centered_rect(width.max(44), text.len() as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Ignore")),
+            popup,
+        );
+    }
+
+    fn render_push_dialog(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let dialog = &self.push_dialog;
+        let options = &dialog.options;
+        let checkbox = |on: bool| if on { "[x]" } else { "[ ]" };
+        let upstream_note = match &self.git_status.upstream {
+            Some(upstream) => format!("tracking {}", upstream),
+            None => "no upstream yet".to_string(),
+        };
+
+        let text = vec![
+            Line::from(vec![
+                Span::raw("Remote:  "),
+                Span::styled(
+                    format!("< {} >", options.remote),
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  (Tab to change)"),
+            ]),
+            match &dialog.branch_input {
+                Some(input) => Line::from(vec![
+                    Span::raw("Branch:  "),
+                    Span::styled(format!("{}█", input), Style::default().fg(theme.highlight)),
+                    Span::raw("  (Enter to keep, empty for the same name)"),
+                ]),
+                None => Line::from(format!(
+                    "Branch:  {} → {}/{}  (b to change)",
+                    options.branch,
+                    options.remote,
+                    options.target()
+                )),
+            },
+            Line::from(""),
+            Line::from(format!(
+                "{} u  --set-upstream ({})",
+                checkbox(options.set_upstream),
+                upstream_note
+            )),
+            Line::from(format!(
+                "{} f  --force-with-lease",
+                checkbox(options.force_with_lease)
+            )),
+            Line::from(format!(
+                "{} t  --follow-tags",
+                checkbox(options.follow_tags)
+            )),
+            Line::from(format!(
+                "{} i  in the terminal, to type a password or passphrase",
+                checkbox(options.in_terminal)
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("git push {}", options.args().join(" ")),
+                Style::default().fg(theme.muted),
+            )),
+            Line::from(""),
+            Line::from("Enter push · Esc cancel"),
+        ];
+
+        let popup = centered_rect(60, text.len() as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: true })
+                .block(Block::default().borders(Borders::ALL).title("Push")),
+            popup,
+        );
+    }
+
+    fn render_ci(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let Some(CiWatch {
+            status: Some(status),
+            selected,
+            ..
+        }) = &self.ci
+        else {
+            return;
+        };
+        let items: Vec<ListItem> = status
+            .checks
+            .iter()
+            .map(|check| {
+                let (icon, color) = match check.state {
+                    CiState::Success => ("✓", theme.success),
+                    CiState::Failure => ("✗", theme.error),
+                    CiState::Pending => ("●", theme.warning),
+                    CiState::Neutral => ("–", theme.muted),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", icon), Style::default().fg(color)),
+                    Span::raw(check.name.clone()),
+                ]))
+            })
+            .collect();
+        let short = &status.commit[..status.commit.len().min(7)];
+        let title = if items.is_empty() {
+            format!(
+                "CI for {} - no checks reported (r reload, Esc close)",
+                short
+            )
+        } else {
+            format!("CI for {} (y copy link, r reload, Esc close)", short)
+        };
+        let popup = centered_rect(60, items.len().max(1) as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(*selected));
+        f.render_stateful_widget(list, popup, &mut state);
+    }
+
+    fn render_profiles(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let items: Vec<ListItem> = self
+            .config
+            .profiles
+            .iter()
+            .map(|profile| {
+                let current = if profile.is_current(&self.identity) {
+                    "● "
+                } else {
+                    "  "
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(current, Style::default().fg(theme.success)),
+                    Span::styled(
+                        format!("{:<10} ", profile.label),
+                        Style::default().fg(theme.highlight),
+                    ),
+                    Span::raw(format!("{} <{}>", profile.name, profile.email)),
+                ]))
+            })
+            .collect();
+        let popup = centered_rect(64, items.len() as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Commit as (Enter use in this repository · Esc back)"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.profile_selected));
+        f.render_stateful_widget(list, popup, &mut state);
+    }
+
+    fn render_issues(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let browser = &self.issues;
+        let popup = centered_rect(72, 16, area);
+        f.render_widget(Clear, popup);
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(popup);
+
+        let title = format!(
+            "{} issues (Enter #N · Tab Closes #N · Esc back)",
+            browser.forge.name()
+        );
+        let query = Paragraph::new(browser.query.as_str())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(query, parts[0]);
+        f.set_cursor_position((
+            parts[0].x + browser.query.width() as u16 + 1,
+            parts[0].y + 1,
+        ));
+
+        let matches = browser.matches();
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|issue| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("#{:<6}", issue.number),
+                        Style::default().fg(theme.highlight),
+                    ),
+                    Span::raw(issue.title.clone()),
+                ]))
+            })
+            .collect();
+        let title = if browser.is_loading() {
+            "Loading…".to_string()
+        } else if browser.issues.is_empty() {
+            "No open issues".to_string()
+        } else {
+            format!("{} of {}", matches.len(), browser.issues.len())
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(browser.selected));
+        f.render_stateful_widget(list, parts[1], &mut state);
+    }
+
+    fn render_pull_request(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let form = &self.pull_request;
+        let request = &form.request;
+        let mut text = Vec::new();
+        if let Some(url) = &form.url {
+            text.push(Line::from(request.title.clone()));
+            text.push(Line::from(""));
+            text.push(Line::from(Span::styled(
+                url.clone(),
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            text.push(Line::from(""));
+            text.push(Line::from("y copy URL · Esc close"));
+        } else {
+            let field = |label: &str, value: &str, which: PullRequestField| {
+                let (cursor, style) = if form.field == which {
+                    ("█", Style::default().fg(theme.highlight))
+                } else {
+                    ("", Style::default())
+                };
+                Line::from(vec![
+                    Span::raw(format!("{:<7}", label)),
+                    Span::styled(format!("{}{}", value, cursor), style),
+                ])
+            };
+            text.push(Line::from(Span::styled(
+                format!("{} → {}", request.head, request.base),
+                Style::default().fg(theme.muted),
+            )));
+            text.push(Line::from(""));
+            text.push(field("Title:", &request.title, PullRequestField::Title));
+            text.push(field("Base:", &request.base, PullRequestField::Base));
+            text.push(Line::from(""));
+            text.push(field("Body:", "", PullRequestField::Body));
+            let body: Vec<&str> = request.body.split('\n').collect();
+            // The end of a long body, where typing goes.
+            let shown = &body[body.len().saturating_sub(12)..];
+            let style = if form.field == PullRequestField::Body {
+                Style::default().fg(theme.highlight)
+            } else {
+                Style::default()
+            };
+            for (index, line) in shown.iter().enumerate() {
+                let cursor = if form.field == PullRequestField::Body && index + 1 == shown.len() {
+                    "█"
+                } else {
+                    ""
+                };
+                text.push(Line::from(Span::styled(
+                    format!("  {}{}", line, cursor),
+                    style,
+                )));
+            }
+            text.push(Line::from(""));
+            text.push(Line::from("Tab next field · Enter create · Esc cancel"));
+        }
+
+        let forge = request.forge;
+        let title = match form.url {
+            Some(_) => format!("{} {} created", forge.name(), forge.request_name()),
+            None => format!("New {} {}", forge.name(), forge.request_name()),
+        };
+        let popup = centered_rect(70, text.len() as u16 + 2, area);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(title)),
+            popup,
+        );
+    }
+
+    fn render_commit_options(&self, f: &mut Frame, area: Rect) {
+        let panel = &self.trailers;
+        let checkbox = |on: bool| if on { "[x]" } else { "[ ]" };
+        let mut items = vec![
+            ListItem::new(format!(
+                "{} Signed-off-by (--signoff)",
+                checkbox(self.commit_options.sign_off)
+            )),
+            ListItem::new(format!(
+                "{} Allow an empty commit (--allow-empty)",
+                checkbox(self.commit_options.allow_empty)
+            )),
+        ];
+        items.extend(panel.co_authors.iter().map(|(name, on)| {
+            ListItem::new(format!("{} {}: {}", checkbox(*on), CO_AUTHORED_BY, name))
+        }));
+        items.extend(
+            panel
+                .custom
+                .iter()
+                .map(|trailer| ListItem::new(format!("    {}", trailer.line()))),
+        );
+
+        let popup = centered_rect(70, items.len() as u16 + 6, area);
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(4)])
+            .split(popup);
+        f.render_widget(Clear, popup);
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Commit options · Space toggle  c co-author  a trailer  d remove"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(panel.selected));
+        f.render_stateful_widget(list, parts[0], &mut state);
+
+        let options = &self.commit_options;
+        let (text, title) = match &panel.input {
+            Some((kind, input)) => (
+                input.clone(),
+                match kind {
+                    PanelInput::Trailer => "New trailer (Key: value, Enter to add)",
+                    PanelInput::Author => "Author (Name <email>, empty for yourself)",
+                    PanelInput::Date => "Date (e.g. 2024-05-01 09:30, empty for now)",
+                },
+            ),
+            None => {
+                let date = match (&options.date, options.committer_date) {
+                    (Some(date), true) => format!("{} (committer too)", date),
+                    (Some(date), false) => format!("{} (author only)", date),
+                    (None, _) => "now".to_string(),
+                };
+                (
+                    format!(
+                        "Author: {}\nDate:   {}",
+                        options.author.as_deref().unwrap_or("you"),
+                        date
+                    ),
+                    "A author  D date  C committer date too",
+                )
+            }
+        };
+        f.render_widget(
+            Paragraph::new(text.as_str())
+                .block(Block::default().borders(Borders::ALL).title(title)),
+            parts[1],
+        );
+        if panel.input.is_some() {
+            f.set_cursor_position((parts[1].x + text.width() as u16 + 1, parts[1].y + 1));
+        }
+    }
+
+    fn render_confirm(&self, f: &mut Frame, area: Rect, confirm: &Confirm) {
+        let theme = self.config.theme;
+        let mut text: Vec<Line> = confirm.message.lines().map(Line::from).collect();
+        if !confirm.action.is_undoable() {
+            text.push(Line::from(Span::styled(
+                "This cannot be undone.",
+                Style::default().fg(theme.error),
+            )));
+        }
+        text.push(Line::from(""));
+        text.push(Line::from("y confirm · n cancel"));
+
+        let popup = centered_rect(60, text.len() as u16 + 3, area);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(text).wrap(Wrap { trim: true }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.error))
+                    .title(confirm.title.as_str()),
+            ),
+            popup,
+        );
+    }
+
+    pub(crate) fn mode_label(&self) -> &'static str {
+        match self.mode {
+            AppMode::FileList => "FILE LIST",
+            AppMode::DiffView => "DIFF VIEW",
+            AppMode::CommitMessage | AppMode::CommitOptions => "COMMIT MESSAGE",
+            AppMode::Help => "HELP",
+            AppMode::PullMenu => "PULL",
+            AppMode::IgnoreMenu => "IGNORE",
+            AppMode::PushDialog => "PUSH",
+            AppMode::FilterInput => "FILTER",
+            AppMode::CommandBar => "COMMAND",
+            AppMode::NewBranch => "NEW BRANCH",
+            AppMode::Compare => "COMPARE",
+            AppMode::PullRequest => "PULL REQUEST",
+            AppMode::Ci => "CI",
+            AppMode::Issues => "ISSUES",
+            AppMode::Profiles => "PROFILES",
+            AppMode::Prefixes => "PREFIXES",
+            AppMode::Spelling => "SPELLING",
+            AppMode::Patch => "PATCH",
+            AppMode::DiffSearch => "SEARCH",
+            AppMode::Log => "HISTORY",
+            AppMode::Tags => "TAGS",
+            AppMode::Release => "RELEASE",
+            AppMode::Reflog => "REFLOG",
+            AppMode::Remotes => "REMOTES",
+            AppMode::Submodules => "SUBMODULES",
+            AppMode::Worktrees => "WORKTREES",
+            AppMode::Clean => "CLEAN",
+            AppMode::Groups => "GROUPS",
+            AppMode::CopyMenu => "COPY",
+            AppMode::Output => "OUTPUT",
+            AppMode::Checks => "CHECKS",
+            AppMode::RebasePick | AppMode::Rebase => "REBASE",
+            AppMode::FixupPick => "FIXUP",
+            AppMode::NoRepo => "NO REPOSITORY",
+            AppMode::RepoSwitch => "SWITCH REPOSITORY",
+            AppMode::Messages => "MESSAGES",
+        }
+    }
+
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let theme = self.config.theme;
+        let status_line = match &self.task {
+            Some(task) => Line::from(format!(
+                "{} {}... ({}s) | Esc to cancel",
+                task.spinner(),
+                task.kind.label(),
+                task.started.elapsed().as_secs()
+            )),
+            None => {
+                let mut spans = vec![Span::raw(self.status_prefix())];
+                for (i, (label, _)) in self.status_hints().into_iter().enumerate() {
+                    if i > 0 {
+                        spans.push(Span::raw(HINT_GAP));
+                    }
+                    spans.push(Span::styled(
+                        label,
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ));
+                }
+                Line::from(spans)
+            }
+        };
+        let status = Paragraph::new(status_line)
+            .style(Style::default().fg(theme.status_fg).bg(theme.status_bg));
+
+        f.render_widget(status, area);
+    }
+
+    fn render_notification(&self, f: &mut Frame, notification: &Notification) {
+        let theme = self.config.theme;
+        let area = Rect {
+            x: f.area().width / 4,
+            y: f.area().height / 2,
+            width: f.area().width / 2,
+            height: 3,
+        };
+
+        f.render_widget(Clear, area);
+
+        let style = match notification.level {
+            Level::Info => Style::default().fg(theme.status_fg).bg(theme.status_bg),
+            Level::Warning => Style::default().fg(theme.banner_fg).bg(theme.warning),
+            Level::Error => Style::default().fg(theme.status_fg).bg(theme.error),
+        };
+        let notification = Paragraph::new(notification.message.as_str())
+            .style(style)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL));
+
+        f.render_widget(notification, area);
+    }
+}
+
+/// Right-aligns a file's `+added -removed` line counts after `spans`, which
+/// fill `width` columns at most. Nothing is added when both are zero.
+fn push_line_counts(
+    spans: &mut Vec<Span>,
+    (insertions, deletions): (usize, usize),
+    width: usize,
+    theme: &Theme,
+) {
+    if insertions + deletions == 0 {
+        return;
+    }
+    let added = format!("+{}", insertions);
+    let removed = format!("-{}", deletions);
+    let used: usize = spans.iter().map(Span::width).sum();
+    let gap = width
+        .saturating_sub(used + added.len() + 1 + removed.len())
+        .max(1);
+    spans.push(Span::raw(" ".repeat(gap)));
+    spans.push(Span::styled(added, Style::default().fg(theme.diff_add)));
+    spans.push(Span::raw(" "));
+    spans.push(Span::styled(
+        removed,
+        Style::default().fg(theme.diff_remove),
+    ));
+}
+
+/// Restyles the bytes of `line` in `range`, splitting spans where needed.
+/// The style is patched over each span's own, so colours not set in `style`
+/// show through.
+fn highlight<'a>(line: Line<'a>, range: &Range<usize>, style: Style) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut at = 0;
+    for span in line.spans {
+        let end = at + span.content.len();
+        let from = range.start.clamp(at, end) - at;
+        let to = range.end.clamp(at, end) - at;
+        if from == to {
+            spans.push(span);
+        } else {
+            let content = span.content.as_ref();
+            for (part, part_style) in [
+                (&content[..from], span.style),
+                (&content[from..to], span.style.patch(style)),
+                (&content[to..], span.style),
+            ] {
+                if !part.is_empty() {
+                    spans.push(Span::styled(part.to_string(), part_style));
+                }
+            }
+        }
+        at = end;
+    }
+    Line::from(spans).style(line.style)
+}
+
+/// Colours a unified diff line by line: added, removed, hunk headers and
+/// the file headers above them. Where a run of removed lines is followed by
+/// added ones, each pair is compared word by word and the changed words are
+/// shown reversed.
+fn diff_lines<'a>(diff: &'a str, theme: &Theme) -> Vec<Line<'a>> {
+    let lines: Vec<&str> = diff.lines().collect();
+    let is_removed = |line: &str| line.starts_with('-') && !line.starts_with("---");
+    let is_added = |line: &str| line.starts_with('+') && !line.starts_with("+++");
+
+    let mut changed = vec![Vec::new(); lines.len()];
+    let mut start = 0;
+    while start < lines.len() {
+        let removed = lines[start..].iter().take_while(|l| is_removed(l)).count();
+        let added = lines[start + removed..]
+            .iter()
+            .take_while(|l| is_added(l))
+            .count();
+        for pair in 0..removed.min(added) {
+            let (old, new) = (start + pair, start + removed + pair);
+            let (old_ranges, new_ranges) =
+                word_diff::changed_ranges(&lines[old][1..], &lines[new][1..]);
+            changed[old] = old_ranges;
+            changed[new] = new_ranges;
+        }
+        start += (removed + added).max(1);
+    }
+
+    lines
+        .into_iter()
+        .zip(changed)
+        .map(|(line, changed)| {
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                Some(theme.diff_meta)
+            } else if line.starts_with('+') {
+                Some(theme.diff_add)
+            } else if line.starts_with('-') {
+                Some(theme.diff_remove)
+            } else if line.starts_with("@@") {
+                Some(theme.diff_hunk)
+            } else if line.starts_with("diff ")
+                || line.starts_with("commit ")
+                || line.starts_with("index ")
+                || line.starts_with("new file")
+                || line.starts_with("deleted file")
+            {
+                Some(theme.diff_meta)
+            } else {
+                None
+            };
+            let Some(color) = color else {
+                return Line::raw(line);
+            };
+            let style = Style::default().fg(color);
+            if changed.is_empty() {
+                return Line::styled(line, style);
+            }
+
+            // The ranges skip the leading `+`/`-`.
+            let mut spans = vec![Span::styled(&line[..1], style)];
+            let mut at = 1;
+            for range in changed {
+                let (from, to) = (range.start + 1, range.end + 1);
+                if at < from {
+                    spans.push(Span::styled(&line[at..from], style));
+                }
+                spans.push(Span::styled(
+                    &line[from..to],
+                    style.add_modifier(Modifier::REVERSED),
+                ));
+                at = to;
+            }
+            if at < line.len() {
+                spans.push(Span::styled(&line[at..], style));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// A short age like "5m ago" for a Unix timestamp.
+fn relative_time(time: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64);
+    let seconds = (now - time).max(0);
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        86400..2592000 => format!("{}d ago", seconds / 86400),
+        2592000..31536000 => format!("{}mo ago", seconds / 2592000),
+        _ => format!("{}y ago", seconds / 31536000),
+    }
+}
+
+/// A `width` x `height` rectangle centred in `area`, clamped to fit.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
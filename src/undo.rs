@@ -0,0 +1,126 @@
+//! Undo and redo in the commit editor. Every change to the message is
+//! saved as a draft, and that is where the steps are taken: the message as
+//! it was before the change goes on the undo stack. Typing a word is one
+//! step rather than one per character.
+
+use crate::app::App;
+
+const MAX_STEPS: usize = 200;
+
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    undo: Vec<String>,
+    redo: Vec<String>,
+    /// The message as of the last change.
+    current: String,
+    /// While typing: where the last typed character ended, and whether it
+    /// was a space, so the next word starts a new step.
+    typing: Option<(usize, bool)>,
+}
+
+impl EditHistory {
+    /// Starts over from `message`, forgetting any steps.
+    pub fn new(message: &str) -> EditHistory {
+        EditHistory {
+            current: message.to_string(),
+            ..EditHistory::default()
+        }
+    }
+
+    /// Takes a step from the last message to `message`, unless it carries
+    /// on the word being typed.
+    pub fn record(&mut self, message: &str) {
+        if message == self.current {
+            return;
+        }
+        let typed = typed_char(&self.current, message);
+        let carries_on = match (self.typing, typed) {
+            (Some((end, after_space)), Some((at, c))) => {
+                end == at && (!after_space || c.is_whitespace())
+            }
+            _ => false,
+        };
+        let previous = std::mem::replace(&mut self.current, message.to_string());
+        if !carries_on {
+            self.undo.push(previous);
+            if self.undo.len() > MAX_STEPS {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.typing = typed.map(|(at, c)| (at + c.len_utf8(), c.is_whitespace()));
+    }
+
+    /// The message before the last step, if any; `message` can be redone.
+    pub fn undo(&mut self, message: &str) -> Option<String> {
+        let previous = self.undo.pop()?;
+        self.redo.push(message.to_string());
+        self.current = previous.clone();
+        self.typing = None;
+        Some(previous)
+    }
+
+    /// The message the last undo went back from, if any.
+    pub fn redo(&mut self, message: &str) -> Option<String> {
+        let next = self.redo.pop()?;
+        self.undo.push(message.to_string());
+        self.current = next.clone();
+        self.typing = None;
+        Some(next)
+    }
+}
+
+/// The character `after` adds to `before` and where, if that's the only
+/// difference.
+fn typed_char(before: &str, after: &str) -> Option<(usize, char)> {
+    let at = common_prefix(before, after);
+    let c = after[at..].chars().next()?;
+    (after.len() == before.len() + c.len_utf8() && after[at + c.len_utf8()..] == before[at..])
+        .then_some((at, c))
+}
+
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, _), _)| i)
+}
+
+/// Where the cursor goes after switching from `before` to `after`: the end
+/// of the text that changed.
+pub fn changed_end(before: &str, after: &str) -> usize {
+    let prefix = common_prefix(before, after);
+    let suffix = before[prefix..]
+        .chars()
+        .rev()
+        .zip(after[prefix..].chars().rev())
+        .take_while(|(x, y)| x == y)
+        .map(|(_, y)| y.len_utf8())
+        .sum::<usize>();
+    after.len() - suffix
+}
+
+impl App {
+    /// `Ctrl+Z` in the commit editor.
+    pub(crate) fn undo_edit(&mut self) {
+        match self.edits.undo(&self.commit_message) {
+            Some(message) => self.restore_edit(message),
+            None => self.show_notification("Nothing to undo".to_string()),
+        }
+    }
+
+    /// `Ctrl+Y` in the commit editor.
+    pub(crate) fn redo_edit(&mut self) {
+        match self.edits.redo(&self.commit_message) {
+            Some(message) => self.restore_edit(message),
+            None => self.show_notification("Nothing to redo".to_string()),
+        }
+    }
+
+    fn restore_edit(&mut self, message: String) {
+        self.history.reset();
+        self.cursor_position = changed_end(&self.commit_message, &message);
+        self.commit_message = message;
+        self.save_draft();
+    }
+}
@@ -0,0 +1,267 @@
+//! Commit message checks run live in the commit editor, similar to what
+//! commitlint enforces in a `commit-msg` hook.
+
+use crate::{breaking::BREAKING_FOOTERS, text::grapheme_count};
+use regex::Regex;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Which checks to run. A limit of `0` disables that check.
+#[derive(Debug, Clone)]
+pub struct ValidationRules {
+    /// Subjects longer than this get a warning.
+    pub subject_soft_limit: usize,
+    /// Subjects longer than this are an error.
+    pub subject_hard_limit: usize,
+    pub body_line_limit: usize,
+    pub no_trailing_period: bool,
+    pub imperative_mood: bool,
+    /// Breaking change footers are spelled `BREAKING CHANGE:` and say what broke.
+    pub breaking_footer: bool,
+    /// When set, the subject must match, e.g. a Conventional Commits pattern.
+    pub subject_pattern: Option<Regex>,
+    /// Refuse to commit while any error-level issue remains.
+    pub block_on_error: bool,
+}
+
+impl Default for ValidationRules {
+    fn default() -> ValidationRules {
+        ValidationRules {
+            subject_soft_limit: 50,
+            subject_hard_limit: 72,
+            body_line_limit: 72,
+            no_trailing_period: true,
+            imperative_mood: true,
+            breaking_footer: true,
+            subject_pattern: None,
+            block_on_error: false,
+        }
+    }
+}
+
+/// The Conventional Commits subject format: `type(scope)!: description`.
+pub fn conventional_commit_pattern() -> Regex {
+    Regex::new(
+        r"^(feat|fix|docs|style|refactor|perf|test|build|ci|chore|revert)(\([^()\s]+\))?!?: \S",
+    )
+    .expect("valid regex")
+}
+
+/// Checks `message` against `rules`, errors first.
+pub fn validate(message: &str, rules: &ValidationRules) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or("").trim_end();
+    if subject.is_empty() {
+        return issues;
+    }
+
+    let length = grapheme_count(subject);
+    if rules.subject_hard_limit > 0 && length > rules.subject_hard_limit {
+        issues.push(error(format!(
+            "Subject is {} characters (max {})",
+            length, rules.subject_hard_limit
+        )));
+    } else if rules.subject_soft_limit > 0 && length > rules.subject_soft_limit {
+        issues.push(warning(format!(
+            "Subject is {} characters (aim for {})",
+            length, rules.subject_soft_limit
+        )));
+    }
+
+    if let Some(pattern) = &rules.subject_pattern
+        && !pattern.is_match(subject)
+    {
+        issues.push(error(format!(
+            "Subject doesn't match the required format ({})",
+            pattern.as_str()
+        )));
+    }
+
+    if rules.no_trailing_period && subject.ends_with('.') {
+        issues.push(warning("Subject ends with a period".to_string()));
+    }
+
+    if rules.imperative_mood
+        && let Some(word) = first_word(subject)
+        && !is_imperative(word)
+    {
+        issues.push(warning(format!(
+            "Use the imperative mood (\"{}\" → e.g. \"Add\", \"Fix\")",
+            word
+        )));
+    }
+
+    if let Some(second) = lines.next()
+        && !second.trim().is_empty()
+    {
+        issues.push(warning(
+            "Separate the subject from the body with a blank line".to_string(),
+        ));
+    }
+
+    if rules.breaking_footer {
+        check_breaking_footer(message, &mut issues);
+    }
+
+    if rules.body_line_limit > 0 {
+        let long_lines = message
+            .lines()
+            .skip(1)
+            .filter(|line| grapheme_count(line) > rules.body_line_limit)
+            .count();
+        if long_lines > 0 {
+            issues.push(warning(format!(
+                "{} body line(s) longer than {} characters",
+                long_lines, rules.body_line_limit
+            )));
+        }
+    }
+
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    issues
+}
+
+/// The byte ranges of `message` past the length limits, for colouring in
+/// the editor: the subject past the soft limit is a warning and past the
+/// hard limit an error, body lines past theirs a warning.
+pub fn overflow(message: &str, rules: &ValidationRules) -> Vec<(Range<usize>, Severity)> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, line) in message.split('\n').enumerate() {
+        let past = |limit: usize| match limit {
+            0 => None,
+            _ => line
+                .grapheme_indices(true)
+                .nth(limit)
+                .map(|(offset, _)| start + offset),
+        };
+        let end = start + line.len();
+        if i == 0 {
+            let hard = past(rules.subject_hard_limit);
+            if let Some(soft) = past(rules.subject_soft_limit) {
+                ranges.push((soft..hard.unwrap_or(end), Severity::Warning));
+            }
+            if let Some(hard) = hard {
+                ranges.push((hard..end, Severity::Error));
+            }
+        } else if let Some(past) = past(rules.body_line_limit) {
+            ranges.push((past..end, Severity::Warning));
+        }
+        start = end + 1;
+    }
+    ranges.retain(|(range, _)| !range.is_empty());
+    ranges
+}
+
+/// A misspelt `BREAKING CHANGE:` footer is not recognised by changelog
+/// tools, and an empty one is a placeholder nobody filled in.
+fn check_breaking_footer(message: &str, issues: &mut Vec<Issue>) {
+    let lines: Vec<&str> = message.lines().skip(1).collect();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(rest) = BREAKING_FOOTERS
+            .iter()
+            .find_map(|token| line.strip_prefix(token))
+        else {
+            let upper = line.to_uppercase();
+            if BREAKING_FOOTERS
+                .iter()
+                .any(|token| upper.starts_with(token))
+            {
+                issues.push(warning(
+                    "Write the breaking change footer as \"BREAKING CHANGE:\"".to_string(),
+                ));
+            }
+            continue;
+        };
+        let continued = lines.get(i + 1).is_some_and(|next| !next.trim().is_empty());
+        if rest.trim().is_empty() && !continued {
+            issues.push(error(
+                "Describe the breaking change after \"BREAKING CHANGE:\"".to_string(),
+            ));
+        }
+    }
+}
+
+fn error(message: String) -> Issue {
+    Issue {
+        severity: Severity::Error,
+        message,
+    }
+}
+
+fn warning(message: String) -> Issue {
+    Issue {
+        severity: Severity::Warning,
+        message,
+    }
+}
+
+/// The first word of the description, skipping any `type(scope):` prefix.
+fn first_word(subject: &str) -> Option<&str> {
+    let description = match subject.split_once(": ") {
+        Some((prefix, rest)) if !prefix.contains(' ') => rest,
+        _ => subject,
+    };
+    description.split_whitespace().next()
+}
+
+/// Verbs that commonly open a subject, used to catch "Adds" and "Added"
+/// without flagging words like "Process" or "Embed".
+const COMMON_VERBS: &[&str] = &[
+    "add",
+    "allow",
+    "bump",
+    "change",
+    "clean",
+    "create",
+    "drop",
+    "enable",
+    "disable",
+    "fix",
+    "handle",
+    "implement",
+    "improve",
+    "make",
+    "merge",
+    "move",
+    "refactor",
+    "remove",
+    "rename",
+    "replace",
+    "revert",
+    "show",
+    "support",
+    "update",
+    "use",
+];
+
+fn is_imperative(word: &str) -> bool {
+    let word = word.to_lowercase();
+    if COMMON_VERBS.contains(&word.as_str()) {
+        return true;
+    }
+    // "Adds", "Fixes", "Added", "Updated", "Making": a known verb plus an
+    // inflection, allowing for a dropped trailing "e".
+    let is_verb = |stem: &str| {
+        COMMON_VERBS
+            .iter()
+            .any(|verb| *verb == stem || verb.strip_suffix('e') == Some(stem))
+    };
+    !["es", "s", "ed", "d", "ing"]
+        .iter()
+        .filter_map(|suffix| word.strip_suffix(suffix))
+        .any(is_verb)
+}
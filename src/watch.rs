@@ -0,0 +1,113 @@
+//! Automatic status refresh: the working tree and `.git` directory are
+//! watched, and a refresh follows once changes have been quiet for a moment.
+
+use crate::app::App;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+pub struct Watcher {
+    // Dropping it stops the watch.
+    _watcher: RecommendedWatcher,
+    events: Receiver<()>,
+    last_change: Option<Instant>,
+    debounce: Duration,
+}
+
+impl Watcher {
+    pub fn new(work_dir: &Path, git_dir: &Path, debounce: Duration) -> Result<Watcher, String> {
+        let (sender, events) = mpsc::channel();
+        let watched_git_dir = git_dir.to_path_buf();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event
+                && !matches!(event.kind, EventKind::Access(_))
+                && event
+                    .paths
+                    .iter()
+                    .any(|path| is_relevant(path, &watched_git_dir))
+            {
+                let _ = sender.send(());
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+        watcher
+            .watch(work_dir, RecursiveMode::Recursive)
+            .map_err(|e| e.to_string())?;
+        // Worktrees and `--git-dir` setups keep it outside the tree.
+        if !git_dir.starts_with(work_dir) {
+            watcher
+                .watch(git_dir, RecursiveMode::Recursive)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(Watcher {
+            _watcher: watcher,
+            events,
+            last_change: None,
+            debounce,
+        })
+    }
+
+    /// True once something changed and then nothing more did for the
+    /// debounce period.
+    pub fn poll(&mut self) -> bool {
+        while self.events.try_recv().is_ok() {
+            self.last_change = Some(Instant::now());
+        }
+        match self.last_change {
+            Some(time) if time.elapsed() >= self.debounce => {
+                self.last_change = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether a change to `path` can affect the status. Inside the git
+/// directory only the index, `HEAD`s, and refs count, which skips object
+/// writes, lock files, and the app's own state files.
+pub fn is_relevant(path: &Path, git_dir: &Path) -> bool {
+    let Ok(inside) = path.strip_prefix(git_dir) else {
+        return true;
+    };
+    let name = inside.to_string_lossy();
+    if name.ends_with(".lock") {
+        return false;
+    }
+    name == "index" || (name.ends_with("HEAD") && !name.contains('/')) || name.starts_with("refs")
+}
+
+impl App {
+    /// Starts watching the repository, if the config asks for it.
+    pub fn start_watcher(&mut self) {
+        let Some(debounce) = self.config.watch else {
+            return;
+        };
+        let (Some(work_dir), Some(git_dir)) = (self.backend.work_dir(), &self.git_dir) else {
+            return;
+        };
+        match Watcher::new(&work_dir, git_dir, debounce) {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => self.show_warning(format!(
+                "Not watching for changes ({}); press r to refresh",
+                e
+            )),
+        }
+    }
+
+    pub(crate) fn poll_watcher(&mut self) {
+        // Changes made while a task runs wait for it to finish.
+        if self.task.is_some() {
+            return;
+        }
+        if let Some(watcher) = &mut self.watcher
+            && watcher.poll()
+        {
+            self.refresh_git_status();
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Quick work-in-progress commits for switching branches in a hurry:
+//! `Ctrl+W` commits everything as `WIP: <branch>`, and `Ctrl+U` puts the
+//! last WIP commit back into the working tree.
+
+use crate::{
+    app::{App, TaskKind},
+    confirm::PendingAction,
+    git::CommitOptions,
+    report::GitError,
+};
+use std::sync::Arc;
+
+/// The subject every WIP commit starts with.
+pub const WIP_PREFIX: &str = "WIP:";
+
+pub fn wip_message(branch: &str) -> String {
+    format!("{} {}", WIP_PREFIX, branch)
+}
+
+pub fn is_wip(subject: &str) -> bool {
+    subject.starts_with(WIP_PREFIX)
+}
+
+impl App {
+    /// `Ctrl+W` in the file list: stages everything and commits it,
+    /// unsigned so no passphrase holds it up.
+    pub(crate) fn wip_commit(&mut self) {
+        if self.all_files.is_empty() {
+            self.show_notification("Nothing to commit: the working tree is clean".to_string());
+            return;
+        }
+        if self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        let backend = Arc::clone(&self.backend);
+        let message = wip_message(&self.git_status.current_branch);
+        let options = CommitOptions {
+            no_verify: self.config.wip_no_verify,
+            sign: Some(false),
+            ..CommitOptions::default()
+        };
+        self.start_task(TaskKind::Wip, move |cancel| {
+            backend.stage_all()?;
+            backend.commit(&message, &options, cancel)
+        });
+    }
+
+    pub(crate) fn finish_wip(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(_) => self.show_notification(format!(
+                "Committed {}; Ctrl+U puts it back",
+                wip_message(&self.git_status.current_branch)
+            )),
+            Err(error) => self.report_error(GitError::new("WIP commit", error)),
+        }
+    }
+
+    /// `Ctrl+U` in the file list, when HEAD is a WIP commit; asks first if
+    /// it has been pushed.
+    pub(crate) fn confirm_unwip(&mut self) {
+        if self.task.is_some() || self.git_status.operation.is_some() {
+            self.show_notification("Finish the current operation first".to_string());
+            return;
+        }
+        let Some(head) = &self.git_status.head else {
+            self.show_notification("No commit to put back".to_string());
+            return;
+        };
+        if !is_wip(&head.subject) {
+            self.show_notification(format!(
+                "The last commit isn't a WIP commit (\"{}\"); u undoes any commit",
+                head.subject
+            ));
+            return;
+        }
+        if self.git_status.upstream.is_some() && self.git_status.ahead == 0 {
+            let message = format!(
+                "{} \"{}\" has already been pushed. Put it back anyway?",
+                head.short_id, head.subject
+            );
+            self.ask("Un-WIP", message, PendingAction::Unwip);
+            return;
+        }
+        self.unwip();
+    }
+
+    /// Soft-resets the WIP commit and unstages its changes, leaving the
+    /// working tree as it was before `Ctrl+W`.
+    pub(crate) fn unwip(&mut self) {
+        let result = self
+            .backend
+            .undo_last_commit()
+            .and_then(|_| self.backend.unstage_all());
+        match result {
+            Ok(()) => {
+                self.show_notification("WIP commit put back into the working tree".to_string())
+            }
+            Err(error) => self.report_error(GitError::new("Un-WIP", error)),
+        }
+        self.refresh_git_status();
+    }
+}
@@ -0,0 +1,85 @@
+//! Word-level differences between a removed line and the added line that
+//! replaced it, so a diff can highlight just the tokens that changed.
+
+use std::ops::Range;
+
+/// Lines longer than this in tokens are left without word highlighting; the
+/// comparison is quadratic.
+const MAX_TOKENS: usize = 300;
+
+/// Splits `line` into words, runs of whitespace, and single punctuation
+/// characters, as byte ranges.
+fn tokens(line: &str) -> Vec<Range<usize>> {
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let mut last_kind = None;
+    for (index, c) in line.char_indices() {
+        let kind = if c.is_alphanumeric() || c == '_' {
+            Some(0)
+        } else if c.is_whitespace() {
+            Some(1)
+        } else {
+            None
+        };
+        match tokens.last_mut() {
+            Some(token) if kind.is_some() && kind == last_kind => token.end = index + c.len_utf8(),
+            _ => tokens.push(index..index + c.len_utf8()),
+        }
+        last_kind = kind;
+    }
+    tokens
+}
+
+/// The byte ranges of `old` and of `new` that aren't shared between them,
+/// with neighbouring ranges merged. Both are empty when the lines have
+/// nothing in common but whitespace, since highlighting everything says no
+/// more than the line colour does.
+pub fn changed_ranges(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokens(old);
+    let new_tokens = tokens(new);
+    if old_tokens.len() > MAX_TOKENS || new_tokens.len() > MAX_TOKENS {
+        return (Vec::new(), Vec::new());
+    }
+
+    // Longest common subsequence of tokens, filled from the end.
+    let (n, m) = (old_tokens.len(), new_tokens.len());
+    let mut lengths = vec![vec![0u16; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut old_changed = Vec::new();
+    let mut new_changed = Vec::new();
+    let mut shared_word = false;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+            shared_word |= !old[old_tokens[i].clone()].trim().is_empty();
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            push_merged(&mut new_changed, new_tokens[j].clone());
+            j += 1;
+        } else {
+            push_merged(&mut old_changed, old_tokens[i].clone());
+            i += 1;
+        }
+    }
+    if shared_word {
+        (old_changed, new_changed)
+    } else {
+        (Vec::new(), Vec::new())
+    }
+}
+
+fn push_merged(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
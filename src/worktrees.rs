@@ -0,0 +1,162 @@
+//! The worktrees panel: every working tree of the repository, switching the
+//! tool to another one, and adding one for a branch.
+
+use crate::{
+    app::{App, AppMode},
+    git::WorktreeInfo,
+    report::GitError,
+    text,
+};
+use crossterm::event::KeyCode;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+pub struct WorktreesView {
+    pub worktrees: Vec<WorktreeInfo>,
+    pub selected: usize,
+    /// The branch being typed after `n`.
+    pub branch_input: Option<String>,
+}
+
+/// Where a new worktree for `branch` goes: beside the main one, named after
+/// both, e.g. `../gch-feature-login` for `feature/login`.
+pub fn worktree_path(main: &Path, branch: &str) -> PathBuf {
+    let name = main
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let dir = format!("{}-{}", name, branch.replace('/', "-"));
+    main.parent().unwrap_or(main).join(dir)
+}
+
+/// Whether `a` and `b` are the same directory, however they are spelled.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+impl App {
+    /// `W` in the file list.
+    pub(crate) fn open_worktrees(&mut self) {
+        if self.reload_worktrees() {
+            let current = self.backend.work_dir();
+            let view = &mut self.worktrees;
+            view.selected = view
+                .worktrees
+                .iter()
+                .position(|w| current.as_deref().is_some_and(|dir| same_dir(&w.path, dir)))
+                .unwrap_or(0);
+            view.branch_input = None;
+            self.mode = AppMode::Worktrees;
+        }
+    }
+
+    fn reload_worktrees(&mut self) -> bool {
+        match self.backend.worktrees() {
+            Ok(worktrees) => {
+                self.worktrees.selected = self
+                    .worktrees
+                    .selected
+                    .min(worktrees.len().saturating_sub(1));
+                self.worktrees.worktrees = worktrees;
+                true
+            }
+            Err(error) => {
+                self.report_error(GitError::new("Listing worktrees", error));
+                false
+            }
+        }
+    }
+
+    /// Whether `worktree` is the one the tool has open.
+    pub(crate) fn is_current_worktree(&self, worktree: &WorktreeInfo) -> bool {
+        self.backend
+            .work_dir()
+            .is_some_and(|dir| same_dir(&worktree.path, &dir))
+    }
+
+    pub(crate) fn handle_worktrees_input(&mut self, key: KeyCode) {
+        if self.worktrees.branch_input.is_some() {
+            self.handle_worktree_branch_input(key);
+            return;
+        }
+        let view = &mut self.worktrees;
+        let selected = view.worktrees.get(view.selected).cloned();
+        match (key, selected) {
+            (KeyCode::Esc | KeyCode::Char('q'), _) => self.mode = AppMode::FileList,
+            (KeyCode::Down | KeyCode::Char('j'), _) if view.selected + 1 < view.worktrees.len() => {
+                view.selected += 1;
+            }
+            (KeyCode::Up | KeyCode::Char('k'), _) => {
+                view.selected = view.selected.saturating_sub(1);
+            }
+            (KeyCode::Char('n'), _) => view.branch_input = Some(String::new()),
+            (KeyCode::Enter, Some(worktree)) => self.switch_worktree(&worktree),
+            _ => {}
+        }
+    }
+
+    fn handle_worktree_branch_input(&mut self, key: KeyCode) {
+        let Some(input) = &mut self.worktrees.branch_input else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.worktrees.branch_input = None,
+            KeyCode::Enter => {
+                let branch = input.trim().to_string();
+                self.worktrees.branch_input = None;
+                if !branch.is_empty() {
+                    self.add_worktree(&branch);
+                }
+            }
+            KeyCode::Backspace => {
+                let end = text::prev_boundary(input, input.len());
+                input.truncate(end);
+            }
+            // Branch names can't contain spaces.
+            KeyCode::Char(' ') => {}
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    fn switch_worktree(&mut self, worktree: &WorktreeInfo) {
+        if self.is_current_worktree(worktree) {
+            self.mode = AppMode::FileList;
+        } else if worktree.bare || worktree.prunable {
+            self.show_notification(format!("{} has no files to open", worktree.path.display()));
+        } else if self.task.is_some() {
+            self.show_notification("Wait for the running operation to finish".to_string());
+        } else {
+            self.open_repo(&worktree.path);
+        }
+    }
+
+    /// Adds a worktree for `branch` beside the main one and selects it.
+    fn add_worktree(&mut self, branch: &str) {
+        let Some(main) = self.worktrees.worktrees.first().map(|w| w.path.clone()) else {
+            return;
+        };
+        let path = worktree_path(&main, branch);
+        match self.backend.add_worktree(&path, branch) {
+            Ok(()) => {
+                self.reload_worktrees();
+                if let Some(index) = self
+                    .worktrees
+                    .worktrees
+                    .iter()
+                    .position(|w| same_dir(&w.path, &path))
+                {
+                    self.worktrees.selected = index;
+                }
+                self.show_notification(format!("Added {}; Enter to switch to it", path.display()));
+            }
+            Err(error) => self.report_error(GitError::new(
+                format!("Adding a worktree for {}", branch),
+                error,
+            )),
+        }
+    }
+}
@@ -0,0 +1,2427 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text, wait_for_task};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use git_commit_helper::{
+    App, AppMode, Config, DiffOptions, External, FileStatus, Focus, GitFile, HeadCommit, Level,
+    MessageProvider, Operation, ReflogEntry, SignatureState, SigningConfig, SplitLayout, TagInfo,
+    diff_nav::file_line_at, ticket::jira_ticket_pattern, trailers::Trailer,
+};
+use ratatui::{Terminal, backend::TestBackend, style::Modifier};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn renders_branch_and_files() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[
+        ("src/lib.rs", FileStatus::Modified, false),
+        ("README.md", FileStatus::Added, true),
+    ]));
+
+    let screen = render(&mut app, 80, 10);
+
+    assert!(screen.contains("Branch: main"));
+    assert!(screen.contains("○ M src/lib.rs"));
+    assert!(screen.contains("● A README.md"));
+}
+
+#[test]
+fn header_shows_head_commit_stashes_and_operation() {
+    let backend = MockBackend {
+        head: Some(HeadCommit {
+            short_id: "1a2b3c4".to_string(),
+            subject: "Add parser".to_string(),
+        }),
+        stash_count: 2,
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Conflicted, false)])
+    };
+    *backend.operation.lock().unwrap() = Some(Operation::Rebase);
+    let (mut app, _) = app_with(backend);
+
+    let screen = render(&mut app, 100, 10);
+    let border = screen.lines().nth(2).unwrap();
+    assert!(border.contains("HEAD 1a2b3c4 Add parser · 2 stashes · rebase in progress"));
+
+    let (mut app, _) = app_with(MockBackend {
+        branch: String::new(),
+        head: Some(HeadCommit {
+            short_id: "1a2b3c4".to_string(),
+            subject: "Add parser".to_string(),
+        }),
+        ..MockBackend::default()
+    });
+    let screen = render(&mut app, 100, 10);
+    assert!(screen.contains("Branch: (detached at 1a2b3c4)"));
+    assert!(screen.contains("HEAD detached at 1a2b3c4 Add parser"));
+    assert!(!screen.contains("no upstream"));
+}
+
+#[test]
+fn detached_head_shows_nearest_tag_and_pushes_after_branching() {
+    let (mut app, backend) = app_with(
+        MockBackend {
+            branch: String::new(),
+            head: Some(HeadCommit {
+                short_id: "1a2b3c4".to_string(),
+                subject: "Add parser".to_string(),
+            }),
+            describe: Some("v1.2.0-3-g1a2b3c4".to_string()),
+            ..MockBackend::default()
+        }
+        .with_remotes(&["origin"]),
+    );
+    let screen = render(&mut app, 100, 10);
+    assert!(screen.contains("Branch: (detached at v1.2.0-3-g1a2b3c4)"));
+    assert!(screen.contains("[b] branch here"));
+
+    app.handle_input(KeyCode::Char('B'));
+    assert!(render(&mut app, 100, 10).contains("b to create a branch here"));
+    assert!(backend.calls().is_empty());
+    app.notification = None;
+
+    app.handle_input(KeyCode::Char('p'));
+    assert_eq!(app.mode, AppMode::NewBranch);
+    assert!(
+        render(&mut app, 100, 10).contains("HEAD is detached; name a branch at 1a2b3c4 to push")
+    );
+    type_text(&mut app, "fix login");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(backend.calls(), ["switch -c fixlogin"]);
+    assert_eq!(app.mode, AppMode::PushDialog);
+    assert_eq!(app.push_dialog.options.branch, "fixlogin");
+}
+
+#[test]
+fn b_creates_a_branch_at_head() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+    app.handle_input(KeyCode::Char('b'));
+    type_text(&mut app, "topic");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(backend.calls(), ["switch -c topic"]);
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(app.git_status.current_branch, "topic");
+    assert!(render(&mut app, 100, 10).contains("Switched to new branch topic"));
+
+    app.handle_input(KeyCode::Char('b'));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(backend.calls().len(), 1);
+}
+
+#[test]
+fn space_toggles_staging_of_selected_file() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("b.txt", FileStatus::Modified, false),
+    ]));
+
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Char(' '));
+    assert!(app.files[1].staged);
+
+    app.handle_input(KeyCode::Char(' '));
+    assert!(!app.files[1].staged);
+    assert_eq!(backend.calls(), ["stage b.txt", "unstage b.txt"]);
+}
+
+#[test]
+fn commit_requires_staged_files() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+
+    app.handle_input(KeyCode::Char('c'));
+
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(render(&mut app, 80, 12).contains("No staged files to commit"));
+}
+
+#[test]
+fn empty_commit_is_offered_with_nothing_staged() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]));
+    app.handle_input(KeyCode::Char('c'));
+    assert!(app.confirm.is_some());
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(render(&mut app, 100, 12).contains("[allow-empty]"));
+
+    type_text(&mut app, "ci: rerun");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["commit --allow-empty ci: rerun"]);
+    assert!(!app.commit_options.allow_empty);
+}
+
+#[test]
+fn dry_run_previews_the_commit_without_making_it() {
+    let git_dir = std::env::temp_dir().join(format!("gch-dry-run-{}", std::process::id()));
+    std::fs::create_dir_all(git_dir.join("hooks")).unwrap();
+    std::fs::write(git_dir.join("hooks/commit-msg"), "#!/bin/sh\n").unwrap();
+    std::fs::write(git_dir.join("hooks/post-commit"), "#!/bin/sh\n").unwrap();
+    std::fs::write(git_dir.join("hooks/pre-push.sample"), "#!/bin/sh\n").unwrap();
+    #[cfg(unix)]
+    for hook in ["commit-msg", "post-commit"] {
+        use std::os::unix::fs::PermissionsExt;
+        let hook = git_dir.join("hooks").join(hook);
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let (mut app, backend) = app_with(MockBackend {
+        git_dir: Some(git_dir.clone()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)])
+    });
+    app.config.checks = vec!["cargo fmt --check".to_string()];
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    app.trailers.custom.push(Trailer {
+        key: "Refs".to_string(),
+        value: "#12".to_string(),
+    });
+
+    app.handle_input(KeyCode::F(7));
+    assert_eq!(app.mode, AppMode::Output);
+    assert_eq!(app.output.title, "Dry run");
+    let text = &app.output.text;
+    assert!(text.contains("Message:\n    Add a\n    \n    Refs: #12\n"));
+    assert!(text.contains("Hooks: commit-msg, post-commit"));
+    assert!(text.contains("Check: cargo fmt --check"));
+    assert!(text.contains("    M  a.txt"));
+    assert_eq!(backend.calls(), ["commit --dry-run Add a\n\nRefs: #12"]);
+
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+
+    // post-commit runs even with --no-verify.
+    app.commit_options.no_verify = true;
+    app.handle_input(KeyCode::F(7));
+    let text = &app.output.text;
+    assert!(text.contains("Hooks: post-commit ("));
+    assert!(text.contains("Skipped (--no-verify): the pre-commit and commit-msg hooks, checks"));
+    assert!(!text.contains("Check:"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.commit_message, "Add a");
+    std::fs::remove_dir_all(git_dir).unwrap();
+}
+
+#[test]
+fn commit_message_starts_from_the_template() {
+    let git_dir = std::env::temp_dir().join(format!("gch-template-{}", std::process::id()));
+    std::fs::create_dir_all(&git_dir).unwrap();
+    let template = git_dir.join("gitmessage");
+    std::fs::write(&template, "\n\nRefs: \n# Say why, not what.\n").unwrap();
+    let (mut app, _) = app_with(MockBackend {
+        git_dir: Some(git_dir.clone()),
+        commit_template: Some(template),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)])
+    });
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.commit_message, "\n\nRefs:");
+    assert_eq!(app.cursor_position, 0);
+
+    std::fs::write(
+        git_dir.join("MERGE_MSG"),
+        "Merge branch 'login'\n\n# Conflicts:\n#\ta.txt\n",
+    )
+    .unwrap();
+    app.commit_message.clear();
+    app.mode = AppMode::FileList;
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.commit_message, "Merge branch 'login'");
+    std::fs::remove_dir_all(git_dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn prepare_commit_msg_hook_fills_the_message() {
+    use std::os::unix::fs::PermissionsExt;
+    let git_dir = std::env::temp_dir().join(format!("gch-prepare-{}", std::process::id()));
+    std::fs::create_dir_all(git_dir.join("hooks")).unwrap();
+    std::fs::write(git_dir.join("SQUASH_MSG"), "Squashed commit\n").unwrap();
+    let hook = git_dir.join("hooks/prepare-commit-msg");
+    std::fs::write(
+        &hook,
+        "#!/bin/sh\nprintf '\\nSource: %s\\n' \"$2\" >> \"$1\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let (mut app, _) = app_with(MockBackend {
+        git_dir: Some(git_dir.clone()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)])
+    });
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.commit_message, "Squashed commit\n\nSource: squash");
+    assert_eq!(app.cursor_position, "Squashed commit".len());
+    std::fs::remove_dir_all(git_dir).unwrap();
+}
+
+#[test]
+fn commit_editor_edits_by_word_and_line() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    app.commit_message.clear();
+    app.cursor_position = 0;
+    type_text(&mut app, "fix: handle the empty-diff case");
+    let key = |app: &mut App, code, modifiers| app.handle_key(KeyEvent::new(code, modifiers));
+
+    key(&mut app, KeyCode::Backspace, KeyModifiers::ALT);
+    assert_eq!(app.commit_message, "fix: handle the empty-diff ");
+    key(&mut app, KeyCode::Char('w'), KeyModifiers::CONTROL);
+    assert_eq!(app.commit_message, "fix: handle the ");
+    key(&mut app, KeyCode::Left, KeyModifiers::CONTROL);
+    key(&mut app, KeyCode::Left, KeyModifiers::CONTROL);
+    assert_eq!(app.cursor_position, "fix: ".len());
+    key(&mut app, KeyCode::Right, KeyModifiers::CONTROL);
+    assert_eq!(app.cursor_position, "fix: handle".len());
+    key(&mut app, KeyCode::Char('k'), KeyModifiers::CONTROL);
+    assert_eq!(app.commit_message, "fix: handle");
+
+    type_text(&mut app, "\nBody line");
+    key(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+    assert_eq!(app.commit_message, "fix: handle\n");
+    assert_eq!(app.cursor_position, app.commit_message.len());
+}
+
+#[test]
+fn commit_editor_undoes_and_redoes_typing_prefixes_and_paste() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    let start = app.commit_message.clone();
+    let key =
+        |app: &mut App, c| app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+
+    type_text(&mut app, "handle empty diffs");
+    app.handle_input(KeyCode::Backspace);
+    app.handle_paste("s\nSkip the hunk header.");
+    let pasted = format!("{start}handle empty diffs\n\nSkip the hunk header.");
+    assert_eq!(app.commit_message, pasted);
+
+    key(&mut app, 'z');
+    assert_eq!(app.commit_message, format!("{start}handle empty diff"));
+    assert_eq!(app.cursor_position, app.commit_message.len());
+    key(&mut app, 'z');
+    assert_eq!(app.commit_message, format!("{start}handle empty diffs"));
+    key(&mut app, 'z');
+    assert_eq!(app.commit_message, format!("{start}handle empty "));
+    key(&mut app, 'z');
+    key(&mut app, 'z');
+    assert_eq!(app.commit_message, start);
+    key(&mut app, 'z');
+    assert_eq!(app.commit_message, start);
+
+    key(&mut app, 'y');
+    key(&mut app, 'y');
+    assert_eq!(app.commit_message, format!("{start}handle empty "));
+    key(&mut app, 'u');
+    type_text(&mut app, "feat: handle empty");
+    app.handle_input(KeyCode::Tab);
+    type_text(&mut app, "fix");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.commit_message, "fix: handle empty");
+    key(&mut app, 'y');
+    assert_eq!(app.commit_message, "fix: handle empty");
+    key(&mut app, 'z');
+    assert_eq!(app.commit_message, "feat: handle empty");
+    assert_eq!(app.cursor_position, "feat".len());
+    key(&mut app, 'y');
+    assert_eq!(app.commit_message, "fix: handle empty");
+}
+
+#[test]
+fn long_message_wraps_and_scrolls_with_the_cursor() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    app.commit_message.clear();
+    app.cursor_position = 0;
+    type_text(&mut app, "fix: wrap long messages");
+    for n in 1..=6 {
+        type_text(
+            &mut app,
+            &format!("\n\nParagraph {} explains the change in words.", n),
+        );
+    }
+    let cursor = |app: &mut App| {
+        let mut terminal = Terminal::new(TestBackend::new(40, 20)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let position = terminal.get_cursor_position().unwrap();
+        let screen = render(app, 40, 20);
+        (position.x, position.y, screen)
+    };
+
+    // The last paragraph wraps before "words", and the view follows it.
+    let (x, y, screen) = cursor(&mut app);
+    let lines: Vec<&str> = screen.lines().collect();
+    assert_eq!(
+        lines[y as usize - 1],
+        "│Paragraph 6 explains the change in    │"
+    );
+    assert_eq!(
+        lines[y as usize],
+        "│words.                                │"
+    );
+    assert_eq!(x, 1 + "words.".len() as u16);
+    assert!(!screen.contains("fix: wrap"));
+    assert!(screen.contains("[9-19/19]"));
+
+    // Back at the start, the view scrolls up to the subject.
+    app.cursor_position = 4;
+    let (x, y, screen) = cursor(&mut app);
+    assert!(
+        screen
+            .lines()
+            .nth(y as usize)
+            .unwrap()
+            .contains("fix: wrap long messages")
+    );
+    assert_eq!(x, 5);
+}
+
+#[test]
+fn commit_runs_in_background_and_clears_message() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    type_text(&mut app, "fix: typo");
+    app.handle_input(KeyCode::Enter);
+    assert!(app.task.is_some());
+    wait_for_task(&mut app);
+
+    assert_eq!(backend.calls(), ["commit fix: typo"]);
+    assert!(app.commit_message.is_empty());
+    assert!(app.files.is_empty());
+}
+
+#[test]
+fn failed_commit_keeps_message() {
+    let (mut app, _) = app_with(MockBackend {
+        commit_error: Some("hook rejected".to_string()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)])
+    });
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "wip");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert_eq!(app.commit_message, "wip");
+    assert!(render(&mut app, 80, 12).contains("Commit failed: hook rejected"));
+}
+
+#[test]
+fn hook_output_opens_scrollable_pane_and_no_verify_is_passed() {
+    let hook_output: String = (1..=30).map(|i| format!("lint error {i}\n")).collect();
+    let (mut app, backend) = app_with(MockBackend {
+        commit_error: Some(hook_output),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)])
+    });
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert_eq!(app.mode, AppMode::Output);
+    let screen = render(&mut app, 80, 12);
+    assert!(screen.contains("Commit failed (1/30)"));
+    assert!(screen.contains("lint error 1"));
+    app.handle_input(KeyCode::Char('G'));
+    assert!(render(&mut app, 80, 12).contains("lint error 30"));
+
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('c'));
+    app.handle_input(KeyCode::F(2));
+    assert!(render(&mut app, 80, 12).contains("[--no-verify]"));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls().last().unwrap(), "commit --no-verify Add a");
+}
+
+#[test]
+fn esc_cancels_running_push() {
+    let (mut app, backend) = app_with(MockBackend {
+        push_hangs: true,
+        upstream: Some("origin/main".to_string()),
+        ..MockBackend::with_files(&[]).with_remotes(&["origin"])
+    });
+
+    app.handle_input(KeyCode::Char('p'));
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 80, 12).contains("Pushing..."));
+    app.handle_input(KeyCode::Esc);
+    wait_for_task(&mut app);
+
+    assert_eq!(backend.calls(), ["push origin main"]);
+    assert!(render(&mut app, 80, 12).contains("Pushing cancelled"));
+}
+
+#[test]
+fn pull_menu_runs_chosen_strategy() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]));
+
+    app.handle_input(KeyCode::Char('P'));
+    assert_eq!(app.mode, AppMode::PullMenu);
+    app.handle_input(KeyCode::Char('r'));
+    wait_for_task(&mut app);
+
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(backend.calls(), ["pull --rebase"]);
+}
+
+#[test]
+fn pull_conflicts_are_reported() {
+    let (mut app, _) = app_with(MockBackend {
+        pull_error: Some("CONFLICT (content): Merge conflict in a.txt".to_string()),
+        ..MockBackend::with_files(&[])
+    });
+
+    app.handle_input(KeyCode::Char('P'));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert!(render(&mut app, 100, 12).contains("1 conflicting file(s)"));
+}
+
+#[test]
+fn push_dialog_sets_upstream_for_new_branch() {
+    let (mut app, backend) =
+        app_with(MockBackend::with_files(&[]).with_remotes(&["origin", "upstream"]));
+
+    app.handle_input(KeyCode::Char('p'));
+    assert_eq!(app.mode, AppMode::PushDialog);
+    app.handle_input(KeyCode::Tab);
+    app.handle_input(KeyCode::Char('t'));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert_eq!(
+        backend.calls(),
+        ["push --set-upstream --follow-tags upstream main"]
+    );
+}
+
+#[test]
+fn push_dialog_pushes_to_another_branch_name() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]).with_remotes(&["origin"]));
+
+    app.handle_input(KeyCode::Char('p'));
+    assert!(render(&mut app, 100, 20).contains("main → origin/main"));
+    app.handle_input(KeyCode::Char('b'));
+    for _ in 0.."main".len() {
+        app.handle_input(KeyCode::Backspace);
+    }
+    type_text(&mut app, "release");
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 100, 20).contains("main → origin/release"));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert_eq!(backend.calls(), ["push --set-upstream origin main:release"]);
+}
+
+#[test]
+fn branch_without_upstream_is_flagged_and_published_with_one_key() {
+    let (mut app, backend) =
+        app_with(MockBackend::with_files(&[]).with_remotes(&["fork", "origin"]));
+
+    assert!(render(&mut app, 120, 20).contains("no upstream (B to publish)"));
+    app.handle_input(KeyCode::Char('B'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["push --set-upstream origin main"]);
+
+    let (mut app, backend) = app_with(MockBackend {
+        upstream: Some("origin/main".to_string()),
+        ..MockBackend::with_files(&[]).with_remotes(&["origin"])
+    });
+    assert!(!render(&mut app, 120, 20).contains("no upstream"));
+    app.handle_input(KeyCode::Char('B'));
+    assert!(app.task.is_none());
+    assert!(render(&mut app, 120, 20).contains("Already tracking origin/main"));
+    assert!(backend.calls().is_empty());
+}
+
+#[test]
+fn push_in_the_terminal_runs_in_the_foreground() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]).with_remotes(&["origin"]));
+
+    app.handle_input(KeyCode::Char('p'));
+    app.handle_input(KeyCode::Char('i'));
+    assert!(render(&mut app, 100, 20).contains("[x] i  in the terminal"));
+    app.handle_input(KeyCode::Enter);
+    assert!(app.task.is_none());
+
+    let program = app.pending_external.take().unwrap();
+    assert!(matches!(&program, External::Push(options) if options.in_terminal));
+    app.run_external(program);
+    assert_eq!(
+        backend.calls(),
+        ["foreground", "push --set-upstream origin main"]
+    );
+    assert!(render(&mut app, 100, 20).contains("Push successful"));
+}
+
+#[test]
+fn push_without_credentials_suggests_the_terminal() {
+    let (mut app, _) = app_with(MockBackend {
+        push_error: Some(
+            "fatal: could not read Username for 'https://example.com': terminal prompts disabled"
+                .to_string(),
+        ),
+        ..MockBackend::with_files(&[]).with_remotes(&["origin"])
+    });
+
+    app.handle_input(KeyCode::Char('p'));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert_eq!(app.mode, AppMode::Output);
+    assert_eq!(app.output.title, "Push needs credentials");
+    assert!(app.output.text.contains("Push again with i ticked"));
+    assert!(app.output.text.ends_with("terminal prompts disabled"));
+}
+
+#[test]
+fn force_push_requires_confirmation() {
+    let (mut app, backend) = app_with(MockBackend {
+        upstream: Some("origin/main".to_string()),
+        ..MockBackend::with_files(&[]).with_remotes(&["origin"])
+    });
+
+    app.handle_input(KeyCode::Char('p'));
+    app.handle_input(KeyCode::Char('f'));
+    app.handle_input(KeyCode::Enter);
+    assert!(app.task.is_none());
+    assert!(app.confirm.is_some());
+    assert!(render(&mut app, 100, 20).contains("Force push may overwrite"));
+
+    app.handle_input(KeyCode::Char('y'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["push --force-with-lease origin main"]);
+}
+
+#[test]
+fn discard_asks_before_touching_the_file() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("new.txt", FileStatus::Untracked, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('x'));
+    app.handle_input(KeyCode::Char('n'));
+    assert!(backend.calls().is_empty());
+
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Char('x'));
+    assert!(render(&mut app, 100, 20).contains("Delete untracked new.txt?"));
+    app.handle_input(KeyCode::Char('y'));
+
+    assert_eq!(backend.calls(), ["discard new.txt untracked=true"]);
+    assert_eq!(app.files.len(), 1);
+}
+
+#[test]
+fn bulk_staging_keys() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Staged, true),
+        ("b.txt", FileStatus::Modified, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('i'));
+    assert_eq!(backend.calls(), ["unstage a.txt", "stage b.txt"]);
+    assert!(!app.files[0].staged && app.files[1].staged);
+
+    app.handle_input(KeyCode::Char('A'));
+    assert!(app.files.iter().all(|f| f.staged));
+    app.handle_input(KeyCode::Char('U'));
+    assert!(app.files.iter().all(|f| !f.staged));
+}
+
+#[test]
+fn intent_to_add_sits_between_untracked_and_staged() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("new.txt", FileStatus::Untracked, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('N'));
+    assert!(backend.calls().is_empty());
+    assert!(render(&mut app, 100, 20).contains("Intent to add is for untracked files"));
+
+    app.handle_input(KeyCode::Down);
+    assert!(render(&mut app, 100, 20).contains("○ ? new.txt"));
+    app.handle_input(KeyCode::Char('N'));
+    assert_eq!(backend.calls(), ["intent_to_add new.txt"]);
+    assert_eq!(app.files[1].status, FileStatus::IntentToAdd);
+    assert!(!app.files[1].staged);
+    assert!(render(&mut app, 100, 20).contains("◌ A new.txt"));
+
+    // `N` again makes it untracked, and `x` deletes it like one.
+    app.handle_input(KeyCode::Char('N'));
+    assert_eq!(app.files[1].status, FileStatus::Untracked);
+    app.handle_input(KeyCode::Char('N'));
+    app.handle_input(KeyCode::Char('x'));
+    assert!(render(&mut app, 100, 20).contains("Delete untracked new.txt?"));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(
+        backend.calls()[1..],
+        [
+            "unstage new.txt",
+            "intent_to_add new.txt",
+            "unstage new.txt",
+            "discard new.txt untracked=true"
+        ]
+    );
+}
+
+#[test]
+fn marked_files_are_staged_together() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("b.txt", FileStatus::Modified, false),
+        ("c.txt", FileStatus::Modified, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('J'));
+    assert!(render(&mut app, 80, 10).contains("Files (2 marked)"));
+    app.handle_input(KeyCode::Char(' '));
+
+    assert_eq!(backend.calls(), ["stage a.txt", "stage b.txt"]);
+    assert!(app.files[0].marked && app.files[1].marked && !app.files[2].marked);
+    assert!(!app.files[2].staged);
+}
+
+#[test]
+fn filter_narrows_list_and_keeps_staging_working() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("src/app.rs", FileStatus::Modified, false),
+        ("src/ui.rs", FileStatus::Modified, false),
+        ("README.md", FileStatus::Modified, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('/'));
+    type_text(&mut app, "sui");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.files.len(), 1);
+    assert!(render(&mut app, 80, 10).contains("Files: 1/3"));
+
+    app.handle_input(KeyCode::Char(' '));
+    assert_eq!(backend.calls(), ["stage src/ui.rs"]);
+
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('F'));
+    let unstaged: Vec<&str> = app.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(unstaged, ["src/app.rs", "README.md"]);
+}
+
+#[test]
+fn tree_view_stages_and_collapses_directories() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("src/app.rs", FileStatus::Modified, false),
+        ("src/git/cli.rs", FileStatus::Modified, false),
+        ("README.md", FileStatus::Modified, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('t'));
+    let screen = render(&mut app, 80, 12);
+    assert!(screen.contains("Files (tree)"));
+    assert!(screen.contains("src/ (2)"));
+    assert!(screen.contains("git/ (1)"));
+
+    // The cursor stays on src/app.rs; Left folds its parent and moves there.
+    app.handle_input(KeyCode::Left);
+    assert!(!render(&mut app, 80, 12).contains("cli.rs"));
+
+    app.handle_input(KeyCode::Char(' '));
+    assert_eq!(
+        backend.calls(),
+        ["stage src/app.rs", "stage src/git/cli.rs"]
+    );
+
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 80, 12).contains("cli.rs"));
+}
+
+#[test]
+fn commit_editor_shows_checks_and_can_block() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        true,
+    )]));
+    app.config.validation.subject_pattern =
+        Some(git_commit_helper::validate::conventional_commit_pattern());
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Fixed it.");
+    let screen = render(&mut app, 80, 16);
+    assert!(screen.contains("Subject doesn't match"));
+    assert!(screen.contains("Subject ends with a period"));
+
+    app.config.validation.block_on_error = true;
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(backend.calls().is_empty());
+}
+
+#[test]
+fn ctrl_e_requests_external_editor_only_in_commit_editor() {
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use git_commit_helper::External;
+
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+    let ctrl_e = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL);
+
+    app.handle_key(ctrl_e);
+    assert_eq!(app.pending_external, None);
+
+    app.handle_input(KeyCode::Char('c'));
+    app.handle_key(ctrl_e);
+    assert_eq!(app.pending_external, Some(External::CommitEditor));
+    assert!(app.commit_message.is_empty());
+}
+
+#[test]
+fn ctrl_z_suspends_outside_the_commit_editor_and_bang_opens_a_shell() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        true,
+    )]));
+    let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+
+    // Not `z`, which would open the fixup picker.
+    app.handle_key(ctrl_z);
+    assert_eq!(app.pending_external.take(), Some(External::Suspend));
+    assert_eq!(app.mode, AppMode::FileList);
+
+    // Where it undoes instead.
+    app.handle_input(KeyCode::Char('c'));
+    app.handle_key(ctrl_z);
+    assert_eq!(app.pending_external, None);
+    assert!(app.commit_message.is_empty());
+
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('!'));
+    assert_eq!(app.pending_external, Some(External::Shell));
+}
+
+#[test]
+fn draft_is_saved_restored_and_cleared_on_commit() {
+    let git_dir = std::env::temp_dir().join(format!("gch-draft-{}", std::process::id()));
+    std::fs::create_dir_all(&git_dir).unwrap();
+    let draft_file = git_dir.join("GIT_COMMIT_HELPER_DRAFT");
+    let backend = || MockBackend {
+        git_dir: Some(git_dir.clone()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)])
+    };
+
+    let (mut app, _) = app_with(backend());
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    assert_eq!(std::fs::read_to_string(&draft_file).unwrap(), "Add a");
+
+    let (mut app, _) = app_with(backend());
+    assert_eq!(app.commit_message, "Add a");
+    assert!(render(&mut app, 80, 12).contains("Restored unsaved commit message"));
+
+    app.handle_input(KeyCode::Char('c'));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert!(!draft_file.exists());
+    std::fs::remove_dir_all(git_dir).unwrap();
+}
+
+#[test]
+fn up_and_down_recall_commit_history() {
+    let git_dir = std::env::temp_dir().join(format!("gch-history-{}", std::process::id()));
+    std::fs::create_dir_all(&git_dir).unwrap();
+    let (mut app, backend) = app_with(MockBackend {
+        git_dir: Some(git_dir.clone()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)])
+    });
+
+    for message in ["Add a", "Fix b\n\nbody"] {
+        app.handle_input(KeyCode::Char('c'));
+        type_text(&mut app, message);
+        app.handle_input(KeyCode::Enter);
+        wait_for_task(&mut app);
+        backend.files.lock().unwrap().push(GitFile {
+            path: "a.txt".to_string(),
+            status: FileStatus::Modified,
+            staged: true,
+            orig_path: None,
+            marked: false,
+            insertions: 0,
+            deletions: 0,
+        });
+        app.refresh_git_status();
+    }
+
+    // A fresh session reads the saved history.
+    let (mut app, _) = app_with(MockBackend {
+        git_dir: Some(git_dir.clone()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)])
+    });
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "dra");
+    app.handle_input(KeyCode::Up);
+    assert_eq!(app.commit_message, "Fix b\n\nbody");
+    assert!(render(&mut app, 80, 16).contains("[history 1/2]"));
+    app.handle_input(KeyCode::Up);
+    app.handle_input(KeyCode::Up);
+    assert_eq!(app.commit_message, "Add a");
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Down);
+    assert_eq!(app.commit_message, "dra");
+    std::fs::remove_dir_all(git_dir).unwrap();
+}
+
+#[test]
+fn commit_editor_edits_multibyte_text_without_panicking() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "añadir 日本");
+    app.handle_input(KeyCode::Left);
+    app.handle_input(KeyCode::Backspace);
+    type_text(&mut app, "語");
+    assert_eq!(app.commit_message, "añadir 語本");
+
+    app.handle_input(KeyCode::Home);
+    app.handle_input(KeyCode::Right);
+    app.handle_input(KeyCode::Delete);
+    assert_eq!(app.commit_message, "aadir 語本");
+    assert!(render(&mut app, 80, 12).contains("Commit Message (subject 8/50)"));
+}
+
+#[test]
+fn renames_show_both_paths_and_unstage_together() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]));
+    backend.files.lock().unwrap().push(GitFile {
+        path: "src/new.rs".to_string(),
+        status: FileStatus::Renamed,
+        staged: true,
+        orig_path: Some("src/old.rs".to_string()),
+        marked: false,
+        insertions: 0,
+        deletions: 0,
+    });
+    app.refresh_git_status();
+
+    assert!(render(&mut app, 80, 10).contains("● R src/old.rs → src/new.rs"));
+    app.handle_input(KeyCode::Char('t'));
+    assert!(render(&mut app, 80, 10).contains("new.rs ← src/old.rs"));
+
+    app.handle_input(KeyCode::Char(' '));
+    assert_eq!(
+        backend.calls(),
+        ["unstage src/new.rs", "unstage src/old.rs"]
+    );
+}
+
+#[test]
+fn conflicts_show_banner_and_resolve_then_continue() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Conflicted, false),
+        ("b.txt", FileStatus::Conflicted, false),
+    ]));
+    *backend.operation.lock().unwrap() = Some(Operation::Merge);
+    app.refresh_git_status();
+
+    let screen = render(&mut app, 120, 10);
+    assert!(screen.contains("Merge stopped: 2 conflict(s)"));
+    assert!(screen.contains("! U a.txt"));
+
+    // Continue is refused while conflicts remain.
+    app.handle_input(KeyCode::Char('C'));
+    assert!(app.task.is_none());
+
+    app.handle_input(KeyCode::Char('<'));
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Char('>'));
+    assert!(render(&mut app, 120, 10).contains("Merge in progress · C continue"));
+
+    app.handle_input(KeyCode::Char('C'));
+    wait_for_task(&mut app);
+    assert_eq!(
+        backend.calls(),
+        [
+            "resolve a.txt --ours",
+            "resolve b.txt --theirs",
+            "merge --continue"
+        ]
+    );
+    assert_eq!(app.git_status.operation, None);
+}
+
+#[test]
+fn abort_asks_for_confirmation() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]));
+    *backend.operation.lock().unwrap() = Some(Operation::Rebase);
+    app.refresh_git_status();
+
+    app.handle_input(KeyCode::Char('X'));
+    assert!(render(&mut app, 80, 12).contains("Abort the rebase"));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(backend.calls(), ["rebase --abort"]);
+}
+
+#[test]
+fn interactive_rebase_builds_plan_from_picked_base() {
+    let (mut app, backend) =
+        app_with(MockBackend::with_files(&[]).with_commits(&["Add a", "Add b", "Fix a", "Add c"]));
+
+    app.handle_input(KeyCode::Char('R'));
+    assert_eq!(app.mode, AppMode::RebasePick);
+    // Include the newest three commits.
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::Rebase);
+    assert!(render(&mut app, 120, 12).contains("pick   c2 Add b"));
+
+    // The first commit has nothing to fold into, so fixup is rejected there.
+    app.handle_input(KeyCode::Char('f'));
+    app.handle_input(KeyCode::Enter);
+    assert!(app.task.is_none());
+    app.handle_input(KeyCode::Char('p'));
+
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Char('f'));
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Char('r'));
+    for _ in 0.."Add c".len() {
+        app.handle_input(KeyCode::Backspace);
+    }
+    type_text(&mut app, "Add c properly");
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('K'));
+    assert!(render(&mut app, 120, 12).contains("reword c4 Add c properly"));
+
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(
+        backend.calls(),
+        ["rebase c2^: pick c2, reword c4 Add c properly, fixup c3"]
+    );
+}
+
+#[test]
+fn undo_commit_confirms_and_restores_message() {
+    let (mut app, backend) = app_with(MockBackend {
+        upstream: Some("origin/main".to_string()),
+        ..MockBackend::with_files(&[]).with_commits(&["Add a", "Add b"])
+    });
+    app.commit_message = "half-written".to_string();
+
+    app.handle_input(KeyCode::Char('u'));
+    assert!(render(&mut app, 100, 12).contains("Undo c2 \"Add b\"?"));
+    let message = &app.confirm.as_ref().unwrap().message;
+    assert!(message.ends_with("It has already been pushed."));
+    assert!(backend.calls().is_empty());
+
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(backend.calls(), ["undo_last_commit"]);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(app.commit_message, "Add b");
+    assert_eq!(app.history.entries, ["half-written"]);
+}
+
+#[test]
+fn fixup_commits_staged_changes_and_autosquash_rebases_from_target() {
+    let (mut app, backend) = app_with(
+        MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)]).with_commits(&[
+            "Add a",
+            "Add b",
+            "Add c",
+            "fixup! Add b",
+        ]),
+    );
+
+    app.handle_input(KeyCode::Char('z'));
+    assert_eq!(app.mode, AppMode::FixupPick);
+    assert!(render(&mut app, 100, 12).contains("c3 Add c"));
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(app.mode, AppMode::FileList);
+
+    // Nothing is staged any more, so there is nothing to fix up with.
+    app.handle_input(KeyCode::Char('z'));
+    assert_eq!(app.mode, AppMode::FileList);
+
+    app.handle_input(KeyCode::Char('Z'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["fixup c3", "autosquash c2^"]);
+}
+
+#[test]
+fn signed_commit_runs_in_foreground_and_explains_failures() {
+    let (mut app, backend) = app_with(MockBackend {
+        signing: SigningConfig {
+            sign_by_default: true,
+            format: "openpgp".to_string(),
+            key: None,
+        },
+        head_signature: Some(SignatureState::Good),
+        commit_error: Some(
+            "error: gpg failed to sign the data\ngpg: signing failed: Inappropriate ioctl for device"
+                .to_string(),
+        ),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)])
+    });
+    assert!(render(&mut app, 100, 12).contains("HEAD ✓ signed"));
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    assert!(render(&mut app, 100, 12).contains("[signed]"));
+    app.handle_input(KeyCode::Enter);
+    assert!(app.task.is_none());
+
+    let program = app.pending_external.take().unwrap();
+    assert_eq!(program, External::SignedCommit);
+    app.run_external(program);
+    assert_eq!(backend.calls(), ["foreground", "commit Add a"]);
+    assert_eq!(app.mode, AppMode::Output);
+    assert!(
+        app.output
+            .text
+            .starts_with("GPG could not ask for the passphrase")
+    );
+}
+
+#[test]
+fn signing_toggle_needs_a_configured_key() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        true,
+    )]));
+
+    app.handle_input(KeyCode::Char('c'));
+    app.handle_input(KeyCode::F(3));
+    assert!(!app.signs_commit());
+
+    app.signing.key = Some("~/.ssh/id_ed25519.pub".to_string());
+    app.signing.format = "ssh".to_string();
+    app.handle_input(KeyCode::F(3));
+    type_text(&mut app, "Add a");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["commit -S Add a"]);
+}
+
+#[test]
+fn commit_options_add_sign_off_and_trailers_and_remember_co_authors() {
+    let git_dir = std::env::temp_dir().join(format!("gch-coauthors-{}", std::process::id()));
+    std::fs::create_dir_all(&git_dir).unwrap();
+    std::fs::write(
+        git_dir.join("GIT_COMMIT_HELPER_COAUTHORS"),
+        "Bo <bo@example.com>",
+    )
+    .unwrap();
+    let (mut app, backend) = app_with(MockBackend {
+        git_dir: Some(git_dir.clone()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)])
+    });
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    app.handle_input(KeyCode::F(4));
+    assert_eq!(app.mode, AppMode::CommitOptions);
+    app.handle_input(KeyCode::Char(' '));
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Cy <cy@example.com>");
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('a'));
+    type_text(&mut app, "Refs: #12");
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 100, 16).contains("[x] Co-authored-by: Cy <cy@example.com>"));
+
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(
+        backend.calls(),
+        ["commit --signoff Add a\n\nCo-authored-by: Cy <cy@example.com>\nRefs: #12"]
+    );
+    // Sign-off is per commit unless `Config::sign_off` is set.
+    assert!(!app.commit_options.sign_off);
+    assert!(app.trailers.trailers().is_empty());
+    assert_eq!(
+        std::fs::read_to_string(git_dir.join("GIT_COMMIT_HELPER_COAUTHORS")).unwrap(),
+        "Cy <cy@example.com>\nBo <bo@example.com>"
+    );
+    std::fs::remove_dir_all(git_dir).unwrap();
+}
+
+#[test]
+fn commit_options_override_the_author_and_date_for_one_commit() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    app.handle_input(KeyCode::F(4));
+
+    app.handle_input(KeyCode::Char('A'));
+    type_text(&mut app, "bo@example.com");
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 100, 16).contains("The author looks like Name <email>"));
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('A'));
+    type_text(&mut app, "Bo <bo@example.com>");
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('D'));
+    type_text(&mut app, "2024-05-01 09:30");
+    app.handle_input(KeyCode::Enter);
+    app.notification = None;
+    let screen = render(&mut app, 100, 24);
+    assert!(screen.contains("Author: Bo <bo@example.com>"));
+    assert!(screen.contains("Date:   2024-05-01 09:30 (committer too)"));
+    assert_eq!(
+        app.commit_options.env(),
+        [("GIT_COMMITTER_DATE", "2024-05-01 09:30")]
+    );
+    app.handle_input(KeyCode::Char('C'));
+    assert!(app.commit_options.env().is_empty());
+
+    app.handle_input(KeyCode::Esc);
+    assert!(
+        render(&mut app, 120, 16)
+            .contains("[author: Bo <bo@example.com>] [dated 2024-05-01 09:30]")
+    );
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(
+        backend.calls(),
+        ["commit --author Bo <bo@example.com> --date 2024-05-01 09:30 Add a"]
+    );
+    // Both go back to the defaults for the next commit.
+    assert_eq!(app.commit_options.author, None);
+    assert_eq!(app.commit_options.date, None);
+}
+
+#[test]
+fn ticket_from_branch_is_added_to_the_subject_unless_skipped() {
+    let backend = Arc::new(MockBackend {
+        branch: "feature/PROJ-42-login".to_string(),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)])
+    });
+    let mut config = Config::default();
+    config.ticket.pattern = Some(jira_ticket_pattern());
+    let mut app = App::new(backend.clone(), config);
+    app.refresh_git_status();
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add login");
+    assert!(render(&mut app, 100, 12).contains("[PROJ-42]"));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    backend.files.lock().unwrap().push(GitFile {
+        path: "b.txt".to_string(),
+        status: FileStatus::Added,
+        staged: true,
+        orig_path: None,
+        marked: false,
+        insertions: 0,
+        deletions: 0,
+    });
+    app.refresh_git_status();
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Tidy up");
+    app.handle_input(KeyCode::F(5));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(
+        backend.calls(),
+        ["commit PROJ-42: Add login", "commit Tidy up"]
+    );
+}
+
+#[test]
+fn suggested_message_is_inserted_for_review() {
+    struct Fake(Mutex<Vec<String>>);
+    impl MessageProvider for Fake {
+        fn suggest(&self, diff: &str) -> Result<String, String> {
+            self.0.lock().unwrap().push(diff.to_string());
+            Ok("feat: add a".to_string())
+        }
+    }
+
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Added, true),
+        ("b.txt", FileStatus::Modified, false),
+    ]));
+    app.handle_input(KeyCode::Char('c'));
+    app.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+    assert!(app.task.is_none());
+
+    let fake = Arc::new(Fake(Mutex::new(Vec::new())));
+    app.suggester = Some(fake.clone());
+    type_text(&mut app, "wip");
+    app.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+    wait_for_task(&mut app);
+
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(app.commit_message, "feat: add a");
+    assert_eq!(app.history.entries, ["wip"]);
+    assert_eq!(
+        *fake.0.lock().unwrap(),
+        ["diff --git a/a.txt b/a.txt\n+added line\n"]
+    );
+    assert!(backend.calls().is_empty());
+}
+
+#[test]
+fn commit_editor_starts_with_prefix_suggested_by_staged_paths() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[
+        ("README.md", FileStatus::Modified, true),
+        ("src/lib.rs", FileStatus::Modified, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.commit_message, "docs: ");
+    // The picker starts from the suggestion.
+    app.handle_input(KeyCode::Tab);
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.commit_message, "style: ");
+}
+
+#[test]
+fn prefix_picker_filters_and_keeps_the_typed_message() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "src/lib.rs",
+        FileStatus::Modified,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "handle empty diffs");
+    app.cursor_position = "handle".len();
+
+    app.handle_input(KeyCode::Tab);
+    assert_eq!(app.mode, AppMode::Prefixes);
+    assert!(render(&mut app, 80, 24).contains("7 chore:    Maintenance tasks"));
+    type_text(&mut app, "bug");
+    let screen = render(&mut app, 80, 24);
+    assert!(screen.contains("Prefixes: bug"));
+    assert!(screen.contains("1 fix:"));
+    assert!(!screen.contains("chore:"));
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(app.commit_message, "fix: handle empty diffs");
+    assert_eq!(app.cursor_position, "fix: handle".len());
+
+    // Picking again swaps the prefix rather than stacking another.
+    app.handle_input(KeyCode::Tab);
+    app.handle_input(KeyCode::Char('1'));
+    assert_eq!(app.commit_message, "feat: handle empty diffs");
+    app.handle_input(KeyCode::Tab);
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.commit_message, "feat: handle empty diffs");
+}
+
+#[test]
+fn repository_prefixes_replace_the_configured_ones() {
+    let (mut app, _) = app_with(MockBackend {
+        git_config: vec![
+            ("gch.prefix".to_string(), "perf: | Performance".to_string()),
+            (
+                "gch.prefix".to_string(),
+                "revert: | Undo a commit".to_string(),
+            ),
+        ],
+        ..MockBackend::with_files(&[("src/lib.rs", FileStatus::Modified, true)])
+    });
+
+    app.handle_input(KeyCode::Char('c'));
+    app.handle_input(KeyCode::Tab);
+    app.handle_input(KeyCode::Char('2'));
+    assert_eq!(app.commit_message, "revert: ");
+    let screen = render(&mut app, 80, 16);
+    assert!(screen.contains("Prefixes (Tab to pick, 2/2)"));
+    assert!(screen.contains("revert: Undo a commit"));
+    app.handle_input(KeyCode::Tab);
+    assert!(render(&mut app, 80, 16).contains("1 perf:   Performance"));
+}
+
+#[test]
+fn split_view_previews_selected_diff_and_scrolls_with_focus() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("b.txt", FileStatus::Modified, false),
+    ]));
+
+    app.handle_input(KeyCode::Char('s'));
+    assert!(render(&mut app, 100, 12).contains("Diff: a.txt"));
+    app.handle_input(KeyCode::Down);
+    let screen = render(&mut app, 100, 12);
+    assert!(screen.contains("diff --git a/b.txt b/b.txt"));
+    assert_eq!(app.mode, AppMode::FileList);
+
+    app.handle_input(KeyCode::Tab);
+    assert_eq!(app.focus, Focus::Diff);
+    app.handle_input(KeyCode::Char('j'));
+    assert_eq!(app.diff_scroll, 1);
+    assert_eq!(app.selected().unwrap().path, "b.txt");
+    // Keys the diff doesn't use still act on the file.
+    app.handle_input(KeyCode::Char('v'));
+    assert!(app.selected().unwrap().marked);
+
+    app.handle_input(KeyCode::Tab);
+    app.handle_input(KeyCode::Up);
+    assert_eq!(app.diff_scroll, 0);
+    assert!(render(&mut app, 100, 12).contains("Diff: a.txt"));
+}
+
+#[test]
+fn pane_resizes_are_clamped_and_remembered() {
+    let git_dir = std::env::temp_dir().join(format!("gch-panes-{}", std::process::id()));
+    std::fs::create_dir_all(&git_dir).unwrap();
+    let backend = || MockBackend {
+        git_dir: Some(git_dir.clone()),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+    };
+    let (mut app, _) = app_with(backend());
+
+    // Nothing to resize outside the split view.
+    app.handle_input(KeyCode::Char(']'));
+    assert_eq!(app.panes.list_percent, 40);
+
+    app.handle_input(KeyCode::Char('s'));
+    app.handle_input(KeyCode::Char('['));
+    assert_eq!(app.panes.list_percent, 35);
+    for _ in 0..20 {
+        app.handle_input(KeyCode::Char(']'));
+    }
+    assert_eq!(app.panes.list_percent, 85);
+
+    let config = Config {
+        split_view: true,
+        split_layout: SplitLayout::Stacked,
+        ..Config::default()
+    };
+    let mut app = App::new(Arc::new(backend()), config);
+    app.refresh_git_status();
+    assert_eq!(app.panes.list_percent, 85);
+    // Stacked: the diff pane sits below the list.
+    let screen = render(&mut app, 60, 30);
+    let list_row = screen.lines().position(|l| l.contains("Files")).unwrap();
+    let diff_row = screen
+        .lines()
+        .position(|l| l.contains("Diff: a.txt"))
+        .unwrap();
+    assert!(diff_row > list_row);
+    std::fs::remove_dir_all(git_dir).unwrap();
+}
+
+fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+    MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+#[test]
+fn mouse_selects_stages_scrolls_and_clicks_hints() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("b.txt", FileStatus::Modified, false),
+        ("c.txt", FileStatus::Modified, false),
+    ]));
+    let screen = render(&mut app, 80, 20);
+    let row_of = |path: &str| screen.lines().position(|l| l.contains(path)).unwrap() as u16;
+    let click = MouseEventKind::Down(MouseButton::Left);
+
+    app.handle_mouse(mouse(click, 20, row_of("b.txt")));
+    assert_eq!(app.selected().unwrap().path, "b.txt");
+    assert!(!app.selected().unwrap().staged);
+
+    // The circle sits after the border, highlight symbol, and mark.
+    let line = screen.lines().nth(row_of("c.txt") as usize).unwrap();
+    let circle = line.chars().position(|c| c == '○').unwrap() as u16;
+    app.handle_mouse(mouse(click, circle, row_of("c.txt")));
+    assert!(backend.calls().contains(&"stage c.txt".to_string()));
+
+    app.handle_mouse(mouse(MouseEventKind::ScrollUp, 20, row_of("a.txt")));
+    assert_eq!(app.selected().unwrap().path, "a.txt");
+
+    let screen = render(&mut app, 80, 20);
+    let status = screen.lines().last().unwrap();
+    let column = status.find("[h] help").unwrap() as u16;
+    app.handle_mouse(mouse(click, column + 1, 19));
+    assert_eq!(app.mode, AppMode::Help);
+}
+
+#[test]
+fn status_bar_hints_follow_the_selection() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("b.txt", FileStatus::Untracked, false),
+        ("c.txt", FileStatus::Conflicted, false),
+    ]));
+    let status = |app: &mut App| render(app, 120, 10).lines().last().unwrap().to_string();
+
+    let line = status(&mut app);
+    assert!(line.contains("[Space] stage  [d] diff  [h] help"));
+    assert!(!line.contains("[c] commit"));
+
+    app.handle_input(KeyCode::Char(' '));
+    let line = status(&mut app);
+    assert!(line.contains("[Space] unstage  [d] diff  [c] commit"));
+
+    app.handle_input(KeyCode::Down);
+    assert!(status(&mut app).contains("[I] ignore"));
+
+    *backend.operation.lock().unwrap() = Some(Operation::Merge);
+    app.refresh_git_status();
+    app.handle_input(KeyCode::Down);
+    let line = status(&mut app);
+    assert!(line.contains("[C] continue  [X] abort  [<] ours  [>] theirs  [M] merge tool"));
+
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(status(&mut app).contains("[Enter] commit"));
+}
+
+#[test]
+fn failed_stage_is_reported_and_kept_in_messages() {
+    let (mut app, _) = app_with(MockBackend {
+        stage_error: Some(
+            "fatal: pathspec 'a.txt' did not match any files\nhint: check the path".to_string(),
+        ),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+    });
+
+    app.handle_input(KeyCode::Char(' '));
+    let notification = app.notification.clone().unwrap();
+    assert_eq!(notification.level, Level::Error);
+    assert_eq!(
+        notification.message,
+        "Staging a.txt failed: pathspec 'a.txt' did not match any files (m for messages)"
+    );
+
+    // The notification expires, but the messages view keeps it.
+    app.notification = None;
+    app.handle_input(KeyCode::Char('m'));
+    assert_eq!(app.mode, AppMode::Messages);
+    let entry = app.messages.entries.last().unwrap();
+    assert_eq!(entry.level, Level::Error);
+    assert!(
+        entry
+            .detail
+            .as_deref()
+            .unwrap()
+            .contains("hint: check the path")
+    );
+    let screen = render(&mut app, 100, 12);
+    assert!(screen.contains("Staging a.txt failed"));
+    assert!(screen.contains("hint: check the path"));
+    app.handle_input(KeyCode::Char('m'));
+    assert_eq!(app.mode, AppMode::FileList);
+}
+
+#[test]
+fn message_log_keeps_the_latest_entries() {
+    let config = Config {
+        message_log_size: 2,
+        ..Config::default()
+    };
+    let mut app = App::new(
+        Arc::new(MockBackend::with_files(&[(
+            "a.txt",
+            FileStatus::Modified,
+            false,
+        )])),
+        config,
+    );
+    for _ in 0..3 {
+        app.handle_input(KeyCode::Char('A'));
+    }
+    app.handle_input(KeyCode::Char('U'));
+    let texts: Vec<&str> = app
+        .messages
+        .entries
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect();
+    assert_eq!(texts, ["Staged all changes", "Unstaged all changes"]);
+}
+
+#[test]
+fn diff_view_reruns_with_context_whitespace_and_algorithm() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+    app.handle_input(KeyCode::Char('d'));
+    assert_eq!(app.mode, AppMode::DiffView);
+    app.handle_input(KeyCode::Char('j'));
+
+    app.handle_input(KeyCode::Char('+'));
+    app.handle_input(KeyCode::Char('w'));
+    app.handle_input(KeyCode::Char('a'));
+    app.handle_input(KeyCode::Char('a'));
+    assert!(
+        app.diff_content
+            .ends_with(" -U4 --ignore-all-space --histogram\n")
+    );
+    assert_eq!(app.diff_scroll, 1);
+    assert!(render(&mut app, 100, 12).contains("Diff (-U4 --ignore-all-space --histogram)"));
+
+    // Back to the defaults: the title and diff lose the flags.
+    app.handle_input(KeyCode::Char('-'));
+    app.handle_input(KeyCode::Char('w'));
+    app.handle_input(KeyCode::Char('a'));
+    assert_eq!(app.diff_options, DiffOptions::default());
+    assert_eq!(
+        app.diff_content,
+        "diff --git a/a.txt b/a.txt\n+added line\n"
+    );
+    assert_eq!(app.diff_scroll, 1);
+    assert!(!render(&mut app, 100, 12).contains("-U"));
+}
+
+#[test]
+fn untracked_files_show_as_added_lines() {
+    let work_dir = std::env::temp_dir().join(format!("gch-untracked-{}", std::process::id()));
+    std::fs::create_dir_all(work_dir.join("notes/img")).unwrap();
+    std::fs::write(work_dir.join("todo.txt"), "first\nsecond").unwrap();
+    std::fs::write(work_dir.join("notes/a.md"), "# A\n").unwrap();
+    std::fs::write(work_dir.join("notes/img/logo.png"), b"\x89PNG\0\0").unwrap();
+    let backend = MockBackend {
+        work_dir: Some(work_dir.clone()),
+        ..MockBackend::with_files(&[
+            ("notes/", FileStatus::Untracked, false),
+            ("todo.txt", FileStatus::Untracked, false),
+        ])
+    };
+    let (mut app, _) = app_with(backend);
+
+    app.handle_input(KeyCode::Char('d'));
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert_eq!(
+        app.diff_content,
+        "diff --git a/notes/a.md b/notes/a.md\nnew file mode 100644\n--- /dev/null\n\
+         +++ b/notes/a.md\n@@ -0,0 +1,1 @@\n+# A\n\
+         diff --git a/notes/img/logo.png b/notes/img/logo.png\nnew file mode 100644\n\
+         --- /dev/null\n+++ b/notes/img/logo.png\n\
+         Binary files /dev/null and b/notes/img/logo.png differ\n"
+    );
+
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Char('d'));
+    assert!(app.diff_content.ends_with(
+        "+++ b/todo.txt\n@@ -0,0 +1,2 @@\n+first\n+second\n\\ No newline at end of file\n"
+    ));
+    std::fs::remove_dir_all(work_dir).unwrap();
+}
+
+#[test]
+fn staged_review_shows_every_staged_file_and_returns_to_the_editor() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, true),
+        ("b.txt", FileStatus::Modified, false),
+        ("c.txt", FileStatus::Added, true),
+    ]));
+
+    app.handle_input(KeyCode::Char('D'));
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert_eq!(
+        app.diff_content,
+        "diff --git a/a.txt b/a.txt\n+added line\ndiff --git a/c.txt b/c.txt\n+added line\n"
+    );
+    assert!(render(&mut app, 100, 12).contains("Staged changes (2 files)"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::FileList);
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "add c");
+    app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+    assert_eq!(app.mode, AppMode::DiffView);
+    // Options re-run the staged diff, not the selected file's.
+    app.handle_input(KeyCode::Char('w'));
+    assert!(app.diff_content.contains("diff --git a/c.txt"));
+    app.handle_input(KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(app.commit_message.ends_with("add c"));
+}
+
+#[test]
+fn file_list_shows_line_counts_and_header_totals() {
+    let backend = MockBackend::with_files(&[
+        ("src/a.txt", FileStatus::Modified, false),
+        ("logo.png", FileStatus::Modified, false),
+        ("src/b.txt", FileStatus::Staged, true),
+    ]);
+    for file in backend.files.lock().unwrap().iter_mut() {
+        (file.insertions, file.deletions) = match file.path.as_str() {
+            "src/a.txt" => (12, 3),
+            "src/b.txt" => (1, 0),
+            _ => (0, 0),
+        };
+    }
+    let (mut app, _) = app_with(backend);
+    let row_of = |screen: &str, name: &str| {
+        screen
+            .lines()
+            .find(|line| line.contains(name))
+            .unwrap()
+            .to_string()
+    };
+
+    let screen = render(&mut app, 60, 12);
+    assert!(screen.contains("Files: 3 (+13 -3)"));
+    // Right-aligned against the border.
+    assert!(row_of(&screen, "src/a.txt").ends_with(" +12 -3│"));
+    assert!(row_of(&screen, "src/b.txt").ends_with(" +1 -0│"));
+    // No counts for a binary change.
+    assert!(!row_of(&screen, "logo.png").contains('+'));
+
+    // Directories add up the files under them.
+    app.handle_input(KeyCode::Char('t'));
+    let screen = render(&mut app, 60, 12);
+    assert!(row_of(&screen, "src/").ends_with(" +13 -3│"));
+}
+
+#[test]
+fn diff_search_jumps_between_matches() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+    app.handle_input(KeyCode::Char('d'));
+    app.diff_content = (0..40)
+        .map(|i| {
+            if i % 10 == 5 {
+                format!("+needle {i}\n")
+            } else {
+                format!(" hay {i}\n")
+            }
+        })
+        .collect();
+
+    app.handle_input(KeyCode::Char('/'));
+    assert_eq!(app.mode, AppMode::DiffSearch);
+    type_text(&mut app, "needle");
+    assert_eq!(app.diff_scroll, 3);
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert!(render(&mut app, 80, 12).contains("Diff [/needle 1/4]"));
+
+    app.handle_input(KeyCode::Char('n'));
+    assert_eq!(app.diff_scroll, 13);
+    app.handle_input(KeyCode::Char('N'));
+    app.handle_input(KeyCode::Char('N'));
+    assert_eq!(app.diff_search.counter(), "4/4");
+    assert_eq!(app.diff_scroll, 33);
+
+    // Esc while typing drops the search but stays in the diff.
+    app.handle_input(KeyCode::Char('/'));
+    type_text(&mut app, "nothing");
+    assert!(render(&mut app, 80, 12).contains("no matches"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert!(app.diff_search.matches.is_empty());
+}
+
+#[test]
+fn diff_view_jumps_by_hunk_and_file_with_a_breadcrumb() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, true),
+        ("b.txt", FileStatus::Modified, true),
+    ]));
+    app.handle_input(KeyCode::Char('D'));
+    app.diff_content = ["a.txt", "b.txt"]
+        .iter()
+        .map(|path| {
+            let hunk = (0..8).map(|i| format!(" line {i}\n")).collect::<String>();
+            format!("diff --git a/{path} b/{path}\n@@ -1 +1 @@\n{hunk}@@ -20 +20 @@\n{hunk}")
+        })
+        .collect();
+
+    // The breadcrumb row under the title, and the part of it in bold.
+    let breadcrumb = |app: &mut App| {
+        let mut terminal = Terminal::new(TestBackend::new(60, 14)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let cells: Vec<_> = (0..60).map(|x| &buffer[(x, 4)]).collect();
+        let text: String = cells.iter().map(|cell| cell.symbol()).collect();
+        let bold: String = cells
+            .iter()
+            .filter(|cell| cell.modifier.contains(Modifier::BOLD))
+            .map(|cell| cell.symbol())
+            .collect();
+        (text, bold)
+    };
+    let (text, bold) = breadcrumb(&mut app);
+    assert!(text.contains("a.txt · b.txt"));
+    assert_eq!(bold, "a.txt");
+
+    app.handle_input(KeyCode::Char(']'));
+    assert_eq!(app.diff_scroll, 1);
+    app.handle_input(KeyCode::Char(']'));
+    assert_eq!(app.diff_scroll, 10);
+    app.handle_input(KeyCode::Char('J'));
+    assert_eq!(app.diff_scroll, 19);
+    assert_eq!(breadcrumb(&mut app).1, "b.txt");
+    app.handle_input(KeyCode::Char('['));
+    assert_eq!(app.diff_scroll, 10);
+    app.handle_input(KeyCode::Char('K'));
+    assert_eq!(app.diff_scroll, 0);
+    // Nothing before the first file.
+    app.handle_input(KeyCode::Char('K'));
+    assert_eq!(app.diff_scroll, 0);
+}
+
+#[test]
+fn m_opens_mergetool_on_conflicts_and_difftool_otherwise() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[
+        ("both.rs", FileStatus::Conflicted, false),
+        ("staged.rs", FileStatus::Modified, true),
+        ("new.rs", FileStatus::Untracked, false),
+    ]));
+    let mut pressed = Vec::new();
+    for _ in 0..3 {
+        app.handle_input(KeyCode::Char('M'));
+        pressed.push(app.pending_external.take());
+        app.handle_input(KeyCode::Char('j'));
+    }
+
+    assert_eq!(
+        pressed,
+        [
+            Some(External::MergeTool("both.rs".to_string())),
+            Some(External::DiffTool {
+                path: "staged.rs".to_string(),
+                staged: true
+            }),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn file_line_at_finds_the_next_change_in_the_new_version() {
+    let diff = "diff --git a/a.txt b/a.txt\n\
+                --- a/a.txt\n\
+                +++ b/a.txt\n\
+                @@ -10,4 +10,4 @@\n\
+                \x20ten\n\
+                -eleven\n\
+                +ELEVEN\n\
+                \x20twelve\n\
+                @@ -40,2 +40,3 @@\n\
+                \x20forty\n\
+                +inserted\n\
+                \x20forty-one\n\
+                diff --git a/b.txt b/b.txt\n\
+                @@ -0,0 +1 @@\n\
+                +new\n";
+    let at = |row| file_line_at(diff, row).unwrap();
+
+    assert_eq!(at(0), ("a.txt".to_string(), 11));
+    assert_eq!(at(6), ("a.txt".to_string(), 11));
+    assert_eq!(at(7), ("a.txt".to_string(), 41));
+    // After the last change, the line on the row.
+    assert_eq!(at(11), ("a.txt".to_string(), 42));
+    assert_eq!(at(12), ("b.txt".to_string(), 1));
+    assert_eq!(file_line_at("", 0), None);
+}
+
+#[test]
+fn e_opens_the_selected_file_at_its_first_change() {
+    let root = std::env::temp_dir().join("gch-edit");
+    let (mut app, _) = app_with(MockBackend {
+        work_dir: Some(root.clone()),
+        diff_body: Some("x\n@@ -7,2 +7,3 @@\n keep\n+added".to_string()),
+        ..MockBackend::with_files(&[
+            ("src/lib.rs", FileStatus::Modified, false),
+            ("gone.rs", FileStatus::Deleted, false),
+        ])
+    });
+    app.handle_input(KeyCode::Char('e'));
+    assert_eq!(
+        app.pending_external.take(),
+        Some(External::EditFile(root.join("src/lib.rs"), Some(8)))
+    );
+
+    app.handle_input(KeyCode::Char('d'));
+    app.handle_input(KeyCode::Char('e'));
+    assert!(matches!(
+        app.pending_external.take(),
+        Some(External::EditFile(path, Some(8))) if path == root.join("src/lib.rs")
+    ));
+
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('e'));
+    assert_eq!(app.pending_external, None);
+}
+
+#[test]
+fn file_history_lists_commits_and_shows_the_file_at_each() {
+    let (mut app, _) = app_with(
+        MockBackend::with_files(&[
+            ("a.txt", FileStatus::Modified, false),
+            ("new.txt", FileStatus::Untracked, false),
+        ])
+        .with_commits(&["Add a", "Tweak a"]),
+    );
+
+    app.handle_input(KeyCode::Char('l'));
+    assert_eq!(app.mode, AppMode::Log);
+    let screen = render(&mut app, 100, 12);
+    assert!(screen.contains("History: a.txt (2 commits)"));
+    assert!(screen.contains("c2 Tweak a"));
+
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('d'));
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert_eq!(
+        app.diff_content,
+        "diff --git a/a.txt b/a.txt\n+c1 changed a.txt\n"
+    );
+    assert!(render(&mut app, 100, 12).contains("c1: a.txt"));
+
+    // Back to the history, then the list.
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Log);
+    assert_eq!(app.log.selected, 1);
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::FileList);
+
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Char('l'));
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(render(&mut app, 100, 12).contains("new.txt has no history yet"));
+}
+
+#[test]
+fn commit_details_open_from_history_and_after_committing() {
+    let (mut app, _) = app_with(
+        MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+            .with_commits(&["Add a", "Tweak a"]),
+    );
+
+    app.handle_input(KeyCode::Char('l'));
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert!(app.diff_content.starts_with("commit c2\n"));
+    assert!(app.diff_content.contains("+c2 changed everything"));
+    assert!(render(&mut app, 100, 12).contains("Commit c2"));
+
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.pending_copy.as_deref(), Some("c2"));
+    assert!(render(&mut app, 100, 12).contains("Copied c2"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Log);
+    app.handle_input(KeyCode::Esc);
+
+    // `H` shows HEAD, and Esc comes back to the list.
+    app.handle_input(KeyCode::Char('H'));
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert!(app.diff_content.starts_with("commit c2\n"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::FileList);
+}
+
+#[test]
+fn copying_is_only_offered_for_commits() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+    app.handle_input(KeyCode::Char('d'));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.pending_copy, None);
+
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('H'));
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(render(&mut app, 100, 12).contains("No commits yet"));
+}
+
+#[test]
+fn y_copies_the_path_branch_last_commit_or_diff() {
+    let (mut app, _) = app_with(
+        MockBackend::with_files(&[("src/a.txt", FileStatus::Modified, false)])
+            .with_commits(&["Add a", "Tweak a"]),
+    );
+    let mut copy = |key| {
+        app.handle_input(KeyCode::Char('y'));
+        assert_eq!(app.mode, AppMode::CopyMenu);
+        app.handle_input(KeyCode::Char(key));
+        assert_eq!(app.mode, AppMode::FileList);
+        app.pending_copy.take()
+    };
+
+    assert_eq!(copy('p').as_deref(), Some("src/a.txt"));
+    assert_eq!(copy('b').as_deref(), Some("main"));
+    assert_eq!(copy('c').as_deref(), Some("c2"));
+    assert_eq!(
+        copy('d').as_deref(),
+        Some("diff --git a/src/a.txt b/src/a.txt\n+added line\n")
+    );
+
+    // The whole diff shown, from the diff view.
+    app.handle_input(KeyCode::Char('d'));
+    app.handle_input(KeyCode::Char('Y'));
+    assert_eq!(app.pending_copy.as_ref(), Some(&app.diff_content));
+}
+
+#[test]
+fn cherry_pick_from_history_and_commit_details() {
+    let (mut app, backend) = app_with(
+        MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+            .with_commits(&["Add a", "Tweak a"]),
+    );
+
+    app.handle_input(KeyCode::Char('l'));
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.mode, AppMode::FileList);
+    wait_for_task(&mut app);
+    assert!(render(&mut app, 100, 12).contains("Cherry-pick complete"));
+
+    app.handle_input(KeyCode::Char('H'));
+    app.handle_input(KeyCode::Char('c'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["cherry-pick c1", "cherry-pick c2"]);
+}
+
+#[test]
+fn cherry_pick_conflicts_leave_it_in_progress() {
+    let (mut app, backend) = app_with(MockBackend {
+        conflicts: true,
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+            .with_commits(&["Add a"])
+    });
+
+    app.handle_input(KeyCode::Char('l'));
+    app.handle_input(KeyCode::Char('c'));
+    wait_for_task(&mut app);
+    assert_eq!(app.git_status.operation, Some(Operation::CherryPick));
+    let screen = render(&mut app, 120, 12);
+    assert!(screen.contains("Cherry-pick stopped: resolve conflicts"));
+
+    // Nothing else is picked until this one is finished.
+    app.handle_input(KeyCode::Char('l'));
+    app.handle_input(KeyCode::Char('c'));
+    assert!(app.task.is_none());
+    assert!(render(&mut app, 120, 12).contains("Finish the current operation first"));
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('X'));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(backend.calls(), ["cherry-pick c1", "cherry-pick --abort"]);
+    assert_eq!(app.git_status.operation, None);
+}
+
+#[test]
+fn revert_from_history_commits_or_stages() {
+    let (mut app, backend) = app_with(
+        MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+            .with_commits(&["Add a", "Tweak a"]),
+    );
+
+    app.handle_input(KeyCode::Char('l'));
+    app.handle_input(KeyCode::Char('r'));
+    assert_eq!(app.mode, AppMode::FileList);
+    wait_for_task(&mut app);
+    assert!(render(&mut app, 100, 12).contains("Revert committed"));
+
+    app.handle_input(KeyCode::Char('H'));
+    app.handle_input(KeyCode::Char('R'));
+    wait_for_task(&mut app);
+    assert!(render(&mut app, 100, 12).contains("Revert staged"));
+    assert_eq!(
+        backend.calls(),
+        ["revert --no-edit c2", "revert --no-commit c2"]
+    );
+}
+
+#[test]
+fn revert_conflicts_leave_it_in_progress() {
+    let (mut app, _) = app_with(MockBackend {
+        conflicts: true,
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+            .with_commits(&["Add a"])
+    });
+
+    app.handle_input(KeyCode::Char('l'));
+    app.handle_input(KeyCode::Char('R'));
+    wait_for_task(&mut app);
+    assert_eq!(app.git_status.operation, Some(Operation::Revert));
+    assert!(render(&mut app, 120, 12).contains("Revert stopped: resolve conflicts"));
+}
+
+#[test]
+fn tags_are_created_deleted_and_pushed() {
+    let (mut app, backend) = app_with(
+        MockBackend::with_files(&[])
+            .with_remotes(&["origin"])
+            .with_commits(&["Add a", "Release"]),
+    );
+
+    app.handle_input(KeyCode::Char('T'));
+    assert_eq!(app.mode, AppMode::Tags);
+    assert!(render(&mut app, 100, 12).contains("Tags (none yet)"));
+
+    app.handle_input(KeyCode::Char('n'));
+    type_text(&mut app, "v 0.1");
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('a'));
+    type_text(&mut app, "v0.2");
+    app.handle_input(KeyCode::Enter);
+    // An annotated tag needs a message.
+    app.handle_input(KeyCode::Enter);
+    assert!(app.tags.input.is_some());
+    type_text(&mut app, "Second release");
+    assert!(render(&mut app, 100, 12).contains("Message for v0.2"));
+    app.handle_input(KeyCode::Enter);
+    assert!(app.tags.input.is_none());
+
+    let screen = render(&mut app, 100, 12);
+    assert!(screen.contains("v0.2  c2 Release  \"Second release\""));
+    assert!(screen.contains("v0.1  c2 Release"));
+
+    app.handle_input(KeyCode::Char('p'));
+    wait_for_task(&mut app);
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('d'));
+    assert!(render(&mut app, 100, 12).contains("Delete tag v0.1?"));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.tags.tags.len(), 1);
+    app.handle_input(KeyCode::Char('P'));
+    wait_for_task(&mut app);
+
+    assert_eq!(
+        backend.calls(),
+        [
+            "tag v0.1",
+            "tag -a v0.2 -m Second release",
+            "push origin v0.2",
+            "tag -d v0.1",
+            "push origin --tags"
+        ]
+    );
+}
+
+#[test]
+fn release_tags_head_with_the_changelog() {
+    let (mut app, backend) = app_with(
+        MockBackend {
+            tags: Mutex::new(vec![TagInfo {
+                name: "v1.2.3".to_string(),
+                target: "c0".to_string(),
+                summary: "Old".to_string(),
+                message: None,
+            }]),
+            ..MockBackend::with_files(&[])
+        }
+        .with_commits(&["fix: crash", "feat(ui): tags panel"]),
+    );
+
+    app.handle_input(KeyCode::Char('V'));
+    assert_eq!(app.mode, AppMode::Release);
+    let screen = render(&mut app, 120, 16);
+    assert!(screen.contains("Version: v1.3.0  minor bump (suggested) · 2 commits since v1.2.3"));
+    assert!(screen.contains("- **ui:** tags panel (c2)"));
+    assert!(screen.contains("[ ] Prepend to CHANGELOG.md"));
+
+    app.handle_input(KeyCode::Char('b'));
+    assert!(render(&mut app, 120, 16).contains("v2.0.0  major bump (suggested: minor)"));
+    app.handle_input(KeyCode::Char('b'));
+    app.handle_input(KeyCode::Char('b'));
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 120, 16).contains("Tag HEAD v1.3.0"));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(
+        backend.calls(),
+        ["tag -a v1.3.0 -m v1.3.0\n\nFeatures:\n- ui: tags panel (c2)\n\nBug Fixes:\n- crash (c1)"]
+    );
+}
+
+#[test]
+fn release_can_commit_the_changelog_first() {
+    let work_dir = std::env::temp_dir().join(format!("gch-release-{}", std::process::id()));
+    std::fs::create_dir_all(&work_dir).unwrap();
+    std::fs::write(work_dir.join("CHANGELOG.md"), "# Changelog\n").unwrap();
+    let (mut app, backend) = app_with(
+        MockBackend {
+            work_dir: Some(work_dir.clone()),
+            ..MockBackend::with_files(&[])
+        }
+        .with_commits(&["feat: first"]),
+    );
+
+    app.handle_input(KeyCode::Char('V'));
+    assert!(render(&mut app, 120, 16).contains("[x] Prepend to CHANGELOG.md and commit it"));
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('y'));
+    wait_for_task(&mut app);
+
+    let written = std::fs::read_to_string(work_dir.join("CHANGELOG.md")).unwrap();
+    assert!(written.starts_with("# Changelog\n\n## v0.1.0 ("));
+    assert!(written.ends_with("### Features\n\n- first (c1)\n"));
+    assert_eq!(
+        backend.calls(),
+        [
+            "stage CHANGELOG.md",
+            "commit chore(release): v0.1.0",
+            "tag -a v0.1.0 -m v0.1.0\n\nFeatures:\n- first (c1)"
+        ]
+    );
+    assert!(render(&mut app, 120, 16).contains("Released v0.1.0"));
+    std::fs::remove_dir_all(work_dir).unwrap();
+}
+
+fn reflog_entry(id: &str, index: usize, action: &str) -> ReflogEntry {
+    ReflogEntry {
+        id: id.to_string(),
+        short_id: id.to_string(),
+        selector: format!("HEAD@{{{index}}}"),
+        action: action.to_string(),
+        summary: format!("{id} summary"),
+    }
+}
+
+#[test]
+fn reflog_recovers_lost_commits() {
+    let (mut app, backend) = app_with(MockBackend {
+        reflog: vec![
+            reflog_entry("c1", 0, "reset: moving to HEAD~1"),
+            reflog_entry("c2", 1, "commit: Lost work"),
+        ],
+        ..MockBackend::with_files(&[])
+    });
+
+    app.handle_input(KeyCode::Char('L'));
+    assert_eq!(app.mode, AppMode::Reflog);
+    let screen = render(&mut app, 120, 12);
+    assert!(screen.contains("Reflog (2 entries)"));
+    assert!(screen.contains("c2 HEAD@{1}   commit: Lost work  c2 summary"));
+
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Enter);
+    assert!(app.diff_content.starts_with("commit c2\n"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Reflog);
+
+    app.handle_input(KeyCode::Char('r'));
+    assert!(render(&mut app, 120, 12).contains("Move main to c2"));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::FileList);
+
+    app.handle_input(KeyCode::Char('L'));
+    app.handle_input(KeyCode::Char('o'));
+    assert!(render(&mut app, 120, 12).contains("HEAD detached at c1"));
+    app.handle_input(KeyCode::Char('L'));
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('c'));
+    wait_for_task(&mut app);
+
+    assert_eq!(
+        backend.calls(),
+        ["reset --keep c2", "checkout --detach c1", "cherry-pick c2"]
+    );
+}
+
+#[test]
+fn remotes_are_added_renamed_repointed_and_removed() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]).with_remotes(&["origin"]));
+
+    app.handle_input(KeyCode::Char('E'));
+    assert_eq!(app.mode, AppMode::Remotes);
+    assert!(render(&mut app, 120, 12).contains("https://example.com/origin.git"));
+
+    app.handle_input(KeyCode::Char('a'));
+    type_text(&mut app, "fork");
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 120, 12).contains("URL for fork"));
+    type_text(&mut app, "git@example.com:me/a.git");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.remotes.selected, 1);
+
+    app.handle_input(KeyCode::Char('r'));
+    for _ in 0.."fork".len() {
+        app.handle_input(KeyCode::Backspace);
+    }
+    type_text(&mut app, "upstream");
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('e'));
+    type_text(&mut app, "2");
+    app.handle_input(KeyCode::Enter);
+
+    app.handle_input(KeyCode::Char('k'));
+    app.handle_input(KeyCode::Char('d'));
+    assert!(render(&mut app, 120, 12).contains("Remove remote origin"));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.remotes.remotes.len(), 1);
+    assert_eq!(app.remotes.remotes[0].name, "upstream");
+
+    assert_eq!(
+        backend.calls(),
+        [
+            "remote add fork git@example.com:me/a.git",
+            "remote rename fork upstream",
+            "remote set-url upstream git@example.com:me/a.git2",
+            "remote remove origin"
+        ]
+    );
+}
+
+#[test]
+fn remotes_panel_pushes_to_the_chosen_remote() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[]).with_remotes(&["origin", "upstream"]));
+
+    app.handle_input(KeyCode::Char('E'));
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('p'));
+    assert_eq!(app.mode, AppMode::PushDialog);
+    assert_eq!(app.push_dialog.options.remote, "upstream");
+}
+
+#[test]
+fn protected_branches_ask_before_commit_and_push() {
+    let backend = Arc::new(
+        MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)]).with_remotes(&["origin"]),
+    );
+    let mut app = App::new(backend.clone(), Config::default());
+    app.refresh_git_status();
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 100, 20).contains("main is protected. Commit to it directly?"));
+    app.handle_input(KeyCode::Char('n'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(app.task.is_none());
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('y'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["commit Add a"]);
+
+    app.handle_input(KeyCode::Char('p'));
+    app.handle_input(KeyCode::Char('f'));
+    app.handle_input(KeyCode::Enter);
+    assert!(render(&mut app, 100, 20).contains("origin/main is protected, and a force push"));
+    app.handle_input(KeyCode::Esc);
+    app.handle_input(KeyCode::Char('f'));
+    app.handle_input(KeyCode::Char('b'));
+    type_text(&mut app, "-x");
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(
+        backend.calls(),
+        ["commit Add a", "push --set-upstream origin main:main-x"]
+    );
+}
+
+#[test]
+fn ctrl_keys_wait_for_a_confirm_over_the_commit_editor() {
+    let backend = Arc::new(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        true,
+    )]));
+    let mut app = App::new(backend.clone(), Config::default());
+    app.refresh_git_status();
+    let ctrl =
+        |app: &mut App, c| app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    app.handle_input(KeyCode::Enter);
+    assert!(app.confirm.is_some());
+    for c in ['u', 'w', 'k', 'd', 'e', 's'] {
+        ctrl(&mut app, c);
+    }
+    assert_eq!(app.commit_message, "Add a");
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(app.pending_external.is_none());
+    assert!(app.confirm.is_some());
+
+    app.handle_input(KeyCode::Char('y'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["commit Add a"]);
+}
+
+#[test]
+fn commit_editor_draws_guides_and_colours_the_overflow() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add tree view");
+    app.commit_message.push_str("\n\nKeep the tree open.");
+    app.cursor_position = app.commit_message.len();
+
+    let screen = render(&mut app, 120, 16);
+    assert!(screen.contains("Commit Message (subject 13/50, body 19)"));
+    let guide = |line: &str| line.chars().position(|c| c == '┊');
+    let subject = screen
+        .lines()
+        .find(|l| l.contains("Add tree view"))
+        .unwrap();
+    let body = screen
+        .lines()
+        .find(|l| l.contains("Keep the tree"))
+        .unwrap();
+    assert_eq!(guide(subject), guide(body).map(|x| x - 22));
+
+    app.commit_message = format!("Add {}", "x".repeat(60));
+    app.cursor_position = app.commit_message.len();
+    let mut terminal = Terminal::new(TestBackend::new(120, 16)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let row = (0..16)
+        .find(|&y| buffer[(1, y)].symbol() == "A" && buffer[(2, y)].symbol() == "d")
+        .unwrap();
+    let theme = app.config.theme;
+    assert_ne!(buffer[(50, row)].fg, theme.warning);
+    assert_eq!(buffer[(51, row)].fg, theme.warning);
+    assert_eq!(buffer[(64, row)].fg, theme.warning);
+}
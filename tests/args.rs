@@ -0,0 +1,25 @@
+use clap::Parser;
+use git_commit_helper::args::Args;
+use std::path::PathBuf;
+
+#[test]
+fn parses_repository_path_and_git_locations() {
+    let args = Args::try_parse_from(["gch"]).unwrap();
+    assert_eq!(args.path, None);
+
+    let args = Args::try_parse_from([
+        "gch",
+        "../repo",
+        "--git-dir",
+        "repo.git",
+        "--work-tree=tree",
+    ])
+    .unwrap();
+    assert_eq!(args.path, Some(PathBuf::from("../repo")));
+    assert_eq!(args.git_dir, Some(PathBuf::from("repo.git")));
+    assert_eq!(args.work_tree, Some(PathBuf::from("tree")));
+
+    let version = Args::try_parse_from(["gch", "--version"]).unwrap_err();
+    assert_eq!(version.kind(), clap::error::ErrorKind::DisplayVersion);
+    assert!(Args::try_parse_from(["gch", "--bogus"]).is_err());
+}
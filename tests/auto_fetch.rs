@@ -0,0 +1,95 @@
+mod common;
+
+use common::{MockBackend, app_with, app_with_config, render, test_config};
+use git_commit_helper::{App, Config, auto_fetch::ago};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+fn app_fetching_every(interval: Duration, backend: MockBackend) -> (App, Arc<MockBackend>) {
+    let config = Config {
+        auto_fetch: Some(interval),
+        ..test_config()
+    };
+    let (mut app, backend) = app_with_config(backend, config);
+    app.start_auto_fetch();
+    (app, backend)
+}
+
+/// Ticks until `backend` has fetched `count` times and the last result is in.
+fn tick_until_fetched(app: &mut App, backend: &MockBackend, count: usize) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        app.tick();
+        let fetches = backend.calls().iter().filter(|c| *c == "fetch").count();
+        if fetches >= count && (app.last_fetch.is_some() || app.fetch_error.is_some()) {
+            // Let the result of the last one arrive.
+            thread::sleep(Duration::from_millis(20));
+            app.tick();
+            return;
+        }
+        assert!(Instant::now() < deadline, "never fetched");
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn elapsed_times_read_briefly() {
+    assert_eq!(ago(Duration::from_secs(5)), "just now");
+    assert_eq!(ago(Duration::from_secs(5 * 60 + 10)), "5m ago");
+    assert_eq!(ago(Duration::from_secs(2 * 3600)), "2h ago");
+    assert_eq!(ago(Duration::from_secs(3 * 86400)), "3d ago");
+}
+
+#[test]
+fn fetches_at_start_then_waits_for_the_interval() {
+    let (mut app, backend) = app_fetching_every(
+        Duration::from_secs(3600),
+        MockBackend::with_files(&[]).with_remotes(&["origin"]),
+    );
+    assert!(app.last_fetch.is_none());
+
+    tick_until_fetched(&mut app, &backend, 1);
+    assert!(render(&mut app, 120, 10).contains("fetched just now"));
+
+    for _ in 0..10 {
+        app.tick();
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(backend.calls(), ["fetch"]);
+}
+
+#[test]
+fn off_unless_configured() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[]));
+    app.start_auto_fetch();
+    for _ in 0..5 {
+        app.tick();
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(backend.calls().is_empty());
+}
+
+#[test]
+fn failures_are_flagged_and_logged_once() {
+    let (mut app, backend) = app_fetching_every(
+        Duration::ZERO,
+        MockBackend {
+            fetch_error: Some("fatal: could not read from remote repository".to_string()),
+            ..MockBackend::with_files(&[]).with_remotes(&["origin"])
+        },
+    );
+
+    tick_until_fetched(&mut app, &backend, 3);
+    assert!(app.notification.is_none());
+    assert!(render(&mut app, 120, 10).contains("fetch failed"));
+    let logged = app
+        .messages
+        .entries
+        .iter()
+        .filter(|entry| entry.text.contains("Background fetch"))
+        .count();
+    assert_eq!(logged, 1);
+}
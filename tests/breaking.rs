@@ -0,0 +1,64 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    AppMode, FileStatus,
+    breaking::{is_breaking, mark_breaking, unmark_breaking},
+};
+
+#[test]
+fn marking_adds_the_bang_and_a_footer() {
+    assert_eq!(
+        mark_breaking("feat(api): drop v1\n\nOld clients stop working."),
+        Some("feat(api)!: drop v1\n\nOld clients stop working.\n\nBREAKING CHANGE: ".to_string())
+    );
+    assert_eq!(
+        mark_breaking("feat: drop v1\n\nBREAKING CHANGE: v1 is gone"),
+        Some("feat!: drop v1\n\nBREAKING CHANGE: v1 is gone".to_string())
+    );
+    assert_eq!(mark_breaking("Drop v1"), None);
+    assert_eq!(mark_breaking("Drop v1: the old API"), None);
+}
+
+#[test]
+fn unmarking_drops_the_footer_up_to_the_next_one() {
+    assert_eq!(
+        unmark_breaking("feat!: drop v1\n\nBREAKING CHANGE: v1 is gone,\nuse v2\nRefs: #12"),
+        "feat: drop v1\n\nRefs: #12"
+    );
+    assert_eq!(
+        unmark_breaking("feat!: drop v1\n\nBREAKING CHANGE: "),
+        "feat: drop v1"
+    );
+    assert!(is_breaking("fix!: x"));
+    assert!(is_breaking("fix: x\n\nBREAKING-CHANGE: y"));
+    assert!(!is_breaking("fix: x! y"));
+}
+
+#[test]
+fn f8_toggles_a_breaking_change() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    app.commit_message.clear();
+    app.cursor_position = 0;
+    type_text(&mut app, "feat: drop v1");
+
+    app.handle_input(KeyCode::F(8));
+    assert_eq!(app.commit_message, "feat!: drop v1\n\nBREAKING CHANGE: ");
+    assert_eq!(app.cursor_position, app.commit_message.len());
+    let screen = render(&mut app, 120, 24);
+    assert!(screen.contains("[breaking]"));
+    assert!(screen.contains("Describe the breaking change"));
+
+    type_text(&mut app, "v1 is gone");
+    assert!(!render(&mut app, 120, 24).contains("Describe the breaking change"));
+    app.handle_input(KeyCode::F(8));
+    assert_eq!(app.commit_message, "feat: drop v1");
+    assert!(!render(&mut app, 120, 24).contains("[breaking]"));
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{MockBackend, app_with_config, render, test_config, type_text, wait_for_task};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    App, AppMode, Config, FileStatus,
+    checks::{CheckState, ChecksRun},
+};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+fn app_checking(checks: &[&str]) -> (App, Arc<MockBackend>) {
+    app_with_config(
+        MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)]),
+        Config {
+            checks: checks.iter().map(|c| c.to_string()).collect(),
+            ..test_config()
+        },
+    )
+}
+
+fn start_commit(app: &mut App) {
+    app.handle_input(KeyCode::Char('c'));
+    type_text(app, "Add a");
+    app.handle_input(KeyCode::Enter);
+}
+
+/// Ticks until the checks are over, one way or the other.
+fn wait_for_checks(app: &mut App) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app
+        .checks
+        .as_ref()
+        .is_some_and(|run| run.state == CheckState::Running)
+    {
+        assert!(Instant::now() < deadline, "checks never finished");
+        thread::sleep(Duration::from_millis(5));
+        app.tick();
+    }
+}
+
+#[test]
+fn runner_streams_output_and_stops_at_the_first_failure() {
+    let mut run = ChecksRun::start(
+        vec![
+            "echo out; echo err >&2".to_string(),
+            "exit 3".to_string(),
+            "echo never".to_string(),
+        ],
+        None,
+    );
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let result = loop {
+        if let Some(result) = run.poll() {
+            break result;
+        }
+        assert!(Instant::now() < deadline, "checks never finished");
+        thread::sleep(Duration::from_millis(5));
+    };
+
+    assert!(result.unwrap_err().starts_with("exit 3 failed"));
+    assert_eq!(run.state, CheckState::Failed);
+    assert_eq!(run.current, 1);
+    assert!(run.output.starts_with("$ echo out; echo err >&2\n"));
+    assert!(run.output.contains("out\n"));
+    assert!(run.output.contains("err\n"));
+    assert!(run.output.ends_with("$ exit 3\n"));
+    assert!(run.poll().is_none());
+}
+
+#[test]
+fn passing_checks_lead_to_the_commit() {
+    let (mut app, backend) = app_checking(&["echo fine"]);
+
+    start_commit(&mut app);
+    assert_eq!(app.mode, AppMode::Checks);
+    assert!(backend.calls().is_empty());
+    wait_for_checks(&mut app);
+    wait_for_task(&mut app);
+
+    assert!(app.checks.is_none());
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(backend.calls(), ["commit Add a"]);
+}
+
+#[test]
+fn a_failing_check_holds_the_commit_until_overridden() {
+    let (mut app, backend) = app_checking(&["echo lint error; false"]);
+
+    start_commit(&mut app);
+    wait_for_checks(&mut app);
+    let screen = render(&mut app, 120, 12);
+    assert!(screen.contains("Check 1/1 failed"));
+    assert!(screen.contains("lint error"));
+    assert!(backend.calls().is_empty());
+
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(app.commit_message, "Add a");
+
+    app.handle_input(KeyCode::Enter);
+    wait_for_checks(&mut app);
+    app.handle_input(KeyCode::Char('c'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["commit Add a"]);
+}
+
+#[test]
+fn no_verify_skips_the_checks() {
+    let (mut app, backend) = app_checking(&["false"]);
+
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Add a");
+    app.handle_input(KeyCode::F(2));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert!(app.checks.is_none());
+    assert_eq!(backend.calls(), ["commit --no-verify Add a"]);
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use common::{MockBackend, app_with, render};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    AppMode, CommitInfo,
+    ci::{
+        CiCheck, CiProvider, CiState, CiStatus, parse_check_runs, parse_commit_statuses,
+        parse_gitlab_statuses,
+    },
+    git::RemoteInfo,
+    pull_request::Forge,
+};
+use serde_json::json;
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn github_check_runs_and_statuses_are_read() {
+    let runs = json!({ "check_runs": [
+        { "name": "build", "status": "completed", "conclusion": "success", "html_url": "https://ci/1" },
+        { "name": "lint", "status": "completed", "conclusion": "failure", "html_url": "" },
+        { "name": "docs", "status": "completed", "conclusion": "skipped" },
+        { "name": "test", "status": "in_progress", "conclusion": null },
+    ]});
+    let states: Vec<_> = parse_check_runs(&runs)
+        .into_iter()
+        .map(|check| (check.name, check.state, check.url))
+        .collect();
+    assert_eq!(
+        states,
+        [
+            (
+                "build".to_string(),
+                CiState::Success,
+                Some("https://ci/1".to_string())
+            ),
+            ("lint".to_string(), CiState::Failure, None),
+            ("docs".to_string(), CiState::Neutral, None),
+            ("test".to_string(), CiState::Pending, None),
+        ]
+    );
+
+    // GitHub says `state`, Gitea `status`.
+    let statuses = json!({ "statuses": [
+        { "context": "ci/jenkins", "state": "pending", "target_url": "https://jenkins/7" },
+        { "context": "woodpecker", "status": "error" },
+    ]});
+    let states: Vec<_> = parse_commit_statuses(&statuses)
+        .into_iter()
+        .map(|check| (check.name, check.state))
+        .collect();
+    assert_eq!(
+        states,
+        [
+            ("ci/jenkins".to_string(), CiState::Pending),
+            ("woodpecker".to_string(), CiState::Failure),
+        ]
+    );
+}
+
+#[test]
+fn gitlab_jobs_allowed_to_fail_do_not_fail_the_pipeline() {
+    let jobs = json!([
+        { "name": "build", "status": "success" },
+        { "name": "audit", "status": "failed", "allow_failure": true },
+        { "name": "deploy", "status": "manual" },
+        { "name": "test", "status": "running" },
+    ]);
+    let status = CiStatus {
+        commit: "c1".to_string(),
+        checks: parse_gitlab_statuses(&jobs),
+    };
+    assert_eq!(status.count(CiState::Neutral), 2);
+    assert_eq!(status.overall(), Some(CiState::Pending));
+}
+
+#[test]
+fn overall_state_is_the_worst_check() {
+    let check = |state| CiCheck {
+        name: String::new(),
+        state,
+        url: None,
+    };
+    let status = |states: &[CiState]| CiStatus {
+        commit: "c1".to_string(),
+        checks: states.iter().map(|state| check(*state)).collect(),
+    };
+    assert_eq!(status(&[]).overall(), None);
+    assert_eq!(
+        status(&[CiState::Success, CiState::Neutral]).overall(),
+        Some(CiState::Success)
+    );
+    assert_eq!(
+        status(&[CiState::Pending, CiState::Failure]).overall(),
+        Some(CiState::Failure)
+    );
+}
+
+#[derive(Default)]
+struct FakeCi(Mutex<Vec<(Forge, String, String)>>);
+
+impl CiProvider for FakeCi {
+    fn checks(&self, forge: Forge, remote_url: &str, commit: &str) -> Result<Vec<CiCheck>, String> {
+        self.0
+            .lock()
+            .unwrap()
+            .push((forge, remote_url.to_string(), commit.to_string()));
+        Ok(vec![
+            CiCheck {
+                name: "build".to_string(),
+                state: CiState::Success,
+                url: None,
+            },
+            CiCheck {
+                name: "test".to_string(),
+                state: CiState::Failure,
+                url: Some("https://ci.example/test".to_string()),
+            },
+        ])
+    }
+}
+
+#[test]
+fn pushed_commit_checks_show_in_the_header_and_popup() {
+    let commit = CommitInfo {
+        id: "0123456789abcdef".to_string(),
+        short_id: "0123456".to_string(),
+        summary: "Add login".to_string(),
+        author: "Ann".to_string(),
+        time: 0,
+    };
+    let backend = MockBackend {
+        branch: "feature".to_string(),
+        upstream: Some("origin/feature".to_string()),
+        ranges: vec![("origin/feature".to_string(), vec![commit])],
+        ..MockBackend::with_files(&[])
+    }
+    .with_remotes(&["origin"]);
+    *backend.remote_list.lock().unwrap() = vec![RemoteInfo {
+        name: "origin".to_string(),
+        url: "git@github.com:sonmessia/gch.git".to_string(),
+        push_url: None,
+    }];
+    let (mut app, _) = app_with(backend);
+    let fake = Arc::new(FakeCi::default());
+    app.ci_provider = fake.clone();
+
+    // Nothing is asked without a token.
+    app.tick();
+    assert!(fake.0.lock().unwrap().is_empty());
+
+    app.config.forges.github_token = Some("ghp_test".to_string());
+    app.ci.as_mut().unwrap().poll_soon();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.ci.as_ref().unwrap().status.is_none() {
+        assert!(Instant::now() < deadline, "CI status never arrived");
+        thread::sleep(Duration::from_millis(5));
+        app.tick();
+    }
+    assert_eq!(
+        fake.0.lock().unwrap()[0],
+        (
+            Forge::GitHub,
+            "git@github.com:sonmessia/gch.git".to_string(),
+            "0123456789abcdef".to_string()
+        )
+    );
+    let screen = render(&mut app, 140, 10);
+    assert!(screen.contains("CI ✗ 1 failed"));
+    assert!(screen.contains("[w] CI failed"));
+
+    app.handle_input(KeyCode::Char('w'));
+    assert_eq!(app.mode, AppMode::Ci);
+    let screen = render(&mut app, 140, 20);
+    assert!(screen.contains("CI for 0123456"));
+    assert!(screen.contains("✓ build"));
+    assert!(screen.contains("✗ test"));
+
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(app.pending_copy.as_deref(), Some("https://ci.example/test"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::FileList);
+}
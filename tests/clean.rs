@@ -0,0 +1,59 @@
+mod common;
+
+use common::{MockBackend, app_with, render};
+use crossterm::event::KeyCode;
+use git_commit_helper::{App, AppMode};
+use std::sync::{Arc, Mutex};
+
+fn app_with_untracked() -> (App, Arc<MockBackend>) {
+    app_with(MockBackend {
+        clean_list: Mutex::new(vec![
+            ("notes.txt".to_string(), false),
+            ("scratch/".to_string(), false),
+            ("target/".to_string(), true),
+        ]),
+        ..MockBackend::default()
+    })
+}
+
+#[test]
+fn previews_untracked_without_ignored_until_asked() {
+    let (mut app, _) = app_with_untracked();
+    app.handle_input(KeyCode::Char('G'));
+    assert_eq!(app.mode, AppMode::Clean);
+    let screen = render(&mut app, 100, 20);
+    assert!(screen.contains("[ ] notes.txt"));
+    assert!(screen.contains("[ ] scratch"));
+    assert!(!screen.contains("target"));
+
+    app.handle_input(KeyCode::Char('x'));
+    let paths: Vec<&str> = app.clean.entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["notes.txt", "scratch", "target"]);
+}
+
+#[test]
+fn deletes_only_the_marked_entries_after_confirmation() {
+    let (mut app, backend) = app_with_untracked();
+    app.handle_input(KeyCode::Char('G'));
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char(' '));
+    app.handle_input(KeyCode::Char('d'));
+    assert!(app.confirm.is_some());
+    assert!(backend.calls().is_empty());
+
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(backend.calls(), ["clean -fd scratch"]);
+    let paths: Vec<&str> = app.clean.entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["notes.txt"]);
+    assert_eq!(app.mode, AppMode::Clean);
+}
+
+#[test]
+fn nothing_marked_asks_nothing() {
+    let (mut app, backend) = app_with_untracked();
+    app.handle_input(KeyCode::Char('G'));
+    app.handle_input(KeyCode::Enter);
+
+    assert!(app.confirm.is_none());
+    assert!(backend.calls().is_empty());
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{MockBackend, app_with, type_text};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    AppMode, FileStatus,
+    clipboard::{normalize_paste, osc52},
+};
+
+#[test]
+fn osc52_base64_encodes_with_padding() {
+    assert_eq!(osc52("hello"), "\x1b]52;c;aGVsbG8=\x07");
+    assert_eq!(osc52("hi"), "\x1b]52;c;aGk=\x07");
+    assert_eq!(osc52("abc"), "\x1b]52;c;YWJj\x07");
+    assert_eq!(osc52(""), "\x1b]52;c;\x07");
+}
+
+#[test]
+fn pasted_text_gets_plain_newlines() {
+    assert_eq!(
+        normalize_paste("Crash on start \r\n\r\n\tSteps:\x1b\rrun it\r\n\r\n"),
+        "Crash on start\n\n Steps:\nrun it"
+    );
+}
+
+#[test]
+fn paste_lands_in_the_commit_editor_in_one_go() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Staged,
+        true,
+    )]));
+    app.handle_input(KeyCode::Char('c'));
+    app.commit_message.clear();
+    app.cursor_position = 0;
+    type_text(&mut app, "fix: ");
+
+    app.handle_paste("Crash on start\r\nIt panics when the config is empty.\r\n");
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(
+        app.commit_message,
+        "fix: Crash on start\n\nIt panics when the config is empty."
+    );
+    assert_eq!(app.cursor_position, app.commit_message.len());
+
+    // In the body, lines go in as they are.
+    app.handle_paste("\nRefs: #12\nSee: #4");
+    assert!(app.commit_message.ends_with("empty.\nRefs: #12\nSee: #4"));
+}
+
+#[test]
+fn paste_elsewhere_is_typed() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+    app.handle_input(KeyCode::Char('/'));
+    assert_eq!(app.mode, AppMode::FilterInput);
+    app.handle_paste("a.t\n");
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(app.filter.query, "a.t");
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text, wait_for_task};
+use crossterm::event::KeyCode;
+use git_commit_helper::{AppMode, command_bar::split_args};
+
+#[test]
+fn splits_words_like_a_shell() {
+    assert_eq!(
+        split_args(r#"commit -m "Fix \"quoted\" bug" --author='A B <a@b>' x\ y"#).unwrap(),
+        [
+            "commit",
+            "-m",
+            "Fix \"quoted\" bug",
+            "--author=A B <a@b>",
+            "x y"
+        ]
+    );
+    assert_eq!(
+        split_args("  fetch   --prune ").unwrap(),
+        ["fetch", "--prune"]
+    );
+    assert_eq!(split_args("log ''").unwrap(), ["log", ""]);
+    assert!(split_args("commit -m 'oops").is_err());
+}
+
+#[test]
+fn runs_the_command_in_the_background_and_logs_its_output() {
+    let (mut app, backend) = app_with(MockBackend {
+        command_result: Some(Ok("From origin\n - [deleted] old".to_string())),
+        ..MockBackend::default()
+    });
+    app.handle_input(KeyCode::Char(':'));
+    assert_eq!(app.mode, AppMode::CommandBar);
+    type_text(&mut app, "git fetch --prune");
+    assert!(render(&mut app, 80, 12).contains(":git fetch --prune"));
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::FileList);
+    wait_for_task(&mut app);
+
+    assert_eq!(backend.calls(), ["fetch --prune"]);
+    assert!(render(&mut app, 80, 12).contains("git fetch --prune done"));
+    let entry = app.messages.entries.last().unwrap();
+    assert_eq!(
+        entry.detail.as_deref(),
+        Some("From origin\n - [deleted] old")
+    );
+}
+
+#[test]
+fn failures_are_reported_with_gits_message() {
+    let (mut app, _) = app_with(MockBackend {
+        command_result: Some(Err("git: 'frobnicate' is not a git command.".to_string())),
+        ..MockBackend::default()
+    });
+    app.handle_input(KeyCode::Char(':'));
+    type_text(&mut app, "frobnicate");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    assert!(render(&mut app, 100, 12).contains("git frobnicate failed: git: 'frobnicate'"));
+}
+
+#[test]
+fn escape_leaves_without_running_anything() {
+    let (mut app, backend) = app_with(MockBackend::default());
+    app.handle_input(KeyCode::Char(':'));
+    type_text(&mut app, "reset --hard");
+    app.handle_input(KeyCode::Esc);
+
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(backend.calls().is_empty());
+}
@@ -0,0 +1,51 @@
+use git_commit_helper::commit_type::{
+    default_prefixes, default_type_rules, replace_prefix, suggest_prefix,
+};
+
+#[test]
+fn suggests_type_only_when_every_path_agrees() {
+    let rules = default_type_rules();
+    assert_eq!(
+        suggest_prefix(&rules, &["README.md", "docs/setup.md"]),
+        Some("docs: ")
+    );
+    assert_eq!(
+        suggest_prefix(&rules, &["tests/app.rs", "src/ui.test.ts"]),
+        Some("test: ")
+    );
+    assert_eq!(
+        suggest_prefix(&rules, &[".github/workflows/ci.yml"]),
+        Some("chore: ")
+    );
+    assert_eq!(suggest_prefix(&rules, &["README.md", "src/lib.rs"]), None);
+    assert_eq!(suggest_prefix(&rules, &[]), None);
+}
+
+#[test]
+fn picked_prefix_replaces_the_current_one() {
+    let known = default_prefixes();
+    assert_eq!(
+        replace_prefix("add login", "feat: ", &known),
+        (0, "feat: ".to_string())
+    );
+    assert_eq!(
+        replace_prefix("feat: add login", "fix: ", &known),
+        (6, "fix: ".to_string())
+    );
+    assert_eq!(
+        replace_prefix("feat(auth)!: add login", "fix: ", &known),
+        (13, "fix(auth)!: ".to_string())
+    );
+    assert_eq!(
+        replace_prefix("fix: add login", "✨ feat: ", &known),
+        (5, "✨ feat: ".to_string())
+    );
+    assert_eq!(
+        replace_prefix("Note: add login", "✨ feat: ", &known),
+        (0, "✨ feat: ".to_string())
+    );
+    assert_eq!(
+        replace_prefix("Note: add login", "fix: ", &known),
+        (0, "fix: ".to_string())
+    );
+}
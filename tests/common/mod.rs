@@ -0,0 +1,755 @@
+#![allow(dead_code)]
+
+use git_commit_helper::{
+    App, CommitInfo, CommitOptions, Config, ConflictSide, DiffOptions, FileStatus, GitBackend,
+    GitFile, GitStatus, HeadCommit, Identity, Operation, PullMode, PushOptions, RebaseAction,
+    RebaseEntry, ReflogEntry, RemoteInfo, SignatureState, SigningConfig, SubmoduleInfo, TagInfo,
+    WorktreeInfo,
+};
+use ratatui::{Terminal, backend::TestBackend};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// In-memory stand-in for a repository. Records every mutating call so tests
+/// can assert on what the app asked git to do.
+#[derive(Default)]
+pub struct MockBackend {
+    pub branch: String,
+    pub upstream: Option<String>,
+    pub remotes: Vec<String>,
+    pub files: Mutex<Vec<GitFile>>,
+    pub calls: Mutex<Vec<String>>,
+    pub commit_error: Option<String>,
+    pub stage_error: Option<String>,
+    pub push_error: Option<String>,
+    pub pull_error: Option<String>,
+    pub fetch_error: Option<String>,
+    /// What each file's diff adds instead of `added line`.
+    pub diff_body: Option<String>,
+    /// When set, `push` blocks until the app cancels it.
+    pub push_hangs: bool,
+    pub git_dir: Option<PathBuf>,
+    pub work_dir: Option<PathBuf>,
+    pub operation: Mutex<Option<Operation>>,
+    /// Newest first, as `log` returns them.
+    pub commits: Vec<CommitInfo>,
+    pub signing: SigningConfig,
+    /// What `identity` reports; `set_identity` changes it.
+    pub identity: Mutex<Identity>,
+    pub lfs_installed: bool,
+    pub head_signature: Option<SignatureState>,
+    /// When set, cherry-picks, reverts, and applied patches stop with a
+    /// conflict.
+    pub conflicts: bool,
+    pub reflog: Vec<ReflogEntry>,
+    /// Newest first, as `tags` returns them.
+    pub tags: Mutex<Vec<TagInfo>>,
+    /// What `remote_list` shows; `with_remotes` fills it in too.
+    pub remote_list: Mutex<Vec<RemoteInfo>>,
+    /// Only read when `work_dir` has a `.gitmodules`, as in a real one.
+    pub submodule_list: Vec<SubmoduleInfo>,
+    pub worktree_list: Mutex<Vec<WorktreeInfo>>,
+    /// `(path, ignored)` for what `git clean` would delete.
+    pub clean_list: Mutex<Vec<(String, bool)>>,
+    /// What a command from the command bar prints.
+    pub command_result: Option<Result<String, String>>,
+    pub head: Option<HeadCommit>,
+    pub stash_count: usize,
+    pub describe: Option<String>,
+    /// What `ahead_behind` reports against any base.
+    pub compare_counts: Option<(usize, usize)>,
+    /// What `log_range` lists for each range; other ranges fail.
+    pub ranges: Vec<(String, Vec<CommitInfo>)>,
+    /// `(key, value)` git config entries, in order.
+    pub git_config: Vec<(String, String)>,
+    /// What `commit.template` points at.
+    pub commit_template: Option<PathBuf>,
+    /// The branch `create_branch` switched to, reported from then on.
+    pub created_branch: Mutex<Option<String>>,
+}
+
+impl MockBackend {
+    pub fn with_remotes(mut self, remotes: &[&str]) -> MockBackend {
+        self.remotes = remotes.iter().map(|r| r.to_string()).collect();
+        self.remote_list = Mutex::new(
+            remotes
+                .iter()
+                .map(|name| RemoteInfo {
+                    name: name.to_string(),
+                    url: format!("https://example.com/{name}.git"),
+                    push_url: None,
+                })
+                .collect(),
+        );
+        self
+    }
+
+    pub fn with_files(files: &[(&str, FileStatus, bool)]) -> MockBackend {
+        MockBackend {
+            branch: "main".to_string(),
+            files: Mutex::new(
+                files
+                    .iter()
+                    .map(|(path, status, staged)| GitFile {
+                        path: path.to_string(),
+                        status: status.clone(),
+                        staged: *staged,
+                        orig_path: None,
+                        marked: false,
+                        insertions: 0,
+                        deletions: 0,
+                    })
+                    .collect(),
+            ),
+            ..MockBackend::default()
+        }
+    }
+
+    /// Adds commits named `c1`, `c2`, ... with `c1` the oldest.
+    pub fn with_commits(mut self, summaries: &[&str]) -> MockBackend {
+        self.commits = summaries
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, summary)| CommitInfo {
+                id: format!("c{}", i + 1),
+                short_id: format!("c{}", i + 1),
+                summary: summary.to_string(),
+                author: "Ann".to_string(),
+                time: 0,
+            })
+            .collect();
+        self
+    }
+
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn record(&self, call: String) {
+        self.calls.lock().unwrap().push(call);
+    }
+
+    fn set_staged(&self, path: &str, staged: bool) {
+        let mut files = self.files.lock().unwrap();
+        if let Some(file) = files.iter_mut().find(|f| f.path == path) {
+            file.staged = staged;
+        }
+    }
+}
+
+impl GitBackend for MockBackend {
+    fn status(&self) -> GitStatus {
+        GitStatus {
+            current_branch: self.current_branch(),
+            upstream: self.upstream.clone(),
+            files: self.files.lock().unwrap().clone(),
+            operation: *self.operation.lock().unwrap(),
+            head_signature: self.head_signature,
+            head: self.head.clone(),
+            stash_count: self.stash_count,
+            describe: self.describe.clone(),
+            ..GitStatus::default()
+        }
+    }
+
+    fn current_branch(&self) -> String {
+        self.created_branch
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| self.branch.clone())
+    }
+
+    fn git_dir(&self) -> Option<PathBuf> {
+        self.git_dir.clone()
+    }
+
+    fn work_dir(&self) -> Option<PathBuf> {
+        self.work_dir.clone()
+    }
+
+    fn remotes(&self) -> Vec<String> {
+        self.remotes.clone()
+    }
+
+    fn signing_config(&self) -> SigningConfig {
+        self.signing.clone()
+    }
+
+    fn identity(&self) -> Identity {
+        self.identity.lock().unwrap().clone()
+    }
+
+    fn set_identity(&self, name: &str, email: &str) -> Result<(), String> {
+        self.record(format!("config user {name} <{email}>"));
+        let mut identity = self.identity.lock().unwrap();
+        identity.name = Some(name.to_string());
+        identity.email = Some(email.to_string());
+        Ok(())
+    }
+
+    fn lfs_installed(&self) -> bool {
+        self.lfs_installed
+    }
+
+    fn stage(&self, path: &str) -> Result<(), String> {
+        self.record(format!("stage {path}"));
+        if let Some(error) = &self.stage_error {
+            return Err(error.clone());
+        }
+        self.set_staged(path, true);
+        Ok(())
+    }
+
+    fn intent_to_add(&self, path: &str) -> Result<(), String> {
+        self.record(format!("intent_to_add {path}"));
+        let mut files = self.files.lock().unwrap();
+        if let Some(file) = files.iter_mut().find(|f| f.path == path) {
+            file.status = FileStatus::IntentToAdd;
+        }
+        Ok(())
+    }
+
+    fn unstage(&self, path: &str) -> Result<(), String> {
+        self.record(format!("unstage {path}"));
+        self.set_staged(path, false);
+        let mut files = self.files.lock().unwrap();
+        if let Some(file) = files.iter_mut().find(|f| f.path == path)
+            && file.status == FileStatus::IntentToAdd
+        {
+            file.status = FileStatus::Untracked;
+        }
+        Ok(())
+    }
+
+    fn stage_all(&self) -> Result<(), String> {
+        self.record("stage_all".to_string());
+        self.files
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .for_each(|f| f.staged = true);
+        Ok(())
+    }
+
+    fn unstage_all(&self) -> Result<(), String> {
+        self.record("unstage_all".to_string());
+        self.files
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .for_each(|f| f.staged = false);
+        Ok(())
+    }
+
+    fn diff(&self, path: &str, _staged: bool, options: &DiffOptions) -> Result<String, String> {
+        let added = self.diff_body.as_deref().unwrap_or("added line");
+        let mut diff = format!("diff --git a/{path} b/{path}\n+{added}\n");
+        // Lets tests see which options a diff was made with.
+        if *options != DiffOptions::default() {
+            diff.push_str(&format!(" {}\n", options.args().join(" ")));
+        }
+        Ok(diff)
+    }
+
+    fn discard(&self, path: &str, untracked: bool) -> Result<(), String> {
+        self.record(format!("discard {path} untracked={untracked}"));
+        self.files.lock().unwrap().retain(|f| f.path != path);
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        message: &str,
+        options: &CommitOptions,
+        _cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        let mut args = options.args();
+        args.push(message);
+        self.record(format!("commit {}", args.join(" ")));
+        match &self.commit_error {
+            Some(error) => Err(error.clone()),
+            None => {
+                self.files.lock().unwrap().retain(|f| !f.staged);
+                Ok(String::new())
+            }
+        }
+    }
+
+    fn commit_foreground(&self, message: &str, options: &CommitOptions) -> Result<String, String> {
+        self.record("foreground".to_string());
+        self.commit(message, options, &AtomicBool::new(false))
+    }
+
+    fn commit_dry_run(&self, message: &str, options: &CommitOptions) -> Result<String, String> {
+        let mut args = options.args();
+        args.push(message);
+        self.record(format!("commit --dry-run {}", args.join(" ")));
+        let files = self.files.lock().unwrap();
+        let staged: Vec<String> = files
+            .iter()
+            .filter(|f| f.staged)
+            .map(|f| format!("M  {}", f.path))
+            .collect();
+        if staged.is_empty() && !options.allow_empty {
+            return Err("nothing added to commit".to_string());
+        }
+        Ok(staged.join("\n"))
+    }
+
+    fn config_values(&self, key: &str) -> Vec<String> {
+        self.git_config
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    fn commit_template(&self) -> Option<PathBuf> {
+        self.commit_template.clone()
+    }
+
+    fn hooks_dir(&self) -> Option<PathBuf> {
+        self.git_dir.as_ref().map(|dir| dir.join("hooks"))
+    }
+
+    fn push(&self, options: &PushOptions, cancel: &AtomicBool) -> Result<String, String> {
+        self.record(format!("push {}", options.args().join(" ")));
+        while self.push_hangs && !cancel.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        if self.push_hangs {
+            return Err("cancelled".to_string());
+        }
+        match &self.push_error {
+            Some(error) => Err(error.clone()),
+            None => Ok(String::new()),
+        }
+    }
+
+    fn push_foreground(&self, options: &PushOptions) -> Result<String, String> {
+        self.record("foreground".to_string());
+        self.push(options, &AtomicBool::new(false))
+    }
+
+    fn fetch(&self, _cancel: &AtomicBool) -> Result<String, String> {
+        self.record("fetch".to_string());
+        match &self.fetch_error {
+            Some(error) => Err(error.clone()),
+            None => Ok(String::new()),
+        }
+    }
+
+    fn pull(&self, mode: PullMode, _cancel: &AtomicBool) -> Result<String, String> {
+        self.record(format!("pull {}", mode.flag()));
+        match &self.pull_error {
+            Some(error) => Err(error.clone()),
+            None => Ok(String::new()),
+        }
+    }
+
+    fn resolve_conflict(&self, path: &str, side: ConflictSide) -> Result<(), String> {
+        self.record(format!("resolve {path} {}", side.flag()));
+        for file in self.files.lock().unwrap().iter_mut() {
+            if file.path == path {
+                file.status = FileStatus::Modified;
+                file.staged = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn continue_operation(
+        &self,
+        operation: Operation,
+        _cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        self.record(format!("{} --continue", operation.command()));
+        *self.operation.lock().unwrap() = None;
+        Ok(String::new())
+    }
+
+    fn abort_operation(&self, operation: Operation) -> Result<(), String> {
+        self.record(format!("{} --abort", operation.command()));
+        *self.operation.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn commit_fixup(&self, target: &str, _cancel: &AtomicBool) -> Result<String, String> {
+        self.record(format!("fixup {target}"));
+        self.files.lock().unwrap().retain(|f| !f.staged);
+        Ok(String::new())
+    }
+
+    fn autosquash(&self, base: Option<&str>, _cancel: &AtomicBool) -> Result<String, String> {
+        self.record(format!("autosquash {}", base.unwrap_or("--root")));
+        Ok(String::new())
+    }
+
+    fn cherry_pick(&self, id: &str, _cancel: &AtomicBool) -> Result<String, String> {
+        self.record(format!("cherry-pick {id}"));
+        if self.conflicts {
+            *self.operation.lock().unwrap() = Some(Operation::CherryPick);
+            return Err(format!(
+                "error: could not apply {id}\nCONFLICT (content): Merge conflict in a.txt"
+            ));
+        }
+        Ok(String::new())
+    }
+
+    fn revert(&self, id: &str, no_commit: bool, _cancel: &AtomicBool) -> Result<String, String> {
+        let mode = if no_commit {
+            "--no-commit"
+        } else {
+            "--no-edit"
+        };
+        self.record(format!("revert {mode} {id}"));
+        if self.conflicts {
+            *self.operation.lock().unwrap() = Some(Operation::Revert);
+            return Err(format!(
+                "error: could not revert {id}\nCONFLICT (content): Merge conflict in a.txt"
+            ));
+        }
+        Ok(String::new())
+    }
+
+    fn staged_patch(&self) -> Result<String, String> {
+        let files = self.files.lock().unwrap();
+        Ok(files
+            .iter()
+            .filter(|f| f.staged)
+            .map(|f| format!("diff --git a/{p} b/{p}\n", p = f.path))
+            .collect())
+    }
+
+    fn format_patch(&self, commits: &str) -> Result<String, String> {
+        Ok(format!(
+            "From {commits} Mon Sep 17 00:00:00 2001\nSubject: [PATCH] {commits}\n"
+        ))
+    }
+
+    fn apply_patch(
+        &self,
+        path: &Path,
+        mailbox: bool,
+        _cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        let command = if mailbox { "am" } else { "apply" };
+        let name = path.file_name().unwrap().to_string_lossy();
+        self.record(format!("{command} --3way {name}"));
+        if self.conflicts {
+            if mailbox {
+                *self.operation.lock().unwrap() = Some(Operation::Am);
+            }
+            let mut files = self.files.lock().unwrap();
+            if let Some(file) = files.first_mut() {
+                file.status = FileStatus::Conflicted;
+            }
+            return Err("error: Failed to merge in the changes.".to_string());
+        }
+        Ok(String::new())
+    }
+
+    fn remote_list(&self) -> Result<Vec<RemoteInfo>, String> {
+        Ok(self.remote_list.lock().unwrap().clone())
+    }
+
+    fn submodules(&self) -> Result<Vec<SubmoduleInfo>, String> {
+        Ok(self.submodule_list.clone())
+    }
+
+    fn update_submodules(&self, _cancel: &AtomicBool) -> Result<String, String> {
+        self.record("submodule update --init --recursive".to_string());
+        Ok(String::new())
+    }
+
+    fn worktrees(&self) -> Result<Vec<WorktreeInfo>, String> {
+        Ok(self.worktree_list.lock().unwrap().clone())
+    }
+
+    fn create_branch(&self, name: &str) -> Result<(), String> {
+        self.record(format!("switch -c {}", name));
+        *self.created_branch.lock().unwrap() = Some(name.to_string());
+        Ok(())
+    }
+
+    fn add_worktree(&self, path: &Path, branch: &str) -> Result<(), String> {
+        self.record(format!("worktree add {} {}", path.display(), branch));
+        self.worktree_list.lock().unwrap().push(WorktreeInfo {
+            path: path.to_path_buf(),
+            head: Some("abc1234".to_string()),
+            branch: Some(branch.to_string()),
+            bare: false,
+            locked: false,
+            prunable: false,
+        });
+        Ok(())
+    }
+
+    fn run_command(&self, args: &[String], _cancel: &AtomicBool) -> Result<String, String> {
+        self.record(args.join(" "));
+        self.command_result.clone().unwrap_or(Ok(String::new()))
+    }
+
+    fn clean_preview(&self, ignored: bool) -> Result<Vec<String>, String> {
+        let list = self.clean_list.lock().unwrap();
+        Ok(list
+            .iter()
+            .filter(|(_, is_ignored)| ignored || !is_ignored)
+            .map(|(path, _)| path.clone())
+            .collect())
+    }
+
+    fn clean(&self, paths: &[String], ignored: bool) -> Result<(), String> {
+        let flags = if ignored { "-fdx" } else { "-fd" };
+        self.record(format!("clean {} {}", flags, paths.join(" ")));
+        self.clean_list
+            .lock()
+            .unwrap()
+            .retain(|(path, _)| !paths.iter().any(|p| p == path.trim_end_matches('/')));
+        Ok(())
+    }
+
+    fn add_remote(&self, name: &str, url: &str) -> Result<(), String> {
+        self.record(format!("remote add {name} {url}"));
+        self.remote_list.lock().unwrap().push(RemoteInfo {
+            name: name.to_string(),
+            url: url.to_string(),
+            push_url: None,
+        });
+        Ok(())
+    }
+
+    fn remove_remote(&self, name: &str) -> Result<(), String> {
+        self.record(format!("remote remove {name}"));
+        self.remote_list.lock().unwrap().retain(|r| r.name != name);
+        Ok(())
+    }
+
+    fn rename_remote(&self, old: &str, new: &str) -> Result<(), String> {
+        self.record(format!("remote rename {old} {new}"));
+        for remote in self.remote_list.lock().unwrap().iter_mut() {
+            if remote.name == old {
+                remote.name = new.to_string();
+            }
+        }
+        Ok(())
+    }
+
+    fn set_remote_url(&self, name: &str, url: &str) -> Result<(), String> {
+        self.record(format!("remote set-url {name} {url}"));
+        for remote in self.remote_list.lock().unwrap().iter_mut() {
+            if remote.name == name {
+                remote.url = url.to_string();
+            }
+        }
+        Ok(())
+    }
+
+    fn ahead_behind(&self, base: &str) -> Result<(usize, usize), String> {
+        self.compare_counts
+            .ok_or_else(|| format!("fatal: ambiguous argument 'HEAD...{}'", base))
+    }
+
+    fn latest_tag(&self) -> Option<String> {
+        self.tags
+            .lock()
+            .unwrap()
+            .first()
+            .map(|tag| tag.name.clone())
+    }
+
+    fn log_since(&self, _tag: Option<&str>) -> Result<Vec<(CommitInfo, String)>, String> {
+        Ok(self
+            .commits
+            .iter()
+            .map(|commit| (commit.clone(), String::new()))
+            .collect())
+    }
+
+    fn reflog(&self, limit: usize) -> Result<Vec<ReflogEntry>, String> {
+        Ok(self.reflog.iter().take(limit).cloned().collect())
+    }
+
+    fn checkout_detached(&self, id: &str) -> Result<(), String> {
+        self.record(format!("checkout --detach {id}"));
+        Ok(())
+    }
+
+    fn reset_keep(&self, id: &str) -> Result<(), String> {
+        self.record(format!("reset --keep {id}"));
+        Ok(())
+    }
+
+    fn tags(&self) -> Result<Vec<TagInfo>, String> {
+        Ok(self.tags.lock().unwrap().clone())
+    }
+
+    fn create_tag(&self, name: &str, message: Option<&str>) -> Result<(), String> {
+        match message {
+            Some(message) => self.record(format!("tag -a {name} -m {message}")),
+            None => self.record(format!("tag {name}")),
+        }
+        let head = self.commits.first();
+        self.tags.lock().unwrap().insert(
+            0,
+            TagInfo {
+                name: name.to_string(),
+                target: head.map(|c| c.short_id.clone()).unwrap_or_default(),
+                summary: head.map(|c| c.summary.clone()).unwrap_or_default(),
+                message: message.map(str::to_string),
+            },
+        );
+        Ok(())
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<(), String> {
+        self.record(format!("tag -d {name}"));
+        self.tags.lock().unwrap().retain(|tag| tag.name != name);
+        Ok(())
+    }
+
+    fn push_tags(
+        &self,
+        remote: &str,
+        name: Option<&str>,
+        _cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        self.record(format!("push {remote} {}", name.unwrap_or("--tags")));
+        Ok(String::new())
+    }
+
+    fn undo_last_commit(&self) -> Result<String, String> {
+        self.record("undo_last_commit".to_string());
+        self.commits
+            .first()
+            .map(|commit| commit.summary.clone())
+            .ok_or_else(|| "no commits".to_string())
+    }
+
+    fn log(&self, limit: usize) -> Result<Vec<CommitInfo>, String> {
+        Ok(self.commits.iter().take(limit).cloned().collect())
+    }
+
+    fn file_log(&self, path: &str, limit: usize) -> Result<Vec<(CommitInfo, String)>, String> {
+        Ok(self
+            .commits
+            .iter()
+            .take(limit)
+            .map(|commit| (commit.clone(), path.to_string()))
+            .collect())
+    }
+
+    fn log_range(&self, range: &str, limit: usize) -> Result<Vec<CommitInfo>, String> {
+        self.ranges
+            .iter()
+            .find(|(known, _)| known == range)
+            .map(|(_, commits)| commits.iter().take(limit).cloned().collect())
+            .ok_or_else(|| format!("fatal: bad revision '{}'", range))
+    }
+
+    fn range_diff(&self, base: &str, head: &str, _options: &DiffOptions) -> Result<String, String> {
+        Ok(format!(
+            "diff --git a/lib.rs b/lib.rs\n+{} changed since {}\n",
+            head, base
+        ))
+    }
+
+    fn show(&self, id: &str, path: Option<&str>, _options: &DiffOptions) -> Result<String, String> {
+        let diff = format!(
+            "diff --git a/{p} b/{p}\n+{id} changed {p}\n",
+            p = path.unwrap_or("everything")
+        );
+        match path {
+            Some(_) => Ok(diff),
+            None => Ok(format!(
+                "commit {id}\nAuthor: Ann\n\n    {id} summary\n\n{diff}"
+            )),
+        }
+    }
+
+    fn rebase_interactive(
+        &self,
+        base: Option<&str>,
+        todo: &[RebaseEntry],
+        _cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        let todo: Vec<String> = todo
+            .iter()
+            .map(|entry| match &entry.action {
+                RebaseAction::Reword(message) => format!("reword {} {message}", entry.commit.id),
+                action => format!("{} {}", action.label(), entry.commit.id),
+            })
+            .collect();
+        self.record(format!(
+            "rebase {}: {}",
+            base.unwrap_or("--root"),
+            todo.join(", ")
+        ));
+        Ok(String::new())
+    }
+}
+
+pub fn app_with(backend: MockBackend) -> (App, Arc<MockBackend>) {
+    app_with_config(backend, test_config())
+}
+
+/// The config the tests share; start from it to change one setting.
+pub fn test_config() -> Config {
+    // The mock is on `main`, which would make every commit and push ask
+    // first; the tests of that turn protection back on.
+    Config {
+        protected_branches: Vec::new(),
+        // Spell checking would depend on the dictionaries installed.
+        dictionaries: Vec::new(),
+        ..Config::default()
+    }
+}
+
+pub fn app_with_config(backend: MockBackend, config: Config) -> (App, Arc<MockBackend>) {
+    let backend = Arc::new(backend);
+    let mut app = App::new(backend.clone(), config);
+    app.refresh_git_status();
+    (app, backend)
+}
+
+pub fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        app.handle_input(crossterm::event::KeyCode::Char(c));
+    }
+}
+
+/// Ticks the app until its background task has finished.
+pub fn wait_for_task(app: &mut App) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.task.is_some() {
+        assert!(Instant::now() < deadline, "background task never finished");
+        thread::sleep(Duration::from_millis(5));
+        app.tick();
+    }
+}
+
+/// Draws the app into an off-screen buffer and returns its text, row by row.
+pub fn render(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
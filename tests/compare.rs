@@ -0,0 +1,156 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    AppMode, CommitInfo, DiffSource, FileStatus, HeadCommit, compare::compare_label,
+};
+
+#[test]
+fn label_drops_a_remote_name() {
+    let remotes = ["origin".to_string(), "upstream".to_string()];
+    assert_eq!(compare_label("origin/main", &remotes), "main");
+    assert_eq!(
+        compare_label("upstream/release/1.0", &remotes),
+        "release/1.0"
+    );
+    assert_eq!(compare_label("fork/main", &remotes), "fork/main");
+    assert_eq!(compare_label("develop", &remotes), "develop");
+}
+
+fn backend() -> MockBackend {
+    MockBackend {
+        head: Some(HeadCommit {
+            short_id: "1a2b3c4".to_string(),
+            subject: "Add parser".to_string(),
+        }),
+        compare_counts: Some((5, 12)),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+    }
+    .with_remotes(&["origin"])
+}
+
+#[test]
+fn header_counts_against_the_compare_ref_too() {
+    let (mut app, _) = app_with(MockBackend {
+        branch: "feature".to_string(),
+        upstream: Some("origin/feature".to_string()),
+        ..backend()
+    });
+    assert!(!render(&mut app, 100, 10).contains("main: "));
+
+    app.config.compare_ref = Some("origin/main".to_string());
+    app.refresh_git_status();
+    assert!(render(&mut app, 100, 10).contains("Branch: feature | main: ↑5 ↓12"));
+}
+
+#[test]
+fn compare_ref_is_skipped_when_it_says_nothing_new() {
+    let (mut app, _) = app_with(MockBackend {
+        upstream: Some("origin/main".to_string()),
+        ..backend()
+    });
+    app.config.compare_ref = Some("origin/main".to_string());
+    app.refresh_git_status();
+    assert_eq!(app.comparison, None);
+
+    app.config.compare_ref = Some("main".to_string());
+    app.refresh_git_status();
+    assert_eq!(app.comparison, None);
+
+    // A ref that doesn't exist leaves the header as it was.
+    let (mut app, _) = app_with(MockBackend {
+        compare_counts: None,
+        ..backend()
+    });
+    app.config.compare_ref = Some("origin/develop".to_string());
+    app.refresh_git_status();
+    assert_eq!(app.comparison, None);
+}
+
+fn commit(id: &str, summary: &str) -> CommitInfo {
+    CommitInfo {
+        id: id.to_string(),
+        short_id: id.to_string(),
+        summary: summary.to_string(),
+        author: "Ann".to_string(),
+        time: 0,
+    }
+}
+
+#[test]
+fn compare_view_lists_both_sides_and_opens_commits_and_the_diff() {
+    let (mut app, _) = app_with(MockBackend {
+        ranges: vec![
+            (
+                "main..HEAD".to_string(),
+                vec![commit("f2", "Add login form"), commit("f1", "Add session")],
+            ),
+            ("HEAD..main".to_string(), vec![commit("m1", "Bump deps")]),
+        ],
+        ..backend()
+    });
+    app.handle_input(KeyCode::Char('='));
+    assert_eq!(app.mode, AppMode::Compare);
+    let screen = render(&mut app, 100, 14);
+    assert!(screen.contains("↑2 only in HEAD"));
+    assert!(screen.contains("f2 Add login form"));
+    assert!(screen.contains("↓1 only in main"));
+    assert!(screen.contains("m1 Bump deps"));
+
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('j'));
+    assert_eq!(app.compare.selected_commit().unwrap().id, "m1");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::DiffView);
+    assert!(app.diff_content.contains("m1 changed everything"));
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Compare);
+
+    app.handle_input(KeyCode::Char('d'));
+    assert_eq!(
+        app.diff_source,
+        DiffSource::Range {
+            base: "main".to_string(),
+            head: "HEAD".to_string(),
+        }
+    );
+    assert!(render(&mut app, 100, 14).contains("main...HEAD"));
+    assert!(app.diff_content.contains("HEAD changed since main"));
+    app.handle_input(KeyCode::Esc);
+
+    app.handle_input(KeyCode::Char('s'));
+    assert_eq!(
+        (app.compare.base.as_str(), app.compare.head.as_str()),
+        ("HEAD", "main")
+    );
+    assert_eq!(app.compare.ahead[0].id, "m1");
+}
+
+#[test]
+fn a_missing_base_can_be_typed_again() {
+    let (mut app, _) = app_with(MockBackend {
+        ranges: vec![
+            (
+                "origin/develop..HEAD".to_string(),
+                vec![commit("f1", "Add session")],
+            ),
+            ("HEAD..origin/develop".to_string(), Vec::new()),
+        ],
+        ..backend()
+    });
+    app.handle_input(KeyCode::Char('='));
+    assert_eq!(app.mode, AppMode::Compare);
+    assert!(app.compare.input.is_some());
+    assert!(render(&mut app, 100, 14).contains("Comparing HEAD with main"));
+
+    for _ in 0.."main".len() {
+        app.handle_input(KeyCode::Backspace);
+    }
+    type_text(&mut app, "origin/develop");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.compare.input, None);
+    assert_eq!(app.compare.base, "origin/develop");
+    assert_eq!(app.compare.ahead.len(), 1);
+    assert!(app.compare.behind.is_empty());
+}
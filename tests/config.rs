@@ -0,0 +1,233 @@
+use git_commit_helper::{
+    Config, Theme, commit_type::CommitPrefix, identity::Profile, pull_request::Forge,
+};
+use ratatui::style::Color;
+use std::{path::PathBuf, time::Duration};
+
+fn write_config(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("gch-config-{}-{}", name, std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn missing_file_gives_the_defaults() {
+    let path = std::env::temp_dir().join("gch-config-does-not-exist");
+    assert_eq!(Config::load(&path).unwrap().theme, Theme::dark());
+}
+
+#[test]
+fn preset_applies_before_overrides() {
+    let path = write_config(
+        "preset",
+        "# colours\ntheme.staged = #00aa00\n\ntheme = \"light\"\ntheme.diff_add = 28\n",
+    );
+    let theme = Config::load(&path).unwrap().theme;
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(theme.staged, Color::Rgb(0, 0xaa, 0));
+    assert_eq!(theme.diff_add, Color::Indexed(28));
+    assert_eq!(theme.header, Theme::light().header);
+}
+
+#[test]
+fn errors_name_the_line() {
+    let cases = [
+        ("theme = solarized", "1: unknown theme 'solarized'"),
+        (
+            "\ntheme.staged = ultraviolet",
+            "2: unknown color 'ultraviolet'",
+        ),
+        ("theme.sidebar = red", "1: unknown theme element 'sidebar'"),
+        ("editor = vim", "1: unknown setting 'editor'"),
+        ("theme", "1: expected key = value"),
+        (
+            "auto_fetch = 5m",
+            "1: auto_fetch: expected seconds, got '5m'",
+        ),
+        (
+            "secret_entropy = high",
+            "1: secret_entropy: expected a number, got 'high'",
+        ),
+        (
+            "prefix = | Speed",
+            "1: prefix: expected a prefix, e.g. perf: | Description",
+        ),
+        (
+            "wip_no_verify = yes",
+            "1: wip_no_verify: expected true or false, got 'yes'",
+        ),
+    ];
+    for (index, (text, error)) in cases.into_iter().enumerate() {
+        let path = write_config(&format!("error{}", index), text);
+        let result = Config::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.unwrap_err().ends_with(error), "{}", text);
+    }
+}
+
+#[test]
+fn auto_fetch_is_set_in_seconds_and_zero_turns_it_off() {
+    let path = write_config("auto_fetch", "auto_fetch = 300\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.auto_fetch, Some(Duration::from_secs(300)));
+
+    let path = write_config("auto_fetch_off", "auto_fetch = 0\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.auto_fetch, None);
+    assert_eq!(Config::default().auto_fetch, None);
+}
+
+#[test]
+fn compare_ref_is_optional() {
+    let path = write_config("compare_ref", "compare_ref = origin/main\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.compare_ref.as_deref(), Some("origin/main"));
+
+    let path = write_config("compare_ref_off", "compare_ref =\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.compare_ref, None);
+}
+
+#[test]
+fn forges_and_their_tokens_are_per_remote() {
+    let path = write_config(
+        "forges",
+        "gitlab_token = glpat-123\nforge.origin = gitlab\nforge.git.corp.example = Gitea\n",
+    );
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.forges.token(Forge::GitLab), Some("glpat-123"));
+    assert_eq!(config.forges.token(Forge::GitHub), None);
+    assert_eq!(
+        config.forges.forges,
+        [
+            ("origin".to_string(), Forge::GitLab),
+            ("git.corp.example".to_string(), Forge::Gitea)
+        ]
+    );
+
+    let path = write_config("forge_typo", "forge.origin = gitlub\n");
+    let error = Config::load(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.ends_with(":1: forge.origin: expected github, gitlab, or gitea, got 'gitlub'"));
+}
+
+#[test]
+fn ci_poll_is_in_seconds_and_zero_turns_it_off() {
+    assert_eq!(Config::default().ci_poll, Some(Duration::from_secs(30)));
+    let path = write_config("ci_poll", "ci_poll = 120\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.ci_poll, Some(Duration::from_secs(120)));
+
+    let path = write_config("ci_poll_off", "ci_poll = 0\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.ci_poll, None);
+}
+
+#[test]
+fn profiles_are_listed_in_order() {
+    let path = write_config(
+        "profiles",
+        "profile.work = Ann Example <ann@corp.example>\nprofile.personal = Ann <ann@mail.example>\n",
+    );
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        config.profiles,
+        [
+            Profile {
+                label: "work".to_string(),
+                name: "Ann Example".to_string(),
+                email: "ann@corp.example".to_string(),
+            },
+            Profile {
+                label: "personal".to_string(),
+                name: "Ann".to_string(),
+                email: "ann@mail.example".to_string(),
+            },
+        ]
+    );
+
+    let path = write_config("profile_typo", "profile.work = ann@corp.example\n");
+    let error = Config::load(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.ends_with(":1: profile.work: expected Name <email>, got 'ann@corp.example'"));
+}
+
+#[test]
+fn protected_branches_are_a_comma_separated_list() {
+    let path = write_config("protected", "protected_branches = main, prod/*\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.protected_branches, ["main", "prod/*"]);
+
+    let path = write_config("unprotected", "protected_branches =\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(config.protected_branches.is_empty());
+}
+
+#[test]
+fn each_check_line_adds_a_command() {
+    let path = write_config(
+        "checks",
+        "check = cargo fmt --check\ncheck = FOO=1 make test\n",
+    );
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.checks, ["cargo fmt --check", "FOO=1 make test"]);
+}
+
+#[test]
+fn secret_patterns_add_to_the_built_in_rules() {
+    let path = write_config(
+        "secrets",
+        "secret_pattern = tok_[0-9a-f]{8}\nsecret_entropy = 0\n",
+    );
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let rules = &config.secret_scan.rules;
+    assert_eq!(rules.last().unwrap().name, "tok_[0-9a-f]{8}");
+    assert!(rules.iter().any(|rule| rule.name == "Private key"));
+    assert_eq!(config.secret_scan.min_entropy, None);
+}
+
+#[test]
+fn large_file_limits_are_in_megabytes_and_zero_turns_them_off() {
+    let path = write_config("large_files", "large_file_mb = 20\nlarge_binary_mb = 0\n");
+    let config = Config::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.large_files.any, Some(20 * 1024 * 1024));
+    assert_eq!(config.large_files.binary, None);
+}
+
+#[test]
+fn listed_prefixes_replace_the_defaults() {
+    let path = write_config(
+        "prefixes",
+        "prefix = perf: | Performance improvements\nprefix = ✨ feat:\n",
+    );
+    let prefixes = Config::load(&path).unwrap().commit_prefixes;
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(
+        prefixes,
+        [
+            CommitPrefix {
+                prefix: "perf: ".to_string(),
+                description: "Performance improvements".to_string(),
+            },
+            CommitPrefix {
+                prefix: "✨ feat: ".to_string(),
+                description: String::new(),
+            },
+        ]
+    );
+    assert_eq!(Config::default().commit_prefixes[0].prefix, "feat: ");
+}
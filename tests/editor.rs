@@ -0,0 +1,43 @@
+use git_commit_helper::editor::{edit_text, line_args, open_file};
+use std::path::Path;
+
+#[test]
+fn edit_text_round_trips_through_the_editor() {
+    let path = std::env::temp_dir().join(format!("gch-test-{}-EDITMSG", std::process::id()));
+
+    // A fake editor that checks it got the draft, then rewrites the file.
+    let editor = r"grep -q '^draft$' $1 && printf 'Add x\n\n# ignored\nbody   \n\n' >";
+    let message = edit_text(editor, "draft", &path).unwrap();
+
+    assert_eq!(message, "Add x\n\nbody");
+    assert!(!path.exists());
+}
+
+#[test]
+fn edit_text_reports_editor_failure() {
+    let path = std::env::temp_dir().join(format!("gch-test-{}-FAIL", std::process::id()));
+    assert!(edit_text("false", "draft", &path).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn line_args_suit_the_editor() {
+    let path = Path::new("/repo/src/lib.rs");
+    assert_eq!(
+        line_args("vim", path, Some(12)),
+        ["+12", "/repo/src/lib.rs"]
+    );
+    assert_eq!(
+        line_args("/usr/bin/code --wait", path, Some(12)),
+        ["--goto", "/repo/src/lib.rs:12"]
+    );
+    assert_eq!(line_args("hx", path, Some(12)), ["/repo/src/lib.rs:12"]);
+    assert_eq!(line_args("nano", path, None), ["/repo/src/lib.rs"]);
+}
+
+#[test]
+fn open_file_passes_the_line_and_reports_failure() {
+    let path = Path::new("lib.rs");
+    assert!(open_file(r#"test "$1" = +3 && test "$2" = lib.rs #"#, path, Some(3)).is_ok());
+    assert!(open_file("false", path, Some(3)).is_err());
+}
@@ -0,0 +1,21 @@
+use git_commit_helper::filter::fuzzy_match;
+
+#[test]
+fn fuzzy_match_requires_ordered_subsequence() {
+    assert!(fuzzy_match("sui", "src/ui.rs").is_some());
+    assert!(fuzzy_match("rsu", "src/ui.rs").is_none());
+    assert!(fuzzy_match("", "anything").is_some());
+}
+
+#[test]
+fn fuzzy_match_is_smart_case() {
+    assert!(fuzzy_match("readme", "README.md").is_some());
+    assert!(fuzzy_match("Readme", "README.md").is_none());
+}
+
+#[test]
+fn fuzzy_match_prefers_word_starts_and_runs() {
+    let word_start = fuzzy_match("ui", "src/ui.rs").unwrap();
+    let scattered = fuzzy_match("ui", "build.rs/info").unwrap();
+    assert!(word_start > scattered);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use clap::Parser;
+use common::MockBackend;
+use git_commit_helper::{
+    Config, FileStatus,
+    args::{Args, Command},
+    headless,
+};
+
+fn commit(message: &str, stage_all: bool, push: bool) -> Command {
+    Command::Commit {
+        message: message.to_string(),
+        stage_all,
+        push,
+        no_verify: false,
+        signoff: false,
+        allow_empty: false,
+    }
+}
+
+#[test]
+fn status_prints_short_text_or_json() {
+    let backend = MockBackend {
+        upstream: Some("origin/main".to_string()),
+        ..MockBackend::with_files(&[
+            ("a.txt", FileStatus::Modified, true),
+            ("b.txt", FileStatus::Untracked, false),
+        ])
+    };
+    let config = Config::default();
+
+    let text = headless::run(&Command::Status { json: false }, &backend, &config).unwrap();
+    assert_eq!(text, "## main...origin/main\nM  a.txt\n?? b.txt\n");
+
+    let json = headless::run(&Command::Status { json: true }, &backend, &config).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["branch"], "main");
+    assert_eq!(json["upstream"], "origin/main");
+    assert_eq!(json["files"][0]["path"], "a.txt");
+    assert_eq!(json["files"][0]["staged"], true);
+    assert_eq!(json["files"][1]["status"], "untracked");
+    assert!(json["operation"].is_null());
+}
+
+#[test]
+fn commit_stages_commits_and_pushes() {
+    let backend = MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+        .with_remotes(&["origin"]);
+    let config = Config::default();
+
+    let error = headless::run(&commit("feat: x", false, false), &backend, &config).unwrap_err();
+    assert_eq!(error, "nothing staged to commit");
+
+    headless::run(&commit("feat: x", true, true), &backend, &config).unwrap();
+    assert_eq!(
+        backend.calls(),
+        [
+            "stage_all",
+            "commit feat: x",
+            "push --set-upstream origin main"
+        ]
+    );
+}
+
+#[test]
+fn allow_empty_commits_with_nothing_staged() {
+    let backend = MockBackend::with_files(&[]);
+    let args = Args::try_parse_from(["gch", "commit", "-m", "ci: rerun", "--allow-empty"]).unwrap();
+    let command = args.command.unwrap();
+    headless::run(&command, &backend, &Config::default()).unwrap();
+    assert_eq!(backend.calls(), ["commit --allow-empty ci: rerun"]);
+}
+
+#[test]
+fn subcommands_parse() {
+    let args = Args::try_parse_from(["gch", "status", "--json"]).unwrap();
+    assert_eq!(args.command, Some(Command::Status { json: true }));
+    assert_eq!(args.path, None);
+
+    let args =
+        Args::try_parse_from(["gch", "commit", "-m", "fix: y", "--stage-all", "--push"]).unwrap();
+    assert_eq!(args.command, Some(commit("fix: y", true, true)));
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{MockBackend, app_with_config, render, test_config, type_text, wait_for_task};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    App, AppMode, Config, FileStatus,
+    git::Identity,
+    identity::{Profile, email_in_domain},
+};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn profiles_are_read_as_name_and_email() {
+    assert_eq!(
+        Profile::parse("work", "Ann Example <ann@corp.example>"),
+        Some(Profile {
+            label: "work".to_string(),
+            name: "Ann Example".to_string(),
+            email: "ann@corp.example".to_string(),
+        })
+    );
+    assert_eq!(Profile::parse("work", "ann@corp.example"), None);
+    assert_eq!(Profile::parse("work", "<ann@corp.example>"), None);
+}
+
+#[test]
+fn emails_match_the_domain_and_its_subdomains() {
+    assert!(email_in_domain("ann@corp.example", "corp.example"));
+    assert!(email_in_domain("ann@EU.Corp.example", "@corp.example"));
+    assert!(!email_in_domain("ann@notcorp.example", "corp.example"));
+    assert!(!email_in_domain("ann@mail.example", "corp.example"));
+}
+
+fn profile(label: &str, name: &str, email: &str) -> Profile {
+    Profile {
+        label: label.to_string(),
+        name: name.to_string(),
+        email: email.to_string(),
+    }
+}
+
+fn app_as(email: &str, domain: Option<&str>) -> (App, Arc<MockBackend>) {
+    let backend = MockBackend {
+        identity: Mutex::new(Identity {
+            name: Some("Ann".to_string()),
+            email: Some(email.to_string()),
+            email_domain: domain.map(str::to_string),
+        }),
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, true)])
+    };
+    let config = Config {
+        profiles: vec![
+            profile("personal", "Ann", "ann@mail.example"),
+            profile("work", "Ann Example", "ann@corp.example"),
+        ],
+        ..test_config()
+    };
+    app_with_config(backend, config)
+}
+
+#[test]
+fn profile_switcher_sets_the_repository_identity() {
+    let (mut app, backend) = app_as("ann@mail.example", None);
+    app.handle_input(KeyCode::Char('c'));
+    assert!(render(&mut app, 100, 20).contains("Ann <ann@mail.example>"));
+
+    app.handle_input(KeyCode::F(6));
+    assert_eq!(app.mode, AppMode::Profiles);
+    assert_eq!(app.profile_selected, 0);
+    let screen = render(&mut app, 100, 20);
+    assert!(screen.contains("● personal"));
+    assert!(screen.contains("work       Ann Example <ann@corp.example>"));
+
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(
+        backend.calls(),
+        ["config user Ann Example <ann@corp.example>"]
+    );
+    assert_eq!(app.identity.email.as_deref(), Some("ann@corp.example"));
+    assert!(render(&mut app, 100, 20).contains("Ann Example <ann@corp.example>"));
+}
+
+#[test]
+fn email_outside_the_domain_asks_before_committing() {
+    let (mut app, backend) = app_as("ann@mail.example", Some("corp.example"));
+    app.handle_input(KeyCode::Char('c'));
+    assert!(render(&mut app, 100, 20).contains("not @corp.example"));
+    type_text(&mut app, "Fix login");
+    app.handle_input(KeyCode::Enter);
+
+    let confirm = app.confirm.as_ref().expect("asks first");
+    assert!(confirm.message.contains("commit as ann@mail.example"));
+    app.handle_input(KeyCode::Char('n'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(backend.calls().is_empty());
+
+    app.handle_input(KeyCode::Enter);
+    app.handle_input(KeyCode::Char('y'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["commit Fix login"]);
+}
+
+#[test]
+fn email_in_the_domain_commits_straight_away() {
+    let (mut app, backend) = app_as("ann@corp.example", Some("corp.example"));
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "Fix login");
+    app.handle_input(KeyCode::Enter);
+    assert!(app.confirm.is_none());
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["commit Fix login"]);
+}
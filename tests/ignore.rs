@@ -0,0 +1,88 @@
+mod common;
+
+use common::{MockBackend, app_with, render};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    App, AppMode, FileStatus,
+    ignore::{append_pattern, ignore_patterns},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[test]
+fn offers_the_file_its_extension_and_its_directory() {
+    assert_eq!(
+        ignore_patterns("logs/debug.log"),
+        ["/logs/debug.log", "*.log", "/logs/"]
+    );
+    assert_eq!(ignore_patterns("notes.txt"), ["/notes.txt", "*.txt"]);
+    assert_eq!(ignore_patterns(".env"), ["/.env"]);
+    assert_eq!(ignore_patterns("target/"), ["/target/"]);
+    assert_eq!(
+        ignore_patterns("out/[draft]*.md"),
+        ["/out/\\[draft]\\*.md", "*.md", "/out/"]
+    );
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("gch-ignore-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+#[test]
+fn appends_on_a_line_of_its_own_once() {
+    let root = temp_dir("append");
+    fs::write(root.join(".gitignore"), "/target").unwrap();
+
+    assert!(append_pattern(&root, "*.log").unwrap());
+    assert!(!append_pattern(&root, "*.log").unwrap());
+    let text = fs::read_to_string(root.join(".gitignore")).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(text, "/target\n*.log\n");
+}
+
+fn app_in(root: &Path) -> (App, Arc<MockBackend>) {
+    app_with(MockBackend {
+        work_dir: Some(root.to_path_buf()),
+        ..MockBackend::with_files(&[
+            ("logs/debug.log", FileStatus::Untracked, false),
+            ("src/main.rs", FileStatus::Modified, false),
+        ])
+    })
+}
+
+#[test]
+fn menu_choice_is_written_and_staged() {
+    let root = temp_dir("menu");
+    let (mut app, backend) = app_in(&root);
+    app.handle_input(KeyCode::Char('I'));
+    assert_eq!(app.mode, AppMode::IgnoreMenu);
+    let screen = render(&mut app, 80, 20);
+    assert!(screen.contains("2  *.log"));
+
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Enter);
+    let text = fs::read_to_string(root.join(".gitignore")).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(text, "*.log\n");
+    assert_eq!(backend.calls(), ["stage .gitignore"]);
+}
+
+#[test]
+fn tracked_files_cannot_be_ignored() {
+    let root = temp_dir("tracked");
+    let (mut app, _) = app_in(&root);
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Char('I'));
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(!root.join(".gitignore").exists());
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use git_commit_helper::{
+    App, AppMode,
+    git::RemoteInfo,
+    issues::{Issue, IssueProvider, issue_reference, parse_issues},
+    pull_request::Forge,
+};
+use serde_json::json;
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn issues_are_read_from_each_forge() {
+    let github = json!([
+        { "number": 12, "title": "Crash on start" },
+        { "number": 13, "title": "Add login", "pull_request": {} },
+    ]);
+    assert_eq!(
+        parse_issues(Forge::GitHub, &github),
+        vec![Issue {
+            number: 12,
+            title: "Crash on start".to_string()
+        }]
+    );
+    let gitlab = json!([{ "id": 9001, "iid": 4, "title": "Slow diff" }]);
+    assert_eq!(parse_issues(Forge::GitLab, &gitlab)[0].number, 4);
+}
+
+#[test]
+fn references_close_the_issue_on_request() {
+    assert_eq!(issue_reference(7, false), "#7");
+    assert_eq!(issue_reference(7, true), "Closes #7");
+}
+
+#[derive(Default)]
+struct FakeIssues(Mutex<Vec<(Forge, String)>>);
+
+impl IssueProvider for FakeIssues {
+    fn open_issues(&self, forge: Forge, remote_url: &str) -> Result<Vec<Issue>, String> {
+        self.0.lock().unwrap().push((forge, remote_url.to_string()));
+        Ok(vec![
+            Issue {
+                number: 12,
+                title: "Crash on start".to_string(),
+            },
+            Issue {
+                number: 40,
+                title: "Login page is blank".to_string(),
+            },
+        ])
+    }
+}
+
+fn wait_for_issues(app: &mut App) {
+    for _ in 0..200 {
+        app.tick();
+        if !app.issues.is_loading() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("issues never loaded");
+}
+
+fn ctrl_r(app: &mut App) {
+    app.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+}
+
+fn app_on(url: &str) -> App {
+    let backend = MockBackend::with_files(&[]).with_remotes(&["origin"]);
+    *backend.remote_list.lock().unwrap() = vec![RemoteInfo {
+        name: "origin".to_string(),
+        url: url.to_string(),
+        push_url: None,
+    }];
+    app_with(backend).0
+}
+
+#[test]
+fn picked_issue_is_inserted_at_the_cursor() {
+    let mut app = app_on("git@github.com:sonmessia/gch.git");
+    let provider = Arc::new(FakeIssues::default());
+    app.issue_provider = provider.clone();
+    app.mode = AppMode::CommitMessage;
+    type_text(&mut app, "fix: login");
+
+    ctrl_r(&mut app);
+    assert_eq!(app.mode, AppMode::Issues);
+    wait_for_issues(&mut app);
+    assert_eq!(
+        provider.0.lock().unwrap()[0],
+        (
+            Forge::GitHub,
+            "git@github.com:sonmessia/gch.git".to_string()
+        )
+    );
+    let screen = render(&mut app, 100, 24);
+    assert!(screen.contains("GitHub issues"));
+    assert!(screen.contains("Crash on start"));
+
+    type_text(&mut app, "blank");
+    let screen = render(&mut app, 100, 24);
+    assert!(screen.contains("Login page is blank"));
+    assert!(!screen.contains("Crash on start"));
+
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(app.commit_message, "fix: login #40");
+    assert_eq!(app.cursor_position, app.commit_message.len());
+
+    app.commit_message.push_str("\n\n");
+    app.cursor_position = app.commit_message.len();
+    ctrl_r(&mut app);
+    wait_for_issues(&mut app);
+    app.handle_input(KeyCode::Tab);
+    assert!(app.commit_message.ends_with("\n\nCloses #12"));
+}
+
+#[test]
+fn issues_need_a_known_forge() {
+    let mut app = app_on("git@git.corp.example:team/tool.git");
+    app.issue_provider = Arc::new(FakeIssues::default());
+    app.mode = AppMode::CommitMessage;
+    ctrl_r(&mut app);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(render(&mut app, 120, 12).contains("Cannot tell where origin is hosted"));
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use common::{MockBackend, app_with_config, test_config};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    App, Config, FileStatus,
+    large_files::{LargeFile, SizeLimits, find_large_files, format_size},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const KB: u64 = 1024;
+
+/// A scratch working tree holding `files` as `(path, size, binary)`.
+fn work_tree(name: &str, files: &[(&str, usize, bool)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("gch-large-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, size, binary) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let byte = if *binary { 0 } else { b'x' };
+        fs::write(path, vec![byte; *size]).unwrap();
+    }
+    root
+}
+
+const LIMITS: SizeLimits = SizeLimits {
+    any: Some(100 * KB),
+    binary: Some(10 * KB),
+};
+
+#[test]
+fn binaries_have_a_lower_limit_than_text() {
+    let root = work_tree(
+        "limits",
+        &[
+            ("notes.txt", 50 * KB as usize, false),
+            ("logo.png", 50 * KB as usize, true),
+            ("dump.sql", 200 * KB as usize, false),
+            ("gone.txt", 0, false),
+        ],
+    );
+    fs::remove_file(root.join("gone.txt")).unwrap();
+    let paths = ["notes.txt", "logo.png", "dump.sql", "gone.txt"].map(String::from);
+    let found = find_large_files(&root, &paths, LIMITS);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        found,
+        [
+            LargeFile {
+                path: "logo.png".to_string(),
+                size: 50 * KB,
+                binary: true,
+            },
+            LargeFile {
+                path: "dump.sql".to_string(),
+                size: 200 * KB,
+                binary: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn untracked_directories_are_searched() {
+    let root = work_tree(
+        "dirs",
+        &[
+            ("build/out/app.bin", 20 * KB as usize, true),
+            ("build/readme.txt", 10, false),
+        ],
+    );
+    let found = find_large_files(&root, &["build/".to_string()], LIMITS);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, "build/out/app.bin");
+}
+
+#[test]
+fn no_limits_find_nothing() {
+    let root = work_tree("none", &[("big.bin", 20 * KB as usize, true)]);
+    let limits = SizeLimits {
+        any: None,
+        binary: None,
+    };
+    let found = find_large_files(&root, &["big.bin".to_string()], limits);
+    fs::remove_dir_all(&root).unwrap();
+    assert!(found.is_empty());
+}
+
+#[test]
+fn sizes_read_in_the_nearest_unit() {
+    assert_eq!(format_size(12), "12 B");
+    assert_eq!(format_size(980 * KB), "980 KB");
+    assert_eq!(format_size(300 * KB * KB + 400 * KB), "300.4 MB");
+}
+
+fn app_in(root: &Path) -> (App, Arc<MockBackend>) {
+    let backend = MockBackend {
+        work_dir: Some(root.to_path_buf()),
+        ..MockBackend::with_files(&[
+            ("small.txt", FileStatus::Untracked, false),
+            ("video.mp4", FileStatus::Untracked, false),
+        ])
+    };
+    let config = Config {
+        large_files: LIMITS,
+        ..test_config()
+    };
+    app_with_config(backend, config)
+}
+
+#[test]
+fn staging_a_large_file_asks_and_can_be_cancelled() {
+    let root = work_tree(
+        "stage",
+        &[
+            ("small.txt", 10, false),
+            ("video.mp4", 30 * KB as usize, true),
+        ],
+    );
+    let (mut app, backend) = app_in(&root);
+
+    app.handle_input(KeyCode::Char('A'));
+    let confirm = app.confirm.as_ref().expect("asks first");
+    assert_eq!(confirm.title, "Large file");
+    assert!(confirm.message.starts_with("video.mp4  30 KB  binary\n"));
+    app.handle_input(KeyCode::Char('n'));
+    assert!(backend.calls().is_empty());
+
+    app.handle_input(KeyCode::Char('A'));
+    app.handle_input(KeyCode::Char('y'));
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(backend.calls(), ["stage_all"]);
+}
+
+#[test]
+fn small_files_stage_without_asking() {
+    let root = work_tree(
+        "small",
+        &[
+            ("small.txt", 10, false),
+            ("video.mp4", 30 * KB as usize, true),
+        ],
+    );
+    let (mut app, backend) = app_in(&root);
+
+    app.handle_input(KeyCode::Char(' '));
+    fs::remove_dir_all(&root).unwrap();
+    assert!(app.confirm.is_none());
+    assert_eq!(backend.calls(), ["stage small.txt"]);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{MockBackend, app_with, render};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    App, FileStatus,
+    lfs::{LfsPatterns, LfsPointer, describe_pointer_diffs},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const ATTRIBUTES: &str = "\
+# binaries
+*.psd filter=lfs diff=lfs merge=lfs -text
+/assets/** filter=lfs diff=lfs merge=lfs -text
+assets/docs/*.md -filter
+*.txt text eol=lf
+";
+
+#[test]
+fn patterns_follow_gitattributes_matching() {
+    let lfs = LfsPatterns::parse(ATTRIBUTES);
+    assert!(lfs.matches("logo.psd"));
+    assert!(lfs.matches("art/covers/logo.psd"));
+    assert!(lfs.matches("assets/audio/theme.ogg"));
+    assert!(!lfs.matches("src/assets/theme.ogg"));
+    // A later line turns it back off.
+    assert!(!lfs.matches("assets/docs/readme.md"));
+    assert!(!lfs.matches("notes.txt"));
+}
+
+#[test]
+fn files_without_lfs_lines_have_no_patterns() {
+    assert!(LfsPatterns::parse("*.txt text\n").is_empty());
+}
+
+#[test]
+fn pointers_parse_their_object() {
+    let pointer = LfsPointer::parse(
+        "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n",
+    )
+    .unwrap();
+    assert_eq!(pointer.size, 12345);
+    assert_eq!(pointer.describe(), "Git LFS object, 12 KB (4d7a214614ab)");
+    assert_eq!(LfsPointer::parse("just text\n"), None);
+}
+
+#[test]
+fn pointer_hunks_read_as_objects() {
+    let diff = "\
+diff --git a/logo.psd b/logo.psd
+index 1111111..2222222 100644
+--- a/logo.psd
++++ b/logo.psd
+@@ -1,3 +1,3 @@
+ version https://git-lfs.github.com/spec/v1
+-oid sha256:aaaaaaaaaaaaaaaaaaaa
+-size 2048
++oid sha256:bbbbbbbbbbbbbbbbbbbb
++size 3145728
+diff --git a/notes.md b/notes.md
+@@ -1 +1 @@
+-old
++new
+";
+    assert_eq!(
+        describe_pointer_diffs(diff),
+        "\
+diff --git a/logo.psd b/logo.psd
+index 1111111..2222222 100644
+--- a/logo.psd
++++ b/logo.psd
+@@ -1,3 +1,3 @@
+-Git LFS object, 2 KB (aaaaaaaaaaaa)
++Git LFS object, 3.0 MB (bbbbbbbbbbbb)
+diff --git a/notes.md b/notes.md
+@@ -1 +1 @@
+-old
++new
+"
+    );
+}
+
+fn work_tree(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("gch-lfs-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join(".gitattributes"), ATTRIBUTES).unwrap();
+    fs::write(root.join("cover.psd"), [0u8; 64]).unwrap();
+    root
+}
+
+fn app_in(root: &Path, lfs_installed: bool) -> (App, Arc<MockBackend>) {
+    app_with(MockBackend {
+        work_dir: Some(root.to_path_buf()),
+        lfs_installed,
+        ..MockBackend::with_files(&[("cover.psd", FileStatus::Untracked, false)])
+    })
+}
+
+#[test]
+fn lfs_files_are_marked_in_the_list() {
+    let root = work_tree("mark");
+    let (mut app, _) = app_in(&root, true);
+    fs::remove_dir_all(&root).unwrap();
+    assert!(render(&mut app, 60, 12).contains("cover.psd LFS"));
+}
+
+#[test]
+fn staging_an_lfs_file_without_lfs_asks_first() {
+    let root = work_tree("missing");
+    let (mut app, backend) = app_in(&root, false);
+    app.handle_input(KeyCode::Char(' '));
+    let confirm = app.confirm.as_ref().expect("asks first");
+    assert!(
+        confirm
+            .message
+            .starts_with("cover.psd  64 B  LFS, but git-lfs isn't set up\n")
+    );
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(backend.calls(), ["stage cover.psd"]);
+
+    let (mut app, backend) = app_in(&root, true);
+    fs::remove_dir_all(&root).unwrap();
+    app.handle_input(KeyCode::Char(' '));
+    assert!(app.confirm.is_none());
+    assert_eq!(backend.calls(), ["stage cover.psd"]);
+}
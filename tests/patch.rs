@@ -0,0 +1,123 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text, wait_for_task};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    AppMode, FileStatus, Operation,
+    patch::{is_mailbox, patch_file_name},
+};
+use std::{fs, path::PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("gch-patch-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+#[test]
+fn names_and_kinds_of_patches() {
+    assert_eq!(patch_file_name("1a2b3c4d5e6f"), "1a2b3c4.patch");
+    assert_eq!(
+        patch_file_name("origin/main..feature/login"),
+        "feature-login-since-origin-main.patch"
+    );
+    assert!(is_mailbox(
+        "From 1a2b Mon Sep 17 00:00:00 2001\nFrom: Ann\nSubject: [PATCH] Fix\n"
+    ));
+    assert!(!is_mailbox("diff --git a/x b/x\n"));
+}
+
+#[test]
+fn staged_changes_are_exported_from_the_staged_diff() {
+    let root = temp_dir("staged");
+    let (mut app, _) = app_with(MockBackend {
+        work_dir: Some(root.clone()),
+        ..MockBackend::with_files(&[
+            ("a.txt", FileStatus::Modified, true),
+            ("b.txt", FileStatus::Modified, false),
+        ])
+    });
+    app.handle_input(KeyCode::Char('D'));
+    app.handle_input(KeyCode::Char('x'));
+    assert_eq!(app.mode, AppMode::Patch);
+    assert!(render(&mut app, 100, 12).contains("Write the staged changes to"));
+    app.handle_input(KeyCode::Enter);
+    let text = fs::read_to_string(root.join("staged.patch"));
+
+    // Never over an existing file.
+    app.handle_input(KeyCode::Char('x'));
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::Patch);
+    app.handle_input(KeyCode::Esc);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(text.unwrap(), "diff --git a/a.txt b/a.txt\n");
+    assert_eq!(app.mode, AppMode::DiffView);
+}
+
+#[test]
+fn a_commit_from_history_is_exported_for_git_am() {
+    let root = temp_dir("commit");
+    let (mut app, _) = app_with(
+        MockBackend {
+            work_dir: Some(root.clone()),
+            ..MockBackend::default()
+        }
+        .with_commits(&["Add parser"]),
+    );
+    app.handle_input(KeyCode::Char('H'));
+    app.handle_input(KeyCode::Char('x'));
+    for _ in 0.."c1.patch".len() {
+        app.handle_input(KeyCode::Backspace);
+    }
+    type_text(&mut app, "out/parser.patch");
+    fs::create_dir_all(root.join("out")).unwrap();
+    app.handle_input(KeyCode::Enter);
+    let text = fs::read_to_string(root.join("out/parser.patch"));
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(text.unwrap().starts_with("From c1 "));
+    assert_eq!(app.mode, AppMode::DiffView);
+}
+
+#[test]
+fn applying_a_mailbox_that_conflicts_stops_the_am() {
+    let root = temp_dir("apply");
+    fs::write(
+        root.join("fix.patch"),
+        "From 1a2b Mon Sep 17 00:00:00 2001\nSubject: [PATCH] Fix\n\ndiff --git a/a.txt b/a.txt\n",
+    )
+    .unwrap();
+    fs::write(root.join("plain.diff"), "diff --git a/a.txt b/a.txt\n").unwrap();
+    let (mut app, backend) = app_with(MockBackend {
+        work_dir: Some(root.clone()),
+        conflicts: true,
+        ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+    });
+
+    app.handle_input(KeyCode::Char('a'));
+    type_text(&mut app, "missing.patch");
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::Patch);
+    assert!(app.task.is_none());
+    app.handle_input(KeyCode::Esc);
+
+    app.handle_input(KeyCode::Char('a'));
+    type_text(&mut app, "fix.patch");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["am --3way fix.patch"]);
+    assert_eq!(app.git_status.operation, Some(Operation::Am));
+    let screen = render(&mut app, 120, 12);
+    assert!(screen.contains("Patch stopped: 1 conflict(s)"));
+
+    *backend.operation.lock().unwrap() = None;
+    app.refresh_git_status();
+    app.handle_input(KeyCode::Char('a'));
+    type_text(&mut app, "plain.diff");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(backend.calls()[1], "apply --3way plain.diff");
+    assert!(render(&mut app, 120, 12).contains("Patch applied with 1 conflict(s)"));
+}
@@ -0,0 +1,23 @@
+use git_commit_helper::protected::{default_protected_branches, is_protected};
+
+#[test]
+fn defaults_cover_main_master_and_release_branches() {
+    let patterns = default_protected_branches();
+    assert!(is_protected(&patterns, "main"));
+    assert!(is_protected(&patterns, "master"));
+    assert!(is_protected(&patterns, "release/1.2"));
+    assert!(!is_protected(&patterns, "release"));
+    assert!(!is_protected(&patterns, "maintenance"));
+    assert!(!is_protected(&patterns, "feature/main"));
+    assert!(!is_protected(&[], "main"));
+}
+
+#[test]
+fn stars_match_any_run_of_characters() {
+    let patterns = vec!["hotfix-*-*".to_string(), "*/stable".to_string()];
+    assert!(is_protected(&patterns, "hotfix-1-2"));
+    assert!(is_protected(&patterns, "hotfix--"));
+    assert!(!is_protected(&patterns, "hotfix-1"));
+    assert!(is_protected(&patterns, "team/a/stable"));
+    assert!(!is_protected(&patterns, "team/stable/x"));
+}
@@ -0,0 +1,196 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text, wait_for_task};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    AppMode, CommitInfo, FileStatus,
+    git::RemoteInfo,
+    pull_request::{Forge, ForgeSettings, PullRequest, PullRequestHost, prefill, remote_repo},
+};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn repositories_are_read_from_remote_urls() {
+    let repo = |host: &str, repo: &str| Some((host.to_string(), repo.to_string()));
+    assert_eq!(
+        remote_repo("https://github.com/sonmessia/gch.git"),
+        repo("github.com", "sonmessia/gch")
+    );
+    assert_eq!(
+        remote_repo("git@github.com:sonmessia/gch.git"),
+        repo("github.com", "sonmessia/gch")
+    );
+    assert_eq!(
+        remote_repo("ssh://git@git.corp.example:2222/team/tool"),
+        repo("git.corp.example", "team/tool")
+    );
+    assert_eq!(
+        remote_repo("git@gitlab.corp.example:platform/infra/tool.git"),
+        repo("gitlab.corp.example", "platform/infra/tool")
+    );
+    assert_eq!(remote_repo("/srv/git/tool.git"), None);
+}
+
+#[test]
+fn forge_comes_from_the_config_or_the_host_name() {
+    let settings = ForgeSettings {
+        forges: vec![
+            ("work".to_string(), Forge::GitLab),
+            ("git.corp.example".to_string(), Forge::Gitea),
+        ],
+        ..ForgeSettings::default()
+    };
+    assert_eq!(
+        settings.forge_for("origin", "github.com"),
+        Some(Forge::GitHub)
+    );
+    assert_eq!(
+        settings.forge_for("origin", "gitlab.example.com"),
+        Some(Forge::GitLab)
+    );
+    assert_eq!(
+        settings.forge_for("origin", "codeberg.org"),
+        Some(Forge::Gitea)
+    );
+    assert_eq!(
+        settings.forge_for("work", "git.corp.example"),
+        Some(Forge::GitLab)
+    );
+    assert_eq!(
+        settings.forge_for("origin", "git.corp.example"),
+        Some(Forge::Gitea)
+    );
+    assert_eq!(settings.forge_for("origin", "example.com"), None);
+}
+
+fn commit(summary: &str) -> CommitInfo {
+    CommitInfo {
+        id: summary.to_string(),
+        short_id: summary.to_string(),
+        summary: summary.to_string(),
+        author: "Ann".to_string(),
+        time: 0,
+    }
+}
+
+#[test]
+fn prefill_uses_one_commit_or_lists_several() {
+    let one = [(commit("Add login"), "Uses the session store.\n".to_string())];
+    assert_eq!(
+        prefill("feature/login", &one),
+        (
+            "Add login".to_string(),
+            "Uses the session store.".to_string()
+        )
+    );
+
+    // Newest first, as git lists them.
+    let two = [
+        (commit("Test login"), String::new()),
+        (commit("Add login"), String::new()),
+    ];
+    assert_eq!(
+        prefill("feature/add-login_form", &two),
+        (
+            "Add login form".to_string(),
+            "- Add login\n- Test login".to_string()
+        )
+    );
+}
+
+#[derive(Default)]
+struct FakeHost(Mutex<Vec<PullRequest>>);
+
+impl PullRequestHost for FakeHost {
+    fn create(&self, request: &PullRequest) -> Result<String, String> {
+        self.0.lock().unwrap().push(request.clone());
+        Ok("https://github.com/sonmessia/gch/pull/7".to_string())
+    }
+}
+
+fn pushed_to(url: &str) -> MockBackend {
+    let backend = MockBackend {
+        branch: "feature/login".to_string(),
+        upstream: Some("origin/feature/login".to_string()),
+        ..MockBackend::with_files(&[])
+    }
+    .with_remotes(&["origin"])
+    .with_commits(&["Add login", "Test login"]);
+    *backend.remote_list.lock().unwrap() = vec![RemoteInfo {
+        name: "origin".to_string(),
+        url: url.to_string(),
+        push_url: None,
+    }];
+    backend
+}
+
+#[test]
+fn pull_request_is_prefilled_created_and_its_url_copied() {
+    let (mut app, _) = app_with(pushed_to("https://github.com/sonmessia/gch.git"));
+    let host = Arc::new(FakeHost::default());
+    app.pull_request_host = host.clone();
+    assert!(render(&mut app, 120, 10).contains("[o] pull request"));
+
+    app.handle_input(KeyCode::Char('o'));
+    assert_eq!(app.mode, AppMode::PullRequest);
+    let screen = render(&mut app, 120, 24);
+    assert!(screen.contains("New GitHub pull request"));
+    assert!(screen.contains("feature/login → main"));
+    assert!(screen.contains("Title: Login"));
+    assert!(screen.contains("- Add login"));
+
+    type_text(&mut app, " page");
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+
+    let sent = host.0.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].title, "Login page");
+    assert_eq!(sent[0].body, "- Add login\n- Test login");
+    assert_eq!(sent[0].base, "main");
+    assert_eq!(sent[0].head, "feature/login");
+    assert_eq!(sent[0].forge, Forge::GitHub);
+    assert_eq!(sent[0].remote_url, "https://github.com/sonmessia/gch.git");
+    assert!(render(&mut app, 120, 24).contains("https://github.com/sonmessia/gch/pull/7"));
+
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(
+        app.pending_copy.as_deref(),
+        Some("https://github.com/sonmessia/gch/pull/7")
+    );
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::FileList);
+}
+
+#[test]
+fn pull_request_needs_a_pushed_branch() {
+    let (mut app, _) = app_with(
+        MockBackend {
+            branch: "feature".to_string(),
+            ..MockBackend::with_files(&[("a.txt", FileStatus::Modified, false)])
+        }
+        .with_remotes(&["origin"]),
+    );
+    app.handle_input(KeyCode::Char('o'));
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(render(&mut app, 100, 12).contains("Push the branch first"));
+}
+
+#[test]
+fn self_hosted_gitlab_gets_a_merge_request() {
+    let (mut app, _) = app_with(pushed_to("git@git.corp.example:platform/tool.git"));
+    let host = Arc::new(FakeHost::default());
+    app.pull_request_host = host.clone();
+
+    app.handle_input(KeyCode::Char('o'));
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(render(&mut app, 120, 12).contains("Cannot tell where origin is hosted"));
+
+    app.config.forges.forges = vec![("origin".to_string(), Forge::GitLab)];
+    app.handle_input(KeyCode::Char('o'));
+    assert!(render(&mut app, 120, 24).contains("New GitLab merge request"));
+    app.handle_input(KeyCode::Enter);
+    wait_for_task(&mut app);
+    assert_eq!(host.0.lock().unwrap()[0].forge, Forge::GitLab);
+    assert!(render(&mut app, 120, 24).contains("GitLab merge request created"));
+}
@@ -0,0 +1,105 @@
+use git_commit_helper::{
+    CommitInfo,
+    release::{Bump, Change, Version, changelog, prepend_changelog, suggest_bump, tag_message},
+};
+
+fn change(summary: &str, body: &str) -> Change {
+    let commit = CommitInfo {
+        id: "abc1234def".to_string(),
+        short_id: "abc1234".to_string(),
+        summary: summary.to_string(),
+        author: "Ann".to_string(),
+        time: 0,
+    };
+    Change::parse(&commit, body)
+}
+
+#[test]
+fn versions_parse_with_prefix_and_bump() {
+    let (prefix, version) = Version::parse("v1.4.2").unwrap();
+    assert_eq!(prefix, "v");
+    assert_eq!(version.bump(Bump::Patch).to_string(), "1.4.3");
+    assert_eq!(version.bump(Bump::Minor).to_string(), "1.5.0");
+    assert_eq!(version.bump(Bump::Major).to_string(), "2.0.0");
+    assert_eq!(Version::parse("release-0.3.0").unwrap().0, "release-");
+    assert_eq!(Version::parse("nightly"), None);
+    assert_eq!(Version::parse("v1.2"), None);
+}
+
+#[test]
+fn conventional_subjects_and_breaking_footers_are_read() {
+    let feat = change("feat(ui): add tags panel", "");
+    assert_eq!(feat.kind, "feat");
+    assert_eq!(feat.scope.as_deref(), Some("ui"));
+    assert_eq!(feat.description, "add tags panel");
+    assert!(!feat.breaking);
+
+    assert!(change("refactor!: drop the old config", "").breaking);
+    assert!(
+        change(
+            "fix: rename flag",
+            "Details.\n\nBREAKING CHANGE: --x is now --y"
+        )
+        .breaking
+    );
+
+    let plain = change("Update README", "");
+    assert_eq!(plain.kind, "");
+    assert_eq!(plain.description, "Update README");
+}
+
+#[test]
+fn bump_follows_the_biggest_change() {
+    let one = Version::parse("1.0.0").unwrap().1;
+    let zero = Version::parse("0.4.0").unwrap().1;
+    let fixes = [change("fix: a", ""), change("docs: b", "")];
+    assert_eq!(suggest_bump(&fixes, one), Bump::Patch);
+    let features = [change("fix: a", ""), change("feat: b", "")];
+    assert_eq!(suggest_bump(&features, one), Bump::Minor);
+    let breaking = [change("feat!: b", "")];
+    assert_eq!(suggest_bump(&breaking, one), Bump::Major);
+    // Before 1.0.0 a breaking change only bumps the minor version.
+    assert_eq!(suggest_bump(&breaking, zero), Bump::Minor);
+}
+
+#[test]
+fn changelog_groups_changes_by_type() {
+    let changes = [
+        change("feat(tags): push tags", ""),
+        change("fix: crash on empty repo", ""),
+        change("feat!: new config format", ""),
+        change("Tidy up", ""),
+    ];
+    assert_eq!(
+        changelog("v2.0.0", "2026-10-16", &changes),
+        "## v2.0.0 (2026-10-16)\n\
+         \n### Breaking Changes\n\n- new config format (abc1234)\n\
+         \n### Features\n\n- **tags:** push tags (abc1234)\n\
+         \n### Bug Fixes\n\n- crash on empty repo (abc1234)\n\
+         \n### Other Changes\n\n- Tidy up (abc1234)\n"
+    );
+    assert_eq!(
+        tag_message("v2.0.0", &changes[..2]),
+        "v2.0.0\n\nFeatures:\n- tags: push tags (abc1234)\n\nBug Fixes:\n- crash on empty repo (abc1234)"
+    );
+}
+
+#[test]
+fn changelog_sections_go_below_the_title() {
+    let section = "## v1.1.0 (2026-10-16)\n\n- b\n";
+    assert_eq!(
+        prepend_changelog("", section),
+        "# Changelog\n\n## v1.1.0 (2026-10-16)\n\n- b\n"
+    );
+    assert_eq!(
+        prepend_changelog(
+            "# Changelog\n\nAll notable changes.\n\n## v1.0.0\n\n- a\n",
+            section
+        ),
+        "# Changelog\n\nAll notable changes.\n\n## v1.1.0 (2026-10-16)\n\n- b\n\n## v1.0.0\n\n- a\n"
+    );
+    assert_eq!(
+        prepend_changelog("## v1.0.0\n\n- a\n", section),
+        "## v1.1.0 (2026-10-16)\n\n- b\n\n## v1.0.0\n\n- a\n"
+    );
+}
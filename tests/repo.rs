@@ -0,0 +1,99 @@
+mod common;
+
+use common::{render, type_text};
+use crossterm::event::KeyCode;
+use git_commit_helper::{App, AppMode, Config, repo};
+
+#[test]
+fn recent_list_keeps_the_latest_first_without_duplicates() {
+    let state_dir = std::env::temp_dir().join(format!("gch-recent-{}", std::process::id()));
+    repo::remember(&state_dir, "/src/a".as_ref()).unwrap();
+    repo::remember(&state_dir, "/src/b".as_ref()).unwrap();
+    repo::remember(&state_dir, "/src/a".as_ref()).unwrap();
+    assert_eq!(
+        repo::load_recent(&state_dir),
+        vec![std::path::PathBuf::from("/src/a"), "/src/b".into()]
+    );
+    std::fs::remove_dir_all(state_dir).unwrap();
+}
+
+// Changes the working directory, so it is the only test in this binary that
+// touches a real repository.
+#[test]
+fn opens_creates_and_switches_repositories() {
+    let root = std::env::temp_dir().join(format!("gch-norepo-{}", std::process::id()));
+    let state_dir = root.join("state");
+    let project = root.join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let config = Config {
+        state_dir: Some(state_dir.clone()),
+        watch: None,
+        ..Config::default()
+    };
+
+    let mut app = App::without_repo(config, "fatal: not a git repository".to_string());
+    assert_eq!(app.mode, AppMode::NoRepo);
+    let screen = render(&mut app, 100, 20);
+    assert!(screen.contains("Not a git repository"));
+    assert!(screen.contains("Recent repositories (none yet)"));
+
+    // Browse to a directory that isn't a repository either.
+    app.handle_input(KeyCode::Char('o'));
+    type_text(&mut app, project.to_str().unwrap());
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::NoRepo);
+    assert_eq!(
+        std::env::current_dir().unwrap().canonicalize().unwrap(),
+        project.canonicalize().unwrap()
+    );
+
+    app.handle_input(KeyCode::Char('i'));
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(app.git_dir.is_some());
+    assert_eq!(
+        repo::load_recent(&state_dir)[0].canonicalize().unwrap(),
+        project.canonicalize().unwrap()
+    );
+
+    // Switch to another repository and back out of a bad path.
+    let other = root.join("other");
+    std::fs::create_dir_all(&other).unwrap();
+    assert!(
+        std::process::Command::new("git")
+            .arg("init")
+            .arg(&other)
+            .output()
+            .unwrap()
+            .status
+            .success()
+    );
+    repo::remember(&state_dir, &other).unwrap();
+    // As `--git-dir` leaves it; switching mustn't keep opening this one.
+    // SAFETY: no other test in this binary reads the environment.
+    unsafe { std::env::set_var("GIT_DIR", project.join(".git")) };
+    app.handle_input(KeyCode::Char('O'));
+    assert_eq!(app.mode, AppMode::RepoSwitch);
+    // The open repository isn't offered.
+    assert_eq!(app.repo_picker.recent, vec![other.clone()]);
+
+    app.handle_input(KeyCode::Char('o'));
+    type_text(&mut app, root.to_str().unwrap());
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::RepoSwitch);
+    assert_eq!(
+        std::env::current_dir().unwrap().canonicalize().unwrap(),
+        project.canonicalize().unwrap()
+    );
+    assert!(std::env::var_os("GIT_DIR").is_some());
+
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::FileList);
+    assert_eq!(
+        app.git_dir.unwrap().canonicalize().unwrap(),
+        other.join(".git").canonicalize().unwrap()
+    );
+    assert!(std::env::var_os("GIT_DIR").is_none());
+
+    std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    std::fs::remove_dir_all(root).unwrap();
+}
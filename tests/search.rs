@@ -0,0 +1,35 @@
+use git_commit_helper::DiffSearch;
+
+fn search(query: &str, text: &str) -> DiffSearch {
+    let mut search = DiffSearch {
+        query: query.to_string(),
+        ..DiffSearch::default()
+    };
+    search.update(text);
+    search
+}
+
+#[test]
+fn lowercase_queries_ignore_case_and_capitals_do_not() {
+    let text = "fn Parse() {}\n+    parse(x); parse(y);\n";
+    let found = search("parse", text);
+    assert_eq!(found.matches, vec![(0, 3..8), (1, 5..10), (1, 15..20)]);
+    assert_eq!(search("Parse", text).matches, vec![(0, 3..8)]);
+    assert_eq!(search("PARSE", text).counter(), "no matches");
+}
+
+#[test]
+fn stepping_wraps_around_and_starts_below_the_scroll_position() {
+    let mut found = search("x", "x\n\nx\nx\n");
+    found.select_from(1);
+    assert_eq!(found.current_line(), Some(2));
+    assert_eq!(found.counter(), "2/3");
+    found.step(true);
+    found.step(true);
+    assert_eq!(found.current_line(), Some(0));
+    found.step(false);
+    assert_eq!(found.current_line(), Some(3));
+    // Past the last match, search from the top.
+    found.select_from(10);
+    assert_eq!(found.counter(), "1/3");
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{MockBackend, app_with_config, render, test_config, type_text};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use git_commit_helper::{
+    App, AppMode, Config, FileStatus,
+    spell::{Dictionary, misspelled},
+};
+
+fn words(message: &str, dictionary: &Dictionary) -> Vec<String> {
+    misspelled(message, dictionary)
+        .into_iter()
+        .map(|range| message[range].to_string())
+        .collect()
+}
+
+#[test]
+fn unknown_words_are_found_but_code_is_skipped() {
+    let dictionary = Dictionary::from_words(["fix", "the", "login", "walk", "try", "stop"]);
+    assert_eq!(
+        words(
+            "fix(ui): fix teh login\n\nCall `parse_args` in src/main.rs, see HTTP and fooBar.",
+            &dictionary
+        ),
+        ["teh", "Call", "see", "and"]
+    );
+    assert!(words("The walked, tries, stopped login's", &dictionary).is_empty());
+}
+
+#[test]
+fn suggestions_are_close_and_keep_the_capital() {
+    let dictionary = Dictionary::from_words(["the", "then", "they", "tea", "login"]);
+    assert_eq!(dictionary.suggest("teh", 2), ["tea", "the"]);
+    assert_eq!(dictionary.suggest("Lgoin", 3), ["Login"]);
+}
+
+fn app_with_words(words: &[&str]) -> (App, std::path::PathBuf) {
+    let dir = std::env::temp_dir().join(format!("gch-spell-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let dic = dir.join("en.dic");
+    std::fs::write(&dic, format!("{}\n{}\n", words.len(), words.join("/S\n"))).unwrap();
+    let config = Config {
+        dictionaries: vec![dic],
+        state_dir: Some(dir.clone()),
+        ..test_config()
+    };
+    let (app, _) = app_with_config(
+        MockBackend::with_files(&[("a.txt", FileStatus::Staged, true)]),
+        config,
+    );
+    (app, dir)
+}
+
+fn ctrl_s(app: &mut App) {
+    app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+}
+
+#[test]
+fn ctrl_s_corrects_the_word_at_the_cursor() {
+    let (mut app, dir) = app_with_words(&["fix", "the", "login", "page", "tea"]);
+    app.handle_input(KeyCode::Char('c'));
+    type_text(&mut app, "fix teh logn page");
+    app.cursor_position = 2;
+
+    ctrl_s(&mut app);
+    assert_eq!(app.mode, AppMode::Spelling);
+    let screen = render(&mut app, 100, 24);
+    assert!(screen.contains("'teh' (Enter replace"));
+    app.handle_input(KeyCode::Down);
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.commit_message, "fix the logn page");
+    assert_eq!(app.cursor_position, 2);
+
+    // The next word along; `a` keeps it for good.
+    ctrl_s(&mut app);
+    app.handle_input(KeyCode::Char('a'));
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("words")).unwrap(),
+        "logn\n"
+    );
+    ctrl_s(&mut app);
+    assert_eq!(app.mode, AppMode::CommitMessage);
+    assert!(render(&mut app, 100, 24).contains("No misspelled words"));
+
+    let (app, _) = app_with_words(&["fix"]);
+    assert!(app.spelling.misspelled("fix logn").is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,228 @@
+use git_commit_helper::{
+    FileStatus, HeadCommit,
+    git::{
+        SubmoduleState, parse_clean_preview, parse_file_log, parse_head_commit, parse_numstat_z,
+        parse_reflog, parse_remotes, parse_status_z, parse_submodule_status, parse_tags,
+        parse_worktrees,
+    },
+};
+
+#[test]
+fn parses_nul_separated_entries_with_renames() {
+    let output = "R  new name.txt\0old name.txt\0 M dir/ünï \"q\".txt\0?? sp ace/\0";
+    let files = parse_status_z(output);
+
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[0].path, "new name.txt");
+    assert_eq!(files[0].orig_path.as_deref(), Some("old name.txt"));
+    assert_eq!(files[0].status, FileStatus::Renamed);
+    assert!(files[0].staged);
+
+    assert_eq!(files[1].path, "dir/ünï \"q\".txt");
+    assert_eq!(files[1].status, FileStatus::Modified);
+    assert!(!files[1].staged);
+
+    assert_eq!(files[2].path, "sp ace/");
+    assert_eq!(files[2].status, FileStatus::Untracked);
+}
+
+#[test]
+fn intent_to_add_entries_are_unstaged() {
+    let files = parse_status_z(" A new.rs\0AM staged.rs\0");
+    assert_eq!(files[0].status, FileStatus::IntentToAdd);
+    assert!(!files[0].staged);
+    assert_eq!(files[1].status, FileStatus::Added);
+    assert!(files[1].staged);
+}
+
+#[test]
+fn unmerged_entries_are_conflicted_and_unstaged() {
+    for code in ["UU", "AA", "DD", "UD", "AU"] {
+        let files = parse_status_z(&format!("{code} file.txt\0"));
+        assert_eq!(files[0].status, FileStatus::Conflicted, "{code}");
+        assert!(!files[0].staged, "{code}");
+    }
+}
+
+#[test]
+fn numstat_counts_follow_renames_and_zero_binaries() {
+    let output = [
+        "1\t0\t",
+        "a.txt",
+        "b.txt",
+        "-\t-\tbin",
+        "12\t3\tsrc/main.rs",
+        "",
+    ]
+    .join("\0");
+    let counts = parse_numstat_z(&output);
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts["b.txt"], (1, 0));
+    assert_eq!(counts["bin"], (0, 0));
+    assert_eq!(counts["src/main.rs"], (12, 3));
+}
+
+#[test]
+fn file_log_records_carry_the_path_at_each_commit() {
+    let record = |fields: [&str; 5], path: &str| format!("\x1e{}\n\n{}\n", fields.join("\0"), path);
+    let output = record(
+        ["fde62f9d", "fde62f9", "Ann", "1792156305", "Rename"],
+        "b.txt",
+    ) + &record(
+        ["65a67215", "65a6721", "Bob", "1792155959", "Initial"],
+        "a.txt",
+    );
+    let log = parse_file_log(&output);
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].0.short_id, "fde62f9");
+    assert_eq!(log[0].0.summary, "Rename");
+    assert_eq!(log[0].1, "b.txt");
+    assert_eq!(log[1].0.author, "Bob");
+    assert_eq!(log[1].0.time, 1792155959);
+    assert_eq!(log[1].1, "a.txt");
+}
+
+#[test]
+fn parses_lightweight_and_annotated_tags() {
+    let output = [
+        ["v0.2", "tag", "a6bd7b6", "7a9a5db", "Release 0.2", "two"].join("\0"),
+        ["v0.1", "commit", "3f70d5f", "", "one", ""].join("\0"),
+    ]
+    .join("\n");
+    let tags = parse_tags(&output);
+
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[0].name, "v0.2");
+    assert_eq!(tags[0].target, "7a9a5db");
+    assert_eq!(tags[0].summary, "two");
+    assert_eq!(tags[0].message.as_deref(), Some("Release 0.2"));
+    assert_eq!(tags[1].target, "3f70d5f");
+    assert_eq!(tags[1].summary, "one");
+    assert_eq!(tags[1].message, None);
+}
+
+#[test]
+fn parses_reflog_entries() {
+    let output = [
+        [
+            "7a9a5db82b",
+            "7a9a5db",
+            "HEAD@{0}",
+            "reset: moving to HEAD~1",
+            "two",
+        ]
+        .join("\0"),
+        [
+            "ffeb968aeb",
+            "ffeb968",
+            "HEAD@{1}",
+            "commit: three",
+            "three",
+        ]
+        .join("\0"),
+    ]
+    .join("\n");
+    let entries = parse_reflog(&output);
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].selector, "HEAD@{0}");
+    assert_eq!(entries[0].action, "reset: moving to HEAD~1");
+    assert_eq!(entries[1].id, "ffeb968aeb");
+    assert_eq!(entries[1].short_id, "ffeb968");
+    assert_eq!(entries[1].summary, "three");
+}
+
+#[test]
+fn parses_remotes_with_separate_push_urls() {
+    let output = "origin\thttps://example.com/a.git (fetch)\n\
+                  origin\thttps://example.com/a.git (push)\n\
+                  upstream\tgit@x:y.git (fetch)\n\
+                  upstream\tgit@x:push.git (push)\n";
+    let remotes = parse_remotes(output);
+
+    assert_eq!(remotes.len(), 2);
+    assert_eq!(remotes[0].name, "origin");
+    assert_eq!(remotes[0].url, "https://example.com/a.git");
+    assert_eq!(remotes[0].push_url, None);
+    assert_eq!(remotes[1].url, "git@x:y.git");
+    assert_eq!(remotes[1].push_url.as_deref(), Some("git@x:push.git"));
+}
+
+#[test]
+fn parses_submodule_status_with_and_without_describe() {
+    let output = " 1111111111111111111111111111111111111111 libs/core (v1.2.0)\n\
+                  +2222222222222222222222222222222222222222 vendor/my lib (heads/main)\n\
+                  -3333333333333333333333333333333333333333 docs/theme\n\
+                  U4444444444444444444444444444444444444444 tools\n";
+    let submodules = parse_submodule_status(output);
+
+    let summary: Vec<(&str, SubmoduleState)> = submodules
+        .iter()
+        .map(|s| (s.path.as_str(), s.state))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("libs/core", SubmoduleState::Current),
+            ("vendor/my lib", SubmoduleState::Moved),
+            ("docs/theme", SubmoduleState::Uninitialized),
+            ("tools", SubmoduleState::Conflicted),
+        ]
+    );
+    assert_eq!(submodules[1].id, "2222222222222222222222222222222222222222");
+}
+
+#[test]
+fn parses_porcelain_worktree_list() {
+    let output = "worktree /work/gch\n\
+                  HEAD 1111111111111111111111111111111111111111\n\
+                  branch refs/heads/main\n\
+                  \n\
+                  worktree /work/gch-feature-login\n\
+                  HEAD 2222222222222222222222222222222222222222\n\
+                  branch refs/heads/feature/login\n\
+                  locked\n\
+                  \n\
+                  worktree /tmp/gch-bisect\n\
+                  HEAD 3333333333333333333333333333333333333333\n\
+                  detached\n\
+                  prunable gitdir file points to non-existent location\n";
+    let worktrees = parse_worktrees(output);
+
+    assert_eq!(worktrees.len(), 3);
+    assert_eq!(worktrees[0].path, std::path::Path::new("/work/gch"));
+    assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+    assert_eq!(worktrees[1].branch.as_deref(), Some("feature/login"));
+    assert!(worktrees[1].locked);
+    assert_eq!(worktrees[2].branch, None);
+    assert_eq!(
+        worktrees[2].head.as_deref(),
+        Some("3333333333333333333333333333333333333333")
+    );
+    assert!(worktrees[2].prunable);
+}
+
+#[test]
+fn parses_clean_preview_and_unquotes_paths() {
+    let output = "Would remove build/\n\
+                  Would remove \"say \\\"hi\\\".txt\"\n\
+                  Would remove \"tab\\there\"\n\
+                  Would skip repository vendor/other\n\
+                  Would remove ünï.txt\n";
+    assert_eq!(
+        parse_clean_preview(output),
+        ["build/", "say \"hi\".txt", "tab\there", "ünï.txt"]
+    );
+}
+
+#[test]
+fn parses_head_commit() {
+    assert_eq!(
+        parse_head_commit("1a2b3c4\0Fix: keep tabs\0in subjects\n"),
+        Some(HeadCommit {
+            short_id: "1a2b3c4".to_string(),
+            subject: "Fix: keep tabs\0in subjects".to_string(),
+        })
+    );
+    assert_eq!(parse_head_commit(""), None);
+}
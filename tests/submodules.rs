@@ -0,0 +1,120 @@
+mod common;
+
+use common::{MockBackend, app_with, render, wait_for_task};
+use crossterm::event::KeyCode;
+use git_commit_helper::{
+    App, AppMode, External, FileStatus,
+    git::{SubmoduleInfo, SubmoduleState},
+    submodules::describe,
+};
+use std::{fs, path::PathBuf, sync::Arc};
+
+fn submodule(path: &str, state: SubmoduleState) -> SubmoduleInfo {
+    SubmoduleInfo {
+        path: path.to_string(),
+        id: "abcdef1234567890".to_string(),
+        state,
+        dirty: false,
+        ahead: 0,
+        behind: 0,
+    }
+}
+
+#[test]
+fn descriptions_sum_up_what_changed() {
+    let moved = SubmoduleInfo {
+        ahead: 3,
+        behind: 1,
+        dirty: true,
+        ..submodule("lib", SubmoduleState::Moved)
+    };
+    assert_eq!(describe(&moved), "↑3 ↓1, modified");
+    assert_eq!(
+        describe(&submodule("lib", SubmoduleState::Uninitialized)),
+        "not initialized"
+    );
+    assert_eq!(describe(&submodule("lib", SubmoduleState::Current)), "");
+}
+
+/// A repository with a moved `vendor/lib` and an uninitialized `docs/theme`.
+fn app_with_submodules(name: &str) -> (App, Arc<MockBackend>, PathBuf) {
+    let root = std::env::temp_dir().join(format!("gch-submodules-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join(".gitmodules"), "").unwrap();
+    let (app, backend) = app_with(MockBackend {
+        work_dir: Some(root.clone()),
+        submodule_list: vec![
+            SubmoduleInfo {
+                ahead: 2,
+                ..submodule("vendor/lib", SubmoduleState::Moved)
+            },
+            submodule("docs/theme", SubmoduleState::Uninitialized),
+        ],
+        ..MockBackend::with_files(&[
+            ("src/main.rs", FileStatus::Modified, false),
+            ("vendor/lib", FileStatus::Modified, false),
+        ])
+    });
+    (app, backend, root)
+}
+
+#[test]
+fn submodules_get_their_own_section() {
+    let (mut app, _, root) = app_with_submodules("section");
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(app.files.len(), 1);
+    let screen = render(&mut app, 80, 20);
+    assert!(screen.contains("Submodules (S)"));
+    assert!(screen.contains("○ vendor/lib abcdef1 ↑2"));
+    assert!(screen.contains("- docs/theme abcdef1 not initialized"));
+}
+
+#[test]
+fn space_stages_the_new_commit_and_enter_opens_a_nested_gch() {
+    let (mut app, backend, root) = app_with_submodules("actions");
+
+    app.handle_input(KeyCode::Char('S'));
+    assert_eq!(app.mode, AppMode::Submodules);
+    app.handle_input(KeyCode::Char(' '));
+    assert_eq!(backend.calls(), ["stage vendor/lib"]);
+    assert!(render(&mut app, 80, 20).contains("● vendor/lib"));
+
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(
+        app.pending_external,
+        Some(External::Submodule(root.join("vendor/lib")))
+    );
+
+    // Not cloned yet, so there is nothing to open.
+    app.pending_external = None;
+    app.handle_input(KeyCode::Char('j'));
+    app.handle_input(KeyCode::Enter);
+    assert_eq!(app.pending_external, None);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn u_updates_every_submodule() {
+    let (mut app, backend, root) = app_with_submodules("update");
+    fs::remove_dir_all(&root).unwrap();
+
+    app.handle_input(KeyCode::Char('S'));
+    app.handle_input(KeyCode::Char('u'));
+    wait_for_task(&mut app);
+    assert_eq!(backend.calls(), ["submodule update --init --recursive"]);
+    app.handle_input(KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::FileList);
+}
+
+#[test]
+fn repositories_without_submodules_say_so() {
+    let (mut app, _) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+    app.handle_input(KeyCode::Char('S'));
+    assert_eq!(app.mode, AppMode::FileList);
+    assert!(!render(&mut app, 80, 20).contains("Submodules"));
+}
@@ -0,0 +1,78 @@
+use git_commit_helper::text::{
+    grapheme_count, next_boundary, next_word, prev_boundary, prev_whitespace_word, prev_word, wrap,
+    wrapped_cell,
+};
+
+#[test]
+fn boundaries_step_over_whole_graphemes() {
+    // "e" + combining acute, then a thumbs up with a skin tone modifier.
+    let text = "e\u{301}👍🏽!";
+    let after_e = next_boundary(text, 0);
+    assert_eq!(&text[..after_e], "e\u{301}");
+    let after_thumb = next_boundary(text, after_e);
+    assert_eq!(&text[after_e..after_thumb], "👍🏽");
+    assert_eq!(prev_boundary(text, after_thumb), after_e);
+    assert_eq!(prev_boundary(text, 0), 0);
+    assert_eq!(next_boundary(text, text.len()), text.len());
+    assert_eq!(grapheme_count(text), 3);
+}
+
+#[test]
+fn wrapped_cell_counts_display_width_per_line() {
+    let text = "修复 bug\n日本";
+    assert_eq!(wrapped_cell(text, "修复".len(), 80), (4, 0));
+    assert_eq!(wrapped_cell(text, text.len(), 80), (4, 1));
+}
+
+#[test]
+fn long_lines_wrap_at_spaces() {
+    let text = "fix: handle empty diffs\nsupercalifragilistic";
+    let rows: Vec<&str> = wrap(text, 10).into_iter().map(|row| &text[row]).collect();
+    assert_eq!(
+        rows,
+        [
+            "fix: ",
+            "handle ",
+            "empty ",
+            "diffs",
+            "supercalif",
+            "ragilistic"
+        ]
+    );
+    // Wide characters count two columns.
+    let rows: Vec<&str> = wrap("修复修复修复", 5)
+        .into_iter()
+        .map(|row| &"修复修复修复"[row])
+        .collect();
+    assert_eq!(rows, ["修复", "修复", "修复"]);
+    assert_eq!(wrap("", 10).len(), 1);
+
+    // Where a row ends and the next starts, the cursor is on the next.
+    assert_eq!(wrapped_cell(text, "fix: ".len(), 10), (0, 1));
+    assert_eq!(wrapped_cell(text, "fix: handle".len(), 10), (6, 1));
+    assert_eq!(
+        wrapped_cell(text, "fix: handle empty diffs".len(), 10),
+        (5, 3)
+    );
+    assert_eq!(wrapped_cell(text, text.len(), 10), (9, 5));
+}
+
+#[test]
+fn word_motions_follow_readline() {
+    let text = "fix(ui): café menu--layout";
+    let end = text.len();
+    assert_eq!(&text[prev_word(text, end)..], "layout");
+    let menu = prev_word(text, prev_word(text, end));
+    assert_eq!(&text[menu..], "menu--layout");
+    assert_eq!(prev_word(text, "fix(ui): ".len()), "fix(".len());
+    assert_eq!(prev_word(text, 0), 0);
+
+    assert_eq!(next_word(text, 0), "fix".len());
+    assert_eq!(next_word(text, "fix".len()), "fix(ui".len());
+    assert_eq!(next_word(text, "fix(ui".len()), "fix(ui): café".len());
+    assert_eq!(next_word(text, menu + "menu".len()), end);
+
+    assert_eq!(&text[prev_whitespace_word(text, end)..], "menu--layout");
+    assert_eq!(&text[prev_whitespace_word(text, menu - 1)..menu], "café ");
+    assert_eq!(prev_whitespace_word("  ", 2), 0);
+}
@@ -0,0 +1,35 @@
+use git_commit_helper::ticket::{TicketRules, jira_ticket_pattern};
+
+fn rules(template: &str) -> TicketRules {
+    TicketRules {
+        pattern: Some(jira_ticket_pattern()),
+        template: template.to_string(),
+    }
+}
+
+#[test]
+fn finds_ticket_in_branch_name() {
+    let rules = rules("{ticket}: {message}");
+    assert_eq!(
+        rules.find("feature/PROJ-123-login"),
+        Some("PROJ-123".to_string())
+    );
+    assert_eq!(rules.find("fix-utf-8-paths"), None);
+    assert_eq!(TicketRules::default().find("PROJ-123"), None);
+}
+
+#[test]
+fn template_rewrites_only_the_subject() {
+    assert_eq!(
+        rules("{ticket}: {message}").apply("Add login\n\nBody.", "PROJ-1"),
+        "PROJ-1: Add login\n\nBody."
+    );
+    assert_eq!(
+        rules("{message} ({ticket})").apply("Add login", "PROJ-1"),
+        "Add login (PROJ-1)"
+    );
+    assert_eq!(
+        rules("{ticket}: {message}").apply("PROJ-1: Add login", "PROJ-1"),
+        "PROJ-1: Add login"
+    );
+}
@@ -0,0 +1,42 @@
+use git_commit_helper::trailers::{Trailer, append_trailers};
+
+fn trailer(key: &str, value: &str) -> Trailer {
+    Trailer {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[test]
+fn parses_key_value_lines() {
+    assert_eq!(
+        Trailer::parse("Reviewed-by: Ann <ann@example.com>"),
+        Some(trailer("Reviewed-by", "Ann <ann@example.com>"))
+    );
+    assert_eq!(Trailer::parse("Not a trailer: line"), None);
+    assert_eq!(Trailer::parse("Refs:"), None);
+}
+
+#[test]
+fn starts_a_block_or_joins_the_existing_one() {
+    let refs = [trailer("Refs", "#12")];
+    assert_eq!(append_trailers("Add a\n", &refs), "Add a\n\nRefs: #12");
+    assert_eq!(
+        append_trailers("Add a\n\nBody text.", &refs),
+        "Add a\n\nBody text.\n\nRefs: #12"
+    );
+    assert_eq!(
+        append_trailers("Add a\n\nAcked-by: Bo <bo@example.com>", &refs),
+        "Add a\n\nAcked-by: Bo <bo@example.com>\nRefs: #12"
+    );
+}
+
+#[test]
+fn skips_trailers_already_in_the_message() {
+    let trailers = [trailer("Refs", "#12"), trailer("Refs", "#12")];
+    assert_eq!(
+        append_trailers("Add a\n\nRefs: #12", &trailers),
+        "Add a\n\nRefs: #12"
+    );
+    assert_eq!(append_trailers("Add a", &trailers), "Add a\n\nRefs: #12");
+}
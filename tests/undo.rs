@@ -0,0 +1,46 @@
+use git_commit_helper::undo::{EditHistory, changed_end};
+
+#[test]
+fn typing_a_word_is_one_step() {
+    let mut edits = EditHistory::new("");
+    let mut message = String::new();
+    for c in "fix the bug".chars() {
+        message.push(c);
+        edits.record(&message);
+    }
+    assert_eq!(edits.undo(&message).as_deref(), Some("fix the "));
+    assert_eq!(edits.undo("fix the ").as_deref(), Some("fix "));
+    assert_eq!(edits.undo("fix ").as_deref(), Some(""));
+    assert_eq!(edits.undo(""), None);
+    assert_eq!(edits.redo("").as_deref(), Some("fix "));
+}
+
+#[test]
+fn moving_the_cursor_starts_a_new_step() {
+    let mut edits = EditHistory::new("ab");
+    edits.record("abc");
+    edits.record("xabc");
+    assert_eq!(edits.undo("xabc").as_deref(), Some("abc"));
+    assert_eq!(edits.undo("abc").as_deref(), Some("ab"));
+}
+
+#[test]
+fn a_new_change_drops_the_redo_steps() {
+    let mut edits = EditHistory::new("");
+    edits.record("Fix");
+    assert_eq!(edits.undo("Fix").as_deref(), Some(""));
+    edits.record("Add");
+    assert_eq!(edits.redo("Add"), None);
+    assert_eq!(edits.undo("Add").as_deref(), Some(""));
+}
+
+#[test]
+fn cursor_lands_after_the_restored_text() {
+    assert_eq!(changed_end("fix: a", "feat: a"), "feat".len());
+    assert_eq!(
+        changed_end("handle ", "handle empty "),
+        "handle empty ".len()
+    );
+    assert_eq!(changed_end("añadir", "aadir"), 1);
+    assert_eq!(changed_end("aa", "a"), 1);
+}
@@ -0,0 +1,121 @@
+use git_commit_helper::validate::{
+    Severity, ValidationRules, conventional_commit_pattern, overflow, validate,
+};
+
+fn messages(message: &str, rules: &ValidationRules) -> Vec<(Severity, String)> {
+    validate(message, rules)
+        .into_iter()
+        .map(|issue| (issue.severity, issue.message))
+        .collect()
+}
+
+#[test]
+fn clean_subject_has_no_issues() {
+    let rules = ValidationRules::default();
+    assert!(validate("Add tree view to the file list", &rules).is_empty());
+    assert!(validate("fix(ui): handle empty diffs\n\nBody text.", &rules).is_empty());
+}
+
+#[test]
+fn subject_length_warns_then_errors() {
+    let rules = ValidationRules::default();
+    let long = format!("Add {}", "x".repeat(50));
+    assert_eq!(validate(&long, &rules)[0].severity, Severity::Warning);
+    let too_long = format!("Add {}", "x".repeat(70));
+    assert_eq!(validate(&too_long, &rules)[0].severity, Severity::Error);
+}
+
+#[test]
+fn flags_period_mood_and_missing_blank_line() {
+    let issues = messages("feat: added things.\nbody", &ValidationRules::default());
+    assert_eq!(issues.len(), 3);
+    assert!(
+        issues
+            .iter()
+            .all(|(severity, _)| *severity == Severity::Warning)
+    );
+    assert!(issues[1].1.contains("\"added\""));
+
+    for word in ["Fixes", "Updated", "Making", "Adds"] {
+        assert!(!validate(&format!("{word} bug"), &ValidationRules::default()).is_empty());
+    }
+    for word in ["Process", "Embed", "Update", "Use"] {
+        assert!(validate(&format!("{word} bug"), &ValidationRules::default()).is_empty());
+    }
+}
+
+#[test]
+fn subject_pattern_is_an_error() {
+    let rules = ValidationRules {
+        subject_pattern: Some(conventional_commit_pattern()),
+        ..ValidationRules::default()
+    };
+    assert!(validate("feat(tree)!: drop flat mode", &rules).is_empty());
+    assert_eq!(
+        validate("Drop flat mode", &rules)[0].severity,
+        Severity::Error
+    );
+}
+
+#[test]
+fn breaking_change_footer_needs_a_description() {
+    let rules = ValidationRules::default();
+    assert!(
+        validate(
+            "feat!: drop flat mode\n\nBREAKING CHANGE: --flat is gone",
+            &rules
+        )
+        .is_empty()
+    );
+    assert!(
+        validate(
+            "feat!: drop flat mode\n\nBREAKING-CHANGE:\n  --flat is gone",
+            &rules
+        )
+        .is_empty()
+    );
+    assert_eq!(
+        messages("feat!: drop flat mode\n\nBREAKING CHANGE: ", &rules),
+        [(
+            Severity::Error,
+            "Describe the breaking change after \"BREAKING CHANGE:\"".to_string()
+        )]
+    );
+    assert_eq!(
+        messages(
+            "feat: drop flat mode\n\nBreaking change: --flat is gone",
+            &rules
+        )[0]
+        .0,
+        Severity::Warning
+    );
+}
+
+#[test]
+fn overflow_marks_text_past_the_limits() {
+    let rules = ValidationRules::default();
+    assert!(overflow("Add tree view\n\nShort body.", &rules).is_empty());
+
+    let subject = format!("Add {}", "é".repeat(76));
+    let body = format!("{}xyz", "b".repeat(72));
+    let message = format!("{subject}\n\n{body}");
+    let soft = subject.char_indices().nth(50).unwrap().0;
+    let hard = subject.char_indices().nth(72).unwrap().0;
+    let body_start = subject.len() + 2;
+    assert_eq!(
+        overflow(&message, &rules),
+        [
+            (soft..hard, Severity::Warning),
+            (hard..subject.len(), Severity::Error),
+            (body_start + 72..message.len(), Severity::Warning),
+        ]
+    );
+
+    let off = ValidationRules {
+        subject_soft_limit: 0,
+        subject_hard_limit: 0,
+        body_line_limit: 0,
+        ..ValidationRules::default()
+    };
+    assert!(overflow(&message, &off).is_empty());
+}
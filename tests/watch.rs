@@ -0,0 +1,49 @@
+use git_commit_helper::watch::{Watcher, is_relevant};
+use std::{
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn only_status_related_git_files_are_relevant() {
+    let git_dir = Path::new("/repo/.git");
+    assert!(is_relevant(Path::new("/repo/src/main.rs"), git_dir));
+    assert!(is_relevant(Path::new("/repo/.git/index"), git_dir));
+    assert!(is_relevant(Path::new("/repo/.git/HEAD"), git_dir));
+    assert!(is_relevant(Path::new("/repo/.git/MERGE_HEAD"), git_dir));
+    assert!(is_relevant(
+        Path::new("/repo/.git/refs/heads/main"),
+        git_dir
+    ));
+    assert!(!is_relevant(Path::new("/repo/.git/index.lock"), git_dir));
+    assert!(!is_relevant(
+        Path::new("/repo/.git/objects/ab/cdef"),
+        git_dir
+    ));
+    assert!(!is_relevant(
+        Path::new("/repo/.git/GIT_COMMIT_HELPER_DRAFT"),
+        git_dir
+    ));
+}
+
+#[test]
+fn changes_are_reported_once_they_settle() {
+    let work_dir = std::env::temp_dir().join(format!("gch-watch-{}", std::process::id()));
+    let git_dir = work_dir.join(".git");
+    std::fs::create_dir_all(&git_dir).unwrap();
+    let mut watcher = Watcher::new(&work_dir, &git_dir, Duration::from_millis(50)).unwrap();
+    assert!(!watcher.poll());
+
+    std::fs::write(work_dir.join("a.txt"), "hello").unwrap();
+    let start = Instant::now();
+    let mut fired = false;
+    while !fired && start.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(20));
+        fired = watcher.poll();
+    }
+    assert!(fired);
+    // Reported once.
+    assert!(!watcher.poll());
+    std::fs::remove_dir_all(work_dir).unwrap();
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{MockBackend, app_with, wait_for_task};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use git_commit_helper::{
+    App, FileStatus,
+    git::HeadCommit,
+    wip::{is_wip, wip_message},
+};
+
+fn ctrl(app: &mut App, c: char) {
+    app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+}
+
+fn head(subject: &str) -> Option<HeadCommit> {
+    Some(HeadCommit {
+        short_id: "1a2b3c4".to_string(),
+        subject: subject.to_string(),
+    })
+}
+
+#[test]
+fn wip_subjects_name_the_branch() {
+    assert_eq!(wip_message("feature/login"), "WIP: feature/login");
+    assert!(is_wip("WIP: main"));
+    assert!(!is_wip("Wipe the cache"));
+}
+
+#[test]
+fn ctrl_w_commits_everything_as_wip() {
+    let (mut app, backend) = app_with(MockBackend::with_files(&[
+        ("a.txt", FileStatus::Modified, false),
+        ("b.txt", FileStatus::Untracked, false),
+    ]));
+    ctrl(&mut app, 'w');
+    wait_for_task(&mut app);
+    assert_eq!(
+        backend.calls(),
+        ["stage_all", "commit --no-gpg-sign WIP: main"]
+    );
+    assert!(app.all_files.is_empty());
+
+    let (mut app, backend) = app_with(MockBackend::with_files(&[(
+        "a.txt",
+        FileStatus::Modified,
+        false,
+    )]));
+    app.config.wip_no_verify = true;
+    ctrl(&mut app, 'w');
+    wait_for_task(&mut app);
+    assert_eq!(
+        backend.calls()[1],
+        "commit --no-verify --no-gpg-sign WIP: main"
+    );
+}
+
+#[test]
+fn ctrl_u_puts_back_only_a_wip_commit() {
+    let (mut app, backend) = app_with(MockBackend {
+        head: head("Fix login"),
+        ..MockBackend::with_files(&[]).with_commits(&["Fix login"])
+    });
+    ctrl(&mut app, 'u');
+    assert!(backend.calls().is_empty());
+    let notification = app.notification.clone().unwrap();
+    assert!(notification.message.contains("isn't a WIP commit"));
+
+    let (mut app, backend) = app_with(MockBackend {
+        head: head("WIP: main"),
+        ..MockBackend::with_files(&[]).with_commits(&["WIP: main"])
+    });
+    ctrl(&mut app, 'u');
+    assert_eq!(backend.calls(), ["undo_last_commit", "unstage_all"]);
+}
+
+#[test]
+fn pushed_wip_commit_asks_first() {
+    let (mut app, backend) = app_with(MockBackend {
+        head: head("WIP: main"),
+        upstream: Some("origin/main".to_string()),
+        ..MockBackend::with_files(&[]).with_commits(&["WIP: main"])
+    });
+    ctrl(&mut app, 'u');
+    let confirm = app.confirm.as_ref().expect("asks first");
+    assert!(confirm.message.contains("already been pushed"));
+    assert!(backend.calls().is_empty());
+    app.handle_input(KeyCode::Char('y'));
+    assert_eq!(backend.calls(), ["undo_last_commit", "unstage_all"]);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use git_commit_helper::word_diff::changed_ranges;
+
+fn changed<'a>(line: &'a str, ranges: &[std::ops::Range<usize>]) -> Vec<&'a str> {
+    ranges.iter().map(|range| &line[range.clone()]).collect()
+}
+
+#[test]
+fn only_the_edited_words_are_marked() {
+    let old = "    let timeout = Duration::from_secs(30);";
+    let new = "    let timeout = Duration::from_millis(300);";
+    let (old_ranges, new_ranges) = changed_ranges(old, new);
+    assert_eq!(changed(old, &old_ranges), vec!["from_secs", "30"]);
+    assert_eq!(changed(new, &new_ranges), vec!["from_millis", "300"]);
+}
+
+#[test]
+fn adjacent_changes_are_merged_and_insertions_leave_the_old_line_alone() {
+    let old = "fn run(path: &str)";
+    let new = "fn run(path: &str, dry_run: bool)";
+    let (old_ranges, new_ranges) = changed_ranges(old, new);
+    assert!(old_ranges.is_empty());
+    assert_eq!(changed(new, &new_ranges), vec![", dry_run: bool"]);
+}
+
+#[test]
+fn unrelated_lines_get_no_word_highlighting() {
+    assert_eq!(
+        changed_ranges("use std::io;", "// Ünïcode ok"),
+        (Vec::new(), Vec::new())
+    );
+    assert_eq!(changed_ranges("a b", "c d"), (Vec::new(), Vec::new()));
+}
+
+#[test]
+fn diff_view_reverses_the_changed_words() {
+    use git_commit_helper::AppMode;
+    use ratatui::{Terminal, backend::TestBackend, style::Modifier};
+
+    let (mut app, _) = common::app_with(common::MockBackend::default());
+    app.diff_content = "@@ -1 +1 @@\n-let retries = 3;\n+let retries = 5;\n".to_string();
+    app.mode = AppMode::DiffView;
+    let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let reversed: Vec<String> = (0..12)
+        .map(|y| {
+            (0..40)
+                .filter(|&x| buffer[(x, y)].modifier.contains(Modifier::REVERSED))
+                .map(|x| buffer[(x, y)].symbol())
+                .collect()
+        })
+        .filter(|text: &String| !text.is_empty())
+        .collect();
+    assert_eq!(reversed, vec!["3", "5"]);
+}
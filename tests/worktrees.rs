@@ -0,0 +1,91 @@
+mod common;
+
+use common::{MockBackend, app_with, render, type_text};
+use crossterm::event::KeyCode;
+use git_commit_helper::{App, AppMode, git::WorktreeInfo, worktrees::worktree_path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+fn worktree(path: &Path, branch: &str) -> WorktreeInfo {
+    WorktreeInfo {
+        path: path.to_path_buf(),
+        head: Some("1234567890abcdef".to_string()),
+        branch: Some(branch.to_string()),
+        bare: false,
+        locked: false,
+        prunable: false,
+    }
+}
+
+/// The tool open in `gch`, which has a second worktree for `fix`.
+fn app_with_worktrees(name: &str) -> (App, Arc<MockBackend>, PathBuf) {
+    let base = std::env::temp_dir().join(format!("gch-worktrees-{}-{}", name, std::process::id()));
+    let main = base.join("gch");
+    fs::create_dir_all(&main).unwrap();
+    let (app, backend) = app_with(MockBackend {
+        work_dir: Some(main.clone()),
+        worktree_list: Mutex::new(vec![
+            worktree(&main, "main"),
+            WorktreeInfo {
+                locked: true,
+                ..worktree(&base.join("gch-fix"), "fix")
+            },
+        ]),
+        ..MockBackend::default()
+    });
+    (app, backend, base)
+}
+
+#[test]
+fn new_worktrees_go_beside_the_main_one() {
+    assert_eq!(
+        worktree_path(Path::new("/work/gch"), "feature/login"),
+        Path::new("/work/gch-feature-login")
+    );
+}
+
+#[test]
+fn lists_worktrees_with_the_current_one_marked() {
+    let (mut app, _, base) = app_with_worktrees("list");
+    app.handle_input(KeyCode::Char('W'));
+    let screen = render(&mut app, 100, 20);
+    fs::remove_dir_all(&base).unwrap();
+
+    assert_eq!(app.mode, AppMode::Worktrees);
+    assert_eq!(app.worktrees.selected, 0);
+    assert!(screen.contains("● main"));
+    assert!(screen.contains("fix"));
+    assert!(screen.contains("locked"));
+}
+
+#[test]
+fn enter_on_the_current_worktree_goes_back() {
+    let (mut app, _, base) = app_with_worktrees("current");
+    app.handle_input(KeyCode::Char('W'));
+    app.handle_input(KeyCode::Enter);
+    fs::remove_dir_all(&base).unwrap();
+
+    assert_eq!(app.mode, AppMode::FileList);
+}
+
+#[test]
+fn n_adds_a_worktree_for_a_branch_and_selects_it() {
+    let (mut app, backend, base) = app_with_worktrees("add");
+    app.handle_input(KeyCode::Char('W'));
+    app.handle_input(KeyCode::Char('n'));
+    type_text(&mut app, "feature/login");
+    app.handle_input(KeyCode::Enter);
+    fs::remove_dir_all(&base).unwrap();
+
+    let path = base.join("gch-feature-login");
+    assert_eq!(
+        backend.calls(),
+        [format!("worktree add {} feature/login", path.display())]
+    );
+    assert_eq!(app.worktrees.worktrees.len(), 3);
+    assert_eq!(app.worktrees.selected, 2);
+    assert_eq!(app.worktrees.branch_input, None);
+}